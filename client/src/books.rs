@@ -0,0 +1,128 @@
+//! Read-watch catalog endpoints (`/read-watch`).
+
+use {
+    crate::{
+        BookExportFormat, BookImportPlan, Client, ClientError, ImportReport, Render, Rendered,
+        Transport,
+        models::{Book, LocalizedBook, NewBook, ProgressEntry, UpdateBook, UpdateProgress},
+        query::{BookQuery, with_render},
+        segment,
+        transport::Method,
+        types::{BookApiResponse, BookBulkDeleteFilter, BookBulkUpdatePayload},
+    },
+    url::form_urlencoded,
+};
+
+fn format_name(format: BookExportFormat) -> &'static str {
+    match format {
+        BookExportFormat::Goodreads => "goodreads",
+        BookExportFormat::StoryGraph => "storygraph",
+    }
+}
+
+impl<T: Transport> Client<T> {
+    /// Searches the catalog.
+    pub async fn search_books(
+        &self,
+        query: &BookQuery,
+    ) -> Result<Vec<Rendered<LocalizedBook>>, ClientError> {
+        self.get(query.params().append_to("/read-watch/search"))
+            .await
+    }
+
+    /// Gets a book with its fields localized.
+    pub async fn get_book(
+        &self,
+        id: &str,
+        render: Option<Render>,
+    ) -> Result<Rendered<LocalizedBook>, ClientError> {
+        self.get(with_render(format!("/read-watch/{}", segment(id)), render))
+            .await
+    }
+
+    /// Gets a book with every locale of its localized fields.
+    pub async fn get_raw_book(&self, id: &str) -> Result<Book, ClientError> {
+        self.get(format!("/read-watch/raw/{}", segment(id))).await
+    }
+
+    pub async fn create_book(&self, book: &NewBook) -> Result<Book, ClientError> {
+        self.send_json(Method::Post, "/read-watch".to_string(), book)
+            .await
+    }
+
+    /// Replaces a book's fields (`PUT`).
+    pub async fn replace_book(&self, id: &str, book: &UpdateBook) -> Result<Book, ClientError> {
+        self.send_json(Method::Put, format!("/read-watch/{}", segment(id)), book)
+            .await
+    }
+
+    /// Updates the fields set in `book` (`PATCH`).
+    pub async fn update_book(&self, id: &str, book: &UpdateBook) -> Result<Book, ClientError> {
+        self.send_json(Method::Patch, format!("/read-watch/{}", segment(id)), book)
+            .await
+    }
+
+    pub async fn delete_book(&self, id: &str) -> Result<BookApiResponse, ClientError> {
+        self.request(Method::Delete, format!("/read-watch/{}", segment(id)))
+            .await
+    }
+
+    pub async fn bulk_delete_books(
+        &self,
+        filter: &BookBulkDeleteFilter,
+    ) -> Result<BookApiResponse, ClientError> {
+        self.send_json(Method::Delete, "/read-watch/bulk".to_string(), filter)
+            .await
+    }
+
+    pub async fn bulk_update_books(
+        &self,
+        payload: &BookBulkUpdatePayload,
+    ) -> Result<BookApiResponse, ClientError> {
+        self.send_json(Method::Patch, "/read-watch/bulk".to_string(), payload)
+            .await
+    }
+
+    /// Records reading progress.
+    pub async fn update_progress(
+        &self,
+        id: &str,
+        progress: &UpdateProgress,
+    ) -> Result<Book, ClientError> {
+        self.send_json(
+            Method::Patch,
+            format!("/read-watch/{}/progress", segment(id)),
+            progress,
+        )
+        .await
+    }
+
+    pub async fn progress_history(&self, id: &str) -> Result<Vec<ProgressEntry>, ClientError> {
+        self.get(format!("/read-watch/{}/progress/history", segment(id)))
+            .await
+    }
+
+    /// Imports a Goodreads or StoryGraph CSV export.
+    ///
+    /// Nothing is written unless `apply` is set; the report then describes
+    /// what the import would do.
+    pub async fn import_books(
+        &self,
+        format: BookExportFormat,
+        csv: impl Into<Vec<u8>>,
+        update_existing: bool,
+        apply: bool,
+    ) -> Result<ImportReport<BookImportPlan>, ClientError> {
+        let query = form_urlencoded::Serializer::new(String::new())
+            .append_pair("format", format_name(format))
+            .append_pair("update", &update_existing.to_string())
+            .append_pair("apply", &apply.to_string())
+            .finish();
+        self.send_raw(
+            format!("/read-watch/import?{}", query),
+            "text/csv",
+            csv.into(),
+        )
+        .await
+    }
+}
@@ -0,0 +1,113 @@
+//! # Client errors
+
+use thiserror::Error;
+
+/// Errors returned by [`crate::Client`] methods.
+///
+/// Non-success responses are mapped to a variant by status code. The message
+/// is the response body, which the API fills with a short explanation for most
+/// errors.
+#[derive(Debug, Error)]
+pub enum ClientError {
+    /// 400: the request was malformed (invalid id, bad query value, ...)
+    #[error("bad request: {0}")]
+    BadRequest(String),
+
+    /// 401: the API key is missing, malformed or unknown
+    #[error("unauthorized: {0}")]
+    Unauthorized(String),
+
+    /// 403: the API key is valid but lacks the required permission
+    #[error("forbidden: {0}")]
+    Forbidden(String),
+
+    /// 404: no such document or route
+    #[error("not found: {0}")]
+    NotFound(String),
+
+    /// 409: the request conflicts with an existing document
+    #[error("conflict: {0}")]
+    Conflict(String),
+
+    /// 422: the body was well-formed JSON but failed validation
+    #[error("unprocessable entity: {0}")]
+    Unprocessable(String),
+
+    /// Any other 4xx or 5xx response
+    #[error("server returned {status}: {message}")]
+    Status { status: u16, message: String },
+
+    /// The request could not be sent or the response could not be read
+    #[error("transport error: {0}")]
+    Transport(String),
+
+    /// The response body did not match the expected type
+    #[error("failed to decode response: {0}")]
+    Decode(#[from] serde_json::Error),
+
+    /// A backup could not be encoded or decoded
+    #[error("invalid backup: {0}")]
+    Backup(#[from] apiodactyl::errors::BackupError),
+
+    /// The base URL or a path could not be parsed
+    #[error("invalid url: {0}")]
+    Url(#[from] url::ParseError),
+}
+
+impl ClientError {
+    /// Builds the error for a non-success response.
+    pub fn from_status(status: u16, body: &[u8]) -> Self {
+        let message = String::from_utf8_lossy(body).trim().to_string();
+
+        match status {
+            400 => ClientError::BadRequest(message),
+            401 => ClientError::Unauthorized(message),
+            403 => ClientError::Forbidden(message),
+            404 => ClientError::NotFound(message),
+            409 => ClientError::Conflict(message),
+            422 => ClientError::Unprocessable(message),
+            _ => ClientError::Status { status, message },
+        }
+    }
+
+    /// The HTTP status code, if the error came from a response.
+    pub fn status(&self) -> Option<u16> {
+        match self {
+            ClientError::BadRequest(_) => Some(400),
+            ClientError::Unauthorized(_) => Some(401),
+            ClientError::Forbidden(_) => Some(403),
+            ClientError::NotFound(_) => Some(404),
+            ClientError::Conflict(_) => Some(409),
+            ClientError::Unprocessable(_) => Some(422),
+            ClientError::Status { status, .. } => Some(*status),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for ClientError {
+    fn from(error: reqwest::Error) -> Self {
+        ClientError::Transport(error.to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_status() {
+        assert!(matches!(
+            ClientError::from_status(400, b"invalid ObjectId\n"),
+            ClientError::BadRequest(message) if message == "invalid ObjectId"
+        ));
+        assert!(matches!(
+            ClientError::from_status(403, b""),
+            ClientError::Forbidden(_)
+        ));
+
+        let error = ClientError::from_status(503, b"down");
+        assert_eq!(error.status(), Some(503));
+        assert_eq!(error.to_string(), "server returned 503: down");
+    }
+}
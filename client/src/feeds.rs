@@ -0,0 +1,11 @@
+//! Activity feed endpoints (`/feeds`).
+
+use crate::{Client, ClientError, FeedFormat, Transport};
+
+impl<T: Transport> Client<T> {
+    /// The activity feed as an Atom, RSS or JSON Feed document, with titles
+    /// and summaries in the client's locale.
+    pub async fn activity_feed(&self, format: FeedFormat) -> Result<String, ClientError> {
+        self.get_text(format.path().to_string()).await
+    }
+}
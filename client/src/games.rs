@@ -0,0 +1,182 @@
+//! Game collection endpoints (`/games`).
+
+use {
+    crate::{
+        Client, ClientError, GameImportPlan, GameLibraryFormat, ImportReport, Render, Rendered,
+        Transport,
+        models::{
+            ChecklistImport, Game, NewChecklistItem, NewGame, NewPlaySession, PlaySession,
+            UpdateChecklistItem, UpdateGame,
+        },
+        query::{GameQuery, with_render},
+        segment,
+        transport::Method,
+        types::{
+            ChecklistImportPayload, ChecklistSummary, GameApiResponse, GameBulkDeleteFilter,
+            GameBulkUpdatePayload,
+        },
+    },
+    url::form_urlencoded,
+};
+
+fn format_name(format: GameLibraryFormat) -> &'static str {
+    match format {
+        GameLibraryFormat::SteamVdf => "steam-vdf",
+        GameLibraryFormat::SteamCsv => "steam-csv",
+        GameLibraryFormat::GogCsv => "gog-csv",
+        GameLibraryFormat::Playnite => "playnite",
+    }
+}
+
+impl<T: Transport> Client<T> {
+    /// Searches the game collection.
+    pub async fn search_games(
+        &self,
+        query: &GameQuery,
+    ) -> Result<Vec<Rendered<Game>>, ClientError> {
+        self.get(query.params().append_to("/games/search")).await
+    }
+
+    pub async fn get_game(
+        &self,
+        id: &str,
+        render: Option<Render>,
+    ) -> Result<Rendered<Game>, ClientError> {
+        self.get(with_render(format!("/games/{}", segment(id)), render))
+            .await
+    }
+
+    pub async fn create_game(&self, game: &NewGame) -> Result<Game, ClientError> {
+        self.send_json(Method::Post, "/games".to_string(), game)
+            .await
+    }
+
+    /// Replaces a game's fields (`PUT`).
+    pub async fn replace_game(&self, id: &str, game: &UpdateGame) -> Result<Game, ClientError> {
+        self.send_json(Method::Put, format!("/games/{}", segment(id)), game)
+            .await
+    }
+
+    /// Updates the fields set in `game` (`PATCH`).
+    pub async fn update_game(&self, id: &str, game: &UpdateGame) -> Result<Game, ClientError> {
+        self.send_json(Method::Patch, format!("/games/{}", segment(id)), game)
+            .await
+    }
+
+    pub async fn delete_game(&self, id: &str) -> Result<GameApiResponse, ClientError> {
+        self.request(Method::Delete, format!("/games/{}", segment(id)))
+            .await
+    }
+
+    pub async fn bulk_delete_games(
+        &self,
+        filter: &GameBulkDeleteFilter,
+    ) -> Result<GameApiResponse, ClientError> {
+        self.send_json(Method::Delete, "/games/bulk".to_string(), filter)
+            .await
+    }
+
+    pub async fn bulk_update_games(
+        &self,
+        payload: &GameBulkUpdatePayload,
+    ) -> Result<GameApiResponse, ClientError> {
+        self.send_json(Method::Patch, "/games/bulk".to_string(), payload)
+            .await
+    }
+
+    /// Logs a play session, returning the game with its updated playtime.
+    pub async fn log_session(
+        &self,
+        id: &str,
+        session: &NewPlaySession,
+    ) -> Result<Game, ClientError> {
+        self.send_json(
+            Method::Post,
+            format!("/games/{}/sessions", segment(id)),
+            session,
+        )
+        .await
+    }
+
+    pub async fn sessions(&self, id: &str) -> Result<Vec<PlaySession>, ClientError> {
+        self.get(format!("/games/{}/sessions", segment(id))).await
+    }
+
+    pub async fn checklist(&self, id: &str) -> Result<ChecklistSummary, ClientError> {
+        self.get(format!("/games/{}/checklist", segment(id))).await
+    }
+
+    pub async fn add_checklist_item(
+        &self,
+        id: &str,
+        item: &NewChecklistItem,
+    ) -> Result<ChecklistSummary, ClientError> {
+        self.send_json(
+            Method::Post,
+            format!("/games/{}/checklist", segment(id)),
+            item,
+        )
+        .await
+    }
+
+    pub async fn update_checklist_item(
+        &self,
+        id: &str,
+        item_id: &str,
+        update: &UpdateChecklistItem,
+    ) -> Result<ChecklistSummary, ClientError> {
+        self.send_json(
+            Method::Patch,
+            format!("/games/{}/checklist/{}", segment(id), segment(item_id)),
+            update,
+        )
+        .await
+    }
+
+    pub async fn delete_checklist_item(
+        &self,
+        id: &str,
+        item_id: &str,
+    ) -> Result<ChecklistSummary, ClientError> {
+        self.request(
+            Method::Delete,
+            format!("/games/{}/checklist/{}", segment(id), segment(item_id)),
+        )
+        .await
+    }
+
+    /// Adds checklist items in bulk, skipping ones already present.
+    pub async fn import_checklist(
+        &self,
+        id: &str,
+        payload: &ChecklistImportPayload,
+    ) -> Result<ChecklistImport, ClientError> {
+        self.send_json(
+            Method::Post,
+            format!("/games/{}/checklist/import", segment(id)),
+            payload,
+        )
+        .await
+    }
+
+    /// Imports a Steam, GOG Galaxy or Playnite library export.
+    ///
+    /// Nothing is written unless `apply` is set.
+    pub async fn import_games(
+        &self,
+        format: GameLibraryFormat,
+        data: impl Into<Vec<u8>>,
+        apply: bool,
+    ) -> Result<ImportReport<GameImportPlan>, ClientError> {
+        let query = form_urlencoded::Serializer::new(String::new())
+            .append_pair("format", format_name(format))
+            .append_pair("apply", &apply.to_string())
+            .finish();
+        self.send_raw(
+            format!("/games/import?{}", query),
+            "text/plain",
+            data.into(),
+        )
+        .await
+    }
+}
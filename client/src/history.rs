@@ -0,0 +1,119 @@
+//! Revision history endpoints (`/…/<id>/history`). Every route requires an
+//! admin key.
+
+use {
+    crate::{
+        Client, ClientError, Transport,
+        models::{Book, Game, Project, Review, Revision, WplaceScreenshot},
+        segment,
+        transport::Method,
+    },
+    serde::de::DeserializeOwned,
+};
+
+impl<T: Transport> Client<T> {
+    /// Lists the revisions of a document under `base`, newest first.
+    async fn history(
+        &self,
+        base: &str,
+        id: &str,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Revision>, ClientError> {
+        self.get(format!(
+            "{}/{}/history?offset={}&limit={}",
+            base,
+            segment(id),
+            offset,
+            limit
+        ))
+        .await
+    }
+
+    /// Writes a revision of a document under `base` back as its current state.
+    async fn revert<R: DeserializeOwned>(
+        &self,
+        base: &str,
+        id: &str,
+        revision: u32,
+    ) -> Result<R, ClientError> {
+        self.request(
+            Method::Post,
+            format!("{}/{}/history/{}/revert", base, segment(id), revision),
+        )
+        .await
+    }
+
+    /// Lists a book's revisions, newest first. The server returns at most 200
+    /// revisions per request.
+    pub async fn book_history(
+        &self,
+        id: &str,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Revision>, ClientError> {
+        self.history("/read-watch", id, offset, limit).await
+    }
+
+    /// Writes a revision back as the current book, restoring it if it was
+    /// deleted.
+    pub async fn revert_book(&self, id: &str, revision: u32) -> Result<Book, ClientError> {
+        self.revert("/read-watch", id, revision).await
+    }
+
+    pub async fn game_history(
+        &self,
+        id: &str,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Revision>, ClientError> {
+        self.history("/games", id, offset, limit).await
+    }
+
+    pub async fn revert_game(&self, id: &str, revision: u32) -> Result<Game, ClientError> {
+        self.revert("/games", id, revision).await
+    }
+
+    pub async fn project_history(
+        &self,
+        id: &str,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Revision>, ClientError> {
+        self.history("/projects", id, offset, limit).await
+    }
+
+    pub async fn revert_project(&self, id: &str, revision: u32) -> Result<Project, ClientError> {
+        self.revert("/projects", id, revision).await
+    }
+
+    pub async fn review_history(
+        &self,
+        id: &str,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Revision>, ClientError> {
+        self.history("/reviews", id, offset, limit).await
+    }
+
+    pub async fn revert_review(&self, id: &str, revision: u32) -> Result<Review, ClientError> {
+        self.revert("/reviews", id, revision).await
+    }
+
+    pub async fn screenshot_history(
+        &self,
+        id: &str,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<Revision>, ClientError> {
+        self.history("/wplace", id, offset, limit).await
+    }
+
+    pub async fn revert_screenshot(
+        &self,
+        id: &str,
+        revision: u32,
+    ) -> Result<WplaceScreenshot, ClientError> {
+        self.revert("/wplace", id, revision).await
+    }
+}
@@ -0,0 +1,464 @@
+//! # Apiodactyl client
+//!
+//! A typed async client for the Apiodactyl API, built on the server's own
+//! model types so requests and responses cannot drift from the handlers.
+//!
+//! ```no_run
+//! use apiodactyl_client::{
+//!     Client,
+//!     query::{BookQuery, GameQuery},
+//! };
+//!
+//! # async fn run() -> Result<(), apiodactyl_client::ClientError> {
+//! let client = Client::new("https://api.bearodactyl.dev")?.api_key("...");
+//! let books = client.search_books(&BookQuery::new().title("dune")).await?;
+//! let games = client.pages(GameQuery::new(), 50).collect_all().await?;
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! ## Modules
+//!
+//! - `query`: builders for list endpoint query strings
+//! - `pagination`: paging through list endpoints with `offset`/`limit`
+//! - `transport`: the HTTP layer, replaceable for tests
+//! - `error`: typed errors mapped from response status codes
+//!
+//! Endpoint methods live in one module per resource (`books`, `games`,
+//! `projects`, `reviews`, `series`, `taxonomy`, `wplace`, `webhooks`, `trash`,
+//! `system`), with revision history of every resource in `history` and the
+//! activity feed in `feeds`.
+
+pub mod error;
+pub mod pagination;
+pub mod query;
+pub mod transport;
+
+mod books;
+mod feeds;
+mod games;
+mod history;
+mod projects;
+mod reviews;
+mod series;
+mod system;
+mod taxonomy;
+mod trash;
+mod webhooks;
+mod wplace;
+
+pub use {
+    apiodactyl::{
+        backup::{ConflictPolicy, RestoreReport},
+        feeds::FeedFormat,
+        importers::{
+            ImportReport,
+            books::{BookExportFormat, BookImportPlan},
+            games::{GameImportPlan, GameLibraryFormat},
+        },
+        markdown::{Render, Rendered},
+        models,
+    },
+    error::ClientError,
+    pagination::Pages,
+    transport::{HttpTransport, Transport},
+};
+
+use {
+    serde::{Serialize, de::DeserializeOwned},
+    transport::{Body, Method, Request, Response},
+};
+
+/// Request and response types defined by the handlers.
+pub mod types {
+    pub use apiodactyl::handlers::{
+        books::{
+            ApiResponse as BookApiResponse, BulkDeleteFilter as BookBulkDeleteFilter,
+            BulkUpdatePayload as BookBulkUpdatePayload,
+        },
+        games::{
+            ApiResponse as GameApiResponse, BulkDeleteFilter as GameBulkDeleteFilter,
+            BulkUpdatePayload as GameBulkUpdatePayload, ChecklistImportPayload, ChecklistSummary,
+        },
+        misc::{CheckStatusResponse, HealthStatus, Liveness, Readiness},
+        reviews::{ReviewStats, RollingAverage},
+        series::{ApiResponse as SeriesApiResponse, MembersPayload},
+        taxonomy::{ApiResponse as TaxonomyApiResponse, MergePayload, RenamePayload, TermSummary},
+        webhooks::ApiResponse as WebhookApiResponse,
+    };
+}
+
+/// A client for one API server.
+///
+/// Clones of a client using [`HttpTransport`] share its connection pool.
+#[derive(Debug, Clone)]
+pub struct Client<T = HttpTransport> {
+    transport: T,
+    api_key: Option<String>,
+    locale: Option<String>,
+}
+
+impl Client<HttpTransport> {
+    /// Creates a client for the server at `base_url`.
+    pub fn new(base_url: &str) -> Result<Self, ClientError> {
+        Ok(Self::with_transport(HttpTransport::new(base_url)?))
+    }
+}
+
+impl<T: Transport> Client<T> {
+    /// Creates a client sending requests through `transport`.
+    pub fn with_transport(transport: T) -> Self {
+        Self {
+            transport,
+            api_key: None,
+            locale: None,
+        }
+    }
+
+    /// Sends `key` as a bearer token with every request.
+    pub fn api_key(mut self, key: impl Into<String>) -> Self {
+        self.api_key = Some(key.into());
+        self
+    }
+
+    /// Requests localized fields in `locale` via `Accept-Language`.
+    pub fn locale(mut self, locale: impl Into<String>) -> Self {
+        self.locale = Some(locale.into());
+        self
+    }
+
+    pub fn transport(&self) -> &T {
+        &self.transport
+    }
+
+    async fn send(
+        &self,
+        method: Method,
+        path: String,
+        body: Option<Body>,
+    ) -> Result<Response, ClientError> {
+        let mut headers = Vec::new();
+        if let Some(key) = &self.api_key {
+            headers.push(("Authorization", format!("Bearer {}", key)));
+        }
+        if let Some(locale) = &self.locale {
+            headers.push(("Accept-Language", locale.clone()));
+        }
+
+        self.transport
+            .send(Request {
+                method,
+                path,
+                headers,
+                body,
+            })
+            .await
+    }
+
+    /// Sends a request and fails on non-2xx responses.
+    async fn call(
+        &self,
+        method: Method,
+        path: String,
+        body: Option<Body>,
+    ) -> Result<Response, ClientError> {
+        let response = self.send(method, path, body).await?;
+        if response.is_success() {
+            Ok(response)
+        } else {
+            Err(ClientError::from_status(response.status, &response.body))
+        }
+    }
+
+    /// Sends a request without a body and decodes the JSON response.
+    async fn request<R: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: String,
+    ) -> Result<R, ClientError> {
+        let response = self.call(method, path, None).await?;
+        Ok(serde_json::from_slice(&response.body)?)
+    }
+
+    async fn get<R: DeserializeOwned>(&self, path: String) -> Result<R, ClientError> {
+        self.request(Method::Get, path).await
+    }
+
+    async fn get_text(&self, path: String) -> Result<String, ClientError> {
+        let response = self.call(Method::Get, path, None).await?;
+        Ok(String::from_utf8_lossy(&response.body).into_owned())
+    }
+
+    async fn send_json<B: Serialize, R: DeserializeOwned>(
+        &self,
+        method: Method,
+        path: String,
+        body: &B,
+    ) -> Result<R, ClientError> {
+        let body = Body {
+            content_type: "application/json",
+            bytes: serde_json::to_vec(body)?,
+        };
+        let response = self.call(method, path, Some(body)).await?;
+        Ok(serde_json::from_slice(&response.body)?)
+    }
+
+    async fn send_raw<R: DeserializeOwned>(
+        &self,
+        path: String,
+        content_type: &'static str,
+        bytes: Vec<u8>,
+    ) -> Result<R, ClientError> {
+        let body = Body {
+            content_type,
+            bytes,
+        };
+        let response = self.call(Method::Post, path, Some(body)).await?;
+        Ok(serde_json::from_slice(&response.body)?)
+    }
+
+    async fn send_empty(&self, method: Method, path: String) -> Result<(), ClientError> {
+        self.call(method, path, None).await.map(|_| ())
+    }
+}
+
+/// Percent-encodes a value for use as one path segment.
+fn segment(value: &str) -> String {
+    percent_encoding::utf8_percent_encode(value, SEGMENT).to_string()
+}
+
+const SEGMENT: &percent_encoding::AsciiSet = &percent_encoding::NON_ALPHANUMERIC
+    .remove(b'-')
+    .remove(b'_')
+    .remove(b'.')
+    .remove(b'~');
+
+#[cfg(test)]
+mod tests {
+    use {
+        super::*,
+        crate::query::{BookQuery, ReviewQuery},
+        apiodactyl::{auth::AuthService, db::BearoData, handlers::misc::Uptime},
+        rocket::{http::Header, local::asynchronous},
+        rocket_db_pools::Database,
+        serde_json::json,
+        std::{collections::VecDeque, sync::Mutex},
+    };
+
+    /// Records requests and answers them with canned responses.
+    #[derive(Default)]
+    struct Recorder {
+        requests: Mutex<Vec<Request>>,
+        responses: Mutex<VecDeque<Response>>,
+    }
+
+    impl Recorder {
+        fn respond(self, status: u16, body: impl Into<Vec<u8>>) -> Self {
+            self.responses.lock().unwrap().push_back(Response {
+                status,
+                body: body.into(),
+            });
+            self
+        }
+    }
+
+    impl Transport for Recorder {
+        async fn send(&self, request: Request) -> Result<Response, ClientError> {
+            self.requests.lock().unwrap().push(request);
+            self.responses
+                .lock()
+                .unwrap()
+                .pop_front()
+                .ok_or_else(|| ClientError::Transport("no response queued".to_string()))
+        }
+    }
+
+    /// Dispatches requests to an in-process Rocket instance.
+    struct Local(asynchronous::Client);
+
+    impl Transport for Local {
+        async fn send(&self, request: Request) -> Result<Response, ClientError> {
+            let method = match request.method {
+                Method::Get => rocket::http::Method::Get,
+                Method::Post => rocket::http::Method::Post,
+                Method::Put => rocket::http::Method::Put,
+                Method::Patch => rocket::http::Method::Patch,
+                Method::Delete => rocket::http::Method::Delete,
+            };
+
+            let mut local = self.0.req(method, request.path);
+            for (name, value) in request.headers {
+                local.add_header(Header::new(name, value));
+            }
+            if let Some(body) = request.body {
+                local = local.body(body.bytes);
+            }
+
+            let response = local.dispatch().await;
+            Ok(Response {
+                status: response.status().code,
+                body: response.into_bytes().await.unwrap_or_default(),
+            })
+        }
+    }
+
+    fn page(chapters: std::ops::Range<i32>) -> Vec<u8> {
+        let reviews: Vec<_> = chapters
+            .map(|chapter| {
+                json!({
+                    "_id": { "$oid": format!("{:024x}", chapter) },
+                    "chapter": chapter,
+                    "description": "",
+                    "rating": 4,
+                    "thoughts": "",
+                })
+            })
+            .collect();
+        serde_json::to_vec(&reviews).unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_requests_carry_key_locale_and_query() {
+        let client = Client::with_transport(Recorder::default().respond(200, "[]"))
+            .api_key("secret")
+            .locale("ja");
+
+        let books = client
+            .search_books(&BookQuery::new().title("dune").limit(5))
+            .await
+            .unwrap();
+        assert!(books.is_empty());
+
+        let requests = client.transport().requests.lock().unwrap();
+        assert_eq!(requests[0].method, Method::Get);
+        assert_eq!(requests[0].path, "/read-watch/search?title=dune&limit=5");
+        assert!(
+            requests[0]
+                .headers
+                .contains(&("Authorization", "Bearer secret".to_string()))
+        );
+        assert!(
+            requests[0]
+                .headers
+                .contains(&("Accept-Language", "ja".to_string()))
+        );
+    }
+
+    #[tokio::test]
+    async fn test_errors_map_from_status() {
+        let client = Client::with_transport(
+            Recorder::default()
+                .respond(404, "Book not found")
+                .respond(200, "not json"),
+        );
+
+        let error = client.get_book("a/b", None).await.unwrap_err();
+        assert!(matches!(error, ClientError::NotFound(message) if message == "Book not found"));
+        assert_eq!(
+            client.transport().requests.lock().unwrap()[0].path,
+            "/read-watch/a%2Fb"
+        );
+
+        let error = client.get_book("x", None).await.unwrap_err();
+        assert!(matches!(error, ClientError::Decode(_)));
+    }
+
+    #[tokio::test]
+    async fn test_pages_until_short_page() {
+        let client = Client::with_transport(
+            Recorder::default()
+                .respond(200, page(0..2))
+                .respond(200, page(2..4))
+                .respond(200, page(4..5)),
+        );
+
+        let reviews = client
+            .pages(ReviewQuery::new().min_rating(3), 2)
+            .collect_all()
+            .await
+            .unwrap();
+        let chapters: Vec<_> = reviews.iter().map(|review| review.item.chapter).collect();
+        assert_eq!(chapters, [0, 1, 2, 3, 4]);
+
+        let paths: Vec<_> = client
+            .transport()
+            .requests
+            .lock()
+            .unwrap()
+            .iter()
+            .map(|request| request.path.clone())
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "/reviews?minRating=3&offset=0&limit=2",
+                "/reviews?minRating=3&offset=2&limit=2",
+                "/reviews?minRating=3&offset=4&limit=2",
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_webhook_paths() {
+        let client = Client::with_transport(
+            Recorder::default()
+                .respond(200, "[]")
+                .respond(200, r#"{"message": "webhook deleted", "deleted": 3}"#),
+        );
+
+        let deliveries = client
+            .list_deliveries(
+                "650000000000000000000000",
+                Some(apiodactyl::models::DeliveryStatus::Failed),
+                20,
+                10,
+            )
+            .await
+            .unwrap();
+        assert!(deliveries.is_empty());
+
+        let deleted = client
+            .delete_webhook("650000000000000000000000")
+            .await
+            .unwrap();
+        assert_eq!(deleted.deleted, Some(3));
+
+        let requests = client.transport().requests.lock().unwrap();
+        assert_eq!(
+            requests[0].path,
+            "/webhooks/650000000000000000000000/deliveries?status=failed&offset=20&limit=10"
+        );
+        assert_eq!(requests[1].method, Method::Delete);
+        assert_eq!(requests[1].path, "/webhooks/650000000000000000000000");
+    }
+
+    #[tokio::test]
+    async fn test_against_local_rocket() {
+        // The MongoDB pool connects lazily, so routes that fail before querying
+        // the database can be exercised without a server.
+        let figment = rocket::Config::figment()
+            .merge(("databases.bearodata.url", "mongodb://127.0.0.1:1/bearodata"));
+        let rocket = apiodactyl::mount_api(rocket::custom(figment))
+            .manage(AuthService::new())
+            .manage(Uptime::default())
+            .attach(BearoData::init());
+        let local = asynchronous::Client::untracked(rocket).await.unwrap();
+        let client = Client::with_transport(Local(local));
+
+        let live = client.live().await.unwrap();
+        assert_eq!(live.status, "ok");
+
+        let spec = client.openapi().await.unwrap();
+        assert_eq!(spec["openapi"], "3.0.3");
+        assert!(spec["paths"]["/read-watch/search"]["get"].is_object());
+
+        let error = client.get_raw_book("not-an-id").await.unwrap_err();
+        assert_eq!(error.status(), Some(400));
+
+        let error = client
+            .delete_book("650000000000000000000000")
+            .await
+            .unwrap_err();
+        assert!(matches!(error, ClientError::Unauthorized(_)));
+    }
+}
@@ -0,0 +1,144 @@
+//! # Pagination
+//!
+//! List endpoints accept `offset` and `limit`. [`Pages`] walks through a
+//! listing one page at a time, stopping at the first short page.
+//!
+//! ```no_run
+//! # async fn run() -> Result<(), apiodactyl_client::ClientError> {
+//! use apiodactyl_client::{Client, query::ReviewQuery};
+//!
+//! let client = Client::new("http://localhost:2379")?;
+//! let mut pages = client.pages(ReviewQuery::new().min_rating(4), 100);
+//! while let Some(reviews) = pages.next_page().await? {
+//!     println!("{} reviews", reviews.len());
+//! }
+//! # Ok(())
+//! # }
+//! ```
+
+use {
+    crate::{
+        Client, ClientError, Rendered, Transport,
+        models::{Game, LocalizedBook, Review},
+        query::{BookQuery, GameQuery, ReviewQuery},
+    },
+    serde::de::DeserializeOwned,
+    std::future::Future,
+};
+
+/// A query for a paginated list endpoint.
+pub trait ListQuery: Clone + Send + Sync {
+    type Item: DeserializeOwned + Send;
+
+    /// Returns this query restricted to one page.
+    fn page(&self, offset: usize, limit: usize) -> Self;
+
+    /// Fetches the results of this query.
+    fn fetch<'a, T: Transport>(
+        &'a self,
+        client: &'a Client<T>,
+    ) -> impl Future<Output = Result<Vec<Self::Item>, ClientError>> + Send + 'a;
+}
+
+impl ListQuery for BookQuery {
+    type Item = Rendered<LocalizedBook>;
+
+    fn page(&self, offset: usize, limit: usize) -> Self {
+        self.clone().offset(offset).limit(limit)
+    }
+
+    fn fetch<'a, T: Transport>(
+        &'a self,
+        client: &'a Client<T>,
+    ) -> impl Future<Output = Result<Vec<Self::Item>, ClientError>> + Send + 'a {
+        client.search_books(self)
+    }
+}
+
+impl ListQuery for GameQuery {
+    type Item = Rendered<Game>;
+
+    fn page(&self, offset: usize, limit: usize) -> Self {
+        self.clone().offset(offset).limit(limit)
+    }
+
+    fn fetch<'a, T: Transport>(
+        &'a self,
+        client: &'a Client<T>,
+    ) -> impl Future<Output = Result<Vec<Self::Item>, ClientError>> + Send + 'a {
+        client.search_games(self)
+    }
+}
+
+impl ListQuery for ReviewQuery {
+    type Item = Rendered<Review>;
+
+    fn page(&self, offset: usize, limit: usize) -> Self {
+        self.clone().offset(offset).limit(limit)
+    }
+
+    fn fetch<'a, T: Transport>(
+        &'a self,
+        client: &'a Client<T>,
+    ) -> impl Future<Output = Result<Vec<Self::Item>, ClientError>> + Send + 'a {
+        client.list_reviews(self)
+    }
+}
+
+/// Pages of results for a [`ListQuery`].
+pub struct Pages<'a, T, Q> {
+    client: &'a Client<T>,
+    query: Q,
+    offset: usize,
+    page_size: usize,
+    done: bool,
+}
+
+impl<'a, T: Transport, Q: ListQuery> Pages<'a, T, Q> {
+    pub(crate) fn new(client: &'a Client<T>, query: Q, page_size: usize) -> Self {
+        Self {
+            client,
+            query,
+            offset: 0,
+            page_size: page_size.max(1),
+            done: false,
+        }
+    }
+
+    /// Fetches the next page, or `None` once the listing is exhausted.
+    pub async fn next_page(&mut self) -> Result<Option<Vec<Q::Item>>, ClientError> {
+        if self.done {
+            return Ok(None);
+        }
+
+        let items = self
+            .query
+            .page(self.offset, self.page_size)
+            .fetch(self.client)
+            .await?;
+        self.offset += items.len();
+        self.done = items.len() < self.page_size;
+
+        if items.is_empty() {
+            Ok(None)
+        } else {
+            Ok(Some(items))
+        }
+    }
+
+    /// Fetches every remaining page.
+    pub async fn collect_all(mut self) -> Result<Vec<Q::Item>, ClientError> {
+        let mut all = Vec::new();
+        while let Some(page) = self.next_page().await? {
+            all.extend(page);
+        }
+        Ok(all)
+    }
+}
+
+impl<T: Transport> Client<T> {
+    /// Pages through the results of `query`, `page_size` at a time.
+    pub fn pages<Q: ListQuery>(&self, query: Q, page_size: usize) -> Pages<'_, T, Q> {
+        Pages::new(self, query, page_size)
+    }
+}
@@ -0,0 +1,58 @@
+//! Project portfolio endpoints (`/projects`).
+
+use crate::{
+    Client, ClientError, Render, Rendered, Transport,
+    models::{NewProject, Project, UpdateProject},
+    query::with_render,
+    segment,
+    transport::Method,
+};
+
+impl<T: Transport> Client<T> {
+    pub async fn list_projects(
+        &self,
+        render: Option<Render>,
+    ) -> Result<Vec<Rendered<Project>>, ClientError> {
+        self.get(with_render("/projects".to_string(), render)).await
+    }
+
+    pub async fn get_project(
+        &self,
+        id: &str,
+        render: Option<Render>,
+    ) -> Result<Rendered<Project>, ClientError> {
+        self.get(with_render(format!("/projects/{}", segment(id)), render))
+            .await
+    }
+
+    pub async fn create_project(&self, project: &NewProject) -> Result<Project, ClientError> {
+        self.send_json(Method::Post, "/projects".to_string(), project)
+            .await
+    }
+
+    /// Replaces a project's fields (`PUT`).
+    pub async fn replace_project(
+        &self,
+        id: &str,
+        project: &UpdateProject,
+    ) -> Result<Project, ClientError> {
+        self.send_json(Method::Put, format!("/projects/{}", segment(id)), project)
+            .await
+    }
+
+    /// Updates the fields set in `project` (`PATCH`).
+    pub async fn update_project(
+        &self,
+        id: &str,
+        project: &UpdateProject,
+    ) -> Result<Project, ClientError> {
+        self.send_json(Method::Patch, format!("/projects/{}", segment(id)), project)
+            .await
+    }
+
+    /// Deletes a project, returning the deleted document.
+    pub async fn delete_project(&self, id: &str) -> Result<Project, ClientError> {
+        self.request(Method::Delete, format!("/projects/{}", segment(id)))
+            .await
+    }
+}
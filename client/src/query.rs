@@ -0,0 +1,454 @@
+//! # Query builders
+//!
+//! Typed builders for the list endpoints' query strings, mirroring the
+//! server's `BookQuery`, `GameQuery` and `ReviewQuery`.
+//!
+//! ```
+//! use apiodactyl_client::query::{BookQuery, BookSort};
+//!
+//! let query = BookQuery::new()
+//!     .genre("+fantasy")
+//!     .genre("-horror")
+//!     .min_rating(4)
+//!     .sort(BookSort::Rating)
+//!     .limit(20);
+//! assert_eq!(
+//!     query.to_query_string(),
+//!     "genre=%2Bfantasy&genre=-horror&minRating=4&sort=rating&limit=20"
+//! );
+//! ```
+
+use {
+    apiodactyl::{
+        markdown::Render,
+        models::{GameStatus, MediaType},
+    },
+    serde::Serialize,
+    url::form_urlencoded,
+};
+
+/// Query string parameters in insertion order.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Params(Vec<(&'static str, String)>);
+
+impl Params {
+    fn set(&mut self, name: &'static str, value: impl ToString) {
+        self.0.retain(|(existing, _)| *existing != name);
+        self.0.push((name, value.to_string()));
+    }
+
+    fn push(&mut self, name: &'static str, value: impl ToString) {
+        self.0.push((name, value.to_string()));
+    }
+
+    /// Encodes the parameters, without a leading `?`.
+    pub fn encode(&self) -> String {
+        form_urlencoded::Serializer::new(String::new())
+            .extend_pairs(&self.0)
+            .finish()
+    }
+
+    /// Appends the parameters to a path.
+    pub fn append_to(&self, path: &str) -> String {
+        if self.0.is_empty() {
+            path.to_string()
+        } else {
+            format!("{}?{}", path, self.encode())
+        }
+    }
+}
+
+/// Returns the serialized name of a unit enum variant, as used in query strings.
+pub(crate) fn wire_name<T: Serialize>(value: &T) -> String {
+    match serde_json::to_value(value) {
+        Ok(serde_json::Value::String(name)) => name,
+        _ => String::new(),
+    }
+}
+
+pub(crate) fn render_name(render: Render) -> &'static str {
+    match render {
+        Render::Markdown => "markdown",
+        Render::Html => "html",
+    }
+}
+
+/// Appends `?render=` to a path when a render mode is requested.
+pub(crate) fn with_render(path: String, render: Option<Render>) -> String {
+    match render {
+        Some(render) => format!("{}?render={}", path, render_name(render)),
+        None => path,
+    }
+}
+
+macro_rules! paging {
+    () => {
+        /// Returns `?render=html` HTML alongside the Markdown fields.
+        pub fn render(mut self, render: Render) -> Self {
+            self.params.set("render", render_name(render));
+            self
+        }
+
+        /// Skips the first `offset` results.
+        pub fn offset(mut self, offset: usize) -> Self {
+            self.params.set("offset", offset);
+            self
+        }
+
+        /// Returns at most `limit` results.
+        pub fn limit(mut self, limit: usize) -> Self {
+            self.params.set("limit", limit);
+            self
+        }
+
+        /// The encoded query string, without a leading `?`.
+        pub fn to_query_string(&self) -> String {
+            self.params.encode()
+        }
+
+        pub(crate) fn params(&self) -> &Params {
+            &self.params
+        }
+    };
+}
+
+/// Sort orders accepted by `/read-watch/search`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BookSort {
+    Title,
+    Author,
+    /// Highest rated first
+    Rating,
+    /// By series, then position in the series
+    Series,
+    /// Most recently added first
+    Recent,
+}
+
+/// Filters for `/read-watch/search`.
+///
+/// Genre and tag filters take a `+` prefix to require a value and a `-` prefix
+/// to exclude it; unprefixed values match if any of them is present.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct BookQuery {
+    params: Params,
+}
+
+impl BookQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Case-insensitive substring match on the title.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.params.set("title", title.into());
+        self
+    }
+
+    /// Case-insensitive substring match on the author.
+    pub fn author(mut self, author: impl Into<String>) -> Self {
+        self.params.set("author", author.into());
+        self
+    }
+
+    /// Adds a genre filter; may be called repeatedly.
+    pub fn genre(mut self, genre: impl Into<String>) -> Self {
+        self.params.push("genre", genre.into());
+        self
+    }
+
+    /// Adds a tag filter; may be called repeatedly.
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.params.push("tag", tag.into());
+        self
+    }
+
+    pub fn status(mut self, status: impl Into<String>) -> Self {
+        self.params.set("status", status.into());
+        self
+    }
+
+    pub fn explicit(mut self, explicit: bool) -> Self {
+        self.params.set("explicit", explicit);
+        self
+    }
+
+    pub fn min_rating(mut self, rating: i32) -> Self {
+        self.params.set("minRating", rating);
+        self
+    }
+
+    pub fn max_rating(mut self, rating: i32) -> Self {
+        self.params.set("maxRating", rating);
+        self
+    }
+
+    pub fn sort(mut self, sort: BookSort) -> Self {
+        let sort = match sort {
+            BookSort::Title => "title",
+            BookSort::Author => "author",
+            BookSort::Rating => "rating",
+            BookSort::Series => "series",
+            BookSort::Recent => "recent",
+        };
+        self.params.set("sort", sort);
+        self
+    }
+
+    /// Locale used for title/author matching and localized fields.
+    pub fn locale(mut self, locale: impl Into<String>) -> Self {
+        self.params.set("locale", locale.into());
+        self
+    }
+
+    pub fn media_type(mut self, media_type: MediaType) -> Self {
+        self.params.set("type", media_type.as_str());
+        self
+    }
+
+    /// Only books in the series with this id, in series order.
+    pub fn series(mut self, series_id: impl Into<String>) -> Self {
+        self.params.set("series", series_id.into());
+        self
+    }
+
+    paging!();
+}
+
+/// Sort orders accepted by `/games/search`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GameSort {
+    Title,
+    Developer,
+    /// Highest rated first
+    Rating,
+    /// Most recently added first
+    Recent,
+}
+
+/// Filters for `/games/search`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct GameQuery {
+    params: Params,
+}
+
+impl GameQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Case-insensitive substring match on the title.
+    pub fn title(mut self, title: impl Into<String>) -> Self {
+        self.params.set("title", title.into());
+        self
+    }
+
+    /// Case-insensitive substring match on the developer.
+    pub fn developer(mut self, developer: impl Into<String>) -> Self {
+        self.params.set("developer", developer.into());
+        self
+    }
+
+    /// Only games with a genre containing this text.
+    pub fn genre(mut self, genre: impl Into<String>) -> Self {
+        self.params.set("genre", genre.into());
+        self
+    }
+
+    /// Only games with a tag containing this text.
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.params.set("tag", tag.into());
+        self
+    }
+
+    pub fn status(mut self, status: GameStatus) -> Self {
+        self.params.set("status", wire_name(&status));
+        self
+    }
+
+    pub fn platform(mut self, platform: impl Into<String>) -> Self {
+        self.params.set("platform", platform.into());
+        self
+    }
+
+    pub fn explicit(mut self, explicit: bool) -> Self {
+        self.params.set("explicit", explicit);
+        self
+    }
+
+    pub fn bad(mut self, bad: bool) -> Self {
+        self.params.set("bad", bad);
+        self
+    }
+
+    pub fn min_progress(mut self, percent: i32) -> Self {
+        self.params.set("minProgress", percent);
+        self
+    }
+
+    pub fn max_progress(mut self, percent: i32) -> Self {
+        self.params.set("maxProgress", percent);
+        self
+    }
+
+    pub fn exact_progress(mut self, percent: i32) -> Self {
+        self.params.set("exactProgress", percent);
+        self
+    }
+
+    pub fn min_rating(mut self, rating: i32) -> Self {
+        self.params.set("minRating", rating);
+        self
+    }
+
+    pub fn max_rating(mut self, rating: i32) -> Self {
+        self.params.set("maxRating", rating);
+        self
+    }
+
+    pub fn exact_rating(mut self, rating: i32) -> Self {
+        self.params.set("exactRating", rating);
+        self
+    }
+
+    pub fn sort(mut self, sort: GameSort) -> Self {
+        let sort = match sort {
+            GameSort::Title => "title",
+            GameSort::Developer => "author",
+            GameSort::Rating => "rating",
+            GameSort::Recent => "recent",
+        };
+        self.params.set("sort", sort);
+        self
+    }
+
+    paging!();
+}
+
+/// Filters for `/reviews` and `/reviews/stats`.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ReviewQuery {
+    params: Params,
+}
+
+impl ReviewQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// First chapter, inclusive.
+    pub fn from(mut self, chapter: i32) -> Self {
+        self.params.set("from", chapter);
+        self
+    }
+
+    /// Last chapter, inclusive.
+    pub fn to(mut self, chapter: i32) -> Self {
+        self.params.set("to", chapter);
+        self
+    }
+
+    pub fn min_rating(mut self, rating: i32) -> Self {
+        self.params.set("minRating", rating);
+        self
+    }
+
+    pub fn max_rating(mut self, rating: i32) -> Self {
+        self.params.set("maxRating", rating);
+        self
+    }
+
+    /// Only reviews of the work with this id.
+    pub fn work(mut self, work_id: impl Into<String>) -> Self {
+        self.params.set("work", work_id.into());
+        self
+    }
+
+    paging!();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use apiodactyl::handlers::{books, games, reviews};
+    use rocket::{form::Form, http::RawStr};
+
+    #[test]
+    fn test_setters_replace_and_push() {
+        let query = BookQuery::new().title("a").title("b").tag("x").tag("y");
+        assert_eq!(query.to_query_string(), "title=b&tag=x&tag=y");
+        assert_eq!(Params::default().append_to("/reviews"), "/reviews");
+    }
+
+    #[test]
+    fn test_queries_parse_as_server_queries() {
+        let query = BookQuery::new()
+            .title("dune")
+            .genre("+sci-fi")
+            .tag("-grimdark")
+            .explicit(false)
+            .min_rating(3)
+            .max_rating(5)
+            .sort(BookSort::Series)
+            .media_type(MediaType::LightNovel)
+            .offset(10)
+            .limit(5);
+        let parsed =
+            Form::<books::BookQuery>::parse_encoded(RawStr::new(&query.to_query_string())).unwrap();
+        let parsed = format!("{:?}", parsed);
+        for expected in [
+            r#"title: Some("dune")"#,
+            r#"genre: Some(["+sci-fi"])"#,
+            r#"tag: Some(["-grimdark"])"#,
+            r#"explicit: Some("false")"#,
+            "min_rating: Some(3)",
+            "max_rating: Some(5)",
+            r#"sort: Some("series")"#,
+            "media_type: Some(LightNovel)",
+            "offset: Some(10)",
+            "limit: Some(5)",
+        ] {
+            assert!(
+                parsed.contains(expected),
+                "{expected} missing from {parsed}"
+            );
+        }
+
+        let query = GameQuery::new()
+            .status(GameStatus::HundredPercent)
+            .exact_progress(100)
+            .exact_rating(4)
+            .sort(GameSort::Developer);
+        let parsed =
+            Form::<games::GameQuery>::parse_encoded(RawStr::new(&query.to_query_string())).unwrap();
+        let parsed = format!("{:?}", parsed);
+        for expected in [
+            "status: Some(HundredPercent)",
+            "exact_progress: Some(100)",
+            "exact_rating: Some(4)",
+            r#"sort: Some("author")"#,
+        ] {
+            assert!(
+                parsed.contains(expected),
+                "{expected} missing from {parsed}"
+            );
+        }
+
+        let query = ReviewQuery::new().from(1).to(20).min_rating(2).limit(3);
+        let parsed =
+            Form::<reviews::ReviewQuery>::parse_encoded(RawStr::new(&query.to_query_string()))
+                .unwrap();
+        let parsed = format!("{:?}", parsed);
+        for expected in [
+            "from: Some(1)",
+            "to: Some(20)",
+            "min_rating: Some(2)",
+            "limit: Some(3)",
+        ] {
+            assert!(
+                parsed.contains(expected),
+                "{expected} missing from {parsed}"
+            );
+        }
+    }
+}
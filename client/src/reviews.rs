@@ -0,0 +1,199 @@
+//! Chapter review endpoints (`/reviews` and `/<works>/<id>/reviews`).
+
+use {
+    crate::{
+        Client, ClientError, Render, Rendered, Transport,
+        models::{NewReview, Review, UpdateReview, WorkKind},
+        query::{ReviewQuery, with_render},
+        segment,
+        transport::Method,
+        types::ReviewStats,
+    },
+    url::form_urlencoded,
+};
+
+/// Path prefix the work review routes are mounted under for a kind of work.
+fn work_prefix(kind: WorkKind) -> &'static str {
+    match kind {
+        WorkKind::Book => "/read-watch",
+        WorkKind::Game => "/games",
+        WorkKind::Series => "/series",
+    }
+}
+
+/// Builds `/reviews/<chapter>?work=..&render=..`.
+fn chapter_path(chapter: i32, work: Option<&str>, render: Option<Render>) -> String {
+    let path = with_render(format!("/reviews/{}", chapter), render);
+    match work {
+        Some(work) => {
+            let separator = if path.contains('?') { '&' } else { '?' };
+            let query = form_urlencoded::Serializer::new(String::new())
+                .append_pair("work", work)
+                .finish();
+            format!("{}{}{}", path, separator, query)
+        }
+        None => path,
+    }
+}
+
+impl<T: Transport> Client<T> {
+    pub async fn create_review(&self, review: &NewReview) -> Result<Review, ClientError> {
+        self.send_json(Method::Post, "/reviews".to_string(), review)
+            .await
+    }
+
+    pub async fn get_review(
+        &self,
+        id: &str,
+        render: Option<Render>,
+    ) -> Result<Rendered<Review>, ClientError> {
+        self.get(with_render(format!("/reviews/{}", segment(id)), render))
+            .await
+    }
+
+    /// Gets the review of a chapter, of the default work unless `work` is given.
+    pub async fn get_review_by_chapter(
+        &self,
+        chapter: i32,
+        work: Option<&str>,
+        render: Option<Render>,
+    ) -> Result<Rendered<Review>, ClientError> {
+        self.get(chapter_path(chapter, work, render)).await
+    }
+
+    /// Lists reviews in chapter order.
+    pub async fn list_reviews(
+        &self,
+        query: &ReviewQuery,
+    ) -> Result<Vec<Rendered<Review>>, ClientError> {
+        self.get(query.params().append_to("/reviews")).await
+    }
+
+    /// Rating statistics, with rolling averages over `window` chapters.
+    pub async fn review_stats(
+        &self,
+        query: &ReviewQuery,
+        window: Option<usize>,
+    ) -> Result<ReviewStats, ClientError> {
+        let mut path = query.params().append_to("/reviews/stats");
+        if let Some(window) = window {
+            let separator = if path.contains('?') { '&' } else { '?' };
+            path = format!("{}{}window={}", path, separator, window);
+        }
+        self.get(path).await
+    }
+
+    pub async fn update_review(
+        &self,
+        id: &str,
+        update: &UpdateReview,
+    ) -> Result<Review, ClientError> {
+        self.send_json(Method::Patch, format!("/reviews/{}", segment(id)), update)
+            .await
+    }
+
+    pub async fn update_review_by_chapter(
+        &self,
+        chapter: i32,
+        work: Option<&str>,
+        update: &UpdateReview,
+    ) -> Result<Review, ClientError> {
+        self.send_json(Method::Patch, chapter_path(chapter, work, None), update)
+            .await
+    }
+
+    pub async fn delete_review(&self, id: &str) -> Result<(), ClientError> {
+        self.send_empty(Method::Delete, format!("/reviews/{}", segment(id)))
+            .await
+    }
+
+    pub async fn delete_review_by_chapter(
+        &self,
+        chapter: i32,
+        work: Option<&str>,
+    ) -> Result<(), ClientError> {
+        self.send_empty(Method::Delete, chapter_path(chapter, work, None))
+            .await
+    }
+
+    /// Deletes the reviews of chapter ranges such as `10-20,25`.
+    pub async fn batch_delete_reviews(
+        &self,
+        chapters: &str,
+        work: Option<&str>,
+    ) -> Result<(), ClientError> {
+        let mut path = format!("/reviews/batch/{}", segment(chapters));
+        if let Some(work) = work {
+            path = format!("{}?work={}", path, segment(work));
+        }
+        self.send_empty(Method::Delete, path).await
+    }
+
+    /// Lists the reviews of one work.
+    pub async fn work_reviews(
+        &self,
+        kind: WorkKind,
+        work_id: &str,
+        render: Option<Render>,
+    ) -> Result<Vec<Rendered<Review>>, ClientError> {
+        let path = format!("{}/{}/reviews", work_prefix(kind), segment(work_id));
+        self.get(with_render(path, render)).await
+    }
+
+    pub async fn work_review(
+        &self,
+        kind: WorkKind,
+        work_id: &str,
+        chapter: i32,
+        render: Option<Render>,
+    ) -> Result<Rendered<Review>, ClientError> {
+        let path = format!(
+            "{}/{}/reviews/{}",
+            work_prefix(kind),
+            segment(work_id),
+            chapter
+        );
+        self.get(with_render(path, render)).await
+    }
+
+    pub async fn create_work_review(
+        &self,
+        kind: WorkKind,
+        work_id: &str,
+        review: &NewReview,
+    ) -> Result<Review, ClientError> {
+        let path = format!("{}/{}/reviews", work_prefix(kind), segment(work_id));
+        self.send_json(Method::Post, path, review).await
+    }
+
+    pub async fn update_work_review(
+        &self,
+        kind: WorkKind,
+        work_id: &str,
+        chapter: i32,
+        update: &UpdateReview,
+    ) -> Result<Review, ClientError> {
+        let path = format!(
+            "{}/{}/reviews/{}",
+            work_prefix(kind),
+            segment(work_id),
+            chapter
+        );
+        self.send_json(Method::Patch, path, update).await
+    }
+
+    pub async fn delete_work_review(
+        &self,
+        kind: WorkKind,
+        work_id: &str,
+        chapter: i32,
+    ) -> Result<(), ClientError> {
+        let path = format!(
+            "{}/{}/reviews/{}",
+            work_prefix(kind),
+            segment(work_id),
+            chapter
+        );
+        self.send_empty(Method::Delete, path).await
+    }
+}
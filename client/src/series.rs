@@ -0,0 +1,53 @@
+//! Series endpoints (`/series`).
+
+use crate::{
+    Client, ClientError, Transport,
+    models::{LocalizedSeries, NewSeries, Series, UpdateSeries},
+    segment,
+    transport::Method,
+    types::{MembersPayload, SeriesApiResponse},
+};
+
+impl<T: Transport> Client<T> {
+    pub async fn list_series(&self) -> Result<Vec<LocalizedSeries>, ClientError> {
+        self.get("/series".to_string()).await
+    }
+
+    /// Gets a series with its member books and reading progress.
+    pub async fn get_series(&self, id: &str) -> Result<LocalizedSeries, ClientError> {
+        self.get(format!("/series/{}", segment(id))).await
+    }
+
+    pub async fn create_series(&self, series: &NewSeries) -> Result<Series, ClientError> {
+        self.send_json(Method::Post, "/series".to_string(), series)
+            .await
+    }
+
+    pub async fn update_series(
+        &self,
+        id: &str,
+        series: &UpdateSeries,
+    ) -> Result<Series, ClientError> {
+        self.send_json(Method::Patch, format!("/series/{}", segment(id)), series)
+            .await
+    }
+
+    /// Sets the books in a series, in order.
+    pub async fn set_series_members(
+        &self,
+        id: &str,
+        members: &MembersPayload,
+    ) -> Result<LocalizedSeries, ClientError> {
+        self.send_json(
+            Method::Put,
+            format!("/series/{}/members", segment(id)),
+            members,
+        )
+        .await
+    }
+
+    pub async fn delete_series(&self, id: &str) -> Result<SeriesApiResponse, ClientError> {
+        self.request(Method::Delete, format!("/series/{}", segment(id)))
+            .await
+    }
+}
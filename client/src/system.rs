@@ -0,0 +1,118 @@
+//! Health, documentation, metrics and backup endpoints.
+
+use crate::{
+    Client, ClientError, ConflictPolicy, RestoreReport, Transport, segment,
+    transport::Method,
+    types::{CheckStatusResponse, HealthStatus, Liveness, Readiness},
+};
+use apiodactyl::backup::Backup;
+
+fn policy_name(policy: ConflictPolicy) -> &'static str {
+    match policy {
+        ConflictPolicy::Skip => "skip",
+        ConflictPolicy::Overwrite => "overwrite",
+        ConflictPolicy::Fail => "fail",
+    }
+}
+
+impl<T: Transport> Client<T> {
+    /// The API banner served at `/`.
+    pub async fn banner(&self) -> Result<String, ClientError> {
+        self.get_text("/".to_string()).await
+    }
+
+    /// The OpenAPI document describing every route.
+    pub async fn openapi(&self) -> Result<serde_json::Value, ClientError> {
+        self.get("/openapi.json".to_string()).await
+    }
+
+    /// Legacy database health check.
+    pub async fn health(&self) -> Result<HealthStatus, ClientError> {
+        self.get("/misc/check-health".to_string()).await
+    }
+
+    pub async fn live(&self) -> Result<Liveness, ClientError> {
+        self.get("/misc/health/live".to_string()).await
+    }
+
+    /// The readiness report.
+    ///
+    /// The report is returned for 503 responses too; check `status` to see
+    /// whether the backend is ready. Collections, indexes and migrations are
+    /// only filled in for admin keys.
+    pub async fn ready(&self) -> Result<Readiness, ClientError> {
+        let response = self
+            .send(Method::Get, "/misc/health/ready".to_string(), None)
+            .await?;
+        match response.status {
+            200 | 503 => Ok(serde_json::from_slice(&response.body)?),
+            status => Err(ClientError::from_status(status, &response.body)),
+        }
+    }
+
+    /// Describes the client's API key.
+    pub async fn check_login(&self) -> Result<CheckStatusResponse, ClientError> {
+        self.get("/misc/check-login".to_string()).await
+    }
+
+    /// Prometheus metrics in the text exposition format.
+    pub async fn metrics(&self) -> Result<String, ClientError> {
+        self.get_text("/metrics".to_string()).await
+    }
+
+    /// Exports the whole catalog, including hashed API keys and webhook
+    /// registrations if `include_keys`.
+    pub async fn export_backup(&self, include_keys: bool) -> Result<Backup, ClientError> {
+        let ndjson = self
+            .get_text(format!("/admin/export?keys={}", include_keys))
+            .await?;
+        Ok(Backup::from_ndjson(&ndjson)?)
+    }
+
+    /// Downloads one file of the CSV backup layout (`manifest.json` or `<collection>.csv`).
+    pub async fn export_backup_file(
+        &self,
+        file: &str,
+        include_keys: bool,
+    ) -> Result<String, ClientError> {
+        self.get_text(format!(
+            "/admin/export/{}?keys={}",
+            segment(file),
+            include_keys
+        ))
+        .await
+    }
+
+    /// Restores a backup.
+    pub async fn restore_backup(
+        &self,
+        backup: &Backup,
+        policy: ConflictPolicy,
+    ) -> Result<RestoreReport, ClientError> {
+        self.send_raw(
+            format!("/admin/import?policy={}", policy_name(policy)),
+            "application/x-ndjson",
+            backup.to_ndjson()?.into_bytes(),
+        )
+        .await
+    }
+
+    /// Restores one collection from a CSV file of the backup layout.
+    pub async fn restore_collection_csv(
+        &self,
+        collection: &str,
+        csv: impl Into<Vec<u8>>,
+        policy: ConflictPolicy,
+    ) -> Result<RestoreReport, ClientError> {
+        self.send_raw(
+            format!(
+                "/admin/import/{}?policy={}",
+                segment(collection),
+                policy_name(policy)
+            ),
+            "text/csv",
+            csv.into(),
+        )
+        .await
+    }
+}
@@ -0,0 +1,82 @@
+//! Genre and tag taxonomy endpoints (`/taxonomy`).
+
+use crate::{
+    Client, ClientError, Transport,
+    models::{NewTaxonomyTerm, TaxonomyTerm, TermKind, UpdateTaxonomyTerm},
+    query::wire_name,
+    segment,
+    transport::Method,
+    types::{MergePayload, RenamePayload, TaxonomyApiResponse, TermSummary},
+};
+
+fn term_path(kind: TermKind, slug: &str) -> String {
+    format!("/taxonomy/{}/{}", wire_name(&kind), segment(slug))
+}
+
+impl<T: Transport> Client<T> {
+    /// Lists terms with usage counts, optionally of one kind only.
+    pub async fn list_terms(
+        &self,
+        kind: Option<TermKind>,
+    ) -> Result<Vec<TermSummary>, ClientError> {
+        let path = match kind {
+            Some(kind) => format!("/taxonomy?kind={}", wire_name(&kind)),
+            None => "/taxonomy".to_string(),
+        };
+        self.get(path).await
+    }
+
+    pub async fn get_term(&self, kind: TermKind, slug: &str) -> Result<TermSummary, ClientError> {
+        self.get(term_path(kind, slug)).await
+    }
+
+    pub async fn create_term(&self, term: &NewTaxonomyTerm) -> Result<TaxonomyTerm, ClientError> {
+        self.send_json(Method::Post, "/taxonomy".to_string(), term)
+            .await
+    }
+
+    pub async fn update_term(
+        &self,
+        kind: TermKind,
+        slug: &str,
+        update: &UpdateTaxonomyTerm,
+    ) -> Result<TaxonomyTerm, ClientError> {
+        self.send_json(Method::Patch, term_path(kind, slug), update)
+            .await
+    }
+
+    /// Renames a term, rewriting references to it.
+    pub async fn rename_term(
+        &self,
+        kind: TermKind,
+        slug: &str,
+        payload: &RenamePayload,
+    ) -> Result<TaxonomyTerm, ClientError> {
+        self.send_json(
+            Method::Post,
+            format!("{}/rename", term_path(kind, slug)),
+            payload,
+        )
+        .await
+    }
+
+    /// Merges terms into a target term, rewriting references to them.
+    pub async fn merge_terms(
+        &self,
+        kind: TermKind,
+        payload: &MergePayload,
+    ) -> Result<TaxonomyTerm, ClientError> {
+        self.send_json(
+            Method::Post,
+            format!("/taxonomy/{}/merge", wire_name(&kind)),
+            payload,
+        )
+        .await
+    }
+
+    /// Rewrites genre and tag references on every document to canonical slugs.
+    pub async fn normalize_references(&self) -> Result<TaxonomyApiResponse, ClientError> {
+        self.request(Method::Post, "/taxonomy/normalize".to_string())
+            .await
+    }
+}
@@ -0,0 +1,148 @@
+//! # Transports
+//!
+//! A [`Transport`] sends one [`Request`] and returns the raw [`Response`].
+//! [`HttpTransport`] talks to a running server over HTTP; tests can supply
+//! their own transport to run the client against an in-process Rocket
+//! instance or canned responses.
+
+use {
+    crate::ClientError,
+    std::{fmt, future::Future},
+    url::Url,
+};
+
+/// HTTP methods used by the API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    Get,
+    Post,
+    Put,
+    Patch,
+    Delete,
+}
+
+impl Method {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Method::Get => "GET",
+            Method::Post => "POST",
+            Method::Put => "PUT",
+            Method::Patch => "PATCH",
+            Method::Delete => "DELETE",
+        }
+    }
+}
+
+impl fmt::Display for Method {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A request body together with its media type.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Body {
+    pub content_type: &'static str,
+    pub bytes: Vec<u8>,
+}
+
+/// A request ready to be sent.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Request {
+    pub method: Method,
+    /// Path and query string, e.g. `/read-watch/search?title=dune`
+    pub path: String,
+    /// Extra headers such as `Authorization` and `Accept-Language`
+    pub headers: Vec<(&'static str, String)>,
+    pub body: Option<Body>,
+}
+
+/// A response as received, before decoding.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Response {
+    pub status: u16,
+    pub body: Vec<u8>,
+}
+
+impl Response {
+    /// Whether the status code is 2xx.
+    pub fn is_success(&self) -> bool {
+        (200..300).contains(&self.status)
+    }
+}
+
+/// Sends requests to the API.
+pub trait Transport: Send + Sync {
+    fn send(&self, request: Request) -> impl Future<Output = Result<Response, ClientError>> + Send;
+}
+
+/// Sends requests to a running server with `reqwest`.
+#[derive(Debug, Clone)]
+pub struct HttpTransport {
+    http: reqwest::Client,
+    base_url: Url,
+}
+
+impl HttpTransport {
+    /// Creates a transport for the server at `base_url`, e.g. `https://api.bearodactyl.dev`.
+    pub fn new(base_url: &str) -> Result<Self, ClientError> {
+        Self::with_client(reqwest::Client::new(), base_url)
+    }
+
+    /// Creates a transport using a preconfigured `reqwest` client (timeouts, proxies, ...).
+    pub fn with_client(http: reqwest::Client, base_url: &str) -> Result<Self, ClientError> {
+        let mut base_url = Url::parse(base_url)?;
+        if !base_url.path().ends_with('/') {
+            base_url.set_path(&format!("{}/", base_url.path()));
+        }
+
+        Ok(Self { http, base_url })
+    }
+}
+
+impl Transport for HttpTransport {
+    async fn send(&self, request: Request) -> Result<Response, ClientError> {
+        let url = self.base_url.join(request.path.trim_start_matches('/'))?;
+        let method = match request.method {
+            Method::Get => reqwest::Method::GET,
+            Method::Post => reqwest::Method::POST,
+            Method::Put => reqwest::Method::PUT,
+            Method::Patch => reqwest::Method::PATCH,
+            Method::Delete => reqwest::Method::DELETE,
+        };
+
+        let mut builder = self.http.request(method, url);
+        for (name, value) in request.headers {
+            builder = builder.header(name, value);
+        }
+        if let Some(body) = request.body {
+            builder = builder
+                .header(reqwest::header::CONTENT_TYPE, body.content_type)
+                .body(body.bytes);
+        }
+
+        let response = builder.send().await?;
+        Ok(Response {
+            status: response.status().as_u16(),
+            body: response.bytes().await?.to_vec(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_base_url_keeps_prefix() {
+        let transport = HttpTransport::new("https://example.com/api").unwrap();
+        assert_eq!(
+            transport
+                .base_url
+                .join("read-watch/search")
+                .unwrap()
+                .as_str(),
+            "https://example.com/api/read-watch/search"
+        );
+    }
+}
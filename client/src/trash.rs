@@ -0,0 +1,37 @@
+//! Trash endpoints (`/trash`). Every route requires an admin key.
+
+use crate::{Client, ClientError, Transport, models::TrashedDocument, segment, transport::Method};
+
+impl<T: Transport> Client<T> {
+    /// Lists trashed documents, most recently deleted first, optionally of one
+    /// collection (e.g. `books`) only.
+    pub async fn list_trash(
+        &self,
+        collection: Option<&str>,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<TrashedDocument>, ClientError> {
+        let mut query = url::form_urlencoded::Serializer::new(String::new());
+        if let Some(collection) = collection {
+            query.append_pair("collection", collection);
+        }
+        let query = query
+            .append_pair("offset", &offset.to_string())
+            .append_pair("limit", &limit.to_string())
+            .finish();
+        self.get(format!("/trash?{}", query)).await
+    }
+
+    /// Takes a document out of the trash, returning it as restored.
+    pub async fn restore_from_trash(
+        &self,
+        collection: &str,
+        id: &str,
+    ) -> Result<serde_json::Value, ClientError> {
+        self.request(
+            Method::Post,
+            format!("/trash/{}/{}/restore", segment(collection), segment(id)),
+        )
+        .await
+    }
+}
@@ -0,0 +1,91 @@
+//! Webhook endpoints (`/webhooks`). Every route requires an admin key.
+
+use {
+    crate::{
+        Client, ClientError, Transport,
+        models::{DeliveryStatus, NewWebhook, UpdateWebhook, Webhook, WebhookDelivery},
+        segment,
+        transport::Method,
+        types::WebhookApiResponse,
+    },
+    url::form_urlencoded,
+};
+
+fn webhook_path(id: &str) -> String {
+    format!("/webhooks/{}", segment(id))
+}
+
+impl<T: Transport> Client<T> {
+    pub async fn list_webhooks(&self) -> Result<Vec<Webhook>, ClientError> {
+        self.get("/webhooks".to_string()).await
+    }
+
+    pub async fn get_webhook(&self, id: &str) -> Result<Webhook, ClientError> {
+        self.get(webhook_path(id)).await
+    }
+
+    /// Registers a webhook. The response is the only one that includes its
+    /// signing secret.
+    pub async fn create_webhook(&self, webhook: &NewWebhook) -> Result<Webhook, ClientError> {
+        self.send_json(Method::Post, "/webhooks".to_string(), webhook)
+            .await
+    }
+
+    pub async fn update_webhook(
+        &self,
+        id: &str,
+        update: &UpdateWebhook,
+    ) -> Result<Webhook, ClientError> {
+        self.send_json(Method::Patch, webhook_path(id), update)
+            .await
+    }
+
+    /// Deletes a webhook and its delivery log.
+    pub async fn delete_webhook(&self, id: &str) -> Result<WebhookApiResponse, ClientError> {
+        self.request(Method::Delete, webhook_path(id)).await
+    }
+
+    /// Lists the deliveries of a webhook, newest first, optionally with one
+    /// status only. The server returns at most 200 deliveries per request.
+    pub async fn list_deliveries(
+        &self,
+        id: &str,
+        status: Option<DeliveryStatus>,
+        offset: usize,
+        limit: usize,
+    ) -> Result<Vec<WebhookDelivery>, ClientError> {
+        let mut query = form_urlencoded::Serializer::new(String::new());
+        if let Some(status) = status {
+            query.append_pair("status", status.as_str());
+        }
+        let query = query
+            .append_pair("offset", &offset.to_string())
+            .append_pair("limit", &limit.to_string())
+            .finish();
+        self.get(format!("{}/deliveries?{}", webhook_path(id), query))
+            .await
+    }
+
+    /// Queues a delivery again, with a fresh set of attempts.
+    pub async fn retry_delivery(
+        &self,
+        id: &str,
+        delivery_id: &str,
+    ) -> Result<WebhookDelivery, ClientError> {
+        self.request(
+            Method::Post,
+            format!(
+                "{}/deliveries/{}/retry",
+                webhook_path(id),
+                segment(delivery_id)
+            ),
+        )
+        .await
+    }
+
+    /// Queues a `ping` delivery, regardless of the webhook's event filters.
+    pub async fn ping_webhook(&self, id: &str) -> Result<WebhookDelivery, ClientError> {
+        self.request(Method::Post, format!("{}/ping", webhook_path(id)))
+            .await
+    }
+}
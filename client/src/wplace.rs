@@ -0,0 +1,31 @@
+//! wplace screenshot endpoints (`/wplace`).
+
+use crate::{
+    Client, ClientError, Transport,
+    models::{NewWplaceScreenshot, WplaceScreenshot},
+    segment,
+    transport::Method,
+};
+
+impl<T: Transport> Client<T> {
+    pub async fn list_screenshots(&self) -> Result<Vec<WplaceScreenshot>, ClientError> {
+        self.get("/wplace".to_string()).await
+    }
+
+    pub async fn get_screenshot(&self, id: &str) -> Result<WplaceScreenshot, ClientError> {
+        self.get(format!("/wplace/{}", segment(id))).await
+    }
+
+    pub async fn create_screenshot(
+        &self,
+        screenshot: &NewWplaceScreenshot,
+    ) -> Result<WplaceScreenshot, ClientError> {
+        self.send_json(Method::Post, "/wplace".to_string(), screenshot)
+            .await
+    }
+
+    pub async fn delete_screenshot(&self, id: &str) -> Result<(), ClientError> {
+        self.send_empty(Method::Delete, format!("/wplace/{}", segment(id)))
+            .await
+    }
+}
@@ -31,4 +31,4 @@ mongosh "$MONGODB_URL" --eval "
 " --quiet
 
 echo "Starting Rocket server..."
-exec ./apiodactyl
+exec ./apiodactyl serve
@@ -0,0 +1,51 @@
+#!/bin/bash
+# Downloads the Swagger UI and Redoc bundles served under /docs/assets.
+#
+# Usage: scripts/vendor-docs-assets.sh [directory]   (default: assets/docs)
+#
+# Every tarball is checked against its pinned npm `dist.integrity` (SHA-512)
+# before it is extracted. When bumping a version, update its integrity too:
+#
+#   npm view swagger-ui-dist@<version> dist.integrity
+#   npm view redoc@<version> dist.integrity
+set -euo pipefail
+
+SWAGGER_UI_VERSION="5.17.14"
+SWAGGER_UI_INTEGRITY=""
+REDOC_VERSION="2.1.5"
+REDOC_INTEGRITY=""
+
+dir="${1:-assets/docs}"
+mkdir -p "$dir"
+
+tmp="$(mktemp -d)"
+trap 'rm -rf "$tmp"' EXIT
+
+# fetch <url> <integrity> <file>: downloads <url> to <file> and fails unless
+# its SHA-512 matches the npm integrity string.
+fetch() {
+    local url="$1" expected="$2" file="$3" actual
+    curl -fsSL --retry 3 -o "$file" "$url"
+    actual="sha512-$(openssl dgst -sha512 -binary "$file" | base64 | tr -d '\n')"
+    if [ -z "$expected" ]; then
+        echo "no integrity pinned for $url (downloaded $actual);" \
+            "check it with \`npm view\` and pin it in $0" >&2
+        exit 1
+    fi
+    if [ "$actual" != "$expected" ]; then
+        echo "integrity mismatch for $url: expected $expected, got $actual" >&2
+        exit 1
+    fi
+}
+
+fetch "https://registry.npmjs.org/swagger-ui-dist/-/swagger-ui-dist-$SWAGGER_UI_VERSION.tgz" \
+    "$SWAGGER_UI_INTEGRITY" "$tmp/swagger-ui-dist.tgz"
+fetch "https://registry.npmjs.org/redoc/-/redoc-$REDOC_VERSION.tgz" \
+    "$REDOC_INTEGRITY" "$tmp/redoc.tgz"
+
+tar -xzf "$tmp/swagger-ui-dist.tgz" -C "$tmp" package/swagger-ui.css package/swagger-ui-bundle.js
+tar -xzf "$tmp/redoc.tgz" -C "$tmp" package/bundles/redoc.standalone.js
+
+cp "$tmp/package/swagger-ui.css" "$tmp/package/swagger-ui-bundle.js" "$dir/"
+cp "$tmp/package/bundles/redoc.standalone.js" "$dir/"
+echo "Docs assets written to $dir"
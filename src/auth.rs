@@ -8,6 +8,7 @@
 //! - SHA256-based API key hashing
 //! - In-memory caching with TTL (7 days)
 //! - Admin and regular user roles
+//! - Optional labels, expiry dates and path scopes per key
 //! - Automatic last-used timestamp updates
 //! - Request guards for authentication
 
@@ -22,7 +23,9 @@ use std::{collections::HashMap, time::SystemTime};
 
 use crate::db::BearoData;
 use crate::errors::AuthError;
-use crate::models::ApiKey;
+use crate::logging::AuthenticatedKey;
+use crate::metrics::METRICS;
+use crate::models::{ApiKey, KeyScope, NewApiKey};
 
 /// Cache entry for storing API keys with timestamp.
 #[derive(Clone, Debug)]
@@ -94,6 +97,13 @@ impl ApiKeyCache {
         }
     }
 
+    /// Removes every entry from the cache.
+    pub fn clear(&self) {
+        if let Ok(mut cache) = self.cache.write() {
+            cache.clear();
+        }
+    }
+
     /// Removes all expired entries from the cache.
     ///
     /// Entries are considered expired after 7 days.
@@ -211,8 +221,10 @@ impl AuthService {
     pub async fn validate_api_key(&self, key: &str, db: &BearoData) -> Result<ApiKey, AuthError> {
         let key_hash = Self::hash_api_key(key);
 
-        if let Some(cached_key) = self.cache.get(&key_hash) {
-            return Ok(cached_key);
+        let cached = self.cache.get(&key_hash);
+        METRICS.record_auth_cache(cached.is_some());
+        if let Some(cached_key) = cached {
+            return Self::check_expiry(cached_key);
         }
 
         let collection = db.database("bearodata").collection::<ApiKey>("api_keys");
@@ -226,7 +238,15 @@ impl AuthService {
 
         self.cache.insert(key_hash, api_key.clone());
 
-        Ok(api_key)
+        Self::check_expiry(api_key)
+    }
+
+    fn check_expiry(api_key: ApiKey) -> Result<ApiKey, AuthError> {
+        if api_key.is_expired(chrono::Utc::now().naive_utc()) {
+            Err(AuthError::ExpiredKey)
+        } else {
+            Ok(api_key)
+        }
     }
 
     pub async fn update_last_used(
@@ -239,7 +259,8 @@ impl AuthService {
         let update = doc! {
             "$set": {
                 "last_used_at": BsonDateTime::from_system_time(SystemTime::now())
-            }
+            },
+            "$inc": { "use_count": 1 }
         };
 
         collection
@@ -265,20 +286,24 @@ impl AuthService {
 
         if admin_count == 0 {
             if let Ok(admin_key) = std::env::var("BOOTSTRAP_ADMIN_KEY") {
-                println!("Creating bootstrap admin key...");
+                tracing::info!("creating bootstrap admin key");
                 match self.create_api_key(&admin_key, true, db).await {
                     Ok(api_key) => {
-                        println!("Bootstrap admin created with ID: {}", api_key.oid);
-                        println!("Remove BOOTSTRAP_ADMIN_KEY from environment after startup!");
+                        tracing::info!(key_id = %api_key.oid, "bootstrap admin created");
+                        tracing::warn!(
+                            "remove BOOTSTRAP_ADMIN_KEY from the environment after startup"
+                        );
                     }
                     Err(e) => {
-                        eprintln!("❌ Failed to create bootstrap admin: {}", e);
+                        tracing::error!(error = %e, "failed to create bootstrap admin");
                         return Err(e);
                     }
                 }
             } else {
-                println!("No admin users exist and no BOOTSTRAP_ADMIN_KEY provided");
-                println!("Set BOOTSTRAP_ADMIN_KEY environment variable or use CLI command");
+                tracing::warn!(
+                    "no admin keys exist and BOOTSTRAP_ADMIN_KEY is not set; \
+                     set it or create one with the create-admin-key command"
+                );
             }
         }
 
@@ -290,6 +315,26 @@ impl AuthService {
         key: &str,
         is_admin: bool,
         db: &BearoData,
+    ) -> Result<ApiKey, AuthError> {
+        let options = NewApiKey {
+            is_admin,
+            ..NewApiKey::default()
+        };
+        self.create_api_key_with(key, options, db).await
+    }
+
+    /// Stores a new API key with a label, expiry date and scopes.
+    ///
+    /// # Arguments
+    ///
+    /// * `key` - The plain text API key
+    /// * `options` - Role, label, expiry and scopes of the key
+    /// * `db` - Database connection
+    pub async fn create_api_key_with(
+        &self,
+        key: &str,
+        options: NewApiKey,
+        db: &BearoData,
     ) -> Result<ApiKey, AuthError> {
         let collection = db.database("bearodata").collection::<ApiKey>("api_keys");
         let key_hash = Self::hash_api_key(key);
@@ -297,9 +342,13 @@ impl AuthService {
         let new_api_key = ApiKey {
             oid: ObjectId::new(),
             key_hash: key_hash.clone(),
-            is_admin,
+            is_admin: options.is_admin,
             created_at: chrono::Utc::now().naive_utc(),
             last_used_at: None,
+            label: options.label,
+            expires_at: options.expires_at,
+            scopes: options.scopes,
+            use_count: 0,
         };
 
         collection
@@ -312,6 +361,25 @@ impl AuthService {
         Ok(new_api_key)
     }
 
+    /// Looks up an API key by its ObjectId or by the plain text key.
+    ///
+    /// # Returns
+    ///
+    /// The API key record, or AuthError::InvalidKey if none matches.
+    pub async fn find_api_key(&self, key_or_id: &str, db: &BearoData) -> Result<ApiKey, AuthError> {
+        let collection = db.database("bearodata").collection::<ApiKey>("api_keys");
+        let filter = match ObjectId::parse_str(key_or_id) {
+            Ok(oid) => doc! { "_id": oid },
+            Err(_) => doc! { "key_hash": Self::hash_api_key(key_or_id) },
+        };
+
+        collection
+            .find_one(filter, None)
+            .await
+            .map_err(|_| AuthError::Database)?
+            .ok_or(AuthError::InvalidKey)
+    }
+
     pub async fn revoke_api_key(&self, key: &str, db: &BearoData) -> Result<(), AuthError> {
         let collection = db.database("bearodata").collection::<ApiKey>("api_keys");
         let key_hash = Self::hash_api_key(key);
@@ -327,6 +395,13 @@ impl AuthService {
         Ok(())
     }
 
+    /// Forgets every cached key, so each key is read from the database again
+    /// on its next use. Called after `api_keys` is written behind the
+    /// service's back, e.g. by a backup restore.
+    pub fn clear_cache(&self) {
+        self.cache.clear();
+    }
+
     pub async fn list_api_keys(&self, db: &BearoData) -> Result<Vec<ApiKey>, AuthError> {
         let collection = db.database("bearodata").collection::<ApiKey>("api_keys");
         let mut cursor = collection
@@ -357,6 +432,41 @@ impl AuthService {
     }
 }
 
+/// Filters for listing API keys.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KeyFilter {
+    /// Only admin (`Some(true)`) or only regular (`Some(false)`) keys
+    pub admin: Option<bool>,
+    /// Case-insensitive substring of the label
+    pub label: Option<String>,
+    /// Only keys that may access this scope
+    pub scope: Option<KeyScope>,
+    /// Only expired (`Some(true)`) or only unexpired (`Some(false)`) keys
+    pub expired: Option<bool>,
+}
+
+impl KeyFilter {
+    /// Whether `api_key` passes every filter at `now`.
+    pub fn matches(&self, api_key: &ApiKey, now: NaiveDateTime) -> bool {
+        let label_matches = self.label.as_ref().is_none_or(|label| {
+            api_key
+                .label
+                .as_ref()
+                .is_some_and(|key_label| key_label.to_lowercase().contains(&label.to_lowercase()))
+        });
+        let scope_matches = self
+            .scope
+            .is_none_or(|scope| api_key.scopes.is_empty() || api_key.scopes.contains(&scope));
+
+        self.admin.is_none_or(|admin| api_key.is_admin == admin)
+            && label_matches
+            && scope_matches
+            && self
+                .expired
+                .is_none_or(|expired| api_key.is_expired(now) == expired)
+    }
+}
+
 #[rocket::async_trait]
 impl<'r> FromRequest<'r> for User {
     type Error = AuthError;
@@ -398,8 +508,15 @@ impl<'r> FromRequest<'r> for User {
         };
 
         match auth_service.validate_api_key(api_key, db).await {
+            Ok(key_record) if !key_record.allows(request.uri().path().as_str()) => {
+                Outcome::Error((
+                    rocket::http::Status::Forbidden,
+                    AuthError::InsufficientPermissions,
+                ))
+            }
             Ok(key_record) => {
                 let key_id = key_record.oid;
+                request.local_cache(|| AuthenticatedKey(Some(key_id.to_hex())));
                 let auth_service_clone = auth_service.inner().clone();
 
                 let _ = auth_service_clone.update_last_used(key_id, db).await;
@@ -481,6 +598,10 @@ mod tests {
             is_admin: true,
             created_at: chrono::Utc::now().naive_utc(),
             last_used_at: None,
+            label: None,
+            expires_at: None,
+            scopes: Vec::new(),
+            use_count: 0,
         };
 
         let regular_key = ApiKey {
@@ -489,6 +610,10 @@ mod tests {
             is_admin: false,
             created_at: chrono::Utc::now().naive_utc(),
             last_used_at: None,
+            label: None,
+            expires_at: None,
+            scopes: Vec::new(),
+            use_count: 0,
         };
 
         let admin_user = User { api_key: admin_key };
@@ -513,6 +638,10 @@ mod tests {
             is_admin: false,
             created_at: chrono::Utc::now().naive_utc(),
             last_used_at: None,
+            label: None,
+            expires_at: None,
+            scopes: Vec::new(),
+            use_count: 0,
         };
 
         assert!(cache.get("test_hash").is_none());
@@ -522,6 +651,12 @@ mod tests {
         assert_eq!(cached.unwrap().key_hash, "test_hash");
         cache.remove("test_hash");
         assert!(cache.get("test_hash").is_none());
+
+        cache.insert("test_hash".to_string(), api_key.clone());
+        cache.insert("other_hash".to_string(), api_key);
+        cache.clear();
+        assert!(cache.get("test_hash").is_none());
+        assert!(cache.get("other_hash").is_none());
     }
 
     #[test]
@@ -546,6 +681,10 @@ mod tests {
             is_admin: true,
             created_at: chrono::Utc::now().naive_utc(),
             last_used_at: None,
+            label: None,
+            expires_at: None,
+            scopes: Vec::new(),
+            use_count: 0,
         };
 
         let user = User { api_key };
@@ -554,4 +693,52 @@ mod tests {
         assert_eq!(admin_user.id(), user.id());
         assert_eq!(admin_user.is_admin(), user.is_admin());
     }
+
+    #[test]
+    fn test_key_filter() {
+        let now = chrono::Utc::now().naive_utc();
+        let api_key = ApiKey {
+            oid: ObjectId::new(),
+            key_hash: "hash".to_string(),
+            is_admin: false,
+            created_at: now,
+            last_used_at: None,
+            label: Some("Reading widget".to_string()),
+            expires_at: Some(now - chrono::Duration::days(1)),
+            scopes: vec![KeyScope::Books],
+            use_count: 3,
+        };
+
+        assert!(KeyFilter::default().matches(&api_key, now));
+        assert!(
+            KeyFilter {
+                admin: Some(false),
+                label: Some("widget".to_string()),
+                scope: Some(KeyScope::Books),
+                expired: Some(true),
+            }
+            .matches(&api_key, now)
+        );
+        assert!(
+            !KeyFilter {
+                admin: Some(true),
+                ..KeyFilter::default()
+            }
+            .matches(&api_key, now)
+        );
+        assert!(
+            !KeyFilter {
+                scope: Some(KeyScope::Games),
+                ..KeyFilter::default()
+            }
+            .matches(&api_key, now)
+        );
+        assert!(
+            !KeyFilter {
+                expired: Some(false),
+                ..KeyFilter::default()
+            }
+            .matches(&api_key, now)
+        );
+    }
 }
@@ -0,0 +1,645 @@
+//! # Catalog backups
+//!
+//! Exports every catalog collection, with revision history and webhook
+//! deliveries (and optionally the hashed API keys and webhook registrations,
+//! which hold signing secrets), and restores them into an empty or existing
+//! database.
+//!
+//! Documents are written as relaxed extended JSON so object ids and other BSON
+//! types survive the round trip. Two layouts are supported:
+//!
+//! - `ndjson`: a single stream whose first line is the manifest, followed by
+//!   one `{"collection": ..., "document": ...}` line per document
+//! - `csv`: a `manifest.json` file plus one `<collection>.csv` file per
+//!   collection, one column per top-level field
+//!
+//! The manifest records the backup format version and per-collection document
+//! counts, so truncated or newer backups are rejected before anything is written.
+
+use {
+    crate::{
+        errors::BackupError,
+        history::REVISIONS,
+        webhooks::{DELIVERIES, WEBHOOKS},
+    },
+    chrono::NaiveDateTime,
+    mongodb::bson::{Bson, Document, doc},
+    rocket::{
+        FromFormField,
+        futures::TryStreamExt,
+        serde::{Deserialize, Serialize},
+    },
+    rocket_db_pools::mongodb::{
+        Client,
+        options::{FindOptions, ReplaceOptions},
+    },
+    schemars::JsonSchema,
+    serde_json::{Value, json},
+    std::{collections::BTreeMap, str::FromStr},
+};
+
+/// Version of the backup layout written by this build.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Collections holding catalog data, its revisions and webhook deliveries.
+pub const CATALOG_COLLECTIONS: [&str; 11] = [
+    "taxonomy",
+    "series",
+    "books",
+    "games",
+    "projects",
+    "reviews",
+    "wplace_screenshots",
+    "play_sessions",
+    "progress_history",
+    REVISIONS,
+    DELIVERIES,
+];
+
+/// Collection holding hashed API keys.
+pub const API_KEYS: &str = "api_keys";
+
+/// Collections holding credentials, only exported on request: hashed API keys
+/// and webhook registrations with their plaintext signing secrets.
+pub const CREDENTIAL_COLLECTIONS: [&str; 2] = [API_KEYS, WEBHOOKS];
+
+/// File name of the manifest in the CSV layout.
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// How a backup is laid out on disk.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromFormField)]
+pub enum BackupFormat {
+    #[field(value = "ndjson")]
+    Ndjson,
+    #[field(value = "csv")]
+    Csv,
+}
+
+impl BackupFormat {
+    /// Every format name accepted on the command line.
+    pub const NAMES: [&'static str; 2] = ["ndjson", "csv"];
+}
+
+impl FromStr for BackupFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "ndjson" => Ok(BackupFormat::Ndjson),
+            "csv" => Ok(BackupFormat::Csv),
+            _ => Err(format!("unknown backup format `{}`", s)),
+        }
+    }
+}
+
+/// What to do when a restored document's `_id` already exists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, FromFormField)]
+pub enum ConflictPolicy {
+    /// Keep the existing document
+    #[field(value = "skip")]
+    Skip,
+    /// Replace the existing document
+    #[field(value = "overwrite")]
+    Overwrite,
+    /// Abort the restore before writing anything
+    #[default]
+    #[field(value = "fail")]
+    Fail,
+}
+
+impl ConflictPolicy {
+    /// Every policy name accepted on the command line.
+    pub const NAMES: [&'static str; 3] = ["skip", "overwrite", "fail"];
+}
+
+impl FromStr for ConflictPolicy {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "skip" => Ok(ConflictPolicy::Skip),
+            "overwrite" => Ok(ConflictPolicy::Overwrite),
+            "fail" => Ok(ConflictPolicy::Fail),
+            _ => Err(format!("unknown conflict policy `{}`", s)),
+        }
+    }
+}
+
+/// Document count of one exported collection.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct CollectionManifest {
+    pub name: String,
+    pub count: usize,
+}
+
+/// Describes the contents of a backup.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct Manifest {
+    /// Backup layout version
+    pub version: u32,
+    /// Version of the application that wrote the backup
+    pub app_version: String,
+    /// When the backup was taken
+    pub created_at: NaiveDateTime,
+    /// Whether hashed API keys and webhook secrets are included
+    pub includes_api_keys: bool,
+    /// Exported collections
+    pub collections: Vec<CollectionManifest>,
+}
+
+/// Documents restored into one collection.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct RestoreCounts {
+    pub inserted: usize,
+    pub overwritten: usize,
+    pub skipped: usize,
+}
+
+/// The outcome of a restore, per collection.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct RestoreReport {
+    pub policy: String,
+    pub collections: BTreeMap<String, RestoreCounts>,
+}
+
+/// A full or partial catalog snapshot.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Backup {
+    pub manifest: Manifest,
+    pub collections: BTreeMap<String, Vec<Document>>,
+}
+
+/// Lists the collections a backup covers.
+pub fn backup_collections(include_credentials: bool) -> Vec<&'static str> {
+    let mut names = CATALOG_COLLECTIONS.to_vec();
+    if include_credentials {
+        names.extend(CREDENTIAL_COLLECTIONS);
+    }
+    names
+}
+
+fn is_known_collection(name: &str) -> bool {
+    CREDENTIAL_COLLECTIONS.contains(&name) || CATALOG_COLLECTIONS.contains(&name)
+}
+
+/// Writes a CSV cell: plain text as-is, everything else as JSON.
+///
+/// Text that would itself parse as JSON (`5`, `true`, `"quoted"`) is quoted so
+/// it reads back as text.
+fn encode_cell(value: &Value) -> String {
+    match value {
+        Value::String(text) if !text.is_empty() && serde_json::from_str::<Value>(text).is_err() => {
+            text.clone()
+        }
+        _ => value.to_string(),
+    }
+}
+
+/// Reads a CSV cell written by [`encode_cell`]; empty cells are missing fields.
+fn decode_cell(cell: &str) -> Option<Value> {
+    (!cell.is_empty())
+        .then(|| serde_json::from_str(cell).unwrap_or_else(|_| Value::String(cell.to_string())))
+}
+
+fn to_extjson(document: &Document) -> Value {
+    Bson::Document(document.clone()).into_relaxed_extjson()
+}
+
+fn from_extjson(value: Value) -> Result<Document, BackupError> {
+    match Bson::try_from(value)? {
+        Bson::Document(document) => Ok(document),
+        other => Err(BackupError::InvalidBackup(format!(
+            "expected a document, found {}",
+            other
+        ))),
+    }
+}
+
+impl Backup {
+    /// Builds a backup from documents already in memory.
+    pub fn new(collections: BTreeMap<String, Vec<Document>>, includes_api_keys: bool) -> Self {
+        let manifest = Manifest {
+            version: FORMAT_VERSION,
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            created_at: chrono::Utc::now().naive_utc(),
+            includes_api_keys,
+            collections: collections
+                .iter()
+                .map(|(name, documents)| CollectionManifest {
+                    name: name.clone(),
+                    count: documents.len(),
+                })
+                .collect(),
+        };
+
+        Self {
+            manifest,
+            collections,
+        }
+    }
+
+    /// Reads the given collections from the database.
+    pub async fn export(client: &Client, names: &[&str]) -> Result<Self, BackupError> {
+        let database = client.database("bearodata");
+        let options = FindOptions::builder().sort(doc! { "_id": 1 }).build();
+
+        let mut collections = BTreeMap::new();
+        for name in names {
+            if !is_known_collection(name) {
+                return Err(BackupError::InvalidBackup(format!(
+                    "unknown collection `{}`",
+                    name
+                )));
+            }
+
+            let documents: Vec<Document> = database
+                .collection::<Document>(name)
+                .find(doc! {}, options.clone())
+                .await?
+                .try_collect()
+                .await?;
+            collections.insert(name.to_string(), documents);
+        }
+
+        Ok(Self::new(collections, names.contains(&API_KEYS)))
+    }
+
+    /// Checks the manifest against the documents actually present.
+    fn validate(&self) -> Result<(), BackupError> {
+        if self.manifest.version > FORMAT_VERSION {
+            return Err(BackupError::UnsupportedVersion(self.manifest.version));
+        }
+
+        for entry in &self.manifest.collections {
+            if !is_known_collection(&entry.name) {
+                return Err(BackupError::InvalidBackup(format!(
+                    "unknown collection `{}`",
+                    entry.name
+                )));
+            }
+
+            let found = self.collections.get(&entry.name).map_or(0, Vec::len);
+            if found != entry.count {
+                return Err(BackupError::InvalidBackup(format!(
+                    "`{}` should have {} documents, found {}",
+                    entry.name, entry.count, found
+                )));
+            }
+        }
+
+        if let Some(name) = self.collections.keys().find(|name| {
+            !self
+                .manifest
+                .collections
+                .iter()
+                .any(|entry| &entry.name == *name)
+        }) {
+            return Err(BackupError::InvalidBackup(format!(
+                "`{}` is missing from the manifest",
+                name
+            )));
+        }
+
+        Ok(())
+    }
+
+    /// Writes the NDJSON layout.
+    pub fn to_ndjson(&self) -> Result<String, BackupError> {
+        let mut output = serde_json::to_string(&json!({ "manifest": self.manifest }))?;
+        output.push('\n');
+
+        for (name, documents) in &self.collections {
+            for document in documents {
+                output.push_str(&serde_json::to_string(&json!({
+                    "collection": name,
+                    "document": to_extjson(document),
+                }))?);
+                output.push('\n');
+            }
+        }
+
+        Ok(output)
+    }
+
+    /// Reads the NDJSON layout.
+    pub fn from_ndjson(input: &str) -> Result<Self, BackupError> {
+        #[derive(Deserialize)]
+        #[serde(crate = "rocket::serde")]
+        struct Header {
+            manifest: Manifest,
+        }
+
+        #[derive(Deserialize)]
+        #[serde(crate = "rocket::serde")]
+        struct Record {
+            collection: String,
+            document: Value,
+        }
+
+        let mut lines = input.lines().filter(|line| !line.trim().is_empty());
+        let header = lines
+            .next()
+            .ok_or_else(|| BackupError::InvalidBackup("empty backup".to_string()))?;
+        let manifest = serde_json::from_str::<Header>(header)?.manifest;
+
+        let mut collections: BTreeMap<String, Vec<Document>> = manifest
+            .collections
+            .iter()
+            .map(|entry| (entry.name.clone(), Vec::new()))
+            .collect();
+        for line in lines {
+            let record: Record = serde_json::from_str(line)?;
+            collections
+                .entry(record.collection)
+                .or_default()
+                .push(from_extjson(record.document)?);
+        }
+
+        let backup = Self {
+            manifest,
+            collections,
+        };
+        backup.validate()?;
+        Ok(backup)
+    }
+
+    /// Writes one collection as CSV, one column per top-level field.
+    pub fn collection_to_csv(documents: &[Document]) -> Result<String, BackupError> {
+        let mut columns: Vec<String> = vec!["_id".to_string()];
+        for document in documents {
+            for key in document.keys() {
+                if !columns.contains(key) {
+                    columns.push(key.clone());
+                }
+            }
+        }
+
+        let mut writer = csv::Writer::from_writer(Vec::new());
+        writer.write_record(&columns)?;
+        for document in documents {
+            let Value::Object(fields) = to_extjson(document) else {
+                continue;
+            };
+            writer.write_record(
+                columns
+                    .iter()
+                    .map(|column| fields.get(column).map(encode_cell).unwrap_or_default()),
+            )?;
+        }
+
+        let bytes = writer
+            .into_inner()
+            .map_err(|e| BackupError::InvalidBackup(e.to_string()))?;
+        Ok(String::from_utf8_lossy(&bytes).into_owned())
+    }
+
+    /// Reads one collection written by [`Backup::collection_to_csv`].
+    pub fn collection_from_csv(input: &str) -> Result<Vec<Document>, BackupError> {
+        let mut reader = csv::Reader::from_reader(input.as_bytes());
+        let columns = reader.headers()?.clone();
+
+        let mut documents = Vec::new();
+        for record in reader.records() {
+            let record = record?;
+            let fields: serde_json::Map<String, Value> = columns
+                .iter()
+                .zip(record.iter())
+                .filter_map(|(column, cell)| {
+                    decode_cell(cell).map(|value| (column.to_string(), value))
+                })
+                .collect();
+            documents.push(from_extjson(Value::Object(fields))?);
+        }
+
+        Ok(documents)
+    }
+
+    /// Writes the CSV layout as a map of file name to contents.
+    pub fn to_csv_files(&self) -> Result<BTreeMap<String, String>, BackupError> {
+        let mut files = BTreeMap::new();
+        files.insert(
+            MANIFEST_FILE.to_string(),
+            serde_json::to_string_pretty(&self.manifest)?,
+        );
+
+        for (name, documents) in &self.collections {
+            files.insert(format!("{}.csv", name), Self::collection_to_csv(documents)?);
+        }
+
+        Ok(files)
+    }
+
+    /// Reads the CSV layout from a map of file name to contents.
+    pub fn from_csv_files(files: &BTreeMap<String, String>) -> Result<Self, BackupError> {
+        let manifest: Manifest =
+            serde_json::from_str(files.get(MANIFEST_FILE).ok_or_else(|| {
+                BackupError::InvalidBackup(format!("missing {}", MANIFEST_FILE))
+            })?)?;
+
+        let mut collections = BTreeMap::new();
+        for entry in &manifest.collections {
+            let file = format!("{}.csv", entry.name);
+            let input = files
+                .get(&file)
+                .ok_or_else(|| BackupError::InvalidBackup(format!("missing {}", file)))?;
+            collections.insert(entry.name.clone(), Self::collection_from_csv(input)?);
+        }
+
+        let backup = Self {
+            manifest,
+            collections,
+        };
+        backup.validate()?;
+        Ok(backup)
+    }
+
+    /// Writes the backup into the database.
+    ///
+    /// Documents are matched on `_id`. With [`ConflictPolicy::Fail`] every
+    /// collection is checked before anything is written, so a failed restore
+    /// leaves the database untouched.
+    ///
+    /// Servers cache API keys, so callers restoring `api_keys` must clear
+    /// the [`AuthService`](crate::auth::AuthService) cache afterwards.
+    pub async fn restore(
+        &self,
+        client: &Client,
+        policy: ConflictPolicy,
+    ) -> Result<RestoreReport, BackupError> {
+        self.validate()?;
+
+        let database = client.database("bearodata");
+        let mut existing: BTreeMap<&str, Vec<Bson>> = BTreeMap::new();
+
+        for (name, documents) in &self.collections {
+            let mut ids = Vec::with_capacity(documents.len());
+            for document in documents {
+                ids.push(document.get("_id").cloned().ok_or_else(|| {
+                    BackupError::InvalidBackup(format!("a `{}` document has no _id", name))
+                })?);
+            }
+
+            let found: Vec<Bson> = database
+                .collection::<Document>(name)
+                .find(
+                    doc! { "_id": { "$in": ids } },
+                    FindOptions::builder().projection(doc! { "_id": 1 }).build(),
+                )
+                .await?
+                .try_collect::<Vec<Document>>()
+                .await?
+                .into_iter()
+                .filter_map(|document| document.get("_id").cloned())
+                .collect();
+            existing.insert(name, found);
+        }
+
+        if policy == ConflictPolicy::Fail {
+            let conflicts: Vec<String> = existing
+                .iter()
+                .filter(|(_, ids)| !ids.is_empty())
+                .map(|(name, ids)| format!("{} in {}", ids.len(), name))
+                .collect();
+            if !conflicts.is_empty() {
+                return Err(BackupError::Conflict(conflicts.join(", ")));
+            }
+        }
+
+        let mut report = RestoreReport {
+            policy: match policy {
+                ConflictPolicy::Skip => "skip",
+                ConflictPolicy::Overwrite => "overwrite",
+                ConflictPolicy::Fail => "fail",
+            }
+            .to_string(),
+            ..Default::default()
+        };
+
+        for (name, documents) in &self.collections {
+            let collection = database.collection::<Document>(name);
+            let found = &existing[name.as_str()];
+            let mut counts = RestoreCounts::default();
+
+            let (conflicting, new): (Vec<&Document>, Vec<&Document>) = documents
+                .iter()
+                .partition(|document| document.get("_id").is_some_and(|id| found.contains(id)));
+
+            if !new.is_empty() {
+                collection.insert_many(new.iter().copied(), None).await?;
+                counts.inserted = new.len();
+            }
+
+            if policy == ConflictPolicy::Overwrite {
+                for document in &conflicting {
+                    collection
+                        .replace_one(
+                            doc! { "_id": document.get("_id").cloned() },
+                            *document,
+                            ReplaceOptions::builder().upsert(true).build(),
+                        )
+                        .await?;
+                }
+                counts.overwritten = conflicting.len();
+            } else {
+                counts.skipped = conflicting.len();
+            }
+
+            report.collections.insert(name.clone(), counts);
+        }
+
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::oid::ObjectId;
+
+    fn sample() -> Backup {
+        let mut collections = BTreeMap::new();
+        collections.insert(
+            "books".to_string(),
+            vec![
+                doc! {
+                    "_id": ObjectId::new(),
+                    "title": { "en": "Dune", "es": "Duna" },
+                    "rating": 5,
+                    "status": "true",
+                    "my_thoughts": "",
+                    "links": Bson::Null,
+                },
+                doc! { "_id": ObjectId::new(), "title": "Piranesi", "series_index": 1.5 },
+            ],
+        );
+        collections.insert("games".to_string(), vec![]);
+        Backup::new(collections, false)
+    }
+
+    #[test]
+    fn test_backup_collections() {
+        let names = backup_collections(false);
+        for name in ["books", "revisions", "webhook_deliveries"] {
+            assert!(names.contains(&name), "{} is not backed up", name);
+        }
+        for name in CREDENTIAL_COLLECTIONS {
+            assert!(!names.contains(&name), "{} is backed up by default", name);
+            assert!(backup_collections(true).contains(&name));
+        }
+    }
+
+    #[test]
+    fn test_ndjson_round_trip() {
+        let backup = sample();
+        let output = backup.to_ndjson().unwrap();
+
+        assert_eq!(output.lines().count(), 3);
+        assert_eq!(Backup::from_ndjson(&output).unwrap(), backup);
+    }
+
+    #[test]
+    fn test_csv_round_trip() {
+        let backup = sample();
+        let files = backup.to_csv_files().unwrap();
+
+        assert!(files["books.csv"].starts_with("_id,title,rating"));
+        assert_eq!(Backup::from_csv_files(&files).unwrap(), backup);
+    }
+
+    #[test]
+    fn test_rejects_invalid_backups() {
+        let mut backup = sample();
+        backup.manifest.version = FORMAT_VERSION + 1;
+        assert!(matches!(
+            Backup::from_ndjson(&backup.to_ndjson().unwrap()),
+            Err(BackupError::UnsupportedVersion(_))
+        ));
+
+        let output = sample().to_ndjson().unwrap();
+        let truncated: Vec<&str> = output.lines().take(2).collect();
+        assert!(matches!(
+            Backup::from_ndjson(&truncated.join("\n")),
+            Err(BackupError::InvalidBackup(_))
+        ));
+
+        let mut backup = sample();
+        backup.manifest.collections[0].name = "users".to_string();
+        assert!(matches!(
+            Backup::from_ndjson(&backup.to_ndjson().unwrap()),
+            Err(BackupError::InvalidBackup(_))
+        ));
+    }
+
+    #[test]
+    fn test_cell_encoding() {
+        for value in [json!("Dune"), json!("5"), json!(""), json!(5), json!(null)] {
+            assert_eq!(decode_cell(&encode_cell(&value)), Some(value));
+        }
+        assert_eq!(encode_cell(&json!("Dune")), "Dune");
+        assert_eq!(decode_cell(""), None);
+    }
+}
@@ -1,83 +1,840 @@
 //! # Command-line interface module
 //!
-//! This module provides CLI commands for managing API keys and other administrative tasks.
+//! This module defines the `apiodactyl` command line: the `serve` command that
+//! starts the HTTP server, and administrative commands that run against the
+//! database and exit.
 //!
 //! ## Available Commands
 //!
+//! - `serve`: Start the HTTP server
 //! - `create-admin-key`: Create a new admin API key
 //! - `list-admins`: List all admin API keys
+//! - `create-key`: Create an API key with a role, label, expiry and scopes
+//! - `list-keys`: List API keys, optionally filtered
+//! - `describe-key`: Show the details of one API key
 //! - `revoke-key`: Revoke an existing API key
+//! - `migrate-reviews`: Attach reviews without a parent work to a default work
+//! - `migrate-games`: Normalize game statuses and completion percentages
+//! - `migrate-timestamps`: Back-fill created/updated timestamps of existing documents
+//! - `migrate-review-index`: Let trashed reviews keep their chapter without blocking new reviews
+//! - `migrate-taxonomy-index`: Merge taxonomy terms sharing a slug and make slugs unique
+//! - `import-games`: Import a Steam, GOG Galaxy or Playnite library export
+//! - `import-books`: Import a Goodreads or StoryGraph library export
+//! - `export`: Back up the whole catalog as NDJSON or CSV
+//! - `import`: Restore a catalog backup
+//! - `books`, `games`, `projects`, `reviews`: List, show, create, edit, patch and
+//!   delete catalog entries
+//! - `receive-webhooks`: Run a local stand-in webhook receiver and print deliveries
+//! - `completions`: Print a shell completion script
 //!
-//! ## Usage
+//! ## Global Options
 //!
-//! The CLI is automatically invoked when command-line arguments are provided to the application.
+//! - `--database-url`: MongoDB connection string (see [`crate::config`])
+//! - `--config`: Rocket config file used by both the server and the commands
+//! - `--json`: Print command results as JSON
 
-use crate::auth::AuthService;
+use crate::auth::{AuthService, KeyFilter};
+use crate::backup::{API_KEYS, Backup, BackupFormat, ConflictPolicy, backup_collections};
+use crate::config;
+use crate::content::{self, Content, DocumentFormat, OutputFormat};
 use crate::db::BearoData;
-use clap::{Arg, Command};
+use crate::errors::{ContentError, MigrationError};
+use crate::importers::books::{BookExportFormat, BookImportPlan, import_book_export};
+use crate::importers::games::{GameImportPlan, GameLibraryFormat, import_library};
+use crate::migrations;
+use crate::models::{ApiKey, Book, Game, KeyScope, NewApiKey, Project, Review};
+use crate::webhooks::{self, ReceivedRequest};
+use chrono::{NaiveDate, NaiveDateTime};
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::{CommandFactory, Parser, Subcommand};
+use clap_complete::Shell;
+use mongodb::bson::oid::ObjectId;
+use rocket::figment::Figment;
+use serde::Serialize;
+use serde_json::json;
+use std::io::{BufRead, Read, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+/// Accepts one of `names` and parses it into `T`.
+fn one_of<T>(names: &'static [&'static str]) -> impl TypedValueParser<Value = T>
+where
+    T: FromStr + Clone + Send + Sync + 'static,
+    T::Err: std::fmt::Debug,
+{
+    PossibleValuesParser::new(names.iter().copied())
+        .map(|name| name.parse().expect("every listed name parses"))
+}
+
+/// The `apiodactyl` command line.
+#[derive(Parser, Debug)]
+#[command(
+    name = "apiodactyl",
+    version,
+    about = "A RESTful API for data management on bearodata.dev"
+)]
+pub struct Cli {
+    /// MongoDB connection string; overrides DATABASE_URL, MONGODB_URL and the config file
+    #[arg(long, global = true, value_name = "URL")]
+    pub database_url: Option<String>,
+
+    /// Rocket config file (defaults to ROCKET_CONFIG or Rocket.toml)
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    /// Print command results as JSON
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    #[command(subcommand)]
+    pub command: Commands,
+}
+
+impl Cli {
+    /// The configuration shared by the server and the commands.
+    pub fn figment(&self) -> Figment {
+        config::figment(self.config.as_deref(), self.database_url.as_deref())
+    }
+}
+
+/// Top-level commands.
+#[derive(Subcommand, Debug)]
+pub enum Commands {
+    /// Start the HTTP server
+    Serve,
+
+    /// Create a new admin API key
+    CreateAdminKey {
+        /// Custom key (optional, will generate if not provided)
+        #[arg(long, value_name = "KEY")]
+        key: Option<String>,
+    },
+
+    /// List all admin API keys
+    ListAdmins,
+
+    /// Create a new API key
+    CreateKey {
+        /// Custom key (optional, will generate if not provided)
+        #[arg(long, value_name = "KEY")]
+        key: Option<String>,
+
+        /// Grant admin privileges
+        #[arg(long)]
+        admin: bool,
+
+        /// What or who the key is for
+        #[arg(long, value_name = "TEXT")]
+        label: Option<String>,
+
+        /// Expiry as a duration (12h, 30d, 2w) or a date (2026-12-31)
+        #[arg(long, value_name = "WHEN")]
+        expires: Option<Expiry>,
+
+        /// Limit the key to an area of the API; may be repeated
+        #[arg(long, value_name = "SCOPE", value_parser = one_of::<KeyScope>(&KeyScope::NAMES))]
+        scope: Vec<KeyScope>,
+    },
+
+    /// List API keys
+    ListKeys {
+        /// Only admin keys
+        #[arg(long, conflicts_with = "regular")]
+        admin: bool,
+
+        /// Only regular keys
+        #[arg(long)]
+        regular: bool,
+
+        /// Only keys whose label contains this text
+        #[arg(long, value_name = "TEXT")]
+        label: Option<String>,
+
+        /// Only keys that may access this scope
+        #[arg(long, value_name = "SCOPE", value_parser = one_of::<KeyScope>(&KeyScope::NAMES))]
+        scope: Option<KeyScope>,
+
+        /// Only expired keys
+        #[arg(long, conflicts_with = "active")]
+        expired: bool,
+
+        /// Only keys that have not expired
+        #[arg(long)]
+        active: bool,
+    },
+
+    /// Show the details of an API key
+    DescribeKey {
+        /// ObjectId of the key, or the key itself
+        #[arg(value_name = "KEY_OR_ID")]
+        key: String,
+    },
+
+    /// Revoke an API key
+    RevokeKey {
+        /// The API key to revoke
+        #[arg(long, value_name = "KEY")]
+        key: String,
+    },
+
+    /// Attach reviews without a work to a default work
+    MigrateReviews {
+        /// ObjectId of the default work (defaults to DEFAULT_REVIEW_WORK)
+        #[arg(long, value_name = "ID")]
+        work: Option<String>,
+    },
+
+    /// Normalize game statuses and completion percentages
+    MigrateGames,
+
+    /// Back-fill created/updated timestamps of existing documents
+    MigrateTimestamps,
+
+    /// Limit the unique chapter review index to reviews that are not in the trash
+    MigrateReviewIndex,
+
+    /// Merge taxonomy terms sharing a slug and make slugs unique per kind
+    MigrateTaxonomyIndex,
+
+    /// Import a game library export (dry run unless --apply is given)
+    ImportGames {
+        /// Export format
+        #[arg(long, value_name = "FORMAT", value_parser = one_of::<GameLibraryFormat>(&GameLibraryFormat::NAMES))]
+        format: GameLibraryFormat,
+
+        /// Write the planned changes to the database
+        #[arg(long)]
+        apply: bool,
+
+        /// Export files; Steam appmanifests may follow libraryfolders.vdf
+        #[arg(value_name = "FILE", required = true)]
+        files: Vec<PathBuf>,
+    },
+
+    /// Import a book library export (dry run unless --apply is given)
+    ImportBooks {
+        /// Export format
+        #[arg(long, value_name = "FORMAT", value_parser = one_of::<BookExportFormat>(&BookExportFormat::NAMES))]
+        format: BookExportFormat,
+
+        /// Update rating, status, progress and thoughts of books already in the catalog
+        #[arg(long)]
+        update: bool,
+
+        /// Write the planned changes to the database
+        #[arg(long)]
+        apply: bool,
+
+        /// CSV export file
+        #[arg(value_name = "FILE")]
+        file: PathBuf,
+    },
+
+    /// Back up the whole catalog
+    Export {
+        /// Backup format; csv writes a directory
+        #[arg(long, value_name = "FORMAT", default_value = "ndjson", value_parser = one_of::<BackupFormat>(&BackupFormat::NAMES))]
+        format: BackupFormat,
+
+        /// Include hashed API keys and webhooks with their signing secrets
+        #[arg(long)]
+        include_keys: bool,
+
+        /// Output file (ndjson) or directory (csv)
+        #[arg(value_name = "PATH")]
+        output: PathBuf,
+    },
+
+    /// Restore a catalog backup
+    Import {
+        /// What to do with documents that already exist
+        #[arg(long, value_name = "POLICY", default_value = "fail", value_parser = one_of::<ConflictPolicy>(&ConflictPolicy::NAMES))]
+        policy: ConflictPolicy,
+
+        /// Backup file (ndjson) or directory (csv)
+        #[arg(value_name = "PATH")]
+        input: PathBuf,
+    },
+
+    /// Manage books directly in the database
+    Books {
+        #[command(subcommand)]
+        action: ContentAction,
+    },
+
+    /// Manage games directly in the database
+    Games {
+        #[command(subcommand)]
+        action: ContentAction,
+    },
+
+    /// Manage projects directly in the database
+    Projects {
+        #[command(subcommand)]
+        action: ContentAction,
+    },
+
+    /// Manage reviews directly in the database
+    Reviews {
+        #[command(subcommand)]
+        action: ContentAction,
+    },
+
+    /// Listen on localhost and print webhook deliveries as they arrive
+    ReceiveWebhooks {
+        /// Port to listen on
+        #[arg(long, value_name = "PORT", default_value_t = 8787)]
+        port: u16,
+
+        /// Webhook secret used to verify signatures
+        #[arg(long, value_name = "SECRET")]
+        secret: Option<String>,
+
+        /// Status code to answer with, e.g. 500 to exercise retries
+        #[arg(long, value_name = "CODE", default_value_t = 200, value_parser = clap::value_parser!(u16).range(100..600))]
+        status: u16,
+    },
+
+    /// Print a shell completion script
+    Completions {
+        /// Target shell
+        #[arg(value_name = "SHELL")]
+        shell: Shell,
+    },
+}
+
+/// Actions of the `books`, `games`, `projects` and `reviews` commands.
+#[derive(Subcommand, Debug)]
+pub enum ContentAction {
+    /// List entries
+    List {
+        /// Maximum number of entries
+        #[arg(long, value_name = "N", value_parser = clap::value_parser!(i64).range(1..))]
+        limit: Option<i64>,
+
+        /// Output format
+        #[arg(long, short, value_name = "FORMAT", default_value = "table", value_parser = one_of::<OutputFormat>(&OutputFormat::NAMES))]
+        output: OutputFormat,
+    },
+
+    /// Show one entry
+    Show {
+        /// ObjectId of the entry
+        #[arg(value_name = "ID")]
+        id: String,
+
+        /// Output format
+        #[arg(long, short, value_name = "FORMAT", default_value = "table", value_parser = one_of::<OutputFormat>(&OutputFormat::NAMES))]
+        output: OutputFormat,
+    },
+
+    /// Create an entry from a JSON/YAML file, stdin (-) or $EDITOR
+    Create {
+        /// Document file; opens $EDITOR on a template if omitted
+        #[arg(value_name = "FILE")]
+        file: Option<String>,
+
+        /// Document format (defaults to the file extension, or yaml in the editor)
+        #[arg(long, value_name = "FORMAT", value_parser = one_of::<DocumentFormat>(&DocumentFormat::NAMES))]
+        format: Option<DocumentFormat>,
+
+        /// Output format
+        #[arg(long, short, value_name = "FORMAT", default_value = "table", value_parser = one_of::<OutputFormat>(&OutputFormat::NAMES))]
+        output: OutputFormat,
+    },
+
+    /// Edit an entry in $EDITOR and save every field
+    Edit {
+        /// ObjectId of the entry
+        #[arg(value_name = "ID")]
+        id: String,
+
+        /// Document format
+        #[arg(long, value_name = "FORMAT", default_value = "yaml", value_parser = one_of::<DocumentFormat>(&DocumentFormat::NAMES))]
+        format: DocumentFormat,
+
+        /// Output format
+        #[arg(long, short, value_name = "FORMAT", default_value = "table", value_parser = one_of::<OutputFormat>(&OutputFormat::NAMES))]
+        output: OutputFormat,
+    },
+
+    /// Update the fields of an entry given in a JSON/YAML file or stdin (-)
+    Patch {
+        /// ObjectId of the entry
+        #[arg(value_name = "ID")]
+        id: String,
+
+        /// Partial document
+        #[arg(value_name = "FILE")]
+        file: String,
+
+        /// Document format (defaults to the file extension)
+        #[arg(long, value_name = "FORMAT", value_parser = one_of::<DocumentFormat>(&DocumentFormat::NAMES))]
+        format: Option<DocumentFormat>,
+
+        /// Locale localized book fields are flattened to, as with Accept-Language
+        #[arg(long, value_name = "LOCALE")]
+        locale: Option<String>,
+
+        /// Output format
+        #[arg(long, short, value_name = "FORMAT", default_value = "table", value_parser = one_of::<OutputFormat>(&OutputFormat::NAMES))]
+        output: OutputFormat,
+    },
+
+    /// Delete an entry
+    Delete {
+        /// ObjectId of the entry
+        #[arg(value_name = "ID")]
+        id: String,
+
+        /// Do not ask for confirmation
+        #[arg(long, short)]
+        yes: bool,
+    },
+}
 
 /// Builds the CLI command structure.
 ///
 /// # Returns
 ///
-/// A configured clap Command with all available subcommands.
-pub fn cli() -> Command {
-    Command::new("your-app")
-        .subcommand(
-            Command::new("create-admin-key")
-                .about("Create a new admin API key")
-                .arg(
-                    Arg::new("key")
-                        .long("key")
-                        .help("Custom key (optional, will generate if not provided)")
-                        .value_name("KEY"),
-                ),
-        )
-        .subcommand(Command::new("list-admins").about("List all admin API keys"))
-        .subcommand(
-            Command::new("revoke-key").about("Revoke an API key").arg(
-                Arg::new("key")
-                    .long("key")
-                    .help("The API key to revoke")
-                    .value_name("KEY")
-                    .required(true),
-            ),
-        )
-}
-
-/// Handles CLI command execution.
+/// The clap Command with all available subcommands, e.g. for generating completions.
+pub fn cli() -> clap::Command {
+    Cli::command()
+}
+
+/// Value of `create-key --expires`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expiry {
+    /// Expires this long after the key is created
+    In(chrono::Duration),
+    /// Expires at this time (UTC)
+    At(NaiveDateTime),
+}
+
+impl Expiry {
+    /// Returns the expiry time for a key created at `now`.
+    pub fn resolve(self, now: NaiveDateTime) -> NaiveDateTime {
+        match self {
+            Expiry::In(duration) => now + duration,
+            Expiry::At(at) => at,
+        }
+    }
+}
+
+impl FromStr for Expiry {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            format!(
+                "invalid expiry `{}`; use e.g. 12h, 30d, 2w or 2026-12-31",
+                s
+            )
+        };
+
+        if let Some(unit) = s.chars().last().filter(char::is_ascii_alphabetic)
+            && let Ok(amount) = s[..s.len() - 1].parse::<u32>()
+        {
+            let amount = i64::from(amount);
+            return match unit {
+                'h' => Ok(Expiry::In(chrono::Duration::hours(amount))),
+                'd' => Ok(Expiry::In(chrono::Duration::days(amount))),
+                'w' => Ok(Expiry::In(chrono::Duration::weeks(amount))),
+                _ => Err(invalid()),
+            };
+        }
+
+        if let Ok(date) = NaiveDate::parse_from_str(s, "%Y-%m-%d") {
+            return Ok(Expiry::At(date.and_time(chrono::NaiveTime::MIN)));
+        }
+
+        chrono::DateTime::parse_from_rfc3339(s)
+            .map(|at| Expiry::At(at.naive_utc()))
+            .map_err(|_| invalid())
+    }
+}
+
+/// Formats an optional timestamp for the key tables.
+fn format_time(time: Option<NaiveDateTime>, missing: &str) -> String {
+    time.map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+        .unwrap_or_else(|| missing.to_string())
+}
+
+/// Lists the scopes of a key, or `all` for an unrestricted key.
+fn scopes_text(api_key: &ApiKey) -> String {
+    if api_key.scopes.is_empty() {
+        "all".to_string()
+    } else {
+        api_key
+            .scopes
+            .iter()
+            .map(KeyScope::as_str)
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+}
+
+/// An API key as printed by `--json`, without its hash.
+fn key_json(api_key: &ApiKey) -> serde_json::Value {
+    json!({
+        "id": api_key.oid.to_hex(),
+        "is_admin": api_key.is_admin,
+        "label": api_key.label,
+        "scopes": api_key.scopes,
+        "created_at": api_key.created_at,
+        "expires_at": api_key.expires_at,
+        "last_used_at": api_key.last_used_at,
+        "use_count": api_key.use_count,
+    })
+}
+
+/// Prints the details of one API key.
+fn print_key_details(api_key: &ApiKey) {
+    let now = chrono::Utc::now().naive_utc();
+
+    println!("ID: {}", api_key.oid);
+    println!(
+        "Role: {}",
+        if api_key.is_admin { "admin" } else { "regular" }
+    );
+    println!("Label: {}", api_key.label.as_deref().unwrap_or("-"));
+    println!("Scopes: {}", scopes_text(api_key));
+    println!("Created at: {}", api_key.created_at);
+    println!("Expires: {}", format_time(api_key.expires_at, "Never"));
+    println!(
+        "Status: {}",
+        if api_key.is_expired(now) {
+            "expired"
+        } else {
+            "active"
+        }
+    );
+    println!("Uses: {}", api_key.use_count);
+    println!("Last used: {}", format_time(api_key.last_used_at, "Never"));
+}
+
+/// Prints a value as pretty JSON.
+fn print_json<T: Serialize>(value: &T) -> Result<(), Box<dyn std::error::Error>> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+/// Prints a webhook delivery received by `receive-webhooks`.
+///
+/// The signature is reported as unchecked when no secret is given.
+fn print_webhook_request(
+    request: &ReceivedRequest,
+    secret: Option<&str>,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let event = request.header(webhooks::EVENT_HEADER).unwrap_or("-");
+    let delivery = request.header(webhooks::DELIVERY_HEADER).unwrap_or("-");
+    let signature = match (secret, request.header(webhooks::SIGNATURE_HEADER)) {
+        (None, _) => "unchecked",
+        (Some(_), None) => "missing",
+        (Some(secret), Some(signature)) => {
+            if webhooks::verify_signature(secret, &request.body, signature) {
+                "valid"
+            } else {
+                "invalid"
+            }
+        }
+    };
+    let body = String::from_utf8_lossy(&request.body);
+
+    if json {
+        let payload: serde_json::Value =
+            serde_json::from_str(&body).unwrap_or_else(|_| json!(body));
+        println!(
+            "{}",
+            json!({
+                "event": event,
+                "delivery": delivery,
+                "signature": signature,
+                "body": payload,
+            })
+        );
+    } else {
+        println!("{} {} [{}]", event, delivery, signature);
+        println!("{}", body);
+    }
+    Ok(())
+}
+
+/// Prints an import plan, one line per game.
+fn print_game_plan(plan: &GameImportPlan) {
+    for game in &plan.create {
+        println!("+ {} ({})", game.title, game.developer);
+    }
+    for update in &plan.update {
+        let changes: Vec<String> = update
+            .changes
+            .iter()
+            .map(|change| format!("{}: {} -> {}", change.field, change.from, change.to))
+            .collect();
+        println!("~ {} [{}]", update.title, changes.join(", "));
+    }
+    for skipped in &plan.skipped {
+        println!("! {}: {}", skipped.source, skipped.reason);
+    }
+    println!(
+        "{} to create, {} to update, {} unchanged, {} skipped",
+        plan.create.len(),
+        plan.update.len(),
+        plan.unchanged.len(),
+        plan.skipped.len()
+    );
+}
+
+/// Prints an import plan, one line per book.
+fn print_book_plan(plan: &BookImportPlan) {
+    for book in &plan.create {
+        println!(
+            "+ {} ({})",
+            book.title.get_text(None),
+            book.author.get_text(None)
+        );
+    }
+    for update in &plan.update {
+        let changes: Vec<&str> = update
+            .changes
+            .iter()
+            .map(|change| change.field.as_ref())
+            .collect();
+        println!("~ {} [{}]", update.title, changes.join(", "));
+    }
+    for skipped in &plan.skipped {
+        println!("! {}: {}", skipped.source, skipped.reason);
+    }
+    for warning in &plan.warnings {
+        println!("? {}: {}", warning.source, warning.reason);
+    }
+    println!(
+        "{} to create, {} to update, {} unchanged, {} skipped",
+        plan.create.len(),
+        plan.update.len(),
+        plan.unchanged.len(),
+        plan.skipped.len()
+    );
+}
+
+/// Reads a document from `path`, or from stdin when `path` is `-`.
+fn read_document(
+    path: &str,
+    format: Option<DocumentFormat>,
+) -> std::io::Result<(String, DocumentFormat)> {
+    if path == "-" {
+        let mut input = String::new();
+        std::io::stdin().read_to_string(&mut input)?;
+        Ok((input, format.unwrap_or(DocumentFormat::Json)))
+    } else {
+        let format = format.unwrap_or_else(|| DocumentFormat::from_path(Path::new(path)));
+        Ok((std::fs::read_to_string(path)?, format))
+    }
+}
+
+/// Prints a single document in the requested output format.
+fn print_document<T: Content>(
+    item: &T,
+    output: OutputFormat,
+) -> Result<(), Box<dyn std::error::Error>> {
+    match output {
+        OutputFormat::Table => print!("{}", content::details(item)?),
+        OutputFormat::Json => print_json(item)?,
+    }
+    Ok(())
+}
+
+/// Asks for confirmation on stdin.
+fn confirm(prompt: &str) -> std::io::Result<bool> {
+    print!("{} [y/N] ", prompt);
+    std::io::stdout().flush()?;
+
+    let mut answer = String::new();
+    std::io::stdin().lock().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+/// The document a content action prints on success, and in which format.
+type Printed<T> = Option<(T, OutputFormat)>;
+
+/// Runs a content action (`books list`, `games edit`, ...) for one document type.
 ///
-/// Processes the command-line arguments and executes the appropriate command.
+/// `--json` overrides the action's `--output` format.
+async fn handle_content<T: Content>(
+    name: &str,
+    action: ContentAction,
+    db: &BearoData,
+    json: bool,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let output_override = |output: OutputFormat| if json { OutputFormat::Json } else { output };
+
+    let (verb, result): (&str, Result<Printed<T>, ContentError>) = match action {
+        ContentAction::List { limit, output } => (
+            "list",
+            async {
+                let items = content::list::<T>(db, limit).await?;
+                match output_override(output) {
+                    OutputFormat::Table => print!("{}", content::table(&items)),
+                    OutputFormat::Json => println!("{}", serde_json::to_string_pretty(&items)?),
+                }
+                Ok(None)
+            }
+            .await,
+        ),
+        ContentAction::Show { id, output } => (
+            "show",
+            async {
+                let item = content::find::<T>(db, content::parse_id(&id)?).await?;
+                Ok(Some((item, output)))
+            }
+            .await,
+        ),
+        ContentAction::Create {
+            file,
+            format,
+            output,
+        } => (
+            "create",
+            async {
+                let (input, format) = match file {
+                    Some(path) => read_document(&path, format)?,
+                    None => {
+                        let format = format.unwrap_or(DocumentFormat::Yaml);
+                        let template = format.render(&T::template())?;
+                        (content::edit_text(&template, format)?, format)
+                    }
+                };
+                let new = format.parse::<T::New>(&input)?;
+                Ok(Some((T::create(db, new).await?, output)))
+            }
+            .await,
+        ),
+        ContentAction::Edit { id, format, output } => (
+            "edit",
+            async {
+                let oid = content::parse_id(&id)?;
+                let current = content::find::<T>(db, oid).await?;
+                let original = format.render(&current)?;
+                let edited = content::edit_text(&original, format)?;
+
+                if edited == original {
+                    println!("no changes.");
+                    return Ok(None);
+                }
+                let update = format.parse::<T::Update>(&edited)?;
+                Ok(Some((T::replace(db, oid, update).await?, output)))
+            }
+            .await,
+        ),
+        ContentAction::Patch {
+            id,
+            file,
+            format,
+            locale,
+            output,
+        } => (
+            "patch",
+            async {
+                let oid = content::parse_id(&id)?;
+                let (input, format) = read_document(&file, format)?;
+                let update = format.parse::<T::Update>(&input)?;
+                let item = T::patch(db, oid, update, locale.as_deref()).await?;
+                Ok(Some((item, output)))
+            }
+            .await,
+        ),
+        ContentAction::Delete { id, yes } => (
+            "delete",
+            async {
+                let oid = content::parse_id(&id)?;
+                if !yes && !confirm(&format!("delete {} {}?", name, oid))? {
+                    println!("aborted.");
+                    return Ok(None);
+                }
+                T::delete(db, oid).await?;
+                if json {
+                    println!("{}", json!({ "deleted": oid.to_hex() }));
+                } else {
+                    println!("deleted {}.", oid);
+                }
+                Ok(None)
+            }
+            .await,
+        ),
+    };
+
+    match result {
+        Ok(Some((item, output))) => print_document(&item, output_override(output)),
+        Ok(None) => Ok(()),
+        Err(e) => {
+            eprintln!("failed to {} {}: {}", verb, name, e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Runs a command other than `serve`.
 ///
 /// # Arguments
 ///
+/// * `cli` - The parsed command line
 /// * `auth_service` - The authentication service for managing API keys
 ///
 /// # Returns
 ///
 /// Ok(()) on success, or an error if command execution fails.
-pub async fn handle_cli(auth_service: AuthService) -> Result<(), Box<dyn std::error::Error>> {
-    let matches = cli().get_matches();
-
-    match matches.subcommand() {
-        Some(("create-admin-key", sub_matches)) => {
-            let key = match sub_matches.get_one::<String>("key") {
-                Some(custom_key) => custom_key.clone(),
-                None => AuthService::generate_api_key(),
-            };
+pub async fn run(cli: Cli, auth_service: AuthService) -> Result<(), Box<dyn std::error::Error>> {
+    let figment = cli.figment();
+    let json = cli.json;
+
+    match cli.command {
+        Commands::Serve => unreachable!("`serve` is handled by the binary"),
+        Commands::Completions { shell } => {
+            clap_complete::generate(
+                shell,
+                &mut Cli::command(),
+                "apiodactyl",
+                &mut std::io::stdout(),
+            );
+        }
+        Commands::ReceiveWebhooks {
+            port,
+            secret,
+            status,
+        } => {
+            let listener = tokio::net::TcpListener::bind(("127.0.0.1", port)).await?;
+            eprintln!("Listening on http://{}", listener.local_addr()?);
 
-            let db = create_db_connection().await?;
+            loop {
+                let request = webhooks::receive(&listener, status).await?;
+                print_webhook_request(&request, secret.as_deref(), json)?;
+            }
+        }
+        Commands::CreateAdminKey { key } => {
+            let key = key.unwrap_or_else(AuthService::generate_api_key);
+
+            let db = create_db_connection(&figment).await?;
 
             match auth_service.create_api_key(&key, true, &db).await {
                 Ok(api_key) => {
-                    println!("admin API key created successfully!");
-                    println!("Key: {}", key);
-                    println!("ID: {}", api_key.oid);
-                    println!("Created at: {}", api_key.created_at);
-
                     let all_keys = auth_service.list_api_keys(&db).await?;
                     let admin_count = all_keys.iter().filter(|k| k.is_admin).count();
-                    println!("Total admin keys: {}", admin_count);
+
+                    if json {
+                        print_json(&json!({
+                            "key": key,
+                            "id": api_key.oid.to_hex(),
+                            "created_at": api_key.created_at,
+                            "admin_count": admin_count,
+                        }))?;
+                    } else {
+                        println!("admin API key created successfully!");
+                        println!("Key: {}", key);
+                        println!("ID: {}", api_key.oid);
+                        println!("Created at: {}", api_key.created_at);
+                        println!("Total admin keys: {}", admin_count);
+                    }
                 }
                 Err(e) => {
                     eprintln!("failed to create admin key: {}", e);
@@ -85,12 +842,24 @@ pub async fn handle_cli(auth_service: AuthService) -> Result<(), Box<dyn std::er
                 }
             }
         }
-        Some(("list-admins", _)) => {
-            let db = create_db_connection().await?;
+        Commands::ListAdmins => {
+            let db = create_db_connection(&figment).await?;
             let all_keys = auth_service.list_api_keys(&db).await?;
             let admin_keys: Vec<_> = all_keys.iter().filter(|k| k.is_admin).collect();
 
-            if admin_keys.is_empty() {
+            if json {
+                let keys: Vec<_> = admin_keys
+                    .iter()
+                    .map(|key| {
+                        json!({
+                            "id": key.oid.to_hex(),
+                            "created_at": key.created_at,
+                            "last_used_at": key.last_used_at,
+                        })
+                    })
+                    .collect();
+                print_json(&keys)?;
+            } else if admin_keys.is_empty() {
                 println!("no admin keys found.");
             } else {
                 println!("Admin API Keys ({} total):", admin_keys.len());
@@ -112,23 +881,127 @@ pub async fn handle_cli(auth_service: AuthService) -> Result<(), Box<dyn std::er
                 }
             }
         }
-        Some(("revoke-key", sub_matches)) => {
-            let key = sub_matches.get_one::<String>("key").unwrap();
-            let db = create_db_connection().await?;
+        Commands::CreateKey {
+            key,
+            admin,
+            label,
+            expires,
+            scope,
+        } => {
+            let key = key.unwrap_or_else(AuthService::generate_api_key);
+            let options = NewApiKey {
+                is_admin: admin,
+                label,
+                expires_at: expires.map(|expires| expires.resolve(chrono::Utc::now().naive_utc())),
+                scopes: scope,
+            };
 
-            match auth_service.revoke_api_key(key, &db).await {
-                Ok(()) => {
-                    println!("api key revoked successfully!");
+            let db = create_db_connection(&figment).await?;
 
+            match auth_service.create_api_key_with(&key, options, &db).await {
+                Ok(api_key) if json => {
+                    let mut output = key_json(&api_key);
+                    output["key"] = json!(key);
+                    print_json(&output)?;
+                }
+                Ok(api_key) => {
+                    println!("API key created successfully!");
+                    println!("Key: {}", key);
+                    print_key_details(&api_key);
+                }
+                Err(e) => {
+                    eprintln!("failed to create key: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::ListKeys {
+            admin,
+            regular,
+            label,
+            scope,
+            expired,
+            active,
+        } => {
+            let filter = KeyFilter {
+                admin: (admin || regular).then_some(admin),
+                label,
+                scope,
+                expired: (expired || active).then_some(expired),
+            };
+            let now = chrono::Utc::now().naive_utc();
+
+            let db = create_db_connection(&figment).await?;
+            let mut keys: Vec<_> = auth_service
+                .list_api_keys(&db)
+                .await?
+                .into_iter()
+                .filter(|key| filter.matches(key, now))
+                .collect();
+            keys.sort_by_key(|key| key.created_at);
+
+            if json {
+                print_json(&keys.iter().map(key_json).collect::<Vec<_>>())?;
+            } else if keys.is_empty() {
+                println!("no keys found.");
+            } else {
+                println!("API Keys ({} total):", keys.len());
+                println!(
+                    "{:<25} {:<20} {:<8} {:<20} {:<20} {:>6} {:<20}",
+                    "ID", "Label", "Role", "Scopes", "Expires", "Uses", "Last Used"
+                );
+                println!("{}", "-".repeat(125));
+
+                for key in keys {
+                    let mut label = key.label.clone().unwrap_or_else(|| "-".to_string());
+                    if label.chars().count() > 20 {
+                        label = format!("{}…", label.chars().take(19).collect::<String>());
+                    }
+
+                    println!(
+                        "{:<25} {:<20} {:<8} {:<20} {:<20} {:>6} {:<20}",
+                        key.oid.to_hex(),
+                        label,
+                        if key.is_admin { "admin" } else { "regular" },
+                        scopes_text(&key),
+                        format_time(key.expires_at, "Never"),
+                        key.use_count,
+                        format_time(key.last_used_at, "Never"),
+                    );
+                }
+            }
+        }
+        Commands::DescribeKey { key } => {
+            let db = create_db_connection(&figment).await?;
+
+            match auth_service.find_api_key(&key, &db).await {
+                Ok(api_key) if json => print_json(&key_json(&api_key))?,
+                Ok(api_key) => print_key_details(&api_key),
+                Err(e) => {
+                    eprintln!("failed to describe key: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::RevokeKey { key } => {
+            let db = create_db_connection(&figment).await?;
+
+            match auth_service.revoke_api_key(&key, &db).await {
+                Ok(()) => {
                     let all_keys = auth_service.list_api_keys(&db).await?;
                     let admin_count = all_keys.iter().filter(|k| k.is_admin).count();
 
-                    if admin_count == 0 {
-                        println!(
-                            "[WARN]: No admin keys remain! You may need to use BOOTSTRAP_ADMIN_KEY."
-                        );
+                    if json {
+                        print_json(&json!({ "revoked": true, "admin_count": admin_count }))?;
                     } else {
-                        println!("remaining admin keys: {}", admin_count);
+                        println!("api key revoked successfully!");
+                        if admin_count == 0 {
+                            println!(
+                                "[WARN]: No admin keys remain! You may need to use BOOTSTRAP_ADMIN_KEY."
+                            );
+                        } else {
+                            println!("remaining admin keys: {}", admin_count);
+                        }
                     }
                 }
                 Err(e) => {
@@ -137,8 +1010,249 @@ pub async fn handle_cli(auth_service: AuthService) -> Result<(), Box<dyn std::er
                 }
             }
         }
-        _ => {
-            cli().print_help()?;
+        Commands::MigrateReviews { work } => {
+            let work_id = match work {
+                Some(id) => Some(ObjectId::parse_str(id)?),
+                None => migrations::default_review_work(),
+            }
+            .ok_or(MigrationError::MissingWork)?;
+
+            let db = create_db_connection(&figment).await?;
+
+            match migrations::migrate_review_works(&db, work_id).await {
+                Ok(migrated) => {
+                    if json {
+                        print_json(&json!({ "work": work_id.to_hex(), "migrated": migrated }))?;
+                    } else {
+                        println!("reviews migrated successfully!");
+                        println!("Reviews assigned to {}: {}", work_id, migrated);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("failed to migrate reviews: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::MigrateGames => {
+            let db = create_db_connection(&figment).await?;
+
+            match migrations::migrate_game_status(&db).await {
+                Ok(migrated) => {
+                    if json {
+                        print_json(&json!({ "migrated": migrated }))?;
+                    } else {
+                        println!("games migrated successfully!");
+                        println!("Games updated: {}", migrated);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("failed to migrate games: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::MigrateTimestamps => {
+            let db = create_db_connection(&figment).await?;
+
+            match migrations::migrate_timestamps(&db).await {
+                Ok(migrated) => {
+                    if json {
+                        print_json(&json!({ "migrated": migrated }))?;
+                    } else {
+                        println!("timestamps migrated successfully!");
+                        println!("Documents updated: {}", migrated);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("failed to migrate timestamps: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::MigrateReviewIndex => {
+            let db = create_db_connection(&figment).await?;
+
+            match migrations::migrate_review_index(&db).await {
+                Ok(migrated) => {
+                    if json {
+                        print_json(&json!({ "migrated": migrated }))?;
+                    } else {
+                        println!("review index migrated successfully!");
+                        println!("Reviews updated: {}", migrated);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("failed to migrate the review index: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::MigrateTaxonomyIndex => {
+            let db = create_db_connection(&figment).await?;
+
+            match migrations::migrate_taxonomy_index(&db).await {
+                Ok(migrated) => {
+                    if json {
+                        print_json(&json!({ "migrated": migrated }))?;
+                    } else {
+                        println!("taxonomy index migrated successfully!");
+                        println!("Terms merged: {}", migrated);
+                    }
+                }
+                Err(e) => {
+                    eprintln!("failed to migrate the taxonomy index: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::ImportGames {
+            format,
+            apply,
+            files,
+        } => {
+            let mut input = String::new();
+            for path in files {
+                input.push_str(&std::fs::read_to_string(path)?);
+                input.push('\n');
+            }
+
+            let db = create_db_connection(&figment).await?;
+
+            match import_library(&db, format, &input, apply).await {
+                Ok(report) if json => print_json(&report)?,
+                Ok(report) => {
+                    print_game_plan(&report.plan);
+                    if report.applied {
+                        println!("games imported successfully!");
+                    } else {
+                        println!("dry run; pass --apply to import");
+                    }
+                }
+                Err(e) => {
+                    eprintln!("failed to import games: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::ImportBooks {
+            format,
+            update,
+            apply,
+            file,
+        } => {
+            let input = std::fs::read_to_string(file)?;
+
+            let db = create_db_connection(&figment).await?;
+
+            match import_book_export(&db, format, &input, update, apply).await {
+                Ok(report) if json => print_json(&report)?,
+                Ok(report) => {
+                    print_book_plan(&report.plan);
+                    if report.applied {
+                        println!("books imported successfully!");
+                    } else {
+                        println!("dry run; pass --apply to import");
+                    }
+                }
+                Err(e) => {
+                    eprintln!("failed to import books: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Export {
+            format,
+            include_keys,
+            output,
+        } => {
+            let db = create_db_connection(&figment).await?;
+
+            let backup = match Backup::export(&db, &backup_collections(include_keys)).await {
+                Ok(backup) => backup,
+                Err(e) => {
+                    eprintln!("failed to export catalog: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            match format {
+                BackupFormat::Ndjson => std::fs::write(&output, backup.to_ndjson()?)?,
+                BackupFormat::Csv => {
+                    std::fs::create_dir_all(&output)?;
+                    for (name, contents) in backup.to_csv_files()? {
+                        std::fs::write(output.join(name), contents)?;
+                    }
+                }
+            }
+
+            if json {
+                print_json(&backup.manifest)?;
+            } else {
+                for collection in &backup.manifest.collections {
+                    println!("{:<20} {}", collection.name, collection.count);
+                }
+                println!("catalog exported successfully!");
+            }
+        }
+        Commands::Import { policy, input } => {
+            let backup = if input.is_dir() {
+                let mut files = std::collections::BTreeMap::new();
+                for entry in std::fs::read_dir(&input)? {
+                    let path = entry?.path();
+                    if let Some(name) = path.file_name().and_then(|name| name.to_str()) {
+                        files.insert(name.to_string(), std::fs::read_to_string(&path)?);
+                    }
+                }
+                Backup::from_csv_files(&files)?
+            } else {
+                Backup::from_ndjson(&std::fs::read_to_string(&input)?)?
+            };
+
+            let db = create_db_connection(&figment).await?;
+
+            match backup.restore(&db, policy).await {
+                Ok(report) if json => print_json(&report)?,
+                Ok(report) => {
+                    println!(
+                        "{:<20} {:>9} {:>12} {:>8}",
+                        "Collection", "Inserted", "Overwritten", "Skipped"
+                    );
+                    println!("{}", "-".repeat(52));
+                    for (name, counts) in &report.collections {
+                        println!(
+                            "{:<20} {:>9} {:>12} {:>8}",
+                            name, counts.inserted, counts.overwritten, counts.skipped
+                        );
+                    }
+                    println!("catalog restored successfully!");
+                    if backup.collections.contains_key(API_KEYS) {
+                        println!(
+                            "running servers keep cached API keys; restart them to use the restored keys"
+                        );
+                    }
+                }
+                Err(e) => {
+                    eprintln!("failed to restore catalog: {}", e);
+                    std::process::exit(1);
+                }
+            }
+        }
+        Commands::Books { action } => {
+            let db = create_db_connection(&figment).await?;
+            handle_content::<Book>("book", action, &db, json).await?;
+        }
+        Commands::Games { action } => {
+            let db = create_db_connection(&figment).await?;
+            handle_content::<Game>("game", action, &db, json).await?;
+        }
+        Commands::Projects { action } => {
+            let db = create_db_connection(&figment).await?;
+            handle_content::<Project>("project", action, &db, json).await?;
+        }
+        Commands::Reviews { action } => {
+            let db = create_db_connection(&figment).await?;
+            handle_content::<Review>("review", action, &db, json).await?;
         }
     }
 
@@ -147,46 +1261,91 @@ pub async fn handle_cli(auth_service: AuthService) -> Result<(), Box<dyn std::er
 
 /// Creates a database connection for CLI operations.
 ///
-/// Reads the database URL from environment variables and establishes a connection.
+/// Uses the same MongoDB URL as the server; see [`crate::config`].
 ///
 /// # Returns
 ///
-/// A connection to the `bearodata` database or an error if connection fails.
-///
-/// # Environment Variables
-///
-/// - `DATABASE_URL` or `MONGODB_URL`: MongoDB connection string (required)
-async fn create_db_connection() -> Result<BearoData, Box<dyn std::error::Error>> {
+/// A connection to the `bearodata` database, or an error if no URL is
+/// configured or the URL is invalid.
+async fn create_db_connection(figment: &Figment) -> Result<BearoData, Box<dyn std::error::Error>> {
     use rocket_db_pools::mongodb::{Client, options::ClientOptions};
-    use std::env;
-
-    dotenvy::dotenv().ok();
-
-    let database_url = env::var("DATABASE_URL")
-        .or_else(|_| env::var("MONGODB_URL"))
-        .expect("DATABASE_URL or MONGODB_URL must be set");
 
+    let database_url = config::database_url(figment)?;
     let client_options = ClientOptions::parse(&database_url).await?;
     let client = Client::with_options(client_options)?;
 
-    Ok(BearoData::from(client))
+    Ok(BearoData::from(crate::db::MeteredClient::from(client)))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn parse(args: &[&str]) -> Result<Commands, clap::Error> {
+        Cli::try_parse_from(std::iter::once("apiodactyl").chain(args.iter().copied()))
+            .map(|cli| cli.command)
+    }
+
     #[test]
     fn test_cli_structure() {
         let cli = cli();
+        cli.clone().debug_assert();
 
-        assert_eq!(cli.get_name(), "your-app");
+        assert_eq!(cli.get_name(), "apiodactyl");
 
         let subcommands: Vec<&str> = cli.get_subcommands().map(|cmd| cmd.get_name()).collect();
 
-        assert!(subcommands.contains(&"create-admin-key"));
-        assert!(subcommands.contains(&"list-admins"));
-        assert!(subcommands.contains(&"revoke-key"));
+        for expected in [
+            "serve",
+            "create-admin-key",
+            "list-admins",
+            "create-key",
+            "list-keys",
+            "describe-key",
+            "revoke-key",
+            "migrate-reviews",
+            "migrate-games",
+            "migrate-timestamps",
+            "migrate-review-index",
+            "migrate-taxonomy-index",
+            "import-games",
+            "import-books",
+            "export",
+            "import",
+            "books",
+            "games",
+            "projects",
+            "reviews",
+            "receive-webhooks",
+            "completions",
+        ] {
+            assert!(subcommands.contains(&expected), "{expected} missing");
+        }
+    }
+
+    #[test]
+    fn test_global_options() {
+        let cli = Cli::try_parse_from([
+            "apiodactyl",
+            "list-admins",
+            "--json",
+            "--database-url",
+            "mongodb://localhost/bearodata",
+            "--config",
+            "Other.toml",
+        ])
+        .expect("global options may follow the subcommand");
+
+        assert!(cli.json);
+        assert_eq!(
+            cli.database_url.as_deref(),
+            Some("mongodb://localhost/bearodata")
+        );
+        assert_eq!(cli.config, Some(PathBuf::from("Other.toml")));
+        assert!(matches!(cli.command, Commands::ListAdmins));
+
+        assert!(Cli::try_parse_from(["apiodactyl"]).is_err());
+        assert!(matches!(parse(&["serve"]), Ok(Commands::Serve)));
     }
 
     #[test]
@@ -210,6 +1369,119 @@ mod tests {
         assert!(args.contains(&"key"));
     }
 
+    #[test]
+    fn test_create_key_command() {
+        let command = parse(&[
+            "create-key",
+            "--label",
+            "reading widget",
+            "--expires",
+            "30d",
+            "--scope",
+            "books",
+            "--scope",
+            "reviews",
+        ])
+        .expect("create-key should parse");
+
+        let Commands::CreateKey {
+            key,
+            admin,
+            label,
+            expires,
+            scope,
+        } = command
+        else {
+            panic!("expected create-key, got {command:?}");
+        };
+        assert!(key.is_none());
+        assert!(!admin);
+        assert_eq!(label.as_deref(), Some("reading widget"));
+        assert_eq!(expires, Some(Expiry::In(chrono::Duration::days(30))));
+        assert_eq!(scope, vec![KeyScope::Books, KeyScope::Reviews]);
+
+        assert!(matches!(
+            parse(&["create-key", "--admin"]),
+            Ok(Commands::CreateKey { admin: true, .. })
+        ));
+        assert!(parse(&["create-key", "--scope", "everything"]).is_err());
+        assert!(parse(&["create-key", "--expires", "soon"]).is_err());
+    }
+
+    #[test]
+    fn test_list_keys_command() {
+        let command = parse(&["list-keys", "--regular", "--scope", "games", "--active"])
+            .expect("list-keys should parse");
+        assert!(matches!(
+            command,
+            Commands::ListKeys {
+                admin: false,
+                regular: true,
+                scope: Some(KeyScope::Games),
+                expired: false,
+                active: true,
+                ..
+            }
+        ));
+
+        assert!(matches!(
+            parse(&["list-keys"]),
+            Ok(Commands::ListKeys { .. })
+        ));
+        assert!(parse(&["list-keys", "--admin", "--regular"]).is_err());
+        assert!(parse(&["list-keys", "--expired", "--active"]).is_err());
+    }
+
+    #[test]
+    fn test_describe_key_command() {
+        let cli = cli();
+        let describe_cmd = cli
+            .get_subcommands()
+            .find(|cmd| cmd.get_name() == "describe-key")
+            .expect("describe-key command should exist");
+
+        let key_arg = describe_cmd
+            .get_arguments()
+            .find(|arg| arg.get_id() == "key")
+            .expect("key argument should exist");
+        assert!(key_arg.is_required_set());
+
+        assert!(parse(&["describe-key"]).is_err());
+    }
+
+    #[test]
+    fn test_expiry_parsing() {
+        let now = NaiveDate::from_ymd_opt(2026, 1, 1)
+            .unwrap()
+            .and_time(chrono::NaiveTime::MIN);
+
+        assert_eq!(
+            "12h".parse::<Expiry>().unwrap().resolve(now),
+            now + chrono::Duration::hours(12)
+        );
+        assert_eq!(
+            "2w".parse::<Expiry>().unwrap(),
+            Expiry::In(chrono::Duration::weeks(2))
+        );
+        assert_eq!(
+            "2026-12-31".parse::<Expiry>().unwrap().resolve(now),
+            NaiveDate::from_ymd_opt(2026, 12, 31)
+                .unwrap()
+                .and_time(chrono::NaiveTime::MIN)
+        );
+        assert_eq!(
+            "2026-06-01T12:00:00+02:00".parse::<Expiry>().unwrap(),
+            Expiry::At(
+                NaiveDate::from_ymd_opt(2026, 6, 1)
+                    .unwrap()
+                    .and_hms_opt(10, 0, 0)
+                    .unwrap()
+            )
+        );
+        assert!("3y".parse::<Expiry>().is_err());
+        assert!("d".parse::<Expiry>().is_err());
+    }
+
     #[test]
     fn test_revoke_key_command() {
         let cli = cli();
@@ -252,4 +1524,163 @@ mod tests {
 
         assert!(required_args.is_empty());
     }
+
+    #[test]
+    fn test_migrate_reviews_command() {
+        let cli = cli();
+        let migrate_cmd = cli
+            .get_subcommands()
+            .find(|cmd| cmd.get_name() == "migrate-reviews")
+            .expect("migrate-reviews command should exist");
+
+        let work_arg = migrate_cmd
+            .get_arguments()
+            .find(|arg| arg.get_id() == "work")
+            .expect("work argument should exist");
+
+        assert!(!work_arg.is_required_set());
+    }
+
+    #[test]
+    fn test_import_games_command() {
+        let command = parse(&[
+            "import-games",
+            "--format",
+            "steam-vdf",
+            "libraryfolders.vdf",
+            "appmanifest_620.acf",
+        ])
+        .expect("import-games should parse");
+
+        let Commands::ImportGames {
+            format,
+            apply,
+            files,
+        } = command
+        else {
+            panic!("expected import-games, got {command:?}");
+        };
+        assert_eq!(format, GameLibraryFormat::SteamVdf);
+        assert!(!apply);
+        assert_eq!(files.len(), 2);
+
+        assert!(parse(&["import-games", "--format", "epic", "a.csv"]).is_err());
+        assert!(parse(&["import-games", "--format", "gog-csv"]).is_err());
+    }
+
+    #[test]
+    fn test_import_books_command() {
+        let command = parse(&[
+            "import-books",
+            "--format",
+            "goodreads",
+            "--update",
+            "goodreads_library_export.csv",
+        ])
+        .expect("import-books should parse");
+
+        let Commands::ImportBooks { update, apply, .. } = command else {
+            panic!("expected import-books, got {command:?}");
+        };
+        assert!(update);
+        assert!(!apply);
+
+        assert!(parse(&["import-books", "--format", "librarything", "a.csv"]).is_err());
+    }
+
+    #[test]
+    fn test_backup_commands() {
+        let command = parse(&["export", "backup.ndjson"]).expect("export should parse");
+        let Commands::Export {
+            format,
+            include_keys,
+            ..
+        } = command
+        else {
+            panic!("expected export, got {command:?}");
+        };
+        assert_eq!(format, BackupFormat::Ndjson);
+        assert!(!include_keys);
+
+        let command =
+            parse(&["import", "--policy", "skip", "backup"]).expect("import should parse");
+        assert!(matches!(
+            command,
+            Commands::Import {
+                policy: ConflictPolicy::Skip,
+                ..
+            }
+        ));
+        assert!(parse(&["import", "--policy", "merge", "backup"]).is_err());
+    }
+
+    #[test]
+    fn test_content_commands() {
+        let command = parse(&["books", "list", "--limit", "5", "-o", "json"])
+            .expect("books list should parse");
+        assert!(matches!(
+            command,
+            Commands::Books {
+                action: ContentAction::List {
+                    limit: Some(5),
+                    output: OutputFormat::Json,
+                }
+            }
+        ));
+
+        let command = parse(&["games", "create"]).expect("create without a file opens the editor");
+        assert!(matches!(
+            command,
+            Commands::Games {
+                action: ContentAction::Create { file: None, .. }
+            }
+        ));
+
+        assert!(parse(&["reviews", "patch", "650000000000000000000001"]).is_err());
+        assert!(parse(&["projects", "list", "--limit", "0"]).is_err());
+        assert!(parse(&["books", "show", "x", "-o", "yaml"]).is_err());
+        assert!(parse(&["books"]).is_err());
+    }
+
+    #[test]
+    fn test_completions_command() {
+        assert!(matches!(
+            parse(&["completions", "fish"]),
+            Ok(Commands::Completions { shell: Shell::Fish })
+        ));
+        assert!(parse(&["completions", "tcsh"]).is_err());
+    }
+
+    #[test]
+    fn test_receive_webhooks_command() {
+        assert!(matches!(
+            parse(&["receive-webhooks"]),
+            Ok(Commands::ReceiveWebhooks {
+                port: 8787,
+                secret: None,
+                status: 200
+            })
+        ));
+        match parse(&[
+            "receive-webhooks",
+            "--port",
+            "9000",
+            "--secret",
+            "whsec_x",
+            "--status",
+            "503",
+        ]) {
+            Ok(Commands::ReceiveWebhooks {
+                port,
+                secret,
+                status,
+            }) => {
+                assert_eq!(port, 9000);
+                assert_eq!(secret.as_deref(), Some("whsec_x"));
+                assert_eq!(status, 503);
+            }
+            other => panic!("unexpected parse result: {:?}", other),
+        }
+        assert!(parse(&["receive-webhooks", "--status", "42"]).is_err());
+    }
 }
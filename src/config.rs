@@ -0,0 +1,90 @@
+//! # Configuration
+//!
+//! Builds the configuration shared by the server and the CLI commands.
+//!
+//! Settings come from the Rocket config file (`Rocket.toml`, or `ROCKET_CONFIG`,
+//! or `--config`), then `ROCKET_*` environment variables. The MongoDB URL may
+//! additionally be given with `--database-url`, `DATABASE_URL` or `MONGODB_URL`,
+//! in that order of precedence, and overrides `databases.bearodata.url`.
+
+use {
+    crate::errors::ConfigError,
+    rocket::{
+        Config,
+        figment::{
+            Figment, Profile,
+            providers::{Env, Format, Toml},
+        },
+    },
+    std::path::Path,
+};
+
+/// Figment key holding the MongoDB connection string.
+pub const DATABASE_URL_KEY: &str = "databases.bearodata.url";
+
+/// Builds the configuration figment.
+///
+/// # Arguments
+///
+/// * `config_file` - Rocket config file replacing `Rocket.toml`/`ROCKET_CONFIG`
+/// * `database_url` - MongoDB connection string overriding every other source
+pub fn figment(config_file: Option<&Path>, database_url: Option<&str>) -> Figment {
+    let figment = match config_file {
+        Some(path) => Figment::from(Config::default())
+            .merge(Toml::file(path).nested())
+            .merge(Env::prefixed("ROCKET_").ignore(&["PROFILE"]).global())
+            .select(Profile::from_env_or(
+                "ROCKET_PROFILE",
+                Config::DEFAULT_PROFILE,
+            )),
+        None => Config::figment(),
+    };
+
+    let database_url = database_url
+        .map(str::to_string)
+        .or_else(|| std::env::var("DATABASE_URL").ok())
+        .or_else(|| std::env::var("MONGODB_URL").ok());
+
+    match database_url {
+        Some(url) => figment.merge((DATABASE_URL_KEY, url)),
+        None => figment,
+    }
+}
+
+/// Returns the configured MongoDB connection string.
+pub fn database_url(figment: &Figment) -> Result<String, ConfigError> {
+    figment
+        .extract_inner::<String>(DATABASE_URL_KEY)
+        .map_err(|_| ConfigError::MissingDatabaseUrl)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_database_url_precedence() {
+        let dir = std::env::temp_dir().join(format!("apiodactyl-config-{}", uuid::Uuid::new_v4()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("Custom.toml");
+        std::fs::write(
+            &path,
+            "[default]\nport = 4242\n\n[default.databases.bearodata]\nurl = \"mongodb://file/bearodata\"\n",
+        )
+        .unwrap();
+
+        let figment = super::figment(Some(&path), Some("mongodb://flag/bearodata"));
+        assert_eq!(database_url(&figment).unwrap(), "mongodb://flag/bearodata");
+        assert_eq!(figment.extract_inner::<u16>("port").unwrap(), 4242);
+
+        let figment = Figment::from(Config::default()).merge(Toml::file(&path).nested());
+        assert_eq!(database_url(&figment).unwrap(), "mongodb://file/bearodata");
+
+        assert!(matches!(
+            database_url(&Figment::from(Config::default())),
+            Err(ConfigError::MissingDatabaseUrl)
+        ));
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+}
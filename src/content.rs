@@ -0,0 +1,630 @@
+//! # Content management
+//!
+//! Direct database access to books, games, projects and reviews for the
+//! content CLI commands. Writes go through the same functions as the HTTP
+//! handlers, so documents are validated and canonicalized exactly as they
+//! would be over the API.
+//!
+//! Documents are read and written as JSON or YAML; listings are printed as
+//! plain-text tables or JSON.
+
+use {
+    crate::{
+        errors::ContentError,
+        handlers::{books, games, projects, reviews},
+        migrations::default_review_work,
+        models::{
+            Book, Game, NewBook, NewGame, NewProject, NewReview, Project, Review, UpdateBook,
+            UpdateGame, UpdateProject, UpdateReview,
+        },
+        taxonomy::Taxonomy,
+        trash,
+    },
+    mongodb::bson::{doc, oid::ObjectId},
+    rocket::{futures::TryStreamExt, http::Status, response::status},
+    rocket_db_pools::mongodb::{Client, options::FindOptions},
+    serde::{Serialize, de::DeserializeOwned},
+    serde_json::{Value, json},
+    std::{future::Future, path::Path, process::Command, str::FromStr},
+};
+
+/// Cells longer than this are truncated in tables.
+const MAX_CELL_WIDTH: usize = 40;
+
+/// The collections managed by the content commands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ContentKind {
+    Books,
+    Games,
+    Projects,
+    Reviews,
+}
+
+impl ContentKind {
+    /// Every kind, named as on the command line.
+    pub const NAMES: [&'static str; 4] = ["books", "games", "projects", "reviews"];
+}
+
+impl FromStr for ContentKind {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "books" => Ok(ContentKind::Books),
+            "games" => Ok(ContentKind::Games),
+            "projects" => Ok(ContentKind::Projects),
+            "reviews" => Ok(ContentKind::Reviews),
+            _ => Err(format!("unknown content kind `{}`", s)),
+        }
+    }
+}
+
+/// Serialization of documents read from files or an editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DocumentFormat {
+    Json,
+    Yaml,
+}
+
+impl DocumentFormat {
+    /// Every format name accepted on the command line.
+    pub const NAMES: [&'static str; 2] = ["json", "yaml"];
+
+    /// Guesses the format from a file extension, defaulting to JSON.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension().and_then(|extension| extension.to_str()) {
+            Some("yaml" | "yml") => DocumentFormat::Yaml,
+            _ => DocumentFormat::Json,
+        }
+    }
+
+    /// File extension used for temporary files opened in an editor.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            DocumentFormat::Json => "json",
+            DocumentFormat::Yaml => "yaml",
+        }
+    }
+
+    pub fn parse<T: DeserializeOwned>(&self, input: &str) -> Result<T, ContentError> {
+        match self {
+            DocumentFormat::Json => Ok(serde_json::from_str(input)?),
+            DocumentFormat::Yaml => Ok(serde_yaml::from_str(input)?),
+        }
+    }
+
+    pub fn render<T: Serialize>(&self, value: &T) -> Result<String, ContentError> {
+        match self {
+            DocumentFormat::Json => Ok(serde_json::to_string_pretty(value)? + "\n"),
+            DocumentFormat::Yaml => Ok(serde_yaml::to_string(value)?),
+        }
+    }
+}
+
+impl FromStr for DocumentFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(DocumentFormat::Json),
+            "yaml" | "yml" => Ok(DocumentFormat::Yaml),
+            _ => Err(format!("unknown document format `{}`", s)),
+        }
+    }
+}
+
+/// How listings and documents are printed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OutputFormat {
+    Table,
+    Json,
+}
+
+impl OutputFormat {
+    /// Every format name accepted on the command line.
+    pub const NAMES: [&'static str; 2] = ["table", "json"];
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "table" => Ok(OutputFormat::Table),
+            "json" => Ok(OutputFormat::Json),
+            _ => Err(format!("unknown output format `{}`", s)),
+        }
+    }
+}
+
+/// A document type managed by the content commands.
+pub trait Content: Serialize + DeserializeOwned + Send + Sync + Unpin + Sized {
+    /// Payload accepted when creating a document
+    type New: DeserializeOwned + Send;
+    /// Payload accepted when replacing or patching a document
+    type Update: DeserializeOwned + Send;
+
+    const COLLECTION: &'static str;
+    /// Table column headers, matching [`Content::row`]
+    const COLUMNS: &'static [&'static str];
+
+    fn row(&self) -> Vec<String>;
+
+    /// A skeleton document offered when creating one in an editor.
+    fn template() -> Value;
+
+    fn create(
+        db: &Client,
+        new: Self::New,
+    ) -> impl Future<Output = Result<Self, ContentError>> + Send;
+
+    /// Writes every field of `update`, like `PUT`.
+    fn replace(
+        db: &Client,
+        oid: ObjectId,
+        update: Self::Update,
+    ) -> impl Future<Output = Result<Self, ContentError>> + Send;
+
+    /// Writes the fields present in `update`, like `PATCH`.
+    ///
+    /// Localized book fields are flattened to `locale`, as with `Accept-Language`.
+    fn patch(
+        db: &Client,
+        oid: ObjectId,
+        update: Self::Update,
+        locale: Option<&str>,
+    ) -> impl Future<Output = Result<Self, ContentError>> + Send;
+
+    fn delete(db: &Client, oid: ObjectId) -> impl Future<Output = Result<(), ContentError>> + Send;
+}
+
+impl From<Status> for ContentError {
+    fn from(status: Status) -> Self {
+        ContentError::Rejected(status.to_string())
+    }
+}
+
+impl From<status::Custom<String>> for ContentError {
+    fn from(status::Custom(status, message): status::Custom<String>) -> Self {
+        ContentError::Rejected(format!("{}: {}", status, message))
+    }
+}
+
+impl Content for Book {
+    type New = NewBook;
+    type Update = UpdateBook;
+
+    const COLLECTION: &'static str = "books";
+    const COLUMNS: &'static [&'static str] = &["ID", "Title", "Author", "Type", "Rating", "Status"];
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.oid.to_hex(),
+            self.title.get_text(None),
+            self.author.get_text(None),
+            self.media_type.as_str().to_string(),
+            self.rating.to_string(),
+            self.status.get_text(None),
+        ]
+    }
+
+    fn template() -> Value {
+        json!({
+            "title": "",
+            "author": "",
+            "genres": [],
+            "tags": [],
+            "rating": 0,
+            "status": "planned",
+            "description": "",
+            "my_thoughts": "",
+            "links": null,
+            "cover_image": "",
+            "explicit": false,
+            "color": null,
+            "media_type": "book",
+        })
+    }
+
+    async fn create(db: &Client, new: NewBook) -> Result<Self, ContentError> {
+        let taxonomy = Taxonomy::load(db).await?;
+        Ok(books::insert_book(db, &taxonomy, new).await?)
+    }
+
+    async fn replace(db: &Client, oid: ObjectId, update: UpdateBook) -> Result<Self, ContentError> {
+        let taxonomy = Taxonomy::load(db).await?;
+        Ok(books::replace_book(db, &taxonomy, oid, update).await?)
+    }
+
+    async fn patch(
+        db: &Client,
+        oid: ObjectId,
+        update: UpdateBook,
+        locale: Option<&str>,
+    ) -> Result<Self, ContentError> {
+        let taxonomy = Taxonomy::load(db).await?;
+        Ok(books::patch_book_fields(db, &taxonomy, oid, update, locale).await?)
+    }
+
+    async fn delete(db: &Client, oid: ObjectId) -> Result<(), ContentError> {
+        Ok(books::remove_book(db, oid).await?)
+    }
+}
+
+impl Content for Game {
+    type New = NewGame;
+    type Update = UpdateGame;
+
+    const COLLECTION: &'static str = "games";
+    const COLUMNS: &'static [&'static str] =
+        &["ID", "Title", "Developer", "Status", "Percent", "Rating"];
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.oid.to_hex(),
+            self.title.clone(),
+            self.developer.clone(),
+            self.status.as_str().to_string(),
+            self.percent.to_string(),
+            self.rating.to_string(),
+        ]
+    }
+
+    fn template() -> Value {
+        json!({
+            "title": "",
+            "developer": "",
+            "genres": [],
+            "tags": [],
+            "rating": 0,
+            "status": "backlog",
+            "description": "",
+            "my_thoughts": "",
+            "links": null,
+            "cover_image": "",
+            "explicit": false,
+            "percent": 0,
+            "bad": false,
+        })
+    }
+
+    async fn create(db: &Client, new: NewGame) -> Result<Self, ContentError> {
+        let taxonomy = Taxonomy::load(db).await?;
+        Ok(games::insert_game(db, &taxonomy, new).await?)
+    }
+
+    async fn replace(db: &Client, oid: ObjectId, update: UpdateGame) -> Result<Self, ContentError> {
+        let taxonomy = Taxonomy::load(db).await?;
+        Ok(games::replace_game(db, &taxonomy, oid, update).await?)
+    }
+
+    async fn patch(
+        db: &Client,
+        oid: ObjectId,
+        update: UpdateGame,
+        _locale: Option<&str>,
+    ) -> Result<Self, ContentError> {
+        let taxonomy = Taxonomy::load(db).await?;
+        Ok(games::patch_game_fields(db, &taxonomy, oid, update).await?)
+    }
+
+    async fn delete(db: &Client, oid: ObjectId) -> Result<(), ContentError> {
+        Ok(games::remove_game(db, oid).await?)
+    }
+}
+
+impl Content for Project {
+    type New = NewProject;
+    type Update = UpdateProject;
+
+    const COLLECTION: &'static str = "projects";
+    const COLUMNS: &'static [&'static str] = &["ID", "Name", "Source"];
+
+    fn row(&self) -> Vec<String> {
+        vec![self.oid.to_hex(), self.name.clone(), self.source.clone()]
+    }
+
+    fn template() -> Value {
+        json!({
+            "name": "",
+            "description": "",
+            "tags": [],
+            "source": "",
+            "cover_image": null,
+            "install_command": null,
+        })
+    }
+
+    async fn create(db: &Client, new: NewProject) -> Result<Self, ContentError> {
+        Ok(projects::insert_project(db, new).await?)
+    }
+
+    async fn replace(
+        db: &Client,
+        oid: ObjectId,
+        update: UpdateProject,
+    ) -> Result<Self, ContentError> {
+        Ok(projects::replace_project(db, oid, update).await?)
+    }
+
+    async fn patch(
+        db: &Client,
+        oid: ObjectId,
+        update: UpdateProject,
+        _locale: Option<&str>,
+    ) -> Result<Self, ContentError> {
+        Ok(projects::patch_project_fields(db, oid, update).await?)
+    }
+
+    async fn delete(db: &Client, oid: ObjectId) -> Result<(), ContentError> {
+        projects::remove_project(db, oid).await?;
+        Ok(())
+    }
+}
+
+impl Content for Review {
+    type New = NewReview;
+    type Update = UpdateReview;
+
+    const COLLECTION: &'static str = "reviews";
+    const COLUMNS: &'static [&'static str] = &["ID", "Work", "Chapter", "Rating"];
+
+    fn row(&self) -> Vec<String> {
+        vec![
+            self.oid.to_hex(),
+            self.work_id.map(|id| id.to_hex()).unwrap_or_default(),
+            self.chapter.to_string(),
+            self.rating.to_string(),
+        ]
+    }
+
+    fn template() -> Value {
+        json!({
+            "work_id": null,
+            "chapter": 1,
+            "description": "",
+            "rating": 0,
+            "thoughts": "",
+        })
+    }
+
+    /// Creates a review for the work given in the document, or the default work.
+    async fn create(db: &Client, new: NewReview) -> Result<Self, ContentError> {
+        let work_id = new.work_id.or_else(default_review_work).ok_or_else(|| {
+            ContentError::Rejected(
+                "work_id is required when DEFAULT_REVIEW_WORK is not set".to_string(),
+            )
+        })?;
+
+        Ok(reviews::insert_review(db, work_id, new).await?)
+    }
+
+    async fn replace(
+        db: &Client,
+        oid: ObjectId,
+        update: UpdateReview,
+    ) -> Result<Self, ContentError> {
+        Ok(reviews::update_review(db, doc! { "_id": oid }, update).await?)
+    }
+
+    async fn patch(
+        db: &Client,
+        oid: ObjectId,
+        update: UpdateReview,
+        _locale: Option<&str>,
+    ) -> Result<Self, ContentError> {
+        Self::replace(db, oid, update).await
+    }
+
+    async fn delete(db: &Client, oid: ObjectId) -> Result<(), ContentError> {
+        Ok(reviews::remove_review(db, oid).await?)
+    }
+}
+
+/// Parses a document id given on the command line.
+pub fn parse_id(id: &str) -> Result<ObjectId, ContentError> {
+    ObjectId::parse_str(id).map_err(|_| ContentError::InvalidId(id.to_string()))
+}
+
+/// Lists documents in insertion order.
+pub async fn list<T: Content>(db: &Client, limit: Option<i64>) -> Result<Vec<T>, ContentError> {
+    let options = FindOptions::builder()
+        .sort(doc! { "_id": 1 })
+        .limit(limit)
+        .build();
+
+    Ok(db
+        .database("bearodata")
+        .collection::<T>(T::COLLECTION)
+        .find(trash::live(doc! {}), options)
+        .await?
+        .try_collect()
+        .await?)
+}
+
+/// Loads one document.
+pub async fn find<T: Content>(db: &Client, oid: ObjectId) -> Result<T, ContentError> {
+    db.database("bearodata")
+        .collection::<T>(T::COLLECTION)
+        .find_one(trash::live(doc! { "_id": oid }), None)
+        .await?
+        .ok_or(ContentError::NotFound(oid))
+}
+
+/// Renders documents as a table with one row each.
+pub fn table<T: Content>(items: &[T]) -> String {
+    let rows: Vec<Vec<String>> = items.iter().map(Content::row).collect();
+    render_table(T::COLUMNS, &rows)
+}
+
+/// Renders one document as a two-column field/value table.
+pub fn details<T: Serialize>(item: &T) -> Result<String, ContentError> {
+    let Value::Object(fields) = serde_json::to_value(item)? else {
+        return Ok(String::new());
+    };
+
+    let rows: Vec<Vec<String>> = fields
+        .iter()
+        .map(|(field, value)| vec![field.clone(), cell(value)])
+        .collect();
+    Ok(render_table(&["Field", "Value"], &rows))
+}
+
+/// Formats a JSON value for a table cell, unwrapping `{"$oid": ...}`.
+fn cell(value: &Value) -> String {
+    match value {
+        Value::Null => String::new(),
+        Value::String(text) => text.clone(),
+        Value::Object(map) if map.len() == 1 && map.contains_key("$oid") => cell(&map["$oid"]),
+        other => other.to_string(),
+    }
+}
+
+fn truncate(text: &str) -> String {
+    let text = text.replace('\n', " ");
+    if text.chars().count() > MAX_CELL_WIDTH {
+        let truncated: String = text.chars().take(MAX_CELL_WIDTH - 1).collect();
+        format!("{}…", truncated)
+    } else {
+        text
+    }
+}
+
+fn render_table(headers: &[&str], rows: &[Vec<String>]) -> String {
+    let rows: Vec<Vec<String>> = rows
+        .iter()
+        .map(|row| row.iter().map(|cell| truncate(cell)).collect())
+        .collect();
+
+    let mut widths: Vec<usize> = headers
+        .iter()
+        .map(|header| header.chars().count())
+        .collect();
+    for row in &rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.chars().count());
+        }
+    }
+
+    let line = |cells: Vec<&str>| {
+        let padded: Vec<String> = cells
+            .iter()
+            .zip(&widths)
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect();
+        padded.join("  ").trim_end().to_string()
+    };
+
+    let mut out = line(headers.to_vec());
+    out.push('\n');
+    out.push_str(&"-".repeat(widths.iter().sum::<usize>() + 2 * (widths.len() - 1)));
+    out.push('\n');
+    for row in &rows {
+        out.push_str(&line(row.iter().map(String::as_str).collect()));
+        out.push('\n');
+    }
+    out
+}
+
+/// Opens `initial` in `$VISUAL` or `$EDITOR` (falling back to `vi`) and
+/// returns the saved text.
+pub fn edit_text(initial: &str, format: DocumentFormat) -> Result<String, ContentError> {
+    let editor = std::env::var("VISUAL")
+        .or_else(|_| std::env::var("EDITOR"))
+        .unwrap_or_else(|_| "vi".to_string());
+
+    let path = std::env::temp_dir().join(format!(
+        "apiodactyl-{}.{}",
+        uuid::Uuid::new_v4(),
+        format.extension()
+    ));
+    std::fs::write(&path, initial)?;
+
+    // The editor may carry arguments, e.g. `code --wait`.
+    let mut words = editor.split_whitespace();
+    let program = words.next().unwrap_or("vi");
+    let status = Command::new(program).args(words).arg(&path).status();
+
+    let edited = std::fs::read_to_string(&path);
+    let _ = std::fs::remove_file(&path);
+
+    match status {
+        Ok(status) if status.success() => Ok(edited?),
+        Ok(status) => Err(ContentError::Editor(format!(
+            "`{}` exited with {}",
+            editor, status
+        ))),
+        Err(e) => Err(ContentError::Editor(format!("`{}`: {}", editor, e))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_document_format() {
+        assert_eq!(
+            DocumentFormat::from_path(Path::new("book.yml")),
+            DocumentFormat::Yaml
+        );
+        assert_eq!(
+            DocumentFormat::from_path(Path::new("book")),
+            DocumentFormat::Json
+        );
+
+        let patch: UpdateGame = DocumentFormat::Yaml
+            .parse("title: Outer Wilds\npercent: 100\nstatus: completed\n")
+            .unwrap();
+        assert_eq!(patch.title.as_deref(), Some("Outer Wilds"));
+        assert_eq!(patch.percent, Some(100));
+        assert!(patch.developer.is_none());
+
+        assert!(
+            DocumentFormat::Json
+                .parse::<UpdateGame>("title: x")
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_templates_parse_as_new_documents() {
+        for format in [DocumentFormat::Json, DocumentFormat::Yaml] {
+            let parse = |template: Value| format.render(&template).unwrap();
+            format.parse::<NewBook>(&parse(Book::template())).unwrap();
+            format.parse::<NewGame>(&parse(Game::template())).unwrap();
+            format
+                .parse::<NewProject>(&parse(Project::template()))
+                .unwrap();
+            format
+                .parse::<NewReview>(&parse(Review::template()))
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_tables() {
+        let review = Review {
+            oid: ObjectId::parse_str("650000000000000000000001").unwrap(),
+            work_id: None,
+            work_kind: None,
+            chapter: 12,
+            description: "a".repeat(60),
+            rating: 4,
+            thoughts: "line one\nline two".to_string(),
+            created_at: None,
+            updated_at: None,
+            deleted_at: None,
+        };
+
+        let listing = table(std::slice::from_ref(&review));
+        let lines: Vec<&str> = listing.lines().collect();
+        assert_eq!(lines[0], "ID                        Work  Chapter  Rating");
+        assert_eq!(lines[2], "650000000000000000000001        12       4");
+
+        let details = details(&review).unwrap();
+        assert!(details.contains("_id          650000000000000000000001"));
+        assert!(details.contains("thoughts     line one line two"));
+        assert!(details.contains(&format!("{}…", "a".repeat(39))));
+    }
+}
@@ -12,7 +12,78 @@
 //! The `BearoData` struct is automatically managed by Rocket and can be injected
 //! into request handlers using the `Connection<BearoData>` guard.
 
-use rocket_db_pools::{Database, mongodb::Client};
+use {
+    crate::metrics::MongoMetrics,
+    rocket::figment::Figment,
+    rocket_db_pools::{
+        Config, Database, Error, Pool,
+        mongodb::{self, Client, options::ClientOptions},
+    },
+    std::{convert::Infallible, ops::Deref, sync::Arc, time::Duration},
+};
+
+/// MongoDB client pool that reports command timings to [`crate::metrics`].
+///
+/// Configured exactly like rocket_db_pools' built-in MongoDB pool; connections
+/// are plain [`Client`] handles.
+#[derive(Clone)]
+pub struct MeteredClient(Client);
+
+impl From<Client> for MeteredClient {
+    fn from(client: Client) -> Self {
+        Self(client)
+    }
+}
+
+impl Deref for MeteredClient {
+    type Target = Client;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+#[rocket::async_trait]
+impl Pool for MeteredClient {
+    type Error = Error<mongodb::error::Error, Infallible>;
+
+    type Connection = Client;
+
+    async fn init(figment: &Figment) -> Result<Self, Self::Error> {
+        let config = figment.extract::<Config>()?;
+        let mut opts = ClientOptions::parse(&config.url)
+            .await
+            .map_err(Error::Init)?;
+        opts.min_pool_size = config.min_connections;
+        opts.max_pool_size = Some(config.max_connections as u32);
+        opts.max_idle_time = config.idle_timeout.map(Duration::from_secs);
+        opts.connect_timeout = Some(Duration::from_secs(config.connect_timeout));
+        opts.server_selection_timeout = Some(Duration::from_secs(config.connect_timeout));
+        opts.command_event_handler = Some(Arc::new(MongoMetrics));
+
+        Client::with_options(opts).map(Self).map_err(Error::Init)
+    }
+
+    async fn get(&self) -> Result<Self::Connection, Self::Error> {
+        Ok(self.0.clone())
+    }
+
+    async fn close(&self) {}
+}
+
+/// Server error code of a write rejected by a unique index.
+const DUPLICATE_KEY: i32 = 11000;
+
+/// Whether `e` was raised by a unique index rejecting a duplicate key.
+pub fn is_duplicate_key(e: &mongodb::error::Error) -> bool {
+    use mongodb::error::{ErrorKind, WriteFailure};
+
+    match e.kind.as_ref() {
+        ErrorKind::Command(error) => error.code == DUPLICATE_KEY,
+        ErrorKind::Write(WriteFailure::WriteError(error)) => error.code == DUPLICATE_KEY,
+        _ => false,
+    }
+}
 
 /// MongoDB database connection pool.
 ///
@@ -22,6 +93,8 @@ use rocket_db_pools::{Database, mongodb::Client};
 /// # Example
 ///
 /// ```rust,no_run
+/// use apiodactyl::db::BearoData;
+/// use rocket::get;
 /// use rocket_db_pools::Connection;
 ///
 /// #[get("/")]
@@ -32,4 +105,4 @@ use rocket_db_pools::{Database, mongodb::Client};
 /// ```
 #[derive(Database)]
 #[database("bearodata")]
-pub struct BearoData(Client);
+pub struct BearoData(MeteredClient);
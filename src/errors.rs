@@ -3,6 +3,7 @@
 //! This module defines custom error types for the application, particularly
 //! authentication errors, and their HTTP response representations.
 
+use mongodb::bson::oid::ObjectId;
 use rocket::Request;
 use rocket::http::Status;
 use rocket::response::{self, Responder, Response};
@@ -24,6 +25,9 @@ pub enum AuthError {
     /// The provided API key is invalid or doesn't exist
     #[error("Invalid API key")]
     InvalidKey,
+    /// The provided API key is past its expiry date
+    #[error("API key expired")]
+    ExpiredKey,
     /// The user lacks the required permissions for the operation
     #[error("Insufficient permissions")]
     InsufficientPermissions,
@@ -32,6 +36,214 @@ pub enum AuthError {
     Database,
 }
 
+/// Errors raised while running database migrations.
+#[derive(Error, Debug)]
+pub enum MigrationError {
+    /// The configured default work does not exist in any work collection
+    #[error("Work {0} not found in books, series or games")]
+    WorkNotFound(ObjectId),
+    /// No default work was given and `DEFAULT_REVIEW_WORK` is not set
+    #[error("No default work configured")]
+    MissingWork,
+    /// A document could not be converted to BSON
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] mongodb::bson::ser::Error),
+    /// A database operation failed
+    #[error("Database error: {0}")]
+    Database(#[from] rocket_db_pools::mongodb::error::Error),
+}
+
+/// Errors raised while importing library exports.
+#[derive(Error, Debug)]
+pub enum ImportError {
+    /// The input is not valid CSV
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+    /// The input is not valid JSON
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    /// The input is not a valid Steam KeyValues (VDF) file
+    #[error("VDF error: {0}")]
+    Vdf(String),
+    /// A required column is missing from a CSV header
+    #[error("Missing column: {0}")]
+    MissingColumn(String),
+    /// A document could not be converted to BSON
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] mongodb::bson::ser::Error),
+    /// A written document could not be read back into its model
+    #[error("Deserialization error: {0}")]
+    Deserialization(#[from] mongodb::bson::de::Error),
+    /// A database operation failed
+    #[error("Database error: {0}")]
+    Database(#[from] rocket_db_pools::mongodb::error::Error),
+}
+
+/// Errors raised while exporting or restoring catalog backups.
+#[derive(Error, Debug)]
+pub enum BackupError {
+    /// A backup file is not valid CSV
+    #[error("CSV error: {0}")]
+    Csv(#[from] csv::Error),
+    /// A backup file is not valid JSON
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    /// A record is not valid extended JSON
+    #[error("Extended JSON error: {0}")]
+    ExtJson(#[from] mongodb::bson::extjson::de::Error),
+    /// The backup was written by a newer, incompatible version
+    #[error("Unsupported backup version {0}")]
+    UnsupportedVersion(u32),
+    /// The backup is incomplete or refers to unknown collections
+    #[error("Invalid backup: {0}")]
+    InvalidBackup(String),
+    /// Documents already exist and the conflict policy is `fail`
+    #[error("Conflicting documents: {0}")]
+    Conflict(String),
+    /// A database operation failed
+    #[error("Database error: {0}")]
+    Database(#[from] rocket_db_pools::mongodb::error::Error),
+}
+
+/// Errors raised while resolving the server and CLI configuration.
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    /// No MongoDB connection string was given anywhere
+    #[error(
+        "No database URL configured; pass --database-url, set DATABASE_URL or MONGODB_URL, or set databases.bearodata.url in the config file"
+    )]
+    MissingDatabaseUrl,
+}
+
+/// Errors raised by the content-management CLI commands.
+#[derive(Error, Debug)]
+pub enum ContentError {
+    /// A document id is not a valid ObjectId
+    #[error("Invalid id: {0}")]
+    InvalidId(String),
+    /// No document has the given id
+    #[error("No document found with id {0}")]
+    NotFound(ObjectId),
+    /// The document failed the same validation the HTTP handlers apply
+    #[error("Rejected: {0}")]
+    Rejected(String),
+    /// A document is not valid JSON
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    /// A document is not valid YAML
+    #[error("YAML error: {0}")]
+    Yaml(#[from] serde_yaml::Error),
+    /// The editor could not be started or exited with an error
+    #[error("Editor error: {0}")]
+    Editor(String),
+    /// A document file could not be read
+    #[error("IO error: {0}")]
+    Io(#[from] std::io::Error),
+    /// A database operation failed
+    #[error("Database error: {0}")]
+    Database(#[from] rocket_db_pools::mongodb::error::Error),
+}
+
+/// Errors raised while parsing event names, ids and stream filters.
+#[derive(Error, Debug, PartialEq)]
+pub enum EventError {
+    /// No event has the given name
+    #[error("Unknown event `{0}`")]
+    UnknownEvent(String),
+    /// A stream filter names no known collection
+    #[error("Unknown collection `{0}`")]
+    UnknownCollection(String),
+    /// An event id is not of the form `<epoch>-<sequence>`
+    #[error("Invalid event id `{0}`")]
+    InvalidId(String),
+}
+
+/// Errors raised while registering webhooks and queueing deliveries.
+#[derive(Error, Debug)]
+pub enum WebhookError {
+    /// The target is not an absolute http or https URL
+    #[error("Invalid webhook URL: {0}")]
+    InvalidUrl(String),
+    /// An event filter names no known event
+    #[error("Unknown event filter `{0}`")]
+    UnknownEvent(String),
+    /// A webhook must subscribe to at least one event
+    #[error("At least one event filter is required")]
+    NoEvents,
+    /// A signing secret was given but is empty
+    #[error("The webhook secret must not be empty")]
+    EmptySecret,
+    /// No webhook or delivery has the given id
+    #[error("No webhook or delivery found with id {0}")]
+    NotFound(ObjectId),
+    /// A document could not be converted to BSON
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] mongodb::bson::ser::Error),
+    /// A payload could not be converted to JSON
+    #[error("JSON error: {0}")]
+    Json(#[from] serde_json::Error),
+    /// A database operation failed
+    #[error("Database error: {0}")]
+    Database(#[from] rocket_db_pools::mongodb::error::Error),
+}
+
+/// Errors raised while recording or restoring document revisions.
+#[derive(Error, Debug)]
+pub enum RevisionError {
+    /// The document has no revision with the given number
+    #[error("No revision {number} of document {document_id}")]
+    NotFound { document_id: ObjectId, number: u32 },
+    /// The snapshot no longer deserializes into the current model
+    #[error("Revision cannot be restored: {0}")]
+    Incompatible(#[from] mongodb::bson::de::Error),
+    /// A document could not be converted to BSON
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] mongodb::bson::ser::Error),
+    /// A database operation failed
+    #[error("Database error: {0}")]
+    Database(#[from] rocket_db_pools::mongodb::error::Error),
+}
+
+/// Errors raised while moving documents to and from the trash.
+#[derive(Error, Debug)]
+pub enum TrashError {
+    /// No collection of trashable documents has the given name
+    #[error("Unknown collection `{0}`")]
+    UnknownCollection(String),
+    /// The trash holds no document with the given id
+    #[error("No trashed document found with id {0}")]
+    NotFound(ObjectId),
+    /// A live document already takes the place of the one being restored
+    #[error("Document {0} conflicts with a document that is not in the trash")]
+    Conflict(ObjectId),
+    /// Bulk deletes must match on at least one field
+    #[error("A non-empty filter is required")]
+    EmptyFilter,
+    /// Bulk deletes must be confirmed with `confirm: true`
+    #[error("Bulk deletes must be confirmed")]
+    Unconfirmed,
+    /// A stored document no longer deserializes into its model
+    #[error("Deserialization error: {0}")]
+    Deserialization(#[from] mongodb::bson::de::Error),
+    /// A document could not be converted to BSON
+    #[error("Serialization error: {0}")]
+    Serialization(#[from] mongodb::bson::ser::Error),
+    /// A database operation failed
+    #[error("Database error: {0}")]
+    Database(#[from] rocket_db_pools::mongodb::error::Error),
+}
+
+/// Reasons a Markdown field is rejected on write.
+#[derive(Error, Debug, PartialEq)]
+pub enum MarkdownError {
+    /// Raw HTML contains a script, embedded frame or event handler
+    #[error("Unsafe HTML is not allowed: {0}")]
+    UnsafeHtml(String),
+    /// A link or image points to a scheme other than http, https or mailto
+    #[error("Unsafe link target: {0}")]
+    UnsafeLink(String),
+}
+
 /// Converts AuthError to HTTP responses with appropriate status codes and JSON bodies.
 ///
 /// # Response Format
@@ -50,6 +262,7 @@ impl<'r> Responder<'r, 'static> for AuthError {
                 (Status::Unauthorized, "Invalid Authorization header format")
             }
             AuthError::InvalidKey => (Status::Unauthorized, "Invalid API key"),
+            AuthError::ExpiredKey => (Status::Unauthorized, "API key expired"),
             AuthError::InsufficientPermissions => (Status::Forbidden, "Insufficient permissions"),
             AuthError::Database => (Status::InternalServerError, "Internal server error"),
         };
@@ -140,6 +353,7 @@ mod tests {
             "Invalid Authorization header format"
         );
         assert_eq!(AuthError::InvalidKey.to_string(), "Invalid API key");
+        assert_eq!(AuthError::ExpiredKey.to_string(), "API key expired");
         assert_eq!(
             AuthError::InsufficientPermissions.to_string(),
             "Insufficient permissions"
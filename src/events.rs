@@ -0,0 +1,456 @@
+//! # Live events
+//!
+//! Every successful write to books, games, projects, reviews, series and
+//! wplace screenshots calls [`publish`]. The document is recorded as a
+//! revision (see [`crate::history`]), and the event is appended to the
+//! in-memory [`EVENTS`] log, pushed to `/events` subscribers and queued for
+//! matching webhooks (see [`crate::webhooks`]).
+//!
+//! ## Events
+//!
+//! Events are named `<resource>.<action>`, e.g. `book.created`,
+//! `screenshot.deleted` or `review.updated`. Their data is the document as
+//! written, or as it was before deletion.
+//!
+//! ## Event log
+//!
+//! The log keeps the last [`LOG_CAPACITY`] events. Event ids have the form
+//! `<epoch>-<sequence>`, where the epoch identifies the server process, so a
+//! client reconnecting with `Last-Event-ID` receives exactly the events it
+//! missed. When those are gone, because the id is older than the log or
+//! comes from an earlier process, [`EventLog::subscribe`] reports a reset and
+//! the client should reload what it shows.
+
+use {
+    crate::{errors::EventError, history, webhooks},
+    chrono::NaiveDateTime,
+    rocket::serde::Serialize,
+    rocket_db_pools::mongodb::Client,
+    serde_json::{Value, json},
+    std::{
+        collections::VecDeque,
+        fmt,
+        str::FromStr,
+        sync::{Arc, LazyLock, Mutex},
+    },
+    tokio::sync::broadcast,
+};
+
+/// Events kept for `Last-Event-ID` resumes.
+pub const LOG_CAPACITY: usize = 1000;
+
+/// The process-wide event log.
+pub static EVENTS: LazyLock<EventLog> = LazyLock::new(|| EventLog::new(LOG_CAPACITY));
+
+/// Kinds of documents that emit events.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Resource {
+    Book,
+    Game,
+    Project,
+    Review,
+    Series,
+    Screenshot,
+}
+
+impl Resource {
+    pub const ALL: [Resource; 6] = [
+        Resource::Book,
+        Resource::Game,
+        Resource::Project,
+        Resource::Review,
+        Resource::Series,
+        Resource::Screenshot,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Resource::Book => "book",
+            Resource::Game => "game",
+            Resource::Project => "project",
+            Resource::Review => "review",
+            Resource::Series => "series",
+            Resource::Screenshot => "screenshot",
+        }
+    }
+
+    /// Name used by the `collections` filter of `/events`.
+    pub fn collection(&self) -> &'static str {
+        match self {
+            Resource::Book => "books",
+            Resource::Game => "games",
+            Resource::Project => "projects",
+            Resource::Review => "reviews",
+            Resource::Series => "series",
+            Resource::Screenshot => "wplace",
+        }
+    }
+}
+
+/// What happened to a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Created,
+    Updated,
+    Deleted,
+}
+
+impl Action {
+    pub const ALL: [Action; 3] = [Action::Created, Action::Updated, Action::Deleted];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Action::Created => "created",
+            Action::Updated => "updated",
+            Action::Deleted => "deleted",
+        }
+    }
+}
+
+/// An event published to `/events` and webhooks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    /// A document was created, updated or deleted
+    Change(Resource, Action),
+    /// A test delivery requested through `POST /webhooks/<id>/ping`
+    Ping,
+}
+
+impl Event {
+    pub fn created(resource: Resource) -> Self {
+        Event::Change(resource, Action::Created)
+    }
+
+    pub fn updated(resource: Resource) -> Self {
+        Event::Change(resource, Action::Updated)
+    }
+
+    pub fn deleted(resource: Resource) -> Self {
+        Event::Change(resource, Action::Deleted)
+    }
+
+    /// Whether a webhook subscribed with `filter` receives this event.
+    ///
+    /// Pings are only sent on request and match no filter.
+    pub fn matches(&self, filter: &str) -> bool {
+        match self {
+            Event::Change(resource, action) => match filter.split_once('.') {
+                _ if filter == "*" => true,
+                Some((name, "*")) => name == resource.as_str(),
+                Some((name, verb)) => name == resource.as_str() && verb == action.as_str(),
+                None => false,
+            },
+            Event::Ping => false,
+        }
+    }
+}
+
+impl fmt::Display for Event {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Event::Change(resource, action) => {
+                write!(f, "{}.{}", resource.as_str(), action.as_str())
+            }
+            Event::Ping => f.write_str("ping"),
+        }
+    }
+}
+
+impl FromStr for Event {
+    type Err = EventError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s == "ping" {
+            return Ok(Event::Ping);
+        }
+
+        Resource::ALL
+            .iter()
+            .flat_map(|resource| {
+                Action::ALL
+                    .iter()
+                    .map(|action| Event::Change(*resource, *action))
+            })
+            .find(|event| event.to_string() == s)
+            .ok_or_else(|| EventError::UnknownEvent(s.to_string()))
+    }
+}
+
+/// Restricts a stream to some collections, e.g. `books,wplace`.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct CollectionFilter(Option<Vec<Resource>>);
+
+impl CollectionFilter {
+    pub fn matches(&self, event: &Event) -> bool {
+        match (&self.0, event) {
+            (None, _) => true,
+            (Some(resources), Event::Change(resource, _)) => resources.contains(resource),
+            (Some(_), Event::Ping) => false,
+        }
+    }
+}
+
+impl FromStr for CollectionFilter {
+    type Err = EventError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                Resource::ALL
+                    .into_iter()
+                    .find(|resource| resource.collection() == name)
+                    .ok_or_else(|| EventError::UnknownCollection(name.to_string()))
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(|resources| CollectionFilter(Some(resources)))
+    }
+}
+
+/// Identifies an event in the log across reconnects.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EventId {
+    pub epoch: i64,
+    pub sequence: u64,
+}
+
+impl fmt::Display for EventId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}-{}", self.epoch, self.sequence)
+    }
+}
+
+impl FromStr for EventId {
+    type Err = EventError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.split_once('-')
+            .and_then(|(epoch, sequence)| {
+                Some(EventId {
+                    epoch: epoch.parse().ok()?,
+                    sequence: sequence.parse().ok()?,
+                })
+            })
+            .ok_or_else(|| EventError::InvalidId(s.to_string()))
+    }
+}
+
+/// An event as kept in the log.
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoggedEvent {
+    pub id: EventId,
+    pub event: Event,
+    pub created_at: NaiveDateTime,
+    pub data: Value,
+}
+
+impl LoggedEvent {
+    /// The JSON sent as the event's data: `{"event": ..., "created_at": ..., "data": {...}}`.
+    pub fn payload(&self) -> String {
+        json!({
+            "event": self.event.to_string(),
+            "created_at": self.created_at,
+            "data": self.data,
+        })
+        .to_string()
+    }
+}
+
+struct LogState {
+    next_sequence: u64,
+    events: VecDeque<Arc<LoggedEvent>>,
+}
+
+/// A bounded log of recent events with a live feed of new ones.
+pub struct EventLog {
+    epoch: i64,
+    capacity: usize,
+    state: Mutex<LogState>,
+    sender: broadcast::Sender<Arc<LoggedEvent>>,
+}
+
+/// What a new subscriber receives: the events it missed, then live ones.
+pub struct Subscription {
+    /// The events after `Last-Event-ID`, oldest first
+    pub backlog: Vec<Arc<LoggedEvent>>,
+    /// Whether events after `Last-Event-ID` were lost
+    pub reset: bool,
+    pub receiver: broadcast::Receiver<Arc<LoggedEvent>>,
+}
+
+impl EventLog {
+    pub fn new(capacity: usize) -> Self {
+        EventLog {
+            epoch: chrono::Utc::now().timestamp_millis(),
+            capacity,
+            state: Mutex::new(LogState {
+                next_sequence: 1,
+                events: VecDeque::with_capacity(capacity),
+            }),
+            sender: broadcast::channel(capacity.max(1)).0,
+        }
+    }
+
+    /// Appends an event, dropping the oldest one when the log is full.
+    pub fn record(&self, event: Event, data: Value) -> Arc<LoggedEvent> {
+        let mut state = self.state.lock().expect("event log lock poisoned");
+
+        let logged = Arc::new(LoggedEvent {
+            id: EventId {
+                epoch: self.epoch,
+                sequence: state.next_sequence,
+            },
+            event,
+            created_at: chrono::Utc::now().naive_utc(),
+            data,
+        });
+        state.next_sequence += 1;
+        state.events.push_back(logged.clone());
+        if state.events.len() > self.capacity {
+            state.events.pop_front();
+        }
+
+        // Sending while holding the lock keeps the feed in log order; having
+        // no subscribers is not an error.
+        let _ = self.sender.send(logged.clone());
+        logged
+    }
+
+    /// Subscribes to new events, replaying those after `last_event_id`.
+    pub fn subscribe(&self, last_event_id: Option<&str>) -> Subscription {
+        let state = self.state.lock().expect("event log lock poisoned");
+        let receiver = self.sender.subscribe();
+
+        let Some(last_event_id) = last_event_id else {
+            return Subscription {
+                backlog: Vec::new(),
+                reset: false,
+                receiver,
+            };
+        };
+
+        let oldest = state
+            .events
+            .front()
+            .map_or(state.next_sequence, |event| event.id.sequence);
+        let last = match last_event_id.parse::<EventId>() {
+            Ok(id)
+                if id.epoch == self.epoch
+                    && id.sequence + 1 >= oldest
+                    && id.sequence < state.next_sequence =>
+            {
+                id
+            }
+            _ => {
+                return Subscription {
+                    backlog: Vec::new(),
+                    reset: true,
+                    receiver,
+                };
+            }
+        };
+
+        Subscription {
+            backlog: state
+                .events
+                .iter()
+                .filter(|event| event.id.sequence > last.sequence)
+                .cloned()
+                .collect(),
+            reset: false,
+            receiver,
+        }
+    }
+}
+
+/// Publishes a change: records a revision, adds the event to [`EVENTS`] and
+/// queues webhook deliveries.
+///
+/// Called after a write has succeeded; failures are logged and do not fail
+/// the write.
+pub async fn publish<T: Serialize>(db: &Client, event: Event, data: &T) {
+    publish_with(db, event, data, None).await
+}
+
+/// Publishes the write-back of revision `reverted_to` by [`history::revert`].
+pub async fn publish_revert<T: Serialize>(db: &Client, event: Event, data: &T, reverted_to: u32) {
+    publish_with(db, event, data, Some(reverted_to)).await
+}
+
+async fn publish_with<T: Serialize>(db: &Client, event: Event, data: &T, reverted_to: Option<u32>) {
+    if let Err(e) = history::record(db, event, data, reverted_to).await {
+        tracing::warn!(event = %event, error = %e, "failed to record revision");
+    }
+
+    match serde_json::to_value(data) {
+        Ok(value) => {
+            EVENTS.record(event, value);
+        }
+        Err(e) => tracing::warn!(event = %event, error = %e, "failed to record event"),
+    }
+
+    webhooks::emit(db, event, data).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_collection_filter() {
+        let filter: CollectionFilter = "books, wplace".parse().unwrap();
+        assert!(filter.matches(&Event::created(Resource::Book)));
+        assert!(filter.matches(&Event::deleted(Resource::Screenshot)));
+        assert!(!filter.matches(&Event::updated(Resource::Game)));
+        assert!(!filter.matches(&Event::Ping));
+
+        assert!(CollectionFilter::default().matches(&Event::updated(Resource::Game)));
+        assert!(matches!(
+            "books,movies".parse::<CollectionFilter>(),
+            Err(EventError::UnknownCollection(name)) if name == "movies"
+        ));
+    }
+
+    #[test]
+    fn test_event_ids() {
+        let id = EventId {
+            epoch: 1_700_000_000_000,
+            sequence: 42,
+        };
+        assert_eq!(id.to_string(), "1700000000000-42");
+        assert_eq!("1700000000000-42".parse::<EventId>().unwrap(), id);
+        assert!("42".parse::<EventId>().is_err());
+        assert!("a-b".parse::<EventId>().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_resume_from_bounded_log() {
+        let log = EventLog::new(3);
+        let ids: Vec<String> = (0..5)
+            .map(|n| {
+                log.record(Event::created(Resource::Book), json!({ "n": n }))
+                    .id
+                    .to_string()
+            })
+            .collect();
+
+        // Only the last three events are kept.
+        let resumed = log.subscribe(Some(&ids[2]));
+        assert!(!resumed.reset);
+        let replayed: Vec<&Value> = resumed.backlog.iter().map(|event| &event.data).collect();
+        assert_eq!(replayed, [&json!({ "n": 3 }), &json!({ "n": 4 })]);
+
+        assert!(log.subscribe(Some(&ids[4])).backlog.is_empty());
+        assert!(log.subscribe(Some(&ids[0])).reset);
+        assert!(log.subscribe(Some("1-1")).reset);
+        assert!(log.subscribe(Some("garbage")).reset);
+
+        let mut live = log.subscribe(None);
+        assert!(live.backlog.is_empty() && !live.reset);
+        log.record(Event::deleted(Resource::Screenshot), json!({}));
+        let event = live.receiver.recv().await.unwrap();
+        assert_eq!(event.event.to_string(), "screenshot.deleted");
+        assert_eq!(event.id.sequence, 6);
+    }
+}
@@ -0,0 +1,660 @@
+//! # Activity feeds
+//!
+//! Builds the activity feed served at `/feeds/activity.atom`, `.rss` and
+//! `.json` from the catalog: books added to the read-watch list, games
+//! finished, chapter reviews written and wplace screenshots posted.
+//!
+//! Item dates come from the documents: a game's `finished_at`, and the
+//! creation time encoded in the ObjectId of everything else. Titles and
+//! summaries are localized for the requested locale.
+//!
+//! Rendering is deterministic for a given catalog, locale and base URL, so the
+//! body hash serves as the `ETag` and the newest item date as `Last-Modified`
+//! (see [`not_modified`]).
+//!
+//! Feed links are absolute, built on `public_url` in `Rocket.toml` or
+//! `ROCKET_PUBLIC_URL` (e.g. `https://api.bearodactyl.dev`). Without it they
+//! fall back to the `Host` the request was made to; forwarded headers are
+//! never trusted, since feed responses are publicly cacheable.
+
+use {
+    crate::{
+        models::{Book, Game, LocalizedString, Review, WorkKind, WplaceScreenshot},
+        trash,
+    },
+    chrono::{DateTime, NaiveDateTime, SecondsFormat, Timelike},
+    mongodb::bson::{self, Document, doc, oid::ObjectId},
+    rocket::futures::TryStreamExt,
+    rocket_db_pools::mongodb::{Client, error::Error, options::FindOptions},
+    serde_json::json,
+    sha2::{Digest, Sha256},
+    std::{cmp::Reverse, collections::HashMap, fmt::Write},
+};
+
+/// Items per feed.
+pub const FEED_SIZE: usize = 50;
+
+/// Title of the activity feed.
+pub const FEED_TITLE: &str = "Bearodactyl activity";
+
+/// Configuration key of the public base URL of the API.
+pub const PUBLIC_URL_KEY: &str = "public_url";
+
+/// Reads the public base URL from the configuration, without a trailing slash.
+pub fn public_url(figment: &rocket::figment::Figment) -> Option<String> {
+    figment
+        .extract_inner::<String>(PUBLIC_URL_KEY)
+        .ok()
+        .map(|url| url.trim_end_matches('/').to_string())
+        .filter(|url| !url.is_empty())
+}
+
+/// What an activity item is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ActivityKind {
+    BookAdded,
+    GameCompleted,
+    ReviewAdded,
+    ScreenshotAdded,
+}
+
+impl ActivityKind {
+    /// Category of the item in every feed format.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ActivityKind::BookAdded => "book.added",
+            ActivityKind::GameCompleted => "game.completed",
+            ActivityKind::ReviewAdded => "review.added",
+            ActivityKind::ScreenshotAdded => "screenshot.added",
+        }
+    }
+}
+
+/// One entry of the activity feed.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActivityItem {
+    pub kind: ActivityKind,
+    /// ObjectId of the document the item is about
+    pub oid: ObjectId,
+    pub title: String,
+    pub summary: String,
+    /// API path of the document, e.g. `/games/<id>`
+    pub path: String,
+    pub image: Option<String>,
+    pub date: NaiveDateTime,
+}
+
+impl ActivityItem {
+    /// Stable, globally unique id of the item.
+    pub fn id(&self) -> String {
+        format!(
+            "urn:apiodactyl:{}:{}",
+            self.kind.as_str(),
+            self.oid.to_hex()
+        )
+    }
+}
+
+/// Output formats of the activity feed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeedFormat {
+    Atom,
+    Rss,
+    Json,
+}
+
+impl FeedFormat {
+    /// Path the feed is served at.
+    pub fn path(&self) -> &'static str {
+        match self {
+            FeedFormat::Atom => "/feeds/activity.atom",
+            FeedFormat::Rss => "/feeds/activity.rss",
+            FeedFormat::Json => "/feeds/activity.json",
+        }
+    }
+
+    /// Media type of the feed, as `(top, sub)`.
+    pub fn media_type(&self) -> (&'static str, &'static str) {
+        match self {
+            FeedFormat::Atom => ("application", "atom+xml"),
+            FeedFormat::Rss => ("application", "rss+xml"),
+            FeedFormat::Json => ("application", "feed+json"),
+        }
+    }
+}
+
+/// The activity feed, ready to render.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ActivityFeed {
+    /// Scheme and host the feed is served from, without a trailing slash
+    pub base_url: String,
+    /// Language of localized titles and summaries
+    pub language: Option<String>,
+    /// Items, newest first
+    pub items: Vec<ActivityItem>,
+}
+
+impl ActivityFeed {
+    /// Date of the newest item.
+    pub fn updated(&self) -> Option<NaiveDateTime> {
+        self.items.iter().map(|item| item.date).max()
+    }
+
+    pub fn render(&self, format: FeedFormat) -> String {
+        match format {
+            FeedFormat::Atom => self.to_atom(),
+            FeedFormat::Rss => self.to_rss(),
+            FeedFormat::Json => self.to_json(),
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        format!("{}{}", self.base_url, path)
+    }
+
+    /// Renders an Atom 1.0 document.
+    pub fn to_atom(&self) -> String {
+        let updated = self.updated().unwrap_or(DateTime::UNIX_EPOCH.naive_utc());
+        let self_url = self.url(FeedFormat::Atom.path());
+
+        let lang = self
+            .language
+            .as_deref()
+            .map(|language| format!(" xml:lang=\"{}\"", escape(language)))
+            .unwrap_or_default();
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        let _ = writeln!(xml, "<feed xmlns=\"http://www.w3.org/2005/Atom\"{}>", lang);
+        let _ = writeln!(xml, "  <title>{}</title>", escape(FEED_TITLE));
+        let _ = writeln!(xml, "  <id>{}</id>", escape(&self_url));
+        let _ = writeln!(
+            xml,
+            "  <link rel=\"self\" type=\"application/atom+xml\" href=\"{}\"/>",
+            escape(&self_url)
+        );
+        let _ = writeln!(
+            xml,
+            "  <link rel=\"alternate\" href=\"{}/\"/>",
+            escape(&self.base_url)
+        );
+        let _ = writeln!(xml, "  <updated>{}</updated>", rfc3339(updated));
+        let _ = writeln!(xml, "  <author><name>bearodactyl</name></author>");
+
+        for item in &self.items {
+            let _ = writeln!(xml, "  <entry>");
+            let _ = writeln!(xml, "    <title>{}</title>", escape(&item.title));
+            let _ = writeln!(xml, "    <id>{}</id>", escape(&item.id()));
+            let _ = writeln!(
+                xml,
+                "    <link href=\"{}\"/>",
+                escape(&self.url(&item.path))
+            );
+            let _ = writeln!(xml, "    <updated>{}</updated>", rfc3339(item.date));
+            let _ = writeln!(xml, "    <category term=\"{}\"/>", item.kind.as_str());
+            if !item.summary.is_empty() {
+                let _ = writeln!(xml, "    <summary>{}</summary>", escape(&item.summary));
+            }
+            if let Some(image) = &item.image {
+                let _ = writeln!(
+                    xml,
+                    "    <link rel=\"enclosure\" href=\"{}\"/>",
+                    escape(image)
+                );
+            }
+            let _ = writeln!(xml, "  </entry>");
+        }
+
+        xml.push_str("</feed>\n");
+        xml
+    }
+
+    /// Renders an RSS 2.0 document.
+    pub fn to_rss(&self) -> String {
+        let self_url = self.url(FeedFormat::Rss.path());
+
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"utf-8\"?>\n");
+        xml.push_str("<rss version=\"2.0\" xmlns:atom=\"http://www.w3.org/2005/Atom\">\n");
+        xml.push_str("  <channel>\n");
+        let _ = writeln!(xml, "    <title>{}</title>", escape(FEED_TITLE));
+        let _ = writeln!(xml, "    <link>{}/</link>", escape(&self.base_url));
+        let _ = writeln!(
+            xml,
+            "    <description>What we are reading, playing and making</description>"
+        );
+        let _ = writeln!(
+            xml,
+            "    <atom:link href=\"{}\" rel=\"self\" type=\"application/rss+xml\"/>",
+            escape(&self_url)
+        );
+        if let Some(language) = &self.language {
+            let _ = writeln!(xml, "    <language>{}</language>", escape(language));
+        }
+        if let Some(updated) = self.updated() {
+            let _ = writeln!(
+                xml,
+                "    <lastBuildDate>{}</lastBuildDate>",
+                rfc2822(updated)
+            );
+        }
+
+        for item in &self.items {
+            let _ = writeln!(xml, "    <item>");
+            let _ = writeln!(xml, "      <title>{}</title>", escape(&item.title));
+            let _ = writeln!(xml, "      <link>{}</link>", escape(&self.url(&item.path)));
+            let _ = writeln!(
+                xml,
+                "      <guid isPermaLink=\"false\">{}</guid>",
+                escape(&item.id())
+            );
+            let _ = writeln!(xml, "      <pubDate>{}</pubDate>", rfc2822(item.date));
+            let _ = writeln!(xml, "      <category>{}</category>", item.kind.as_str());
+            if !item.summary.is_empty() {
+                let _ = writeln!(
+                    xml,
+                    "      <description>{}</description>",
+                    escape(&item.summary)
+                );
+            }
+            let _ = writeln!(xml, "    </item>");
+        }
+
+        xml.push_str("  </channel>\n</rss>\n");
+        xml
+    }
+
+    /// Renders a JSON Feed 1.1 document.
+    pub fn to_json(&self) -> String {
+        let items: Vec<_> = self
+            .items
+            .iter()
+            .map(|item| {
+                let mut entry = json!({
+                    "id": item.id(),
+                    "url": self.url(&item.path),
+                    "title": item.title,
+                    "content_text": item.summary,
+                    "date_published": rfc3339(item.date),
+                    "tags": [item.kind.as_str()],
+                });
+                if let Some(image) = &item.image {
+                    entry["image"] = json!(image);
+                }
+                entry
+            })
+            .collect();
+
+        let mut feed = json!({
+            "version": "https://jsonfeed.org/version/1.1",
+            "title": FEED_TITLE,
+            "home_page_url": format!("{}/", self.base_url),
+            "feed_url": self.url(FeedFormat::Json.path()),
+            "items": items,
+        });
+        if let Some(language) = &self.language {
+            feed["language"] = json!(language);
+        }
+        serde_json::to_string_pretty(&feed).expect("feeds serialize to JSON")
+    }
+}
+
+/// Escapes text for XML content and attribute values.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters are not allowed in XML 1.0
+            c if c.is_control() && !matches!(c, '\n' | '\r' | '\t') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn rfc3339(date: NaiveDateTime) -> String {
+    date.and_utc().to_rfc3339_opts(SecondsFormat::Secs, true)
+}
+
+fn rfc2822(date: NaiveDateTime) -> String {
+    date.and_utc().to_rfc2822()
+}
+
+/// Formats a date for the `Last-Modified` header.
+pub fn http_date(date: NaiveDateTime) -> String {
+    date.format("%a, %d %b %Y %H:%M:%S GMT").to_string()
+}
+
+/// Parses an `If-Modified-Since` header.
+pub fn parse_http_date(value: &str) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(value.trim(), "%a, %d %b %Y %H:%M:%S GMT").ok()
+}
+
+/// Strong entity tag of a rendered feed.
+pub fn etag(body: &str) -> String {
+    let digest = hex::encode(Sha256::digest(body.as_bytes()));
+    format!("\"{}\"", &digest[..32])
+}
+
+/// Whether a conditional request may be answered with `304 Not Modified`.
+///
+/// `If-None-Match` takes precedence over `If-Modified-Since`, which is
+/// compared at the one-second resolution of HTTP dates.
+pub fn not_modified(
+    if_none_match: Option<&str>,
+    if_modified_since: Option<&str>,
+    etag: &str,
+    last_modified: Option<NaiveDateTime>,
+) -> bool {
+    if let Some(if_none_match) = if_none_match {
+        return if_none_match
+            .split(',')
+            .map(str::trim)
+            .any(|tag| tag == "*" || tag.strip_prefix("W/").unwrap_or(tag) == etag);
+    }
+
+    match (
+        if_modified_since.and_then(parse_http_date),
+        last_modified.and_then(|date| date.with_nanosecond(0)),
+    ) {
+        (Some(since), Some(modified)) => modified <= since,
+        _ => false,
+    }
+}
+
+/// The creation time encoded in an ObjectId.
+fn created_at(oid: &ObjectId) -> NaiveDateTime {
+    DateTime::from_timestamp_millis(oid.timestamp().timestamp_millis())
+        .unwrap_or_default()
+        .naive_utc()
+}
+
+/// API path of the mount point of a work kind.
+fn work_mount(kind: WorkKind) -> &'static str {
+    match kind {
+        WorkKind::Book => "/read-watch",
+        WorkKind::Series => "/series",
+        WorkKind::Game => "/games",
+    }
+}
+
+/// Finds the newest documents of a collection, newest first.
+async fn newest<T>(
+    db: &Client,
+    collection: &str,
+    filter: Document,
+    sort: Document,
+) -> Result<Vec<T>, Error>
+where
+    T: serde::de::DeserializeOwned + Unpin + Send + Sync,
+{
+    let options = FindOptions::builder()
+        .sort(sort)
+        .limit(FEED_SIZE as i64)
+        .build();
+
+    db.database("bearodata")
+        .collection::<T>(collection)
+        .find(trash::live(filter), options)
+        .await?
+        .try_collect()
+        .await
+}
+
+/// Looks up the localized titles of the works the given reviews belong to.
+async fn work_titles(
+    db: &Client,
+    reviews: &[Review],
+    locale: Option<&str>,
+) -> Result<HashMap<ObjectId, String>, Error> {
+    let mut titles = HashMap::new();
+
+    for kind in [WorkKind::Book, WorkKind::Series, WorkKind::Game] {
+        let ids: Vec<ObjectId> = reviews
+            .iter()
+            .filter(|review| review.work_kind == Some(kind))
+            .filter_map(|review| review.work_id)
+            .collect();
+        if ids.is_empty() {
+            continue;
+        }
+
+        let works: Vec<Document> = db
+            .database("bearodata")
+            .collection::<Document>(kind.collection())
+            .find(trash::live(doc! { "_id": { "$in": ids } }), None)
+            .await?
+            .try_collect()
+            .await?;
+        for work in works {
+            let (Ok(oid), Some(title)) = (work.get_object_id("_id"), work.get("title")) else {
+                continue;
+            };
+            if let Ok(title) = bson::from_bson::<LocalizedString>(title.clone()) {
+                titles.insert(oid, title.get_text(locale));
+            }
+        }
+    }
+
+    Ok(titles)
+}
+
+/// Collects the newest [`FEED_SIZE`] activity items, newest first.
+pub async fn load_activity(db: &Client, locale: Option<&str>) -> Result<Vec<ActivityItem>, Error> {
+    let by_id = doc! { "_id": -1 };
+
+    let books: Vec<Book> = newest(db, "books", doc! {}, by_id.clone()).await?;
+    let games: Vec<Game> = newest(
+        db,
+        "games",
+        doc! {
+            "status": { "$in": ["completed", "100%"] },
+            "finished_at": { "$ne": null },
+        },
+        doc! { "finished_at": -1 },
+    )
+    .await?;
+    let reviews: Vec<Review> = newest(db, "reviews", doc! {}, by_id.clone()).await?;
+    let screenshots: Vec<WplaceScreenshot> =
+        newest(db, "wplace_screenshots", doc! {}, by_id).await?;
+    let titles = work_titles(db, &reviews, locale).await?;
+
+    let mut items = Vec::new();
+    items.extend(books.iter().map(|book| ActivityItem {
+        kind: ActivityKind::BookAdded,
+        oid: book.oid,
+        title: format!("Added {}", book.title.get_text(locale)),
+        summary: book.description.get_text(locale),
+        path: format!("/read-watch/{}", book.oid.to_hex()),
+        image: Some(book.cover_image.clone()).filter(|url| !url.is_empty()),
+        date: created_at(&book.oid),
+    }));
+    items.extend(games.iter().filter_map(|game| {
+        Some(ActivityItem {
+            kind: ActivityKind::GameCompleted,
+            oid: game.oid,
+            title: format!("Completed {}", game.title),
+            summary: game.my_thoughts.clone(),
+            path: format!("/games/{}", game.oid.to_hex()),
+            image: Some(game.cover_image.clone()).filter(|url| !url.is_empty()),
+            date: game.finished_at?,
+        })
+    }));
+    items.extend(reviews.iter().map(|review| {
+        let work = review.work_id.zip(review.work_kind);
+        let title = match work.and_then(|(work_id, _)| titles.get(&work_id)) {
+            Some(work_title) => format!("Chapter {} of {}", review.chapter, work_title),
+            None => format!("Chapter {}", review.chapter),
+        };
+        let path = match work {
+            Some((work_id, kind)) => format!(
+                "{}/{}/reviews/{}",
+                work_mount(kind),
+                work_id.to_hex(),
+                review.chapter
+            ),
+            None => format!("/reviews/{}", review.oid.to_hex()),
+        };
+
+        ActivityItem {
+            kind: ActivityKind::ReviewAdded,
+            oid: review.oid,
+            title,
+            summary: review.description.clone(),
+            path,
+            image: None,
+            date: created_at(&review.oid),
+        }
+    }));
+    items.extend(screenshots.iter().map(|screenshot| ActivityItem {
+        kind: ActivityKind::ScreenshotAdded,
+        oid: screenshot.oid,
+        title: "New wplace screenshot".to_string(),
+        summary: screenshot.alt.clone(),
+        path: format!("/wplace/{}", screenshot.oid.to_hex()),
+        image: Some(screenshot.cover_image.clone()).filter(|url| !url.is_empty()),
+        date: created_at(&screenshot.oid),
+    }));
+
+    items.sort_by_key(|item| Reverse(item.date));
+    items.truncate(FEED_SIZE);
+    Ok(items)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+
+    fn date(day: u32) -> NaiveDateTime {
+        NaiveDate::from_ymd_opt(2025, 3, day)
+            .unwrap()
+            .and_hms_opt(12, 30, 0)
+            .unwrap()
+    }
+
+    fn feed() -> ActivityFeed {
+        ActivityFeed {
+            base_url: "https://api.example.com".to_string(),
+            language: Some("de".to_string()),
+            items: vec![
+                ActivityItem {
+                    kind: ActivityKind::GameCompleted,
+                    oid: ObjectId::parse_str("65f0a1b2c3d4e5f601234567").unwrap(),
+                    title: "Completed Celeste".to_string(),
+                    summary: "Tight & <hard>".to_string(),
+                    path: "/games/65f0a1b2c3d4e5f601234567".to_string(),
+                    image: None,
+                    date: date(14),
+                },
+                ActivityItem {
+                    kind: ActivityKind::BookAdded,
+                    oid: ObjectId::parse_str("65f0a1b2c3d4e5f601234568").unwrap(),
+                    title: "Added Der Zauberberg".to_string(),
+                    summary: String::new(),
+                    path: "/read-watch/65f0a1b2c3d4e5f601234568".to_string(),
+                    image: Some("https://img.example.com/cover.jpg".to_string()),
+                    date: date(2),
+                },
+            ],
+        }
+    }
+
+    #[test]
+    fn test_atom_and_rss() {
+        let feed = feed();
+
+        let atom = feed.to_atom();
+        assert!(atom.contains("<feed xmlns=\"http://www.w3.org/2005/Atom\" xml:lang=\"de\">"));
+        assert!(atom.contains("<updated>2025-03-14T12:30:00Z</updated>"));
+        assert!(atom.contains("<summary>Tight &amp; &lt;hard&gt;</summary>"));
+        assert!(atom.contains("<id>urn:apiodactyl:game.completed:65f0a1b2c3d4e5f601234567</id>"));
+        assert!(atom.contains(
+            "<link href=\"https://api.example.com/read-watch/65f0a1b2c3d4e5f601234568\"/>"
+        ));
+
+        let rss = feed.to_rss();
+        assert!(rss.contains("<language>de</language>"));
+        assert!(rss.contains("<pubDate>Fri, 14 Mar 2025 12:30:00 +0000</pubDate>"));
+        assert!(rss.contains("<category>book.added</category>"));
+        assert_eq!(rss.matches("<item>").count(), 2);
+    }
+
+    #[test]
+    fn test_json_feed() {
+        let json: serde_json::Value = serde_json::from_str(&feed().to_json()).unwrap();
+        assert_eq!(json["version"], "https://jsonfeed.org/version/1.1");
+        assert_eq!(
+            json["feed_url"],
+            "https://api.example.com/feeds/activity.json"
+        );
+        assert_eq!(json["language"], "de");
+        assert_eq!(json["items"][0]["date_published"], "2025-03-14T12:30:00Z");
+        assert_eq!(
+            json["items"][1]["image"],
+            "https://img.example.com/cover.jpg"
+        );
+        assert!(json["items"][0].get("image").is_none());
+    }
+
+    #[test]
+    fn test_conditional_requests() {
+        let body = feed().to_atom();
+        let tag = etag(&body);
+        assert_eq!(tag, etag(&feed().to_atom()));
+        assert_ne!(tag, etag(&feed().to_rss()));
+
+        let modified = Some(date(14));
+        assert_eq!(http_date(date(14)), "Fri, 14 Mar 2025 12:30:00 GMT");
+
+        assert!(not_modified(Some(&tag), None, &tag, modified));
+        assert!(not_modified(
+            Some(&format!("\"x\", W/{tag}")),
+            None,
+            &tag,
+            modified
+        ));
+        assert!(!not_modified(Some("\"x\""), None, &tag, modified));
+        // If-None-Match wins over If-Modified-Since
+        assert!(!not_modified(
+            Some("\"x\""),
+            Some("Fri, 14 Mar 2025 12:30:00 GMT"),
+            &tag,
+            modified
+        ));
+
+        assert!(not_modified(
+            None,
+            Some("Fri, 14 Mar 2025 12:30:00 GMT"),
+            &tag,
+            modified
+        ));
+        assert!(!not_modified(
+            None,
+            Some("Fri, 14 Mar 2025 12:29:59 GMT"),
+            &tag,
+            modified
+        ));
+        assert!(!not_modified(None, Some("yesterday"), &tag, modified));
+        assert!(!not_modified(None, None, &tag, modified));
+    }
+
+    #[test]
+    fn test_public_url() {
+        use rocket::figment::Figment;
+
+        let figment = Figment::new().merge((PUBLIC_URL_KEY, "https://api.example.com/"));
+        assert_eq!(
+            public_url(&figment).as_deref(),
+            Some("https://api.example.com")
+        );
+        assert_eq!(public_url(&Figment::new()), None);
+        assert_eq!(
+            public_url(&Figment::new().merge((PUBLIC_URL_KEY, ""))),
+            None
+        );
+    }
+}
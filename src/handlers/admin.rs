@@ -0,0 +1,191 @@
+//! # Admin handlers
+//!
+//! Full-catalog export and restore. Every route requires an admin key.
+//!
+//! - `GET /admin/export` returns the whole catalog as NDJSON
+//! - `GET /admin/export/<file>` returns one file of the CSV layout
+//!   (`manifest.json` or `<collection>.csv`)
+//! - `POST /admin/import` restores an NDJSON backup
+//! - `POST /admin/import/<collection>` restores one collection from CSV
+//!
+//! Restoring `api_keys` clears the server's API key cache, so restored keys
+//! take effect on their next use.
+
+use {
+    crate::{
+        auth::{AuthService, User},
+        backup::{
+            API_KEYS, Backup, ConflictPolicy, MANIFEST_FILE, RestoreReport, backup_collections,
+        },
+        db::BearoData,
+        errors::BackupError,
+    },
+    rocket::{
+        Route, State,
+        data::{Data, ToByteUnit},
+        get,
+        http::{ContentType, Status},
+        post,
+        response::status,
+        routes,
+        serde::json::Json,
+    },
+    rocket_db_pools::Connection,
+    std::collections::BTreeMap,
+};
+
+fn error_status(e: BackupError) -> status::Custom<String> {
+    let status = match e {
+        BackupError::Database(_) => Status::InternalServerError,
+        BackupError::Conflict(_) => Status::Conflict,
+        _ => Status::UnprocessableEntity,
+    };
+    status::Custom(status, e.to_string())
+}
+
+fn ndjson() -> ContentType {
+    ContentType::new("application", "x-ndjson")
+}
+
+async fn read_body(file: Data<'_>) -> Result<String, status::Custom<String>> {
+    let input = file
+        .open(64.mebibytes())
+        .into_string()
+        .await
+        .map_err(|e| status::Custom(Status::BadRequest, e.to_string()))?;
+    if !input.is_complete() {
+        return Err(status::Custom(
+            Status::PayloadTooLarge,
+            "backup is too large".to_string(),
+        ));
+    }
+
+    Ok(input.into_inner())
+}
+
+/// Restores a backup, clearing the API key cache if it covers `api_keys`,
+/// even when the restore fails partway.
+async fn restore(
+    db: &Connection<BearoData>,
+    auth: &AuthService,
+    backup: &Backup,
+    policy: ConflictPolicy,
+) -> Result<Json<RestoreReport>, status::Custom<String>> {
+    let result = backup.restore(db, policy).await;
+    if backup.collections.contains_key(API_KEYS) {
+        auth.clear_cache();
+    }
+
+    result.map(Json).map_err(error_status)
+}
+
+/// Exports the whole catalog as NDJSON, including hashed API keys and webhook
+/// registrations if `keys=true`.
+#[get("/export?<keys>")]
+pub async fn export_ndjson(
+    db: Connection<BearoData>,
+    user: User,
+    keys: Option<bool>,
+) -> Result<(ContentType, String), status::Custom<String>> {
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
+
+    let backup = Backup::export(&db, &backup_collections(keys.unwrap_or(false)))
+        .await
+        .map_err(error_status)?;
+
+    Ok((ndjson(), backup.to_ndjson().map_err(error_status)?))
+}
+
+/// Exports one file of the CSV layout.
+#[get("/export/<file>?<keys>")]
+pub async fn export_csv_file(
+    db: Connection<BearoData>,
+    user: User,
+    file: &str,
+    keys: Option<bool>,
+) -> Result<(ContentType, String), status::Custom<String>> {
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
+
+    let keys = keys.unwrap_or(false);
+    let names = if file == MANIFEST_FILE {
+        backup_collections(keys)
+    } else {
+        let name = file.strip_suffix(".csv").unwrap_or_default();
+        match backup_collections(keys)
+            .into_iter()
+            .find(|known| *known == name)
+        {
+            Some(name) => vec![name],
+            None => {
+                return Err(status::Custom(
+                    Status::NotFound,
+                    format!("no backup file named `{}`", file),
+                ));
+            }
+        }
+    };
+
+    let mut files = Backup::export(&db, &names)
+        .await
+        .and_then(|backup| backup.to_csv_files())
+        .map_err(error_status)?;
+    let content_type = if file == MANIFEST_FILE {
+        ContentType::JSON
+    } else {
+        ContentType::CSV
+    };
+
+    Ok((content_type, files.remove(file).unwrap_or_default()))
+}
+
+/// Restores an NDJSON backup.
+#[post("/import?<policy>", data = "<file>")]
+pub async fn import_ndjson(
+    db: Connection<BearoData>,
+    user: User,
+    auth: &State<AuthService>,
+    policy: Option<ConflictPolicy>,
+    file: Data<'_>,
+) -> Result<Json<RestoreReport>, status::Custom<String>> {
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
+
+    let input = read_body(file).await?;
+    let backup = Backup::from_ndjson(&input).map_err(error_status)?;
+
+    restore(&db, auth, &backup, policy.unwrap_or_default()).await
+}
+
+/// Restores one collection from a CSV file of the backup layout.
+#[post("/import/<collection>?<policy>", data = "<file>")]
+pub async fn import_csv_collection(
+    db: Connection<BearoData>,
+    user: User,
+    auth: &State<AuthService>,
+    collection: &str,
+    policy: Option<ConflictPolicy>,
+    file: Data<'_>,
+) -> Result<Json<RestoreReport>, status::Custom<String>> {
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
+
+    let input = read_body(file).await?;
+    let documents = Backup::collection_from_csv(&input).map_err(error_status)?;
+
+    let mut collections = BTreeMap::new();
+    collections.insert(collection.to_string(), documents);
+    let backup = Backup::new(collections, collection == API_KEYS);
+
+    restore(&db, auth, &backup, policy.unwrap_or_default()).await
+}
+
+pub fn routes() -> Vec<Route> {
+    routes![
+        export_ndjson,
+        export_csv_file,
+        import_ndjson,
+        import_csv_collection
+    ]
+}
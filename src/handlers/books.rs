@@ -7,32 +7,53 @@ use {
     crate::{
         auth::User,
         db::BearoData,
-        models::{Book, Locale, LocalizedBook, LocalizedStringArray, NewBook, UpdateBook},
+        errors::ImportError,
+        events::{self, Event, Resource},
+        history,
+        importers::{
+            ImportReport,
+            books::{BookExportFormat, BookImportPlan, import_book_export},
+        },
+        markdown::{self, Render, Rendered},
+        models::{
+            Book, Locale, LocalizedBook, LocalizedString, LocalizedStringArray, MediaDetails,
+            MediaType, NewBook, ProgressEntry, ReadingProgress, Series, TermKind, UpdateBook,
+            UpdateProgress,
+        },
+        taxonomy::Taxonomy,
+        trash,
     },
-    mongodb::bson::{self, Document, doc, oid::ObjectId},
+    chrono::NaiveDateTime,
+    mongodb::bson::{self, Bson, Document, doc, oid::ObjectId},
     rocket::{
-        Route, delete,
+        Route,
+        data::{Data, ToByteUnit},
+        delete,
         form::FromForm,
         futures::TryStreamExt,
         get,
         http::Status,
-        patch, post, put, routes,
+        patch, post, put,
+        response::status,
+        routes,
         serde::{Deserialize, Serialize, json::Json},
     },
     rocket_db_pools::{
         Connection,
         mongodb::{
-            Collection,
+            Client, Collection,
             options::{FindOptions, UpdateOptions},
         },
     },
+    schemars::JsonSchema,
     std::collections::HashMap,
 };
 
 /// Query parameters for filtering book searches.
 ///
-/// Supports filtering by title, author, genres, tags, status, rating, and explicit content.
-#[derive(FromForm, Debug)]
+/// Supports filtering by title, author, genres, tags, status, rating, explicit content,
+/// media type, and series.
+#[derive(FromForm, Debug, JsonSchema)]
 pub struct BookQuery {
     title: Option<String>,
     author: Option<String>,
@@ -41,11 +62,21 @@ pub struct BookQuery {
     status: Option<String>,
     explicit: Option<String>,
     #[field(name = "minRating")]
+    #[schemars(rename = "minRating")]
     min_rating: Option<i32>,
     #[field(name = "maxRating")]
+    #[schemars(rename = "maxRating")]
     max_rating: Option<i32>,
     sort: Option<String>,
     locale: Option<String>,
+    #[field(name = "type")]
+    #[schemars(rename = "type")]
+    media_type: Option<MediaType>,
+    series: Option<String>,
+    /// Number of results to skip
+    offset: Option<usize>,
+    /// Maximum number of results to return
+    limit: Option<usize>,
 }
 
 /// Filter operation type for advanced query filtering.
@@ -75,6 +106,141 @@ impl FilterOperation {
             })
             .collect()
     }
+
+    fn map_value(self, f: impl Fn(&str) -> String) -> FilterOperation {
+        match self {
+            FilterOperation::Include(s) => FilterOperation::Include(f(&s)),
+            FilterOperation::Require(s) => FilterOperation::Require(f(&s)),
+            FilterOperation::Exclude(s) => FilterOperation::Exclude(f(&s)),
+        }
+    }
+}
+
+/// Replaces genre and tag slugs on a book with their localized taxonomy labels.
+pub fn render_terms(book: &mut Book, taxonomy: &Taxonomy) {
+    book.genres = taxonomy.render_array(TermKind::Genre, &book.genres);
+    book.tags = taxonomy.render_array(TermKind::Tag, &book.tags);
+}
+
+/// Checks that a referenced series exists.
+async fn ensure_series_exists(
+    db: &Client,
+    series_id: Option<ObjectId>,
+) -> Result<(), status::Custom<String>> {
+    let Some(series_id) = series_id else {
+        return Ok(());
+    };
+
+    db.database("bearodata")
+        .collection::<Series>("series")
+        .find_one(doc! { "_id": series_id }, None)
+        .await
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?
+        .map(|_| ())
+        .ok_or_else(|| {
+            status::Custom(
+                Status::UnprocessableEntity,
+                format!("series_id: no series {}", series_id),
+            )
+        })
+}
+
+/// Rejects thoughts containing scripts or unsafe links.
+fn validate_thoughts(my_thoughts: &LocalizedString) -> Result<(), status::Custom<String>> {
+    markdown::validate_localized(my_thoughts)
+        .map_err(|e| status::Custom(Status::UnprocessableEntity, format!("my_thoughts: {}", e)))
+}
+
+/// Validates progress sent with a whole book, returning the status it implies.
+///
+/// Applies the same rules as `PATCH /read-watch/<id>/progress`.
+fn validate_progress(
+    progress: &ReadingProgress,
+) -> Result<LocalizedString, status::Custom<String>> {
+    progress
+        .validate()
+        .map_err(|e| status::Custom(Status::UnprocessableEntity, format!("progress: {}", e)))?;
+    Ok(LocalizedString::Simple(
+        progress.status().as_str().to_string(),
+    ))
+}
+
+/// Records a book's progress in the `progress_history` collection.
+async fn record_progress(
+    db: &Client,
+    book_id: ObjectId,
+    progress: ReadingProgress,
+    now: NaiveDateTime,
+) -> Result<(), status::Custom<String>> {
+    let status = progress.status();
+    db.database("bearodata")
+        .collection::<ProgressEntry>("progress_history")
+        .insert_one(
+            ProgressEntry {
+                oid: ObjectId::new(),
+                book_id,
+                progress,
+                status,
+                recorded_at: now,
+            },
+            None,
+        )
+        .await
+        .map(|_| ())
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))
+}
+
+/// Detail fields to clear when `update` changes a book's media type: those
+/// that do not apply to the new type.
+fn stale_media_fields(book: &Book, update: &UpdateBook) -> &'static [&'static str] {
+    match update.media_type {
+        Some(media_type) if media_type != book.media_type => media_type.inapplicable_fields(),
+        _ => &[],
+    }
+}
+
+/// Validates the media fields a book would have after applying an update.
+///
+/// Stored details that a media type change clears are not carried over.
+fn validate_media_update(book: &Book, update: &UpdateBook) -> Result<(), String> {
+    let media_type = update.media_type.unwrap_or(book.media_type);
+    let author = update.author.as_ref().unwrap_or(&book.author);
+    let stale = stale_media_fields(book, update);
+    let stored = |field: &str| !stale.contains(&field);
+    let details = MediaDetails {
+        volumes: update
+            .volumes
+            .or(book.details.volumes.filter(|_| stored("volumes"))),
+        episodes: update
+            .episodes
+            .or(book.details.episodes.filter(|_| stored("episodes"))),
+        runtime_minutes: update.runtime_minutes.or(book
+            .details
+            .runtime_minutes
+            .filter(|_| stored("runtime_minutes"))),
+        studio: update
+            .studio
+            .clone()
+            .or_else(|| book.details.studio.clone().filter(|_| stored("studio"))),
+    };
+
+    media_type.validate(author, &details)
+}
+
+/// Builds the update writing `set` to a book and unsetting the detail fields
+/// its media type change cleared.
+fn media_update(set: Document, stale: &[&str]) -> Document {
+    let unset: Document = stale
+        .iter()
+        .filter(|field| !set.contains_key(field))
+        .map(|field| (field.to_string(), Bson::String(String::new())))
+        .collect();
+
+    if unset.is_empty() {
+        doc! { "$set": set }
+    } else {
+        doc! { "$set": set, "$unset": unset }
+    }
 }
 
 fn matches_filter_operations(
@@ -141,35 +307,42 @@ fn matches_filter_operations(
     true
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[schemars(rename = "BookBulkDeleteFilter")]
 pub struct BulkDeleteFilter {
-    author: Option<String>,
-    status: Option<String>,
+    pub author: Option<String>,
+    pub status: Option<String>,
+    /// Must be `true`; guards against trashing books by accident
+    pub confirm: Option<bool>,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[schemars(rename = "BookBulkUpdatePayload")]
 pub struct BulkUpdatePayload {
-    filter: HashMap<String, String>,
-    update: HashMap<String, serde_json::Value>,
+    pub filter: HashMap<String, String>,
+    pub update: HashMap<String, serde_json::Value>,
 }
 
-#[derive(Serialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[schemars(rename = "BookApiResponse")]
 pub struct ApiResponse {
-    message: String,
+    pub message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    deleted: Option<i64>,
+    pub deleted: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    updated: Option<i64>,
+    pub updated: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    count: Option<usize>,
+    pub count: Option<usize>,
 }
 
-#[get("/search?<query..>")]
+#[get("/search?<render>&<query..>")]
 pub async fn get_books(
     db: Connection<BearoData>,
+    taxonomy: &Taxonomy,
+    render: Option<Render>,
     query: BookQuery,
     locale: Locale,
-) -> Result<Json<Vec<LocalizedBook>>, Status> {
+) -> Result<Json<Vec<Rendered<LocalizedBook>>>, Status> {
     let collection: Collection<Book> = db.database("bearodata").collection("books");
     let mut filter = Document::new();
     let mut options = FindOptions::default();
@@ -199,6 +372,23 @@ pub async fn get_books(
         }
     }
 
+    if let Some(media_type) = query.media_type {
+        if media_type == MediaType::Book {
+            filter.insert(
+                "media_type",
+                doc! { "$in": [media_type.as_str(), Bson::Null] },
+            );
+        } else {
+            filter.insert("media_type", media_type.as_str());
+        }
+    }
+
+    if let Some(series_filter) = &query.series {
+        let series_oid = ObjectId::parse_str(series_filter).map_err(|_| Status::BadRequest)?;
+        filter.insert("series_id", series_oid);
+        options.sort = Some(doc! { "series_index": 1 });
+    }
+
     if query.min_rating.is_some() || query.max_rating.is_some() {
         let mut rating_filter = Document::new();
         if let Some(min_rating) = query.min_rating {
@@ -215,6 +405,8 @@ pub async fn get_books(
             "title" => doc! { "title": 1 },
             "author" => doc! { "author": 1 },
             "rating" => doc! { "rating": -1 },
+            "series" => doc! { "series_id": 1, "series_index": 1 },
+            "recent" => history::recent_sort(),
             _ => Document::new(),
         };
         if !sort_doc.is_empty() {
@@ -223,7 +415,7 @@ pub async fn get_books(
     }
 
     let mut cursor = collection
-        .find(filter, Some(options))
+        .find(trash::live(filter), Some(options))
         .await
         .map_err(|_| Status::InternalServerError)?;
 
@@ -236,6 +428,10 @@ pub async fn get_books(
         results.push(book);
     }
 
+    results
+        .iter_mut()
+        .for_each(|book| render_terms(book, taxonomy));
+
     if let Some(title_filter) = &query.title {
         let title_lower = title_filter.to_lowercase();
         results.retain(|book| {
@@ -257,43 +453,66 @@ pub async fn get_books(
     }
 
     if let Some(genre_filters) = &query.genre {
-        let genre_operations = FilterOperation::parse_filters(genre_filters);
+        let genre_operations: Vec<_> = FilterOperation::parse_filters(genre_filters)
+            .into_iter()
+            .map(|op| {
+                op.map_value(|value| {
+                    let slug = taxonomy.canonicalize(TermKind::Genre, value);
+                    taxonomy.render(TermKind::Genre, &slug, current_locale)
+                })
+            })
+            .collect();
         results.retain(|book| {
             matches_filter_operations(&book.genres, &genre_operations, current_locale)
         });
     }
 
     if let Some(tag_filters) = &query.tag {
-        let tag_operations = FilterOperation::parse_filters(tag_filters);
+        let tag_operations: Vec<_> = FilterOperation::parse_filters(tag_filters)
+            .into_iter()
+            .map(|op| {
+                op.map_value(|value| {
+                    let slug = taxonomy.canonicalize(TermKind::Tag, value);
+                    taxonomy.render(TermKind::Tag, &slug, current_locale)
+                })
+            })
+            .collect();
         results
             .retain(|book| matches_filter_operations(&book.tags, &tag_operations, current_locale));
     }
 
-    let localized_results = results
+    let localized_results = super::paginate(results, query.offset, query.limit)
         .into_iter()
-        .map(|book| book.localize(current_locale))
+        .map(|book| Rendered::new(book.localize(current_locale), render))
         .collect();
 
     Ok(Json(localized_results))
 }
 
-#[get("/<book_id>")]
+#[get("/<book_id>?<render>")]
 pub async fn get_book_by_id(
     db: Connection<BearoData>,
+    taxonomy: &Taxonomy,
     book_id: String,
+    render: Option<Render>,
     locale: Locale,
-) -> Result<Json<LocalizedBook>, Status> {
+) -> Result<Json<Rendered<LocalizedBook>>, Status> {
     let collection = db.database("bearodata").collection::<Book>("books");
 
     let oid = ObjectId::parse_str(&book_id).map_err(|_| Status::BadRequest)?;
 
-    let book = collection
-        .find_one(doc! { "_id": oid }, None)
+    let mut book = collection
+        .find_one(trash::live(doc! { "_id": oid }), None)
         .await
         .map_err(|_| Status::InternalServerError)?
         .ok_or(Status::NotFound)?;
 
-    Ok(Json(book.localize(locale.0.as_deref())))
+    render_terms(&mut book, taxonomy);
+
+    Ok(Json(Rendered::new(
+        book.localize(locale.0.as_deref()),
+        render,
+    )))
 }
 
 #[get("/raw/<book_id>")]
@@ -305,7 +524,7 @@ pub async fn get_raw_book_by_id(
     let oid = ObjectId::parse_str(&book_id).map_err(|_| Status::BadRequest)?;
 
     let book = collection
-        .find_one(doc! { "_id": oid }, None)
+        .find_one(trash::live(doc! { "_id": oid }), None)
         .await
         .map_err(|_| Status::InternalServerError)?
         .ok_or(Status::NotFound)?;
@@ -313,102 +532,171 @@ pub async fn get_raw_book_by_id(
     Ok(Json(book))
 }
 
-#[post("/", format = "json", data = "<new_book>")]
-pub async fn post_books(
-    db: Connection<BearoData>,
-    user: User,
-    new_book: Json<NewBook>,
-) -> Result<Json<Book>, Status> {
-    user.require_admin().map_err(|_| Status::Forbidden)?;
+/// Validates a new book, canonicalizes its terms and inserts it.
+///
+/// Shared by `POST /read-watch` and the `books create` CLI command.
+pub async fn insert_book(
+    db: &Client,
+    taxonomy: &Taxonomy,
+    mut new_book: NewBook,
+) -> Result<Book, status::Custom<String>> {
+    let collection: Collection<Document> = db.database("bearodata").collection("books");
+
+    new_book
+        .media_type
+        .validate(&new_book.author, &new_book.details)
+        .map_err(|e| status::Custom(Status::UnprocessableEntity, e))?;
+    validate_thoughts(&new_book.my_thoughts)?;
+    if let Some(progress) = &new_book.progress {
+        new_book.status = validate_progress(progress)?;
+    }
+    ensure_series_exists(db, new_book.series_id).await?;
+    new_book.genres = taxonomy.canonicalize_array(TermKind::Genre, &new_book.genres);
+    new_book.tags = taxonomy.canonicalize_array(TermKind::Tag, &new_book.tags);
 
-    let collection: Collection<NewBook> = db.database("bearodata").collection("books");
+    let now = history::now();
+    let mut document = bson::to_document(&new_book)
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?;
+    history::stamp_created(&mut document, now);
 
     let result = collection
-        .insert_one(new_book.into_inner(), None)
+        .insert_one(document, None)
         .await
-        .map_err(|_| Status::InternalServerError)?;
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?;
 
     let book_collection = db.database("bearodata").collection::<Book>("books");
-    let inserted_book = book_collection
+    let book = book_collection
         .find_one(doc! { "_id": result.inserted_id }, None)
         .await
-        .map_err(|_| Status::InternalServerError)?
-        .ok_or(Status::InternalServerError)?;
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?
+        .ok_or_else(|| {
+            status::Custom(
+                Status::InternalServerError,
+                "Book not found after insert".to_string(),
+            )
+        })?;
+
+    if let Some(progress) = new_book.progress {
+        record_progress(db, book.oid, progress, now).await?;
+    }
 
-    Ok(Json(inserted_book))
+    events::publish(db, Event::created(Resource::Book), &book).await;
+    Ok(book)
 }
 
-#[put("/<book_id>", format = "json", data = "<updated_book>")]
-pub async fn update_book(
-    db: Connection<BearoData>,
-    user: User,
-    book_id: String,
-    updated_book: Json<UpdateBook>,
-) -> Result<Json<Book>, Status> {
-    user.require_admin().map_err(|_| Status::Forbidden)?;
-
+/// Validates and writes every field of `updated_book` to an existing book.
+///
+/// Shared by `PUT /read-watch/<id>` and the `books edit` CLI command.
+pub async fn replace_book(
+    db: &Client,
+    taxonomy: &Taxonomy,
+    oid: ObjectId,
+    mut updated_book: UpdateBook,
+) -> Result<Book, status::Custom<String>> {
     let collection = db.database("bearodata").collection::<Book>("books");
-    let oid = ObjectId::parse_str(&book_id).map_err(|_| Status::BadRequest)?;
 
-    let update_doc = mongodb::bson::to_document(&updated_book.into_inner())
-        .map_err(|_| Status::InternalServerError)?;
+    let existing = collection
+        .find_one(trash::live(doc! { "_id": oid }), None)
+        .await
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?
+        .ok_or_else(|| status::Custom(Status::NotFound, "Book not found".to_string()))?;
+    validate_media_update(&existing, &updated_book)
+        .map_err(|e| status::Custom(Status::UnprocessableEntity, e))?;
+    let stale = stale_media_fields(&existing, &updated_book);
+    if let Some(my_thoughts) = &updated_book.my_thoughts {
+        validate_thoughts(my_thoughts)?;
+    }
+    ensure_series_exists(db, updated_book.series_id).await?;
+
+    updated_book.genres = updated_book
+        .genres
+        .map(|genres| taxonomy.canonicalize_array(TermKind::Genre, &genres));
+    updated_book.tags = updated_book
+        .tags
+        .map(|tags| taxonomy.canonicalize_array(TermKind::Tag, &tags));
+
+    let mut update_doc = mongodb::bson::to_document(&updated_book)
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?;
+    history::stamp_updated(&mut update_doc, history::now());
 
     let options = UpdateOptions::builder().upsert(false).build();
 
     collection
-        .update_one(doc! { "_id": oid }, doc! { "$set": update_doc }, options)
+        .update_one(
+            trash::live(doc! { "_id": oid }),
+            media_update(update_doc, stale),
+            options,
+        )
         .await
-        .map_err(|_| Status::InternalServerError)?;
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?;
 
-    let updated_book = collection
-        .find_one(doc! { "_id": oid }, None)
+    let book = collection
+        .find_one(trash::live(doc! { "_id": oid }), None)
         .await
-        .map_err(|_| Status::InternalServerError)?
-        .ok_or(Status::NotFound)?;
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?
+        .ok_or_else(|| status::Custom(Status::NotFound, "Book not found".to_string()))?;
 
-    Ok(Json(updated_book))
+    events::publish(db, Event::updated(Resource::Book), &book).await;
+    Ok(book)
 }
 
-#[patch("/<book_id>", format = "json", data = "<patch_data>")]
-pub async fn patch_book(
-    db: Connection<BearoData>,
-    user: User,
-    book_id: String,
-    patch_data: Json<UpdateBook>,
-    locale: Locale,
-) -> Result<Json<Book>, Status> {
-    user.require_admin().map_err(|_| Status::Forbidden)?;
-
+/// Validates and writes the fields present in `patch` to an existing book,
+/// flattening localized values to `locale`.
+///
+/// Shared by `PATCH /read-watch/<id>` and the `books patch` CLI command.
+pub async fn patch_book_fields(
+    db: &Client,
+    taxonomy: &Taxonomy,
+    oid: ObjectId,
+    patch: UpdateBook,
+    locale: Option<&str>,
+) -> Result<Book, status::Custom<String>> {
     let collection = db.database("bearodata").collection::<Book>("books");
-    let oid = ObjectId::parse_str(&book_id).map_err(|_| Status::BadRequest)?;
 
     let mut update_doc = Document::new();
-    let patch = patch_data.into_inner();
-    let selected_locale = locale.0.as_deref();
+
+    let existing = collection
+        .find_one(trash::live(doc! { "_id": oid }), None)
+        .await
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?
+        .ok_or_else(|| status::Custom(Status::NotFound, "Book not found".to_string()))?;
+    validate_media_update(&existing, &patch)
+        .map_err(|e| status::Custom(Status::UnprocessableEntity, e))?;
+    let stale = stale_media_fields(&existing, &patch);
+    if let Some(my_thoughts) = &patch.my_thoughts {
+        validate_thoughts(my_thoughts)?;
+    }
+    ensure_series_exists(db, patch.series_id).await?;
 
     if let Some(title) = patch.title {
-        update_doc.insert("title", title.get_text(selected_locale));
+        update_doc.insert("title", title.get_text(locale));
     }
     if let Some(author) = patch.author {
-        update_doc.insert("author", author.get_text(selected_locale));
+        update_doc.insert("author", author.get_text(locale));
     }
     if let Some(genres) = patch.genres {
-        update_doc.insert("genres", genres.get_texts(selected_locale));
+        update_doc.insert(
+            "genres",
+            taxonomy.canonicalize_list(TermKind::Genre, &genres.get_texts(locale)),
+        );
     }
     if let Some(tags) = patch.tags {
-        update_doc.insert("tags", tags.get_texts(selected_locale));
+        update_doc.insert(
+            "tags",
+            taxonomy.canonicalize_list(TermKind::Tag, &tags.get_texts(locale)),
+        );
     }
     if let Some(rating) = patch.rating {
         update_doc.insert("rating", rating);
     }
     if let Some(status) = patch.status {
-        update_doc.insert("status", status.get_text(selected_locale));
+        update_doc.insert("status", status.get_text(locale));
     }
     if let Some(description) = patch.description {
-        update_doc.insert("description", description.get_text(selected_locale));
+        update_doc.insert("description", description.get_text(locale));
     }
     if let Some(my_thoughts) = patch.my_thoughts {
-        update_doc.insert("my_thoughts", my_thoughts.get_text(selected_locale));
+        update_doc.insert("my_thoughts", my_thoughts.get_text(locale));
     }
     if let Some(links) = patch.links {
         update_doc.insert(
@@ -425,19 +713,118 @@ pub async fn patch_book(
     if let Some(color) = patch.color {
         update_doc.insert("color", color);
     }
+    if let Some(media_type) = patch.media_type {
+        update_doc.insert("media_type", media_type.as_str());
+    }
+    if let Some(volumes) = patch.volumes {
+        update_doc.insert("volumes", volumes);
+    }
+    if let Some(episodes) = patch.episodes {
+        update_doc.insert("episodes", episodes);
+    }
+    if let Some(runtime_minutes) = patch.runtime_minutes {
+        update_doc.insert("runtime_minutes", runtime_minutes);
+    }
+    if let Some(studio) = patch.studio {
+        update_doc.insert("studio", studio.get_text(locale));
+    }
+    if let Some(series_id) = patch.series_id {
+        update_doc.insert("series_id", series_id);
+    }
+    if let Some(series_index) = patch.series_index {
+        update_doc.insert("series_index", series_index);
+    }
+    history::stamp_updated(&mut update_doc, history::now());
 
     collection
-        .update_one(doc! { "_id": oid }, doc! { "$set": update_doc }, None)
+        .update_one(
+            trash::live(doc! { "_id": oid }),
+            media_update(update_doc, stale),
+            None,
+        )
         .await
-        .map_err(|_| Status::InternalServerError)?;
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?;
 
-    let updated_book = collection
-        .find_one(doc! { "_id": oid }, None)
+    let book = collection
+        .find_one(trash::live(doc! { "_id": oid }), None)
+        .await
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?
+        .ok_or_else(|| status::Custom(Status::NotFound, "Book not found".to_string()))?;
+
+    events::publish(db, Event::updated(Resource::Book), &book).await;
+    Ok(book)
+}
+
+/// Moves a book to the trash.
+///
+/// Shared by `DELETE /read-watch/<id>` and the `books delete` CLI command.
+pub async fn remove_book(db: &Client, oid: ObjectId) -> Result<(), Status> {
+    trash::trash::<Book>(db, Resource::Book, doc! { "_id": oid })
         .await
         .map_err(|_| Status::InternalServerError)?
-        .ok_or(Status::NotFound)?;
+        .pop()
+        .map(|_| ())
+        .ok_or(Status::NotFound)
+}
 
-    Ok(Json(updated_book))
+#[post("/", format = "json", data = "<new_book>")]
+pub async fn post_books(
+    db: Connection<BearoData>,
+    user: User,
+    taxonomy: &Taxonomy,
+    new_book: Json<NewBook>,
+) -> Result<Json<Book>, status::Custom<String>> {
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
+
+    insert_book(&db, taxonomy, new_book.into_inner())
+        .await
+        .map(Json)
+}
+
+#[put("/<book_id>", format = "json", data = "<updated_book>")]
+pub async fn update_book(
+    db: Connection<BearoData>,
+    user: User,
+    taxonomy: &Taxonomy,
+    book_id: String,
+    updated_book: Json<UpdateBook>,
+) -> Result<Json<Book>, status::Custom<String>> {
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
+
+    let oid = ObjectId::parse_str(&book_id)
+        .map_err(|_| status::Custom(Status::BadRequest, "Invalid book id".to_string()))?;
+
+    replace_book(&db, taxonomy, oid, updated_book.into_inner())
+        .await
+        .map(Json)
+}
+
+#[patch("/<book_id>", format = "json", data = "<patch_data>")]
+pub async fn patch_book(
+    db: Connection<BearoData>,
+    user: User,
+    taxonomy: &Taxonomy,
+    book_id: String,
+    patch_data: Json<UpdateBook>,
+    locale: Locale,
+) -> Result<Json<Book>, status::Custom<String>> {
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
+
+    let oid = ObjectId::parse_str(&book_id)
+        .map_err(|_| status::Custom(Status::BadRequest, "Invalid book id".to_string()))?;
+
+    patch_book_fields(
+        &db,
+        taxonomy,
+        oid,
+        patch_data.into_inner(),
+        locale.0.as_deref(),
+    )
+    .await
+    .map(Json)
 }
 
 #[delete("/<book_id>")]
@@ -448,24 +835,16 @@ pub async fn delete_book(
 ) -> Result<Json<ApiResponse>, Status> {
     _user.require_admin().map_err(|_| Status::Forbidden)?;
 
-    let collection = db.database("bearodata").collection::<Book>("books");
     let oid = ObjectId::parse_str(&book_id).map_err(|_| Status::BadRequest)?;
 
-    let result = collection
-        .delete_one(doc! { "_id": oid }, None)
-        .await
-        .map_err(|_| Status::InternalServerError)?;
+    remove_book(&db, oid).await?;
 
-    if result.deleted_count > 0 {
-        Ok(Json(ApiResponse {
-            message: "book deleted".to_string(),
-            deleted: None,
-            updated: None,
-            count: None,
-        }))
-    } else {
-        Err(Status::NotFound)
-    }
+    Ok(Json(ApiResponse {
+        message: "book deleted".to_string(),
+        deleted: None,
+        updated: None,
+        count: None,
+    }))
 }
 
 #[delete("/bulk", format = "json", data = "<filter>")]
@@ -476,7 +855,6 @@ pub async fn bulk_delete_books(
 ) -> Result<Json<ApiResponse>, Status> {
     _user.require_admin().map_err(|_| Status::Forbidden)?;
 
-    let collection = db.database("bearodata").collection::<Book>("books");
     let filter = filter.into_inner();
 
     let mut delete_filter = Document::new();
@@ -489,14 +867,15 @@ pub async fn bulk_delete_books(
         delete_filter.insert("status", status_filter);
     }
 
-    let result = collection
-        .delete_many(delete_filter, None)
+    trash::guard_bulk(&delete_filter, filter.confirm).map_err(|_| Status::UnprocessableEntity)?;
+
+    let deleted = trash::trash::<Book>(&db, Resource::Book, delete_filter)
         .await
         .map_err(|_| Status::InternalServerError)?;
 
     Ok(Json(ApiResponse {
         message: "bulk delete complete".to_string(),
-        deleted: Some(result.deleted_count as i64),
+        deleted: Some(deleted.len() as i64),
         updated: None,
         count: None,
     }))
@@ -532,12 +911,41 @@ pub async fn bulk_update_books(
     if let Some(new_rating) = payload.update.get("rating").and_then(|v| v.as_f64()) {
         update_doc.insert("rating", new_rating as i32);
     }
+    if update_doc.is_empty() {
+        return Err(Status::UnprocessableEntity);
+    }
+    history::stamp_updated(&mut update_doc, history::now());
+
+    let ids: Vec<ObjectId> = collection
+        .find(trash::live(filter_doc), None)
+        .await
+        .map_err(|_| Status::InternalServerError)?
+        .map_ok(|book| book.oid)
+        .try_collect()
+        .await
+        .map_err(|_| Status::InternalServerError)?;
 
     let result = collection
-        .update_many(filter_doc, doc! { "$set": update_doc }, None)
+        .update_many(
+            doc! { "_id": { "$in": &ids } },
+            doc! { "$set": update_doc },
+            None,
+        )
         .await
         .map_err(|_| Status::InternalServerError)?;
 
+    let mut updated = collection
+        .find(doc! { "_id": { "$in": ids } }, None)
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+    while let Some(book) = updated
+        .try_next()
+        .await
+        .map_err(|_| Status::InternalServerError)?
+    {
+        events::publish(&db, Event::updated(Resource::Book), &book).await;
+    }
+
     Ok(Json(ApiResponse {
         message: "bulk update complete".to_string(),
         deleted: None,
@@ -546,6 +954,139 @@ pub async fn bulk_update_books(
     }))
 }
 
+/// Updates a book's progress, transitions its status and records a history entry.
+#[patch("/<book_id>/progress", format = "json", data = "<update>")]
+pub async fn patch_progress(
+    db: Connection<BearoData>,
+    user: User,
+    book_id: String,
+    update: Json<UpdateProgress>,
+) -> Result<Json<Book>, status::Custom<String>> {
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
+
+    let collection = db.database("bearodata").collection::<Book>("books");
+    let oid = ObjectId::parse_str(&book_id)
+        .map_err(|_| status::Custom(Status::BadRequest, "invalid book id".to_string()))?;
+
+    let book = collection
+        .find_one(trash::live(doc! { "_id": oid }), None)
+        .await
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?
+        .ok_or_else(|| status::Custom(Status::NotFound, "book not found".to_string()))?;
+
+    let now = history::now();
+    let progress = ReadingProgress::apply(book.progress.as_ref(), &update, now)
+        .map_err(|e| status::Custom(Status::UnprocessableEntity, e))?;
+    let status = progress.status();
+
+    let progress_bson = bson::to_bson(&progress)
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?;
+
+    let mut update_doc = doc! { "progress": progress_bson, "status": status.as_str() };
+    history::stamp_updated(&mut update_doc, now);
+
+    collection
+        .update_one(
+            trash::live(doc! { "_id": oid }),
+            doc! { "$set": update_doc },
+            None,
+        )
+        .await
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?;
+
+    record_progress(&db, oid, progress, now).await?;
+
+    let updated_book = collection
+        .find_one(trash::live(doc! { "_id": oid }), None)
+        .await
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?
+        .ok_or_else(|| status::Custom(Status::NotFound, "book not found".to_string()))?;
+
+    events::publish(&db, Event::updated(Resource::Book), &updated_book).await;
+    Ok(Json(updated_book))
+}
+
+/// Lists a book's progress history, newest first.
+#[get("/<book_id>/progress/history")]
+pub async fn get_progress_history(
+    db: Connection<BearoData>,
+    book_id: String,
+) -> Result<Json<Vec<ProgressEntry>>, Status> {
+    let oid = ObjectId::parse_str(&book_id).map_err(|_| Status::BadRequest)?;
+    let live = db
+        .database("bearodata")
+        .collection::<Book>("books")
+        .count_documents(trash::live(doc! { "_id": oid }), None)
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+    if live == 0 {
+        return Err(Status::NotFound);
+    }
+    let options = FindOptions::builder()
+        .sort(doc! { "recorded_at": -1 })
+        .build();
+
+    let entries = db
+        .database("bearodata")
+        .collection::<ProgressEntry>("progress_history")
+        .find(doc! { "book_id": oid }, options)
+        .await
+        .map_err(|_| Status::InternalServerError)?
+        .try_collect()
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+
+    Ok(Json(entries))
+}
+
+/// Imports a Goodreads or StoryGraph library export.
+///
+/// Books already in the catalog are left alone unless `update=true`. Returns
+/// the planned changes without writing anything unless `apply=true`.
+#[post("/import?<format>&<update>&<apply>", data = "<file>")]
+pub async fn import_books(
+    db: Connection<BearoData>,
+    user: User,
+    format: BookExportFormat,
+    update: Option<bool>,
+    apply: Option<bool>,
+    file: Data<'_>,
+) -> Result<Json<ImportReport<BookImportPlan>>, status::Custom<String>> {
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
+
+    let input = file
+        .open(16.mebibytes())
+        .into_string()
+        .await
+        .map_err(|e| status::Custom(Status::BadRequest, e.to_string()))?;
+    if !input.is_complete() {
+        return Err(status::Custom(
+            Status::PayloadTooLarge,
+            "library export is too large".to_string(),
+        ));
+    }
+
+    import_book_export(
+        &db,
+        format,
+        &input,
+        update.unwrap_or(false),
+        apply.unwrap_or(false),
+    )
+    .await
+    .map(Json)
+    .map_err(|e| match e {
+        ImportError::Database(_)
+        | ImportError::Serialization(_)
+        | ImportError::Deserialization(_) => {
+            status::Custom(Status::InternalServerError, e.to_string())
+        }
+        _ => status::Custom(Status::UnprocessableEntity, e.to_string()),
+    })
+}
+
 pub fn routes() -> Vec<Route> {
     routes![
         get_books,
@@ -556,7 +1097,10 @@ pub fn routes() -> Vec<Route> {
         delete_book,
         bulk_delete_books,
         bulk_update_books,
-        get_raw_book_by_id
+        get_raw_book_by_id,
+        patch_progress,
+        get_progress_history,
+        import_books
     ]
 }
 
@@ -649,7 +1193,7 @@ mod tests {
     fn test_routes_registration() {
         let routes = routes();
 
-        assert_eq!(routes.len(), 9);
+        assert_eq!(routes.len(), 12);
 
         let route_names: Vec<&str> = routes
             .iter()
@@ -665,5 +1209,49 @@ mod tests {
         assert!(route_names.contains(&"bulk_delete_books"));
         assert!(route_names.contains(&"bulk_update_books"));
         assert!(route_names.contains(&"get_raw_book_by_id"));
+        assert!(route_names.contains(&"patch_progress"));
+        assert!(route_names.contains(&"get_progress_history"));
+        assert!(route_names.contains(&"import_books"));
+    }
+
+    #[test]
+    fn test_media_type_change_clears_stale_details() {
+        let manga: Book = bson::from_document(doc! {
+            "_id": ObjectId::new(),
+            "title": "Berserk",
+            "author": "Kentaro Miura",
+            "genres": [],
+            "tags": [],
+            "rating": 5,
+            "status": "",
+            "description": "",
+            "my_thoughts": "",
+            "cover_image": "",
+            "explicit": true,
+            "media_type": "manga",
+            "volumes": 41,
+        })
+        .unwrap();
+        let to_anime: UpdateBook = bson::from_document(doc! { "media_type": "anime" }).unwrap();
+
+        assert_eq!(stale_media_fields(&manga, &to_anime), ["volumes"]);
+        assert_eq!(validate_media_update(&manga, &to_anime), Ok(()));
+        assert_eq!(
+            media_update(
+                doc! { "media_type": "anime" },
+                stale_media_fields(&manga, &to_anime)
+            ),
+            doc! { "$set": { "media_type": "anime" }, "$unset": { "volumes": "" } }
+        );
+
+        let with_volumes: UpdateBook =
+            bson::from_document(doc! { "media_type": "anime", "volumes": 3 }).unwrap();
+        assert_eq!(
+            validate_media_update(&manga, &with_volumes),
+            Err("`volumes` is not applicable to anime entries".to_string())
+        );
+
+        let same_type: UpdateBook = bson::from_document(doc! { "media_type": "manga" }).unwrap();
+        assert!(stale_media_fields(&manga, &same_type).is_empty());
     }
 }
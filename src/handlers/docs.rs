@@ -0,0 +1,122 @@
+//! # API documentation handlers
+//!
+//! Serves the OpenAPI document built by [`crate::openapi`] and two viewers for
+//! it. The viewers load Swagger UI and Redoc from `/docs/assets`, served from
+//! the `docs_assets` directory in `Rocket.toml` or `ROCKET_DOCS_ASSETS`
+//! ([`DEFAULT_ASSETS_DIR`] by default). `scripts/vendor-docs-assets.sh`
+//! downloads pinned versions of them there, checking each download against
+//! its pinned npm integrity hash.
+
+use {
+    crate::openapi,
+    rocket::{
+        Request,
+        fs::NamedFile,
+        get,
+        request::{FromRequest, Outcome},
+        response::content::RawHtml,
+        routes,
+        serde::json::Json,
+    },
+    serde_json::Value,
+    std::{convert::Infallible, path::PathBuf},
+};
+
+/// Configuration key of the directory holding the viewer assets.
+pub const ASSETS_KEY: &str = "docs_assets";
+
+/// Directory holding the viewer assets unless configured otherwise.
+pub const DEFAULT_ASSETS_DIR: &str = "assets/docs";
+
+/// Files the viewers load from `/docs/assets`.
+pub const ASSETS: [&str; 3] = [
+    "swagger-ui.css",
+    "swagger-ui-bundle.js",
+    "redoc.standalone.js",
+];
+
+const SWAGGER_UI: &str = r##"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <title>Apiodactyl API</title>
+  <link rel="stylesheet" href="/docs/assets/swagger-ui.css">
+</head>
+<body>
+  <div id="swagger-ui"></div>
+  <script src="/docs/assets/swagger-ui-bundle.js"></script>
+  <script>
+    SwaggerUIBundle({ url: "/openapi.json", dom_id: "#swagger-ui" });
+  </script>
+</body>
+</html>
+"##;
+
+const REDOC: &str = r#"<!DOCTYPE html>
+<html lang="en">
+<head>
+  <meta charset="utf-8">
+  <title>Apiodactyl API</title>
+</head>
+<body>
+  <redoc spec-url="/openapi.json"></redoc>
+  <script src="/docs/assets/redoc.standalone.js"></script>
+</body>
+</html>
+"#;
+
+/// The OpenAPI document for the routes mounted on the running instance.
+pub struct Spec(Value);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Spec {
+    type Error = Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(Spec(openapi::spec(request.rocket().routes())))
+    }
+}
+
+#[get("/openapi.json")]
+pub fn openapi_json(spec: Spec) -> Json<Value> {
+    Json(spec.0)
+}
+
+#[get("/docs")]
+pub fn swagger_ui() -> RawHtml<&'static str> {
+    RawHtml(SWAGGER_UI)
+}
+
+#[get("/redoc")]
+pub fn redoc_ui() -> RawHtml<&'static str> {
+    RawHtml(REDOC)
+}
+
+/// The configured directory of viewer assets.
+pub struct AssetsDir(PathBuf);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for AssetsDir {
+    type Error = Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let dir = request
+            .rocket()
+            .figment()
+            .extract_inner::<PathBuf>(ASSETS_KEY)
+            .unwrap_or_else(|_| PathBuf::from(DEFAULT_ASSETS_DIR));
+        Outcome::Success(AssetsDir(dir))
+    }
+}
+
+#[get("/docs/assets/<file>")]
+pub async fn docs_asset(dir: AssetsDir, file: &str) -> Option<NamedFile> {
+    if !ASSETS.contains(&file) {
+        return None;
+    }
+    NamedFile::open(dir.0.join(file)).await.ok()
+}
+
+pub fn routes() -> Vec<rocket::Route> {
+    routes![openapi_json, swagger_ui, redoc_ui, docs_asset]
+}
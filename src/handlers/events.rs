@@ -0,0 +1,93 @@
+//! # Event stream handler
+//!
+//! `GET /events` is a server-sent events stream of the changes published
+//! through [`crate::events`]. Each message has the event id, the event name
+//! (e.g. `game.updated`) and the JSON payload of [`LoggedEvent::payload`].
+//!
+//! - `?collections=books,games` limits the stream to some of `books`,
+//!   `games`, `projects`, `reviews`, `series` and `wplace`
+//! - a `Last-Event-ID` header, sent by `EventSource` when it reconnects,
+//!   replays the events missed since that id. A `reset` event is sent
+//!   instead when they are no longer in the log.
+
+use {
+    crate::events::{CollectionFilter, EVENTS, LoggedEvent},
+    rocket::{
+        Request, Shutdown, get,
+        http::Status,
+        request::{FromRequest, Outcome},
+        response::{
+            status,
+            stream::{Event, EventStream},
+        },
+        tokio::{select, sync::broadcast::error::RecvError},
+    },
+};
+
+/// The `Last-Event-ID` header of a reconnecting client.
+pub struct LastEventId(pub Option<String>);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for LastEventId {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let id = request
+            .headers()
+            .get_one("Last-Event-ID")
+            .map(|id| id.trim().to_string());
+        Outcome::Success(LastEventId(id))
+    }
+}
+
+fn message(event: &LoggedEvent) -> Event {
+    Event::data(event.payload())
+        .event(event.event.to_string())
+        .id(event.id.to_string())
+}
+
+fn reset() -> Event {
+    Event::data("{}").event("reset")
+}
+
+/// Streams live changes, replaying missed ones after `Last-Event-ID`.
+#[get("/?<collections>")]
+pub fn stream_events(
+    collections: Option<&str>,
+    last_event_id: LastEventId,
+    mut shutdown: Shutdown,
+) -> Result<EventStream![], status::Custom<String>> {
+    let filter = collections
+        .map(str::parse::<CollectionFilter>)
+        .transpose()
+        .map_err(|e| status::Custom(Status::BadRequest, e.to_string()))?
+        .unwrap_or_default();
+    let mut subscription = EVENTS.subscribe(last_event_id.0.as_deref());
+
+    Ok(EventStream! {
+        if subscription.reset {
+            yield reset();
+        }
+        for event in subscription.backlog.iter().filter(|event| filter.matches(&event.event)) {
+            yield message(event);
+        }
+
+        loop {
+            let event = select! {
+                received = subscription.receiver.recv() => match received {
+                    Ok(event) => event,
+                    Err(RecvError::Lagged(_)) => {
+                        yield reset();
+                        continue;
+                    }
+                    Err(RecvError::Closed) => break,
+                },
+                _ = &mut shutdown => break,
+            };
+
+            if filter.matches(&event.event) {
+                yield message(&event);
+            }
+        }
+    })
+}
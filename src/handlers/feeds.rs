@@ -0,0 +1,174 @@
+//! # Feed handlers
+//!
+//! Serves the activity feed of [`crate::feeds`] as Atom, RSS and JSON Feed.
+//! Titles and summaries follow the `Accept-Language` locale. Responses carry
+//! `ETag` and `Last-Modified`, and matching `If-None-Match` or
+//! `If-Modified-Since` requests get `304 Not Modified`.
+
+use {
+    crate::{
+        db::BearoData,
+        feeds::{self, ActivityFeed, FeedFormat},
+        models::Locale,
+    },
+    chrono::NaiveDateTime,
+    rocket::{
+        Request, Response, Route, get,
+        http::{ContentType, Header, Status},
+        request::{FromRequest, Outcome},
+        response::{self, Responder},
+        routes,
+    },
+    rocket_db_pools::Connection,
+    std::io::Cursor,
+};
+
+/// Base URL for absolute feed links.
+///
+/// The configured [`feeds::public_url`], or the `Host` the request was made
+/// to. `X-Forwarded-*` headers are ignored: any client could set them, and
+/// feed responses are publicly cacheable.
+pub struct BaseUrl(pub String);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for BaseUrl {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        if let Some(url) = feeds::public_url(request.rocket().figment()) {
+            return Outcome::Success(BaseUrl(url));
+        }
+        let host = request.headers().get_one("Host").unwrap_or("localhost");
+        Outcome::Success(BaseUrl(format!("http://{}", host)))
+    }
+}
+
+/// The `If-None-Match` and `If-Modified-Since` headers of a request.
+pub struct Conditions {
+    pub if_none_match: Option<String>,
+    pub if_modified_since: Option<String>,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Conditions {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let headers = request.headers();
+        Outcome::Success(Conditions {
+            if_none_match: headers.get_one("If-None-Match").map(str::to_string),
+            if_modified_since: headers.get_one("If-Modified-Since").map(str::to_string),
+        })
+    }
+}
+
+/// A rendered feed, or `304 Not Modified`.
+pub struct FeedResponse {
+    format: FeedFormat,
+    /// `None` when the client's copy is current
+    body: Option<String>,
+    etag: String,
+    last_modified: Option<NaiveDateTime>,
+}
+
+impl<'r> Responder<'r, 'static> for FeedResponse {
+    fn respond_to(self, _: &'r Request<'_>) -> response::Result<'static> {
+        let (top, sub) = self.format.media_type();
+        let mut response = Response::build();
+        response
+            .header(Header::new("ETag", self.etag))
+            .header(Header::new("Vary", "Accept-Language"))
+            .header(Header::new("Cache-Control", "public, max-age=300"));
+        if let Some(last_modified) = self.last_modified {
+            response.header(Header::new(
+                "Last-Modified",
+                feeds::http_date(last_modified),
+            ));
+        }
+
+        match self.body {
+            Some(body) => response
+                .header(ContentType::new(top, sub).with_params(("charset", "utf-8")))
+                .sized_body(body.len(), Cursor::new(body))
+                .ok(),
+            None => response.status(Status::NotModified).ok(),
+        }
+    }
+}
+
+async fn activity(
+    db: &Connection<BearoData>,
+    locale: Locale,
+    base_url: BaseUrl,
+    conditions: Conditions,
+    format: FeedFormat,
+) -> Result<FeedResponse, Status> {
+    // Accept-Language entries may carry a quality, e.g. `de;q=0.8`
+    let language = locale
+        .0
+        .as_deref()
+        .and_then(|locale| locale.split(';').next())
+        .map(str::trim)
+        .filter(|language| !language.is_empty() && *language != "*")
+        .map(str::to_string);
+
+    let items = feeds::load_activity(db, language.as_deref())
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+    let feed = ActivityFeed {
+        base_url: base_url.0,
+        language,
+        items,
+    };
+
+    let body = feed.render(format);
+    let etag = feeds::etag(&body);
+    let last_modified = feed.updated();
+    let current = feeds::not_modified(
+        conditions.if_none_match.as_deref(),
+        conditions.if_modified_since.as_deref(),
+        &etag,
+        last_modified,
+    );
+
+    Ok(FeedResponse {
+        format,
+        body: (!current).then_some(body),
+        etag,
+        last_modified,
+    })
+}
+
+#[get("/activity.atom")]
+pub async fn activity_atom(
+    db: Connection<BearoData>,
+    locale: Locale,
+    base_url: BaseUrl,
+    conditions: Conditions,
+) -> Result<FeedResponse, Status> {
+    activity(&db, locale, base_url, conditions, FeedFormat::Atom).await
+}
+
+#[get("/activity.rss")]
+pub async fn activity_rss(
+    db: Connection<BearoData>,
+    locale: Locale,
+    base_url: BaseUrl,
+    conditions: Conditions,
+) -> Result<FeedResponse, Status> {
+    activity(&db, locale, base_url, conditions, FeedFormat::Rss).await
+}
+
+#[get("/activity.json")]
+pub async fn activity_json(
+    db: Connection<BearoData>,
+    locale: Locale,
+    base_url: BaseUrl,
+    conditions: Conditions,
+) -> Result<FeedResponse, Status> {
+    activity(&db, locale, base_url, conditions, FeedFormat::Json).await
+}
+
+pub fn routes() -> Vec<Route> {
+    routes![activity_atom, activity_rss, activity_json]
+}
@@ -1,68 +1,257 @@
 use crate::auth::User;
 use crate::db::BearoData;
-use crate::models::{Game, NewGame, UpdateGame};
+use crate::errors::ImportError;
+use crate::events::{self, Event, Resource};
+use crate::history;
+use crate::importers::{
+    ImportReport,
+    games::{GameImportPlan, GameLibraryFormat, import_library},
+};
+use crate::markdown::{self, Render, Rendered};
+use crate::models::{
+    ChecklistImport, ChecklistItem, Game, GameStatus, Locale, NewChecklistItem, NewGame,
+    NewPlaySession, PlaySession, TermKind, UpdateChecklistItem, UpdateGame, clamp_percent,
+};
+use crate::taxonomy::Taxonomy;
+use crate::trash;
 use mongodb::bson;
 use mongodb::bson::{Document, doc, oid::ObjectId};
+use rocket::data::{Data, ToByteUnit};
 use rocket::form::FromForm;
 use rocket::futures::TryStreamExt;
 use rocket::serde::{Deserialize, Serialize, json::Json};
-use rocket::{delete, get, http::Status, patch, post, put, routes};
-use rocket_db_pools::mongodb::options::{FindOptions, UpdateOptions};
-use rocket_db_pools::{Connection, mongodb::Collection};
+use rocket::{delete, get, http::Status, patch, post, put, response::status, routes};
+use rocket_db_pools::mongodb::options::{
+    FindOneAndUpdateOptions, FindOptions, ReturnDocument, UpdateOptions,
+};
+use rocket_db_pools::{
+    Connection,
+    mongodb::{Client, Collection},
+};
+use schemars::JsonSchema;
 use std::collections::HashMap;
 
-#[derive(FromForm, Debug)]
+#[derive(FromForm, Debug, JsonSchema)]
 pub struct GameQuery {
     title: Option<String>,
     developer: Option<String>,
     genre: Option<String>,
     tag: Option<String>,
-    status: Option<String>,
+    status: Option<GameStatus>,
+    platform: Option<String>,
     explicit: Option<String>,
     bad: Option<String>,
     #[field(name = "minProgress")]
+    #[schemars(rename = "minProgress")]
     min_progress: Option<i32>,
     #[field(name = "maxProgress")]
+    #[schemars(rename = "maxProgress")]
     max_progress: Option<i32>,
     #[field(name = "exactProgress")]
+    #[schemars(rename = "exactProgress")]
     exact_progress: Option<i32>,
     #[field(name = "minRating")]
+    #[schemars(rename = "minRating")]
     min_rating: Option<i32>,
     #[field(name = "maxRating")]
+    #[schemars(rename = "maxRating")]
     max_rating: Option<i32>,
     #[field(name = "exactRating")]
+    #[schemars(rename = "exactRating")]
     exact_rating: Option<i32>,
     sort: Option<String>,
+    /// Number of results to skip
+    offset: Option<usize>,
+    /// Maximum number of results to return
+    limit: Option<usize>,
 }
 
-#[derive(Deserialize)]
+/// Replaces genre and tag slugs on a game with their taxonomy labels in the given locale.
+fn render_terms(game: &mut Game, taxonomy: &Taxonomy, locale: Option<&str>) {
+    for genre in game.genres.iter_mut().flatten() {
+        *genre = taxonomy.render(TermKind::Genre, genre, locale);
+    }
+    for tag in game.tags.iter_mut().flatten() {
+        *tag = taxonomy.render(TermKind::Tag, tag, locale);
+    }
+}
+
+/// Rejects thoughts containing scripts or unsafe links.
+fn validate_thoughts(my_thoughts: &str) -> Result<(), status::Custom<String>> {
+    markdown::validate(my_thoughts)
+        .map_err(|e| status::Custom(Status::UnprocessableEntity, format!("my_thoughts: {}", e)))
+}
+
+/// Rejects negative or non-finite playtime, as logging a session does.
+fn validate_playtime(playtime_hours: f64) -> Result<(), status::Custom<String>> {
+    if !playtime_hours.is_finite() || playtime_hours < 0.0 {
+        return Err(status::Custom(
+            Status::UnprocessableEntity,
+            "playtime_hours must be a non-negative number".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Loads a game, re-deriving `percent` from its checklist when enabled.
+async fn sync_checklist_percent(
+    collection: &Collection<Game>,
+    oid: ObjectId,
+) -> Result<Game, status::Custom<String>> {
+    let mut game = collection
+        .find_one(trash::live(doc! { "_id": oid }), None)
+        .await
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?
+        .ok_or_else(|| status::Custom(Status::NotFound, "Game not found".to_string()))?;
+
+    let percent = game.percent;
+    game.sync_checklist_percent();
+    if game.percent != percent {
+        collection
+            .update_one(
+                doc! { "_id": oid },
+                doc! { "$set": { "percent": game.percent } },
+                None,
+            )
+            .await
+            .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?;
+    }
+
+    Ok(game)
+}
+
+/// Applies a checklist `update` to a live game and re-derives its percent
+/// from the checklist the update returns.
+///
+/// `filter` narrows the match further, e.g. to games holding a given item.
+/// Array operators keep concurrent checklist changes from overwriting each
+/// other. The update is stamped and the game published.
+///
+/// # Returns
+///
+/// The updated game, or `None` if no game matched.
+async fn update_checklist(
+    db: &Client,
+    oid: ObjectId,
+    mut filter: Document,
+    mut update: Document,
+    array_filters: Option<Vec<Document>>,
+) -> Result<Option<Game>, Status> {
+    let collection = db.database("bearodata").collection::<Game>("games");
+    let mut set = update.get_document("$set").cloned().unwrap_or_default();
+    history::stamp_updated(&mut set, history::now());
+    update.insert("$set", set);
+
+    filter.insert("_id", oid);
+    let options = FindOneAndUpdateOptions::builder()
+        .array_filters(array_filters)
+        .return_document(ReturnDocument::After)
+        .build();
+    let Some(mut game) = collection
+        .find_one_and_update(trash::live(filter), update, options)
+        .await
+        .map_err(|_| Status::InternalServerError)?
+    else {
+        return Ok(None);
+    };
+
+    let percent = game.percent;
+    game.sync_checklist_percent();
+    if game.percent != percent {
+        // Only the latest checklist change writes its percent.
+        let updated_at =
+            bson::to_bson(&game.updated_at).map_err(|_| Status::InternalServerError)?;
+        collection
+            .update_one(
+                doc! { "_id": oid, "updated_at": updated_at },
+                doc! { "$set": { "percent": game.percent } },
+                None,
+            )
+            .await
+            .map_err(|_| Status::InternalServerError)?;
+    }
+
+    events::publish(db, Event::updated(Resource::Game), &game).await;
+    Ok(Some(game))
+}
+
+async fn find_game(collection: &Collection<Game>, game_id: &str) -> Result<Game, Status> {
+    let oid = ObjectId::parse_str(game_id).map_err(|_| Status::BadRequest)?;
+
+    collection
+        .find_one(trash::live(doc! { "_id": oid }), None)
+        .await
+        .map_err(|_| Status::InternalServerError)?
+        .ok_or(Status::NotFound)
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct ChecklistImportPayload {
+    pub items: Vec<NewChecklistItem>,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct ChecklistSummary {
+    pub items: Vec<ChecklistItem>,
+    pub unlocked: usize,
+    pub total: usize,
+    pub percent: Option<i32>,
+    pub percent_from_checklist: bool,
+}
+
+impl From<Game> for ChecklistSummary {
+    fn from(game: Game) -> Self {
+        ChecklistSummary {
+            unlocked: game
+                .checklist
+                .iter()
+                .filter(|item| item.is_unlocked())
+                .count(),
+            total: game.checklist.len(),
+            percent: game.checklist_percent(),
+            percent_from_checklist: game.percent_from_checklist,
+            items: game.checklist,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[schemars(rename = "GameBulkDeleteFilter")]
 pub struct BulkDeleteFilter {
-    developer: Option<String>,
-    status: Option<String>,
+    pub developer: Option<String>,
+    pub status: Option<GameStatus>,
+    /// Must be `true`; guards against trashing games by accident
+    pub confirm: Option<bool>,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize, JsonSchema)]
+#[schemars(rename = "GameBulkUpdatePayload")]
 pub struct BulkUpdatePayload {
-    filter: HashMap<String, String>,
-    update: HashMap<String, serde_json::Value>,
+    pub filter: HashMap<String, String>,
+    pub update: HashMap<String, serde_json::Value>,
 }
 
-#[derive(Serialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[schemars(rename = "GameApiResponse")]
 pub struct ApiResponse {
-    message: String,
+    pub message: String,
     #[serde(skip_serializing_if = "Option::is_none")]
-    deleted: Option<i64>,
+    pub deleted: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    updated: Option<i64>,
+    pub updated: Option<i64>,
     #[serde(skip_serializing_if = "Option::is_none")]
-    count: Option<usize>,
+    pub count: Option<usize>,
 }
 
-#[get("/search?<query..>")]
+#[get("/search?<render>&<query..>")]
 pub async fn get_games(
     db: Connection<BearoData>,
+    taxonomy: &Taxonomy,
+    render: Option<Render>,
     query: GameQuery,
-) -> Result<Json<Vec<Game>>, Status> {
+    locale: Locale,
+) -> Result<Json<Vec<Rendered<Game>>>, Status> {
     let collection = db.database("bearodata").collection::<Game>("games");
 
     let mut filter = Document::new();
@@ -80,7 +269,14 @@ pub async fn get_games(
     }
 
     if let Some(status_filter) = &query.status {
-        filter.insert("status", status_filter);
+        filter.insert("status", status_filter.as_str());
+    }
+
+    if let Some(platform_filter) = &query.platform {
+        filter.insert(
+            "platforms",
+            doc! { "$regex": platform_filter, "$options": "i" },
+        );
     }
 
     if let Some(bad_filter) = &query.bad {
@@ -147,6 +343,7 @@ pub async fn get_games(
             "title" => doc! { "title": 1 },
             "author" => doc! { "developer": 1 },
             "rating" => doc! { "rating": -1 },
+            "recent" => history::recent_sort(),
             _ => Document::new(),
         };
         if !sort_doc.is_empty() {
@@ -155,7 +352,7 @@ pub async fn get_games(
     }
 
     let mut cursor = collection
-        .find(filter, options)
+        .find(trash::live(filter), options)
         .await
         .map_err(|_| Status::InternalServerError)?;
 
@@ -168,7 +365,17 @@ pub async fn get_games(
         results.push(game);
     }
 
+    let current_locale = locale.0.as_deref();
+    results
+        .iter_mut()
+        .for_each(|game| render_terms(game, taxonomy, current_locale));
+
     if let Some(genre_filter) = &query.genre {
+        let genre_filter = taxonomy.render(
+            TermKind::Genre,
+            &taxonomy.canonicalize(TermKind::Genre, genre_filter),
+            current_locale,
+        );
         results.retain(|game| {
             game.genres.iter().any(|g| {
                 if let Some(genre) = g {
@@ -181,6 +388,11 @@ pub async fn get_games(
     }
 
     if let Some(tag_filter) = &query.tag {
+        let tag_filter = taxonomy.render(
+            TermKind::Tag,
+            &taxonomy.canonicalize(TermKind::Tag, tag_filter),
+            current_locale,
+        );
         results.retain(|game| {
             game.tags.iter().any(|t| {
                 if let Some(tag) = t {
@@ -192,97 +404,134 @@ pub async fn get_games(
         });
     }
 
-    Ok(Json(results))
+    Ok(Json(Rendered::all(
+        super::paginate(results, query.offset, query.limit),
+        render,
+    )))
 }
 
-#[get("/<game_id>")]
+#[get("/<game_id>?<render>")]
 pub async fn get_game_by_id(
     db: Connection<BearoData>,
+    taxonomy: &Taxonomy,
     game_id: String,
-) -> Result<Json<Game>, Status> {
+    render: Option<Render>,
+    locale: Locale,
+) -> Result<Json<Rendered<Game>>, Status> {
     let collection = db.database("bearodata").collection::<Game>("games");
 
     let oid = ObjectId::parse_str(&game_id).map_err(|_| Status::BadRequest)?;
 
-    let game = collection
-        .find_one(doc! { "_id": oid }, None)
+    let mut game = collection
+        .find_one(trash::live(doc! { "_id": oid }), None)
         .await
         .map_err(|_| Status::InternalServerError)?
         .ok_or(Status::NotFound)?;
 
-    Ok(Json(game))
-}
+    render_terms(&mut game, taxonomy, locale.0.as_deref());
 
-#[post("/", format = "json", data = "<new_game>")]
-pub async fn post_games(
-    db: Connection<BearoData>,
-    user: User,
-    new_game: Json<NewGame>,
-) -> Result<Json<Game>, Status> {
-    user.require_admin().map_err(|_| Status::Forbidden)?;
+    Ok(Json(Rendered::new(game, render)))
+}
 
-    let collection: Collection<NewGame> = db.database("bearodata").collection("games");
+/// Validates a new game, canonicalizes its terms and inserts it.
+///
+/// Shared by `POST /games` and the `games create` CLI command.
+pub async fn insert_game(
+    db: &Client,
+    taxonomy: &Taxonomy,
+    mut new_game: NewGame,
+) -> Result<Game, status::Custom<String>> {
+    let collection: Collection<Document> = db.database("bearodata").collection("games");
+
+    validate_thoughts(&new_game.my_thoughts)?;
+    validate_playtime(new_game.playtime_hours)?;
+    new_game.genres = taxonomy.canonicalize_list(TermKind::Genre, &new_game.genres);
+    new_game.tags = taxonomy.canonicalize_list(TermKind::Tag, &new_game.tags);
+    new_game.percent = clamp_percent(new_game.percent);
+
+    let mut document = bson::to_document(&new_game)
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?;
+    history::stamp_created(&mut document, history::now());
 
     let result = collection
-        .insert_one(new_game.into_inner(), None)
+        .insert_one(document, None)
         .await
-        .map_err(|_| Status::InternalServerError)?;
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?;
 
     let game_collection = db.database("bearodata").collection::<Game>("games");
-    let inserted_game = game_collection
+    let game = game_collection
         .find_one(doc! { "_id": result.inserted_id }, None)
         .await
-        .map_err(|_| Status::InternalServerError)?
-        .ok_or(Status::InternalServerError)?;
-
-    Ok(Json(inserted_game))
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?
+        .ok_or_else(|| {
+            status::Custom(
+                Status::InternalServerError,
+                "Game not found after insert".to_string(),
+            )
+        })?;
+
+    events::publish(db, Event::created(Resource::Game), &game).await;
+    Ok(game)
 }
 
-#[put("/<game_id>", format = "json", data = "<updated_game>")]
-pub async fn update_game(
-    db: Connection<BearoData>,
-    user: User,
-    game_id: String,
-    updated_game: Json<UpdateGame>,
-) -> Result<Json<Game>, Status> {
-    user.require_admin().map_err(|_| Status::Forbidden)?;
-
+/// Validates and writes every field of `updated_game` to an existing game.
+///
+/// Shared by `PUT /games/<id>` and the `games edit` CLI command.
+pub async fn replace_game(
+    db: &Client,
+    taxonomy: &Taxonomy,
+    oid: ObjectId,
+    mut updated_game: UpdateGame,
+) -> Result<Game, status::Custom<String>> {
     let collection = db.database("bearodata").collection::<Game>("games");
-    let oid = ObjectId::parse_str(&game_id).map_err(|_| Status::BadRequest)?;
 
-    let update_doc = mongodb::bson::to_document(&updated_game.into_inner())
-        .map_err(|_| Status::InternalServerError)?;
+    if let Some(my_thoughts) = &updated_game.my_thoughts {
+        validate_thoughts(my_thoughts)?;
+    }
+    if let Some(playtime_hours) = updated_game.playtime_hours {
+        validate_playtime(playtime_hours)?;
+    }
+    updated_game.genres = updated_game
+        .genres
+        .map(|genres| taxonomy.canonicalize_list(TermKind::Genre, &genres));
+    updated_game.tags = updated_game
+        .tags
+        .map(|tags| taxonomy.canonicalize_list(TermKind::Tag, &tags));
+    updated_game.percent = updated_game.percent.map(clamp_percent);
+
+    let mut update_doc = mongodb::bson::to_document(&updated_game)
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?;
+    history::stamp_updated(&mut update_doc, history::now());
 
     let options = UpdateOptions::builder().upsert(false).build();
 
     collection
-        .update_one(doc! { "_id": oid }, doc! { "$set": update_doc }, options)
+        .update_one(
+            trash::live(doc! { "_id": oid }),
+            doc! { "$set": update_doc },
+            options,
+        )
         .await
-        .map_err(|_| Status::InternalServerError)?;
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?;
 
-    let updated_game = collection
-        .find_one(doc! { "_id": oid }, None)
-        .await
-        .map_err(|_| Status::InternalServerError)?
-        .ok_or(Status::NotFound)?;
+    let game = sync_checklist_percent(&collection, oid).await?;
 
-    Ok(Json(updated_game))
+    events::publish(db, Event::updated(Resource::Game), &game).await;
+    Ok(game)
 }
 
-#[patch("/<game_id>", format = "json", data = "<patch_data>")]
-pub async fn patch_game(
-    db: Connection<BearoData>,
-    user: User,
-    game_id: String,
-    patch_data: Json<UpdateGame>,
-) -> Result<Json<Game>, Status> {
-    user.require_admin().map_err(|_| Status::Forbidden)?;
-
+/// Validates and writes the fields present in `patch` to an existing game.
+///
+/// Shared by `PATCH /games/<id>` and the `games patch` CLI command.
+pub async fn patch_game_fields(
+    db: &Client,
+    taxonomy: &Taxonomy,
+    oid: ObjectId,
+    patch: UpdateGame,
+) -> Result<Game, status::Custom<String>> {
     let collection = db.database("bearodata").collection::<Game>("games");
-    let oid = ObjectId::parse_str(&game_id).map_err(|_| Status::BadRequest)?;
 
     let mut update_doc = Document::new();
-    let patch = patch_data.into_inner();
 
     if let Some(title) = patch.title {
         update_doc.insert("title", title);
@@ -291,21 +540,25 @@ pub async fn patch_game(
         update_doc.insert("developer", developer);
     }
     if let Some(genres) = patch.genres {
-        update_doc.insert("genres", genres);
+        update_doc.insert(
+            "genres",
+            taxonomy.canonicalize_list(TermKind::Genre, &genres),
+        );
     }
     if let Some(tags) = patch.tags {
-        update_doc.insert("tags", tags);
+        update_doc.insert("tags", taxonomy.canonicalize_list(TermKind::Tag, &tags));
     }
     if let Some(rating) = patch.rating {
         update_doc.insert("rating", rating);
     }
     if let Some(status) = patch.status {
-        update_doc.insert("status", status);
+        update_doc.insert("status", status.as_str());
     }
     if let Some(description) = patch.description {
         update_doc.insert("description", description);
     }
     if let Some(my_thoughts) = patch.my_thoughts {
+        validate_thoughts(&my_thoughts)?;
         update_doc.insert("my_thoughts", my_thoughts);
     }
     if let Some(links) = patch.links {
@@ -318,24 +571,115 @@ pub async fn patch_game(
         update_doc.insert("explicit", explicit);
     }
     if let Some(percent) = patch.percent {
-        update_doc.insert("percent", percent);
+        update_doc.insert("percent", clamp_percent(percent));
     }
     if let Some(bad) = patch.bad {
         update_doc.insert("bad", bad);
     }
+    if let Some(playtime_hours) = patch.playtime_hours {
+        validate_playtime(playtime_hours)?;
+        update_doc.insert("playtime_hours", playtime_hours);
+    }
+    if let Some(platforms) = patch.platforms {
+        update_doc.insert("platforms", platforms);
+    }
+    if let Some(started_at) = patch.started_at {
+        update_doc.insert(
+            "started_at",
+            bson::to_bson(&started_at)
+                .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?,
+        );
+    }
+    if let Some(finished_at) = patch.finished_at {
+        update_doc.insert(
+            "finished_at",
+            bson::to_bson(&finished_at)
+                .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?,
+        );
+    }
+    if let Some(percent_from_checklist) = patch.percent_from_checklist {
+        update_doc.insert("percent_from_checklist", percent_from_checklist);
+    }
+    history::stamp_updated(&mut update_doc, history::now());
 
     collection
-        .update_one(doc! { "_id": oid }, doc! { "$set": update_doc }, None)
+        .update_one(
+            trash::live(doc! { "_id": oid }),
+            doc! { "$set": update_doc },
+            None,
+        )
         .await
-        .map_err(|_| Status::InternalServerError)?;
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?;
 
-    let updated_game = collection
-        .find_one(doc! { "_id": oid }, None)
+    let game = sync_checklist_percent(&collection, oid).await?;
+
+    events::publish(db, Event::updated(Resource::Game), &game).await;
+    Ok(game)
+}
+
+/// Moves a game to the trash.
+///
+/// Shared by `DELETE /games/<id>` and the `games delete` CLI command.
+pub async fn remove_game(db: &Client, oid: ObjectId) -> Result<(), Status> {
+    trash::trash::<Game>(db, Resource::Game, doc! { "_id": oid })
         .await
         .map_err(|_| Status::InternalServerError)?
-        .ok_or(Status::NotFound)?;
+        .pop()
+        .map(|_| ())
+        .ok_or(Status::NotFound)
+}
 
-    Ok(Json(updated_game))
+#[post("/", format = "json", data = "<new_game>")]
+pub async fn post_games(
+    db: Connection<BearoData>,
+    user: User,
+    taxonomy: &Taxonomy,
+    new_game: Json<NewGame>,
+) -> Result<Json<Game>, status::Custom<String>> {
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
+
+    insert_game(&db, taxonomy, new_game.into_inner())
+        .await
+        .map(Json)
+}
+
+#[put("/<game_id>", format = "json", data = "<updated_game>")]
+pub async fn update_game(
+    db: Connection<BearoData>,
+    user: User,
+    taxonomy: &Taxonomy,
+    game_id: String,
+    updated_game: Json<UpdateGame>,
+) -> Result<Json<Game>, status::Custom<String>> {
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
+
+    let oid = ObjectId::parse_str(&game_id)
+        .map_err(|_| status::Custom(Status::BadRequest, "Invalid game id".to_string()))?;
+
+    replace_game(&db, taxonomy, oid, updated_game.into_inner())
+        .await
+        .map(Json)
+}
+
+#[patch("/<game_id>", format = "json", data = "<patch_data>")]
+pub async fn patch_game(
+    db: Connection<BearoData>,
+    user: User,
+    taxonomy: &Taxonomy,
+    game_id: String,
+    patch_data: Json<UpdateGame>,
+) -> Result<Json<Game>, status::Custom<String>> {
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
+
+    let oid = ObjectId::parse_str(&game_id)
+        .map_err(|_| status::Custom(Status::BadRequest, "Invalid game id".to_string()))?;
+
+    patch_game_fields(&db, taxonomy, oid, patch_data.into_inner())
+        .await
+        .map(Json)
 }
 
 #[delete("/<game_id>")]
@@ -346,24 +690,16 @@ pub async fn delete_game(
 ) -> Result<Json<ApiResponse>, Status> {
     user.require_admin().map_err(|_| Status::Forbidden)?;
 
-    let collection = db.database("bearodata").collection::<Game>("games");
     let oid = ObjectId::parse_str(&game_id).map_err(|_| Status::BadRequest)?;
 
-    let result = collection
-        .delete_one(doc! { "_id": oid }, None)
-        .await
-        .map_err(|_| Status::InternalServerError)?;
+    remove_game(&db, oid).await?;
 
-    if result.deleted_count > 0 {
-        Ok(Json(ApiResponse {
-            message: "game deleted".to_string(),
-            deleted: None,
-            updated: None,
-            count: None,
-        }))
-    } else {
-        Err(Status::NotFound)
-    }
+    Ok(Json(ApiResponse {
+        message: "game deleted".to_string(),
+        deleted: None,
+        updated: None,
+        count: None,
+    }))
 }
 
 #[delete("/bulk", format = "json", data = "<filter>")]
@@ -374,7 +710,6 @@ pub async fn bulk_delete_games(
 ) -> Result<Json<ApiResponse>, Status> {
     user.require_admin().map_err(|_| Status::Forbidden)?;
 
-    let collection = db.database("bearodata").collection::<Game>("games");
     let filter = filter.into_inner();
 
     let mut delete_filter = Document::new();
@@ -384,17 +719,18 @@ pub async fn bulk_delete_games(
     }
 
     if let Some(status_filter) = &filter.status {
-        delete_filter.insert("status", status_filter);
+        delete_filter.insert("status", status_filter.as_str());
     }
 
-    let result = collection
-        .delete_many(delete_filter, None)
+    trash::guard_bulk(&delete_filter, filter.confirm).map_err(|_| Status::UnprocessableEntity)?;
+
+    let deleted = trash::trash::<Game>(&db, Resource::Game, delete_filter)
         .await
         .map_err(|_| Status::InternalServerError)?;
 
     Ok(Json(ApiResponse {
         message: "bulk delete complete".to_string(),
-        deleted: Some(result.deleted_count as i64),
+        deleted: Some(deleted.len() as i64),
         updated: None,
         count: None,
     }))
@@ -418,23 +754,54 @@ pub async fn bulk_update_games(
     }
 
     if let Some(status_filter) = payload.filter.get("status") {
-        filter_doc.insert("status", status_filter);
+        let status = GameStatus::parse(status_filter).ok_or(Status::UnprocessableEntity)?;
+        filter_doc.insert("status", status.as_str());
     }
 
     let mut update_doc = Document::new();
 
     if let Some(new_status) = payload.update.get("status").and_then(|v| v.as_str()) {
-        update_doc.insert("status", new_status);
+        let status = GameStatus::parse(new_status).ok_or(Status::UnprocessableEntity)?;
+        update_doc.insert("status", status.as_str());
     }
 
     if let Some(new_rating) = payload.update.get("rating").and_then(|v| v.as_f64()) {
         update_doc.insert("rating", new_rating as i32);
     }
+    if update_doc.is_empty() {
+        return Err(Status::UnprocessableEntity);
+    }
+    history::stamp_updated(&mut update_doc, history::now());
+
+    let ids: Vec<ObjectId> = collection
+        .find(trash::live(filter_doc), None)
+        .await
+        .map_err(|_| Status::InternalServerError)?
+        .map_ok(|game| game.oid)
+        .try_collect()
+        .await
+        .map_err(|_| Status::InternalServerError)?;
 
     let result = collection
-        .update_many(filter_doc, doc! { "$set": update_doc }, None)
+        .update_many(
+            doc! { "_id": { "$in": &ids } },
+            doc! { "$set": update_doc },
+            None,
+        )
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+
+    let mut updated = collection
+        .find(doc! { "_id": { "$in": ids } }, None)
         .await
         .map_err(|_| Status::InternalServerError)?;
+    while let Some(game) = updated
+        .try_next()
+        .await
+        .map_err(|_| Status::InternalServerError)?
+    {
+        events::publish(&db, Event::updated(Resource::Game), &game).await;
+    }
 
     Ok(Json(ApiResponse {
         message: "bulk update complete".to_string(),
@@ -444,6 +811,374 @@ pub async fn bulk_update_games(
     }))
 }
 
+/// Attempts at logging a play session before giving up on concurrent changes.
+const SESSION_ATTEMPTS: usize = 5;
+
+/// Logs a play session, updating the game's playtime, dates, progress and status.
+///
+/// The session is stored first, then the game is updated only if it still has
+/// the percent and `updated_at` it was read with. When another write
+/// got there first, the session is removed and the whole update is retried.
+#[post("/<game_id>/sessions", format = "json", data = "<session>")]
+pub async fn post_session(
+    db: Connection<BearoData>,
+    user: User,
+    game_id: String,
+    session: Json<NewPlaySession>,
+) -> Result<Json<Game>, status::Custom<String>> {
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
+
+    let collection = db.database("bearodata").collection::<Game>("games");
+    let sessions = db
+        .database("bearodata")
+        .collection::<PlaySession>("play_sessions");
+    let oid = ObjectId::parse_str(&game_id)
+        .map_err(|_| status::Custom(Status::BadRequest, "Invalid game id".to_string()))?;
+    let session = session.into_inner();
+
+    let to_bson = |value: &Option<chrono::NaiveDateTime>| {
+        bson::to_bson(value).map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))
+    };
+
+    for _ in 0..SESSION_ATTEMPTS {
+        let mut game = collection
+            .find_one(trash::live(doc! { "_id": oid }), None)
+            .await
+            .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?
+            .ok_or_else(|| status::Custom(Status::NotFound, "Game not found".to_string()))?;
+
+        let filter = trash::live(doc! {
+            "_id": oid,
+            "percent": game.percent,
+            "updated_at": to_bson(&game.updated_at)?,
+        });
+
+        let now = history::now();
+        game.record_session(&session, now)
+            .map_err(|e| status::Custom(Status::UnprocessableEntity, e))?;
+
+        let entry = PlaySession {
+            oid: ObjectId::new(),
+            game_id: oid,
+            hours: session.hours,
+            played_at: session.played_at.unwrap_or(now),
+            platform: session.platform.clone(),
+            notes: session.notes.clone(),
+            percent: game.percent,
+            status: game.status,
+            playtime_hours: game.playtime_hours,
+        };
+        sessions
+            .insert_one(&entry, None)
+            .await
+            .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?;
+
+        let mut set = doc! {
+            "status": game.status.as_str(),
+            "percent": game.percent,
+            "started_at": to_bson(&game.started_at)?,
+            "finished_at": to_bson(&game.finished_at)?,
+        };
+        history::stamp_updated(&mut set, now);
+
+        let mut update = doc! {
+            "$set": set,
+            "$inc": { "playtime_hours": session.hours },
+        };
+        if let Some(platform) = &session.platform {
+            update.insert("$addToSet", doc! { "platforms": platform });
+        }
+
+        let updated = collection
+            .find_one_and_update(
+                filter,
+                update,
+                FindOneAndUpdateOptions::builder()
+                    .return_document(ReturnDocument::After)
+                    .build(),
+            )
+            .await;
+        match updated {
+            Ok(Some(game)) => {
+                events::publish(&db, Event::updated(Resource::Game), &game).await;
+                return Ok(Json(game));
+            }
+            Ok(None) => {
+                sessions
+                    .delete_one(doc! { "_id": entry.oid }, None)
+                    .await
+                    .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?;
+            }
+            Err(e) => {
+                let _ = sessions.delete_one(doc! { "_id": entry.oid }, None).await;
+                return Err(status::Custom(Status::InternalServerError, e.to_string()));
+            }
+        }
+    }
+
+    Err(status::Custom(
+        Status::Conflict,
+        "Game changed while logging the session; try again".to_string(),
+    ))
+}
+
+/// Lists a game's play sessions, most recent first.
+#[get("/<game_id>/sessions")]
+pub async fn get_sessions(
+    db: Connection<BearoData>,
+    game_id: String,
+) -> Result<Json<Vec<PlaySession>>, Status> {
+    let games = db.database("bearodata").collection::<Game>("games");
+    let oid = find_game(&games, &game_id).await?.oid;
+    let options = FindOptions::builder()
+        .sort(doc! { "played_at": -1 })
+        .build();
+
+    let sessions = db
+        .database("bearodata")
+        .collection::<PlaySession>("play_sessions")
+        .find(doc! { "game_id": oid }, options)
+        .await
+        .map_err(|_| Status::InternalServerError)?
+        .try_collect()
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+
+    Ok(Json(sessions))
+}
+
+#[get("/<game_id>/checklist")]
+pub async fn get_checklist(
+    db: Connection<BearoData>,
+    game_id: String,
+) -> Result<Json<ChecklistSummary>, Status> {
+    let collection = db.database("bearodata").collection::<Game>("games");
+    let game = find_game(&collection, &game_id).await?;
+
+    Ok(Json(ChecklistSummary::from(game)))
+}
+
+/// Adds an item to a game's checklist, rejecting duplicate names.
+#[post("/<game_id>/checklist", format = "json", data = "<item>")]
+pub async fn add_checklist_item(
+    db: Connection<BearoData>,
+    user: User,
+    game_id: String,
+    item: Json<NewChecklistItem>,
+) -> Result<Json<ChecklistSummary>, Status> {
+    user.require_admin().map_err(|_| Status::Forbidden)?;
+
+    let collection = db.database("bearodata").collection::<Game>("games");
+    let game = find_game(&collection, &game_id).await?;
+
+    let item = item.into_inner();
+    if item.name.trim().is_empty() {
+        return Err(Status::UnprocessableEntity);
+    }
+    if game.find_checklist_item(&item.name).is_some() {
+        return Err(Status::Conflict);
+    }
+
+    let item =
+        bson::to_bson(&ChecklistItem::from(item)).map_err(|_| Status::InternalServerError)?;
+    let game = update_checklist(
+        &db,
+        game.oid,
+        Document::new(),
+        doc! { "$push": { "checklist": item } },
+        None,
+    )
+    .await?
+    .ok_or(Status::NotFound)?;
+
+    Ok(Json(ChecklistSummary::from(game)))
+}
+
+/// Edits a checklist item; `unlocked` ticks or unticks it.
+#[patch("/<game_id>/checklist/<item_id>", format = "json", data = "<update>")]
+pub async fn patch_checklist_item(
+    db: Connection<BearoData>,
+    user: User,
+    game_id: String,
+    item_id: String,
+    update: Json<UpdateChecklistItem>,
+) -> Result<Json<ChecklistSummary>, Status> {
+    user.require_admin().map_err(|_| Status::Forbidden)?;
+
+    let collection = db.database("bearodata").collection::<Game>("games");
+    let game = find_game(&collection, &game_id).await?;
+    let item_oid = ObjectId::parse_str(&item_id).map_err(|_| Status::BadRequest)?;
+    let update = update.into_inner();
+
+    if update
+        .name
+        .as_ref()
+        .is_some_and(|name| name.trim().is_empty())
+    {
+        return Err(Status::UnprocessableEntity);
+    }
+    if let Some(name) = &update.name
+        && game
+            .find_checklist_item(name)
+            .is_some_and(|index| game.checklist[index].id != item_oid)
+    {
+        return Err(Status::Conflict);
+    }
+
+    let item = game
+        .checklist
+        .iter()
+        .find(|item| item.id == item_oid)
+        .ok_or(Status::NotFound)?;
+
+    let mut set = Document::new();
+    if let Some(name) = update.name {
+        set.insert("checklist.$[item].name", name.trim());
+    }
+    if let Some(description) = update.description {
+        set.insert("checklist.$[item].description", description);
+    }
+    if let Some(hidden) = update.hidden {
+        set.insert("checklist.$[item].hidden", hidden);
+    }
+    let unlocked_at = match update.unlocked {
+        Some(true) => Some(Some(
+            update
+                .unlocked_at
+                .or(item.unlocked_at)
+                .unwrap_or_else(|| chrono::Utc::now().naive_utc()),
+        )),
+        Some(false) => Some(None),
+        None => update.unlocked_at.map(Some),
+    };
+    if let Some(unlocked_at) = unlocked_at {
+        set.insert(
+            "checklist.$[item].unlocked_at",
+            bson::to_bson(&unlocked_at).map_err(|_| Status::InternalServerError)?,
+        );
+    }
+
+    // MongoDB rejects array filters the update does not use.
+    let array_filters = (!set.is_empty()).then(|| vec![doc! { "item.id": item_oid }]);
+    let game = update_checklist(
+        &db,
+        game.oid,
+        doc! { "checklist.id": item_oid },
+        doc! { "$set": set },
+        array_filters,
+    )
+    .await?
+    .ok_or(Status::NotFound)?;
+
+    Ok(Json(ChecklistSummary::from(game)))
+}
+
+#[delete("/<game_id>/checklist/<item_id>")]
+pub async fn delete_checklist_item(
+    db: Connection<BearoData>,
+    user: User,
+    game_id: String,
+    item_id: String,
+) -> Result<Json<ChecklistSummary>, Status> {
+    user.require_admin().map_err(|_| Status::Forbidden)?;
+
+    let collection = db.database("bearodata").collection::<Game>("games");
+    let game = find_game(&collection, &game_id).await?;
+    let item_oid = ObjectId::parse_str(&item_id).map_err(|_| Status::BadRequest)?;
+
+    let game = update_checklist(
+        &db,
+        game.oid,
+        doc! { "checklist.id": item_oid },
+        doc! { "$pull": { "checklist": { "id": item_oid } } },
+        None,
+    )
+    .await?
+    .ok_or(Status::NotFound)?;
+
+    Ok(Json(ChecklistSummary::from(game)))
+}
+
+/// Imports a list of checklist items, merging them by name with existing items.
+#[post("/<game_id>/checklist/import", format = "json", data = "<payload>")]
+pub async fn import_checklist(
+    db: Connection<BearoData>,
+    user: User,
+    game_id: String,
+    payload: Json<ChecklistImportPayload>,
+) -> Result<Json<ChecklistImport>, Status> {
+    user.require_admin().map_err(|_| Status::Forbidden)?;
+
+    let collection = db.database("bearodata").collection::<Game>("games");
+    let mut game = find_game(&collection, &game_id).await?;
+
+    let items = payload.into_inner().items;
+    if items.iter().any(|item| item.name.trim().is_empty()) {
+        return Err(Status::UnprocessableEntity);
+    }
+
+    // Merging by name rewrites the whole checklist, so only write it if no
+    // other change landed since it was read.
+    let unchanged = if game.checklist.is_empty() {
+        doc! { "checklist": { "$in": [[], null] } }
+    } else {
+        doc! { "checklist": bson::to_bson(&game.checklist).map_err(|_| Status::InternalServerError)? }
+    };
+    let result = game.import_checklist(items);
+    let checklist = bson::to_bson(&game.checklist).map_err(|_| Status::InternalServerError)?;
+    update_checklist(
+        &db,
+        game.oid,
+        unchanged,
+        doc! { "$set": { "checklist": checklist } },
+        None,
+    )
+    .await?
+    .ok_or(Status::Conflict)?;
+
+    Ok(Json(result))
+}
+
+/// Imports a Steam, GOG Galaxy or Playnite library export.
+///
+/// Returns the planned changes without writing anything unless `apply=true`.
+#[post("/import?<format>&<apply>", data = "<file>")]
+pub async fn import_games(
+    db: Connection<BearoData>,
+    user: User,
+    format: GameLibraryFormat,
+    apply: Option<bool>,
+    file: Data<'_>,
+) -> Result<Json<ImportReport<GameImportPlan>>, status::Custom<String>> {
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
+
+    let input = file
+        .open(16.mebibytes())
+        .into_string()
+        .await
+        .map_err(|e| status::Custom(Status::BadRequest, e.to_string()))?;
+    if !input.is_complete() {
+        return Err(status::Custom(
+            Status::PayloadTooLarge,
+            "library export is too large".to_string(),
+        ));
+    }
+
+    import_library(&db, format, &input, apply.unwrap_or(false))
+        .await
+        .map(Json)
+        .map_err(|e| match e {
+            ImportError::Database(_)
+            | ImportError::Serialization(_)
+            | ImportError::Deserialization(_) => {
+                status::Custom(Status::InternalServerError, e.to_string())
+            }
+            _ => status::Custom(Status::UnprocessableEntity, e.to_string()),
+        })
+}
+
 pub fn routes() -> Vec<rocket::Route> {
     routes![
         get_games,
@@ -453,6 +1188,14 @@ pub fn routes() -> Vec<rocket::Route> {
         patch_game,
         delete_game,
         bulk_delete_games,
-        bulk_update_games
+        bulk_update_games,
+        post_session,
+        get_sessions,
+        get_checklist,
+        add_checklist_item,
+        patch_checklist_item,
+        delete_checklist_item,
+        import_checklist,
+        import_games
     ]
 }
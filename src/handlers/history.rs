@@ -0,0 +1,216 @@
+//! # History handlers
+//!
+//! Revision history of books, games, projects, reviews and wplace
+//! screenshots. See [`crate::history`] for how revisions are recorded.
+//!
+//! - `GET /…/<id>/history` lists a document's revisions, newest first, at
+//!   most [`history::MAX_PAGE`] at a time
+//! - `POST /…/<id>/history/<revision>/revert` writes a revision back as the
+//!   current document, restoring it if it was deleted
+//!
+//! Both require an admin key, since revisions hold full snapshots of trashed
+//! documents too.
+//!
+//! The routes are mounted under `/read-watch`, `/games`, `/projects`,
+//! `/reviews` and `/wplace`.
+
+use {
+    crate::{
+        auth::User,
+        db::BearoData,
+        errors::RevisionError,
+        events::Resource,
+        history,
+        models::{Book, Game, Project, Review, Revision, WplaceScreenshot},
+    },
+    mongodb::bson::oid::ObjectId,
+    rocket::{
+        Route, get, http::Status, post, response::status, routes, serde::Serialize,
+        serde::json::Json,
+    },
+    rocket_db_pools::{Connection, mongodb::Client},
+    serde::de::DeserializeOwned,
+};
+
+fn error_status(e: RevisionError) -> status::Custom<String> {
+    let status = match e {
+        RevisionError::NotFound { .. } => Status::NotFound,
+        RevisionError::Incompatible(_) => Status::Conflict,
+        RevisionError::Serialization(_) | RevisionError::Database(_) => Status::InternalServerError,
+    };
+    status::Custom(status, e.to_string())
+}
+
+fn parse_id(id: &str) -> Result<ObjectId, status::Custom<String>> {
+    ObjectId::parse_str(id)
+        .map_err(|_| status::Custom(Status::BadRequest, "invalid id".to_string()))
+}
+
+async fn list(
+    db: &Client,
+    user: User,
+    resource: Resource,
+    id: &str,
+    offset: Option<u64>,
+    limit: Option<u32>,
+) -> Result<Json<Vec<Revision>>, status::Custom<String>> {
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
+
+    let collection = history::tracked_collection(resource)
+        .ok_or_else(|| status::Custom(Status::NotFound, "no history".to_string()))?;
+
+    history::history(db, collection, parse_id(id)?, offset, limit)
+        .await
+        .map(Json)
+        .map_err(error_status)
+}
+
+async fn restore<T>(
+    db: &Client,
+    user: User,
+    resource: Resource,
+    id: &str,
+    revision: u32,
+) -> Result<Json<T>, status::Custom<String>>
+where
+    T: Serialize + DeserializeOwned + Send + Sync + Unpin,
+{
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
+
+    history::revert(db, resource, parse_id(id)?, revision)
+        .await
+        .map(Json)
+        .map_err(error_status)
+}
+
+#[get("/<id>/history?<offset>&<limit>")]
+pub async fn get_book_history(
+    db: Connection<BearoData>,
+    user: User,
+    id: &str,
+    offset: Option<u64>,
+    limit: Option<u32>,
+) -> Result<Json<Vec<Revision>>, status::Custom<String>> {
+    list(&db, user, Resource::Book, id, offset, limit).await
+}
+
+#[post("/<id>/history/<revision>/revert")]
+pub async fn revert_book(
+    db: Connection<BearoData>,
+    user: User,
+    id: &str,
+    revision: u32,
+) -> Result<Json<Book>, status::Custom<String>> {
+    restore(&db, user, Resource::Book, id, revision).await
+}
+
+#[get("/<id>/history?<offset>&<limit>")]
+pub async fn get_game_history(
+    db: Connection<BearoData>,
+    user: User,
+    id: &str,
+    offset: Option<u64>,
+    limit: Option<u32>,
+) -> Result<Json<Vec<Revision>>, status::Custom<String>> {
+    list(&db, user, Resource::Game, id, offset, limit).await
+}
+
+#[post("/<id>/history/<revision>/revert")]
+pub async fn revert_game(
+    db: Connection<BearoData>,
+    user: User,
+    id: &str,
+    revision: u32,
+) -> Result<Json<Game>, status::Custom<String>> {
+    restore(&db, user, Resource::Game, id, revision).await
+}
+
+#[get("/<id>/history?<offset>&<limit>")]
+pub async fn get_project_history(
+    db: Connection<BearoData>,
+    user: User,
+    id: &str,
+    offset: Option<u64>,
+    limit: Option<u32>,
+) -> Result<Json<Vec<Revision>>, status::Custom<String>> {
+    list(&db, user, Resource::Project, id, offset, limit).await
+}
+
+#[post("/<id>/history/<revision>/revert")]
+pub async fn revert_project(
+    db: Connection<BearoData>,
+    user: User,
+    id: &str,
+    revision: u32,
+) -> Result<Json<Project>, status::Custom<String>> {
+    restore(&db, user, Resource::Project, id, revision).await
+}
+
+#[get("/<id>/history?<offset>&<limit>")]
+pub async fn get_review_history(
+    db: Connection<BearoData>,
+    user: User,
+    id: &str,
+    offset: Option<u64>,
+    limit: Option<u32>,
+) -> Result<Json<Vec<Revision>>, status::Custom<String>> {
+    list(&db, user, Resource::Review, id, offset, limit).await
+}
+
+#[post("/<id>/history/<revision>/revert")]
+pub async fn revert_review(
+    db: Connection<BearoData>,
+    user: User,
+    id: &str,
+    revision: u32,
+) -> Result<Json<Review>, status::Custom<String>> {
+    restore(&db, user, Resource::Review, id, revision).await
+}
+
+#[get("/<id>/history?<offset>&<limit>")]
+pub async fn get_screenshot_history(
+    db: Connection<BearoData>,
+    user: User,
+    id: &str,
+    offset: Option<u64>,
+    limit: Option<u32>,
+) -> Result<Json<Vec<Revision>>, status::Custom<String>> {
+    list(&db, user, Resource::Screenshot, id, offset, limit).await
+}
+
+#[post("/<id>/history/<revision>/revert")]
+pub async fn revert_screenshot(
+    db: Connection<BearoData>,
+    user: User,
+    id: &str,
+    revision: u32,
+) -> Result<Json<WplaceScreenshot>, status::Custom<String>> {
+    restore(&db, user, Resource::Screenshot, id, revision).await
+}
+
+/// History routes of books, mounted under `/read-watch`.
+pub fn book_routes() -> Vec<Route> {
+    routes![get_book_history, revert_book]
+}
+
+/// History routes of games, mounted under `/games`.
+pub fn game_routes() -> Vec<Route> {
+    routes![get_game_history, revert_game]
+}
+
+/// History routes of projects, mounted under `/projects`.
+pub fn project_routes() -> Vec<Route> {
+    routes![get_project_history, revert_project]
+}
+
+/// History routes of reviews, mounted under `/reviews`.
+pub fn review_routes() -> Vec<Route> {
+    routes![get_review_history, revert_review]
+}
+
+/// History routes of wplace screenshots, mounted under `/wplace`.
+pub fn screenshot_routes() -> Vec<Route> {
+    routes![get_screenshot_history, revert_screenshot]
+}
@@ -0,0 +1,57 @@
+//! # Metrics handler
+//!
+//! Serves [`crate::metrics::METRICS`] in the Prometheus text format. Requires an
+//! admin API key when `METRICS_REQUIRE_ADMIN` is set.
+
+use {
+    crate::{
+        auth::User,
+        backup::backup_collections,
+        db::BearoData,
+        metrics::{METRICS, require_admin},
+    },
+    mongodb::bson::Document,
+    rocket::{
+        Route, get,
+        http::{ContentType, Status},
+        routes,
+    },
+    rocket_db_pools::Connection,
+};
+
+/// Renders every metric, refreshing collection document counts first.
+#[get("/")]
+pub async fn metrics(
+    db: Connection<BearoData>,
+    user: Option<User>,
+) -> Result<(ContentType, String), Status> {
+    if require_admin() {
+        match user {
+            Some(user) => user.require_admin().map_err(|_| Status::Forbidden)?,
+            None => return Err(Status::Unauthorized),
+        }
+    }
+
+    let database = db.database("bearodata");
+    for name in backup_collections(true) {
+        if let Ok(count) = database
+            .collection::<Document>(name)
+            .estimated_document_count(None)
+            .await
+        {
+            METRICS
+                .documents
+                .with_label_values(&[name])
+                .set(count as i64);
+        }
+    }
+
+    Ok((
+        ContentType::new("text", "plain").with_params(("version", "0.0.4")),
+        METRICS.render(),
+    ))
+}
+
+pub fn routes() -> Vec<Route> {
+    routes![metrics]
+}
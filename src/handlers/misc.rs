@@ -1,62 +1,257 @@
-use mongodb::bson::doc;
-use rocket::{get, http::Status, routes as rocket_routes, serde::json::Json};
-use rocket_db_pools::Connection;
+use mongodb::bson::{Document, doc};
+use rocket::{
+    State, get, http::Status, response::status, routes as rocket_routes, serde::json::Json,
+};
+use rocket_db_pools::{Connection, mongodb::Client};
+use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, time::Instant};
 
-use crate::{auth::User, db::BearoData};
+use crate::{
+    auth::User,
+    backup::backup_collections,
+    db::BearoData,
+    history::{REVISION_INDEX, REVISIONS},
+    migrations::{
+        self, ALL_MIGRATIONS, MigrationRecord, REVIEW_INDEX, REVIEW_INDEX_NAME, TAXONOMY_INDEX,
+        TAXONOMY_INDEX_NAME, TIMESTAMPS,
+    },
+};
 
-#[derive(Serialize, Deserialize, Clone, Default)]
+/// Indexes created by migrations, as `(collection, index, migration)`.
+const EXPECTED_INDEXES: [(&str, &str, &str); 3] = [
+    ("reviews", REVIEW_INDEX_NAME, REVIEW_INDEX),
+    (REVISIONS, REVISION_INDEX, TIMESTAMPS),
+    ("taxonomy", TAXONOMY_INDEX_NAME, TAXONOMY_INDEX),
+];
+
+/// When the server started, used to report uptime.
+pub struct Uptime(Instant);
+
+impl Default for Uptime {
+    fn default() -> Self {
+        Self(Instant::now())
+    }
+}
+
+impl Uptime {
+    pub fn seconds(&self) -> u64 {
+        self.0.elapsed().as_secs()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct CollectionStatus {
     pub books: String,
 }
 
-#[derive(Serialize, Deserialize, Clone, Default)]
+#[derive(Serialize, Deserialize, Clone, Default, JsonSchema)]
 pub struct HealthStatus {
     pub db_status: String,
     pub collections_status: CollectionStatus,
 }
 
-#[derive(Serialize)]
+/// Liveness report; the process is up and serving requests.
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct Liveness {
+    pub status: String,
+    pub version: String,
+    pub uptime_seconds: u64,
+}
+
+/// Database connectivity as seen by the readiness probe.
+#[derive(Serialize, Deserialize, Default, JsonSchema)]
+pub struct DatabaseHealth {
+    pub online: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ping_ms: Option<f64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Status of one collection.
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct CollectionHealth {
+    pub exists: bool,
+    pub documents: u64,
+}
+
+/// Status of an index created by a migration.
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct IndexHealth {
+    pub collection: String,
+    pub name: String,
+    pub present: bool,
+    pub migration: String,
+}
+
+/// Applied and pending migrations.
+#[derive(Serialize, Deserialize, Default, JsonSchema)]
+pub struct MigrationHealth {
+    pub applied: Vec<MigrationRecord>,
+    pub pending: Vec<String>,
+}
+
+/// Readiness report; the backend can serve traffic once the database answers.
+///
+/// Missing collections, indexes and pending migrations are reported but do not
+/// make the backend unready, since a fresh database has none of them. They are
+/// only filled in for admin keys; other callers get the ping result alone.
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct Readiness {
+    pub status: String,
+    pub version: String,
+    pub uptime_seconds: u64,
+    pub database: DatabaseHealth,
+    pub collections: BTreeMap<String, CollectionHealth>,
+    pub indexes: Vec<IndexHealth>,
+    pub migrations: MigrationHealth,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
 pub struct CheckStatusResponse {
-    valid: bool,
-    is_admin: bool,
-    user_id: String,
-    created_at: String,
-    last_used_at: Option<String>,
+    pub valid: bool,
+    pub is_admin: bool,
+    pub user_id: String,
+    pub created_at: String,
+    pub last_used_at: Option<String>,
 }
 
 #[get("/check-health")]
 pub async fn health(db: Connection<BearoData>) -> Result<Json<HealthStatus>, Status> {
-    let mut collections_status = CollectionStatus::default();
     let mut health = HealthStatus::default();
 
-    if db
+    match db
         .database("bearodata")
         .list_collection_names(doc! {})
         .await
-        .is_ok()
     {
-        health.db_status = "database online!".to_string();
-
-        let collections = db
-            .database("bearodata")
-            .list_collection_names(doc! {})
-            .await
-            .expect("Failed to get collection names");
-
-        collections.clone().iter().for_each(|col| {
-            let collection = col.to_owned();
-            if collection == "books" {
-                collections_status.books = "Books collection online!".to_string();
+        Ok(collections) => {
+            health.db_status = "database online!".to_string();
+
+            if collections.iter().any(|collection| collection == "books") {
+                health.collections_status.books = "Books collection online!".to_string();
             }
-        });
-    } else {
-        health.db_status = "database offline :(".to_string();
+        }
+        Err(_) => health.db_status = "database offline :(".to_string(),
     }
 
     Ok(Json(health))
 }
 
+/// Reports that the process is alive, without touching the database.
+#[get("/health/live")]
+pub fn live(uptime: &State<Uptime>) -> Json<Liveness> {
+    Json(Liveness {
+        status: "ok".to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        uptime_seconds: uptime.seconds(),
+    })
+}
+
+/// Pings the database and, when `detailed`, reports its collections, indexes
+/// and migrations.
+async fn inspect_database(
+    db: &Client,
+    readiness: &mut Readiness,
+    detailed: bool,
+) -> Result<(), String> {
+    let database = db.database("bearodata");
+
+    let started = Instant::now();
+    database
+        .run_command(doc! { "ping": 1 }, None)
+        .await
+        .map_err(|e| e.to_string())?;
+    readiness.database.online = true;
+    readiness.database.ping_ms = Some(started.elapsed().as_secs_f64() * 1000.0);
+
+    if !detailed {
+        return Ok(());
+    }
+
+    let existing = database
+        .list_collection_names(doc! {})
+        .await
+        .map_err(|e| e.to_string())?;
+
+    for name in backup_collections(true) {
+        let exists = existing.iter().any(|collection| collection == name);
+        let documents = if exists {
+            database
+                .collection::<Document>(name)
+                .estimated_document_count(None)
+                .await
+                .map_err(|e| e.to_string())?
+        } else {
+            0
+        };
+        readiness
+            .collections
+            .insert(name.to_string(), CollectionHealth { exists, documents });
+    }
+
+    for (collection, name, migration) in EXPECTED_INDEXES {
+        let present = existing.iter().any(|existing| existing == collection)
+            && database
+                .collection::<Document>(collection)
+                .list_index_names()
+                .await
+                .map_err(|e| e.to_string())?
+                .iter()
+                .any(|index| index == name);
+        readiness.indexes.push(IndexHealth {
+            collection: collection.to_string(),
+            name: name.to_string(),
+            present,
+            migration: migration.to_string(),
+        });
+    }
+
+    let applied = migrations::applied_migrations(db)
+        .await
+        .map_err(|e| e.to_string())?;
+    readiness.migrations.pending = ALL_MIGRATIONS
+        .into_iter()
+        .filter(|name| !applied.iter().any(|record| record.name == *name))
+        .map(str::to_string)
+        .collect();
+    readiness.migrations.applied = applied;
+
+    Ok(())
+}
+
+/// Reports whether the backend can serve traffic.
+///
+/// Responds with 200 when MongoDB answers a ping and 503 otherwise. Admin keys
+/// also get the collection, index and migration breakdown.
+#[get("/health/ready")]
+pub async fn ready(
+    db: Connection<BearoData>,
+    user: Option<User>,
+    uptime: &State<Uptime>,
+) -> status::Custom<Json<Readiness>> {
+    let mut readiness = Readiness {
+        status: "ready".to_string(),
+        version: env!("CARGO_PKG_VERSION").to_string(),
+        uptime_seconds: uptime.seconds(),
+        database: DatabaseHealth::default(),
+        collections: BTreeMap::new(),
+        indexes: Vec::new(),
+        migrations: MigrationHealth::default(),
+    };
+
+    let detailed = user.is_some_and(|user| user.is_admin());
+    match inspect_database(&db, &mut readiness, detailed).await {
+        Ok(()) => status::Custom(Status::Ok, Json(readiness)),
+        Err(e) => {
+            readiness.status = "unavailable".to_string();
+            readiness.database.error = Some(e);
+            status::Custom(Status::ServiceUnavailable, Json(readiness))
+        }
+    }
+}
+
 #[get("/check-login")]
 pub async fn check_admin_status(user: User) -> Json<CheckStatusResponse> {
     Json(CheckStatusResponse {
@@ -71,5 +266,26 @@ pub async fn check_admin_status(user: User) -> Json<CheckStatusResponse> {
 }
 
 pub fn routes() -> Vec<rocket::Route> {
-    rocket_routes![health, check_admin_status]
+    rocket_routes![health, live, ready, check_admin_status]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::local::blocking::Client;
+
+    #[test]
+    fn test_live_route() {
+        let rocket = rocket::build()
+            .manage(Uptime::default())
+            .mount("/misc", rocket_routes![live]);
+        let client = Client::tracked(rocket).expect("valid rocket");
+
+        let response = client.get("/misc/health/live").dispatch();
+        assert_eq!(response.status(), Status::Ok);
+
+        let body: serde_json::Value = response.into_json().unwrap();
+        assert_eq!(body["status"], "ok");
+        assert_eq!(body["version"], env!("CARGO_PKG_VERSION"));
+    }
 }
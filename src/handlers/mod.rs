@@ -5,20 +5,52 @@
 //!
 //! ## Submodules
 //!
+//! - `admin`: Handlers for full-catalog export and restore
 //! - `reviews`: Handlers for review-related operations
 //! - `wplace`: Handlers for workplace screenshot management
 //! - `books`: Handlers for book catalog operations
+//! - `docs`: OpenAPI document and API documentation viewers
+//! - `events`: Server-sent events stream of live changes
+//! - `feeds`: Atom, RSS and JSON Feed activity feeds
 //! - `games`: Handlers for game collection management
+//! - `history`: Revision history and revert of documents
 //! - `projects`: Handlers for project portfolio
+//! - `metrics`: Prometheus metrics endpoint
 //! - `misc`: Miscellaneous handlers
+//! - `taxonomy`: Handlers for the shared genre/tag taxonomy
+//! - `series`: Handlers for grouping books into series
+//! - `trash`: Listing and restoring deleted documents
+//! - `webhooks`: Handlers for webhook registration and delivery logs
 
+pub mod admin;
 pub mod books;
+pub mod docs;
+pub mod events;
+pub mod feeds;
 pub mod games;
+pub mod history;
+pub mod metrics;
 pub mod misc;
 pub mod projects;
 pub mod reviews;
+pub mod series;
+pub mod taxonomy;
+pub mod trash;
+pub mod webhooks;
 pub mod wplace;
 
+/// Applies `?offset=&limit=` pagination to a list of results.
+///
+/// List handlers paginate after filtering and sorting, so a page holds the same
+/// documents no matter how the filters were evaluated.
+pub fn paginate<T>(items: Vec<T>, offset: Option<usize>, limit: Option<usize>) -> Vec<T> {
+    items
+        .into_iter()
+        .skip(offset.unwrap_or(0))
+        .take(limit.unwrap_or(usize::MAX))
+        .collect()
+}
+
 #[rocket::get("/")]
 pub fn index() -> &'static str {
     r#"
@@ -147,4 +179,14 @@ mod tests {
         let body = response.into_string().unwrap();
         assert!(body.contains("8"));
     }
+
+    #[test]
+    fn test_paginate() {
+        let items: Vec<i32> = (1..=5).collect();
+
+        assert_eq!(paginate(items.clone(), None, None), items);
+        assert_eq!(paginate(items.clone(), Some(1), Some(2)), vec![2, 3]);
+        assert_eq!(paginate(items.clone(), Some(4), Some(10)), vec![5]);
+        assert!(paginate(items, Some(9), None).is_empty());
+    }
 }
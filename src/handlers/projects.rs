@@ -1,62 +1,212 @@
 use crate::auth::User;
 use crate::db::BearoData;
+use crate::events::{self, Event, Resource};
+use crate::history;
+use crate::markdown::{self, Render, Rendered};
 use crate::models::{NewProject, Project, UpdateProject};
+use crate::trash;
 use mongodb::bson::{Document, doc, oid::ObjectId};
 use rocket::futures::TryStreamExt;
+use rocket::response::status;
 use rocket::serde::json::Json;
 use rocket::{delete, get, http::Status, patch, post, put, routes};
-use rocket_db_pools::mongodb::options::UpdateOptions;
-use rocket_db_pools::{Connection, mongodb::Collection};
+use rocket_db_pools::mongodb::options::{FindOptions, UpdateOptions};
+use rocket_db_pools::{
+    Connection,
+    mongodb::{Client, Collection},
+};
+
+/// Rejects descriptions containing scripts or unsafe links.
+fn validate_description(description: &str) -> Result<(), status::Custom<String>> {
+    markdown::validate(description)
+        .map_err(|e| status::Custom(Status::UnprocessableEntity, format!("description: {}", e)))
+}
 
-#[post("/", format = "json", data = "<new_project>")]
-pub async fn create_project(
-    db: Connection<BearoData>,
-    user: User,
-    new_project: Json<NewProject>,
-) -> Result<Json<Project>, Status> {
-    user.require_admin().map_err(|_| Status::Forbidden)?;
+/// Validates a new project and inserts it.
+///
+/// Shared by `POST /projects` and the `projects create` CLI command.
+pub async fn insert_project(
+    db: &Client,
+    new_project: NewProject,
+) -> Result<Project, status::Custom<String>> {
+    validate_description(&new_project.description)?;
 
-    let collection: Collection<NewProject> = db.database("bearodata").collection("projects");
+    let collection: Collection<Document> = db.database("bearodata").collection("projects");
+
+    let mut document = mongodb::bson::to_document(&new_project)
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?;
+    history::stamp_created(&mut document, history::now());
 
     let result = collection
-        .insert_one(new_project.into_inner(), None)
+        .insert_one(document, None)
         .await
-        .map_err(|_| Status::InternalServerError)?;
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?;
 
     let project_collection = db.database("bearodata").collection::<Project>("projects");
-    let inserted_project = project_collection
+    let project = project_collection
         .find_one(doc! { "_id": result.inserted_id }, None)
+        .await
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?
+        .ok_or_else(|| {
+            status::Custom(
+                Status::InternalServerError,
+                "Project not found after insert".to_string(),
+            )
+        })?;
+
+    events::publish(db, Event::created(Resource::Project), &project).await;
+    Ok(project)
+}
+
+/// Validates and writes every field of `update_data` to an existing project.
+///
+/// Shared by `PUT /projects/<id>` and the `projects edit` CLI command.
+pub async fn replace_project(
+    db: &Client,
+    oid: ObjectId,
+    update_data: UpdateProject,
+) -> Result<Project, status::Custom<String>> {
+    let collection = db.database("bearodata").collection::<Project>("projects");
+
+    if let Some(description) = &update_data.description {
+        validate_description(description)?;
+    }
+
+    let mut update_doc = mongodb::bson::to_document(&update_data)
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?;
+    history::stamp_updated(&mut update_doc, history::now());
+
+    let options = UpdateOptions::builder().upsert(false).build();
+
+    collection
+        .update_one(
+            trash::live(doc! { "_id": oid }),
+            doc! { "$set": update_doc },
+            options,
+        )
+        .await
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?;
+
+    let project = collection
+        .find_one(trash::live(doc! { "_id": oid }), None)
+        .await
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?
+        .ok_or_else(|| status::Custom(Status::NotFound, "Project not found".to_string()))?;
+
+    events::publish(db, Event::updated(Resource::Project), &project).await;
+    Ok(project)
+}
+
+/// Validates and writes the fields present in `patch` to an existing project.
+///
+/// Shared by `PATCH /projects/<id>` and the `projects patch` CLI command.
+pub async fn patch_project_fields(
+    db: &Client,
+    oid: ObjectId,
+    patch: UpdateProject,
+) -> Result<Project, status::Custom<String>> {
+    let collection = db.database("bearodata").collection::<Project>("projects");
+
+    let mut update_doc = Document::new();
+
+    if let Some(name) = patch.name {
+        update_doc.insert("name", name);
+    }
+    if let Some(description) = patch.description {
+        validate_description(&description)?;
+        update_doc.insert("description", description);
+    }
+    if let Some(tags) = patch.tags {
+        update_doc.insert("tags", tags);
+    }
+    if let Some(source) = patch.source {
+        update_doc.insert("source", source);
+    }
+    if let Some(cover_image) = patch.cover_image {
+        update_doc.insert("cover_image", cover_image);
+    }
+    if let Some(install_command) = patch.install_command {
+        update_doc.insert("install_command", install_command);
+    }
+    history::stamp_updated(&mut update_doc, history::now());
+
+    collection
+        .update_one(
+            trash::live(doc! { "_id": oid }),
+            doc! { "$set": update_doc },
+            None,
+        )
+        .await
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?;
+
+    let project = collection
+        .find_one(trash::live(doc! { "_id": oid }), None)
+        .await
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?
+        .ok_or_else(|| status::Custom(Status::NotFound, "Project not found".to_string()))?;
+
+    events::publish(db, Event::updated(Resource::Project), &project).await;
+    Ok(project)
+}
+
+/// Moves a project to the trash, returning it.
+///
+/// Shared by `DELETE /projects/<id>` and the `projects delete` CLI command.
+pub async fn remove_project(db: &Client, oid: ObjectId) -> Result<Project, Status> {
+    trash::trash(db, Resource::Project, doc! { "_id": oid })
         .await
         .map_err(|_| Status::InternalServerError)?
-        .ok_or(Status::InternalServerError)?;
+        .pop()
+        .ok_or(Status::NotFound)
+}
 
-    Ok(Json(inserted_project))
+#[post("/", format = "json", data = "<new_project>")]
+pub async fn create_project(
+    db: Connection<BearoData>,
+    user: User,
+    new_project: Json<NewProject>,
+) -> Result<Json<Project>, status::Custom<String>> {
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
+
+    insert_project(&db, new_project.into_inner())
+        .await
+        .map(Json)
 }
 
-#[get("/<project_id>")]
+#[get("/<project_id>?<render>")]
 pub async fn get_project(
     db: Connection<BearoData>,
     project_id: String,
-) -> Result<Json<Project>, Status> {
+    render: Option<Render>,
+) -> Result<Json<Rendered<Project>>, Status> {
     let collection = db.database("bearodata").collection::<Project>("projects");
 
     let oid = ObjectId::parse_str(&project_id).map_err(|_| Status::BadRequest)?;
 
     let project = collection
-        .find_one(doc! { "_id": oid }, None)
+        .find_one(trash::live(doc! { "_id": oid }), None)
         .await
         .map_err(|_| Status::InternalServerError)?
         .ok_or(Status::NotFound)?;
 
-    Ok(Json(project))
+    Ok(Json(Rendered::new(project, render)))
 }
 
-#[get("/")]
-pub async fn get_projects(db: Connection<BearoData>) -> Result<Json<Vec<Project>>, Status> {
+/// Lists projects; `sort=recent` puts the most recently created first.
+#[get("/?<render>&<sort>")]
+pub async fn get_projects(
+    db: Connection<BearoData>,
+    render: Option<Render>,
+    sort: Option<&str>,
+) -> Result<Json<Vec<Rendered<Project>>>, Status> {
     let collection = db.database("bearodata").collection::<Project>("projects");
+    let options = FindOptions::builder()
+        .sort((sort == Some("recent")).then(history::recent_sort))
+        .build();
 
     let mut cursor = collection
-        .find(doc! {}, None)
+        .find(trash::live(doc! {}), options)
         .await
         .map_err(|_| Status::InternalServerError)?;
 
@@ -69,7 +219,7 @@ pub async fn get_projects(db: Connection<BearoData>) -> Result<Json<Vec<Project>
         projects.push(project);
     }
 
-    Ok(Json(projects))
+    Ok(Json(Rendered::all(projects, render)))
 }
 
 #[put("/<project_id>", format = "json", data = "<update_data>")]
@@ -78,29 +228,16 @@ pub async fn update_project(
     user: User,
     project_id: String,
     update_data: Json<UpdateProject>,
-) -> Result<Json<Project>, Status> {
-    user.require_admin().map_err(|_| Status::Forbidden)?;
-
-    let collection = db.database("bearodata").collection::<Project>("projects");
-    let oid = ObjectId::parse_str(&project_id).map_err(|_| Status::BadRequest)?;
-
-    let update_doc = mongodb::bson::to_document(&update_data.into_inner())
-        .map_err(|_| Status::InternalServerError)?;
+) -> Result<Json<Project>, status::Custom<String>> {
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
 
-    let options = UpdateOptions::builder().upsert(false).build();
-
-    collection
-        .update_one(doc! { "_id": oid }, doc! { "$set": update_doc }, options)
-        .await
-        .map_err(|_| Status::InternalServerError)?;
+    let oid = ObjectId::parse_str(&project_id)
+        .map_err(|_| status::Custom(Status::BadRequest, "Invalid project id".to_string()))?;
 
-    let updated_project = collection
-        .find_one(doc! { "_id": oid }, None)
+    replace_project(&db, oid, update_data.into_inner())
         .await
-        .map_err(|_| Status::InternalServerError)?
-        .ok_or(Status::NotFound)?;
-
-    Ok(Json(updated_project))
+        .map(Json)
 }
 
 #[delete("/<project_id>")]
@@ -111,25 +248,9 @@ pub async fn delete_project(
 ) -> Result<Json<Project>, Status> {
     user.require_admin().map_err(|_| Status::Forbidden)?;
 
-    let collection = db.database("bearodata").collection::<Project>("projects");
     let oid = ObjectId::parse_str(&project_id).map_err(|_| Status::BadRequest)?;
 
-    let project = collection
-        .find_one(doc! { "_id": oid }, None)
-        .await
-        .map_err(|_| Status::InternalServerError)?
-        .ok_or(Status::NotFound)?;
-
-    let result = collection
-        .delete_one(doc! { "_id": oid }, None)
-        .await
-        .map_err(|_| Status::InternalServerError)?;
-
-    if result.deleted_count > 0 {
-        Ok(Json(project))
-    } else {
-        Err(Status::NotFound)
-    }
+    remove_project(&db, oid).await.map(Json)
 }
 
 #[patch("/<project_id>", format = "json", data = "<update_data>")]
@@ -138,46 +259,16 @@ pub async fn patch_project(
     user: User,
     project_id: String,
     update_data: Json<UpdateProject>,
-) -> Result<Json<Project>, Status> {
-    user.require_admin().map_err(|_| Status::Forbidden)?;
-
-    let collection = db.database("bearodata").collection::<Project>("projects");
-    let oid = ObjectId::parse_str(&project_id).map_err(|_| Status::BadRequest)?;
+) -> Result<Json<Project>, status::Custom<String>> {
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
 
-    let mut update_doc = Document::new();
-    let patch = update_data.into_inner();
+    let oid = ObjectId::parse_str(&project_id)
+        .map_err(|_| status::Custom(Status::BadRequest, "Invalid project id".to_string()))?;
 
-    if let Some(name) = patch.name {
-        update_doc.insert("name", name);
-    }
-    if let Some(description) = patch.description {
-        update_doc.insert("description", description);
-    }
-    if let Some(tags) = patch.tags {
-        update_doc.insert("tags", tags);
-    }
-    if let Some(source) = patch.source {
-        update_doc.insert("source", source);
-    }
-    if let Some(cover_image) = patch.cover_image {
-        update_doc.insert("cover_image", cover_image);
-    }
-    if let Some(install_command) = patch.install_command {
-        update_doc.insert("install_command", install_command);
-    }
-
-    collection
-        .update_one(doc! { "_id": oid }, doc! { "$set": update_doc }, None)
-        .await
-        .map_err(|_| Status::InternalServerError)?;
-
-    let updated_project = collection
-        .find_one(doc! { "_id": oid }, None)
+    patch_project_fields(&db, oid, update_data.into_inner())
         .await
-        .map_err(|_| Status::InternalServerError)?
-        .ok_or(Status::NotFound)?;
-
-    Ok(Json(updated_project))
+        .map(Json)
 }
 
 pub fn routes() -> Vec<rocket::Route> {
@@ -1,106 +1,540 @@
 use {
     crate::{
-        db::BearoData,
-        models::{NewReview, Review, UpdateReview},
+        auth::User,
+        db::{BearoData, is_duplicate_key},
+        events::{self, Event, Resource},
+        history,
+        markdown::{self, Render, Rendered},
+        migrations::default_review_work,
+        models::{NewReview, Review, UpdateReview, WorkKind},
+        trash,
     },
-    mongodb::bson::{self, doc, oid::ObjectId},
+    mongodb::bson::{self, Document, doc, oid::ObjectId},
     rocket::{
-        delete, futures::StreamExt, get, http::Status, patch, post, response::status, routes,
-        serde::json::Json,
+        FromForm, delete,
+        futures::{StreamExt, TryStreamExt},
+        get,
+        http::Status,
+        patch, post,
+        response::status,
+        routes,
+        serde::{Deserialize, Serialize, json::Json},
     },
-    rocket_db_pools::Connection,
+    rocket_db_pools::{
+        Connection,
+        mongodb::{
+            Client,
+            error::Error,
+            options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+        },
+    },
+    schemars::JsonSchema,
+    std::{collections::BTreeMap, ops::RangeInclusive},
 };
 
-#[post("/", data = "<review>")]
-pub async fn create_review(
-    db: Connection<BearoData>,
-    review: Json<NewReview>,
-) -> Result<Json<Review>, status::Custom<String>> {
+/// Looks up which work collection, if any, contains the given id.
+pub async fn find_work_kind(db: &Client, work_id: ObjectId) -> Result<Option<WorkKind>, Error> {
+    for kind in [WorkKind::Book, WorkKind::Series, WorkKind::Game] {
+        let count = db
+            .database("bearodata")
+            .collection::<Document>(kind.collection())
+            .count_documents(trash::live(doc! { "_id": work_id }), None)
+            .await?;
+        if count > 0 {
+            return Ok(Some(kind));
+        }
+    }
+
+    Ok(None)
+}
+
+fn parse_review_id(id: &str) -> Result<ObjectId, status::Custom<String>> {
+    ObjectId::parse_str(id)
+        .map_err(|_| status::Custom(Status::BadRequest, format!("Invalid review id: {}", id)))
+}
+
+fn parse_work_id(work_id: &str) -> Result<ObjectId, status::Custom<String>> {
+    ObjectId::parse_str(work_id)
+        .map_err(|_| status::Custom(Status::BadRequest, format!("Invalid work id: {}", work_id)))
+}
+
+/// Resolves the work a chapter route acts on: `?work=` or the default work.
+fn resolve_work(work: Option<&str>) -> Result<ObjectId, status::Custom<String>> {
+    match work {
+        Some(work) => parse_work_id(work),
+        None => default_review_work().ok_or_else(|| {
+            status::Custom(
+                Status::UnprocessableEntity,
+                "work is required when DEFAULT_REVIEW_WORK is not set".to_string(),
+            )
+        }),
+    }
+}
+
+/// Builds the filter for a live chapter review, scoped to the given work or the default work.
+fn chapter_filter(chapter: i32, work: Option<&str>) -> Result<Document, status::Custom<String>> {
+    Ok(trash::live(
+        doc! { "chapter": chapter, "work_id": resolve_work(work)? },
+    ))
+}
+
+/// Rejects review thoughts containing scripts or unsafe links.
+fn validate_thoughts(thoughts: Option<&str>) -> Result<(), status::Custom<String>> {
+    thoughts.map_or(Ok(()), |thoughts| {
+        markdown::validate(thoughts)
+            .map_err(|e| status::Custom(Status::UnprocessableEntity, format!("thoughts: {}", e)))
+    })
+}
+
+/// Inserts a review for a work, rejecting a second review of the same chapter.
+pub async fn insert_review(
+    db: &Client,
+    work_id: ObjectId,
+    review: NewReview,
+) -> Result<Review, status::Custom<String>> {
+    validate_thoughts(Some(&review.thoughts))?;
+
+    let work_kind = find_work_kind(db, work_id)
+        .await
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?
+        .ok_or_else(|| {
+            status::Custom(
+                Status::NotFound,
+                format!("No work found with id {}", work_id),
+            )
+        })?;
+
     let collection = db.database("bearodata").collection::<Review>("reviews");
 
+    let existing = collection
+        .find_one(
+            trash::live(doc! { "work_id": work_id, "chapter": review.chapter }),
+            None,
+        )
+        .await
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?;
+    if existing.is_some() {
+        return Err(status::Custom(
+            Status::Conflict,
+            format!(
+                "Review for chapter {} of work {} already found",
+                review.chapter, work_id
+            ),
+        ));
+    }
+
+    let now = history::now();
     let new_review = Review {
         oid: ObjectId::new(),
+        work_id: Some(work_id),
+        work_kind: Some(work_kind),
         chapter: review.chapter,
-        description: review.description.clone(),
+        description: review.description,
         rating: review.rating,
-        thoughts: review.thoughts.clone(),
+        thoughts: review.thoughts,
+        created_at: Some(now),
+        updated_at: Some(now),
+        deleted_at: None,
     };
 
-    if collection
-        .find_one(doc! { "chapter": new_review.chapter }, None)
+    collection
+        .insert_one(&new_review, None)
         .await
-        .expect("Failed")
-        .is_none()
-    {
-        collection
-            .insert_one(new_review.clone(), None)
-            .await
-            .expect("Failed to insert review");
+        .map_err(|e| {
+            if is_duplicate_key(&e) {
+                chapter_conflict(new_review.chapter)
+            } else {
+                status::Custom(Status::InternalServerError, e.to_string())
+            }
+        })?;
 
-        Ok(Json(new_review))
-    } else {
-        Err(status::Custom(
-            Status::Conflict,
-            format!("Review for chapter {} already found", new_review.chapter),
-        ))
-    }
+    events::publish(db, Event::created(Resource::Review), &new_review).await;
+    Ok(new_review)
 }
 
-#[get("/<id>")]
-pub async fn get_review_by_oid(db: Connection<BearoData>, id: &str) -> Option<Json<Review>> {
-    let collection = db.database("bearodata").collection::<Review>("reviews");
-    let oid = ObjectId::parse_str(id).expect("Failed to parse oid");
-    let found_review = collection
-        .find_one(doc! { "_id": oid }, None)
+/// Moves the reviews matching `filter` to the trash.
+async fn trash_reviews(
+    db: &Client,
+    filter: Document,
+) -> Result<Vec<Review>, status::Custom<String>> {
+    trash::trash(db, Resource::Review, filter)
         .await
-        .unwrap();
+        .map_err(|e| {
+            status::Custom(
+                Status::InternalServerError,
+                format!("failed to delete reviews: {}", e),
+            )
+        })
+}
+
+/// Moves the live review matching `filter` to the trash, if there is one.
+async fn trash_review(
+    db: &Client,
+    filter: Document,
+) -> Result<Option<Review>, status::Custom<String>> {
+    let review = db
+        .database("bearodata")
+        .collection::<Review>("reviews")
+        .find_one(trash::live(filter), None)
+        .await
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?;
+
+    match review {
+        Some(review) => Ok(trash_reviews(db, doc! { "_id": review.oid }).await?.pop()),
+        None => Ok(None),
+    }
+}
 
-    Some(Json(found_review.unwrap()))
+/// Creates a review for the work given in the body, or the default work.
+#[post("/", data = "<review>")]
+pub async fn create_review(
+    db: Connection<BearoData>,
+    user: User,
+    review: Json<NewReview>,
+) -> Result<Json<Review>, status::Custom<String>> {
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
+
+    let review = review.into_inner();
+    let work_id = review.work_id.or_else(default_review_work).ok_or_else(|| {
+        status::Custom(
+            Status::UnprocessableEntity,
+            "work_id is required when DEFAULT_REVIEW_WORK is not set".to_string(),
+        )
+    })?;
+
+    insert_review(&db, work_id, review).await.map(Json)
 }
 
-#[get("/<chapter>", rank = 2)]
+#[get("/<id>?<render>", rank = 2)]
+pub async fn get_review_by_oid(
+    db: Connection<BearoData>,
+    id: &str,
+    render: Option<Render>,
+) -> Result<Json<Rendered<Review>>, status::Custom<String>> {
+    let oid = parse_review_id(id)?;
+
+    db.database("bearodata")
+        .collection::<Review>("reviews")
+        .find_one(trash::live(doc! { "_id": oid }), None)
+        .await
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?
+        .map(|review| Json(Rendered::new(review, render)))
+        .ok_or_else(|| status::Custom(Status::NotFound, "No review found with this ID".into()))
+}
+
+#[get("/<chapter>?<work>&<render>")]
 pub async fn get_review_by_chapter(
     db: Connection<BearoData>,
     chapter: i32,
-) -> Option<Json<Review>> {
-    let collection = db.database("bearodata").collection::<Review>("reviews");
-    let found_review = collection
-        .find_one(doc! { "chapter": chapter }, None)
+    work: Option<&str>,
+    render: Option<Render>,
+) -> Result<Json<Rendered<Review>>, status::Custom<String>> {
+    let filter = chapter_filter(chapter, work)?;
+
+    db.database("bearodata")
+        .collection::<Review>("reviews")
+        .find_one(filter, None)
         .await
-        .unwrap();
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?
+        .map(|review| Json(Rendered::new(review, render)))
+        .ok_or_else(|| status::Custom(Status::NotFound, "No review found for this chapter".into()))
+}
 
-    Some(Json(found_review.unwrap()))
+/// Query parameters for filtering reviews by chapter range and rating.
+#[derive(FromForm, Debug, Default, JsonSchema)]
+pub struct ReviewQuery {
+    from: Option<i32>,
+    to: Option<i32>,
+    #[field(name = "minRating")]
+    #[schemars(rename = "minRating")]
+    min_rating: Option<i32>,
+    #[field(name = "maxRating")]
+    #[schemars(rename = "maxRating")]
+    max_rating: Option<i32>,
+    work: Option<String>,
+    /// `chapter` (default) or `recent`
+    sort: Option<String>,
+    /// Number of results to skip
+    offset: Option<usize>,
+    /// Maximum number of results to return
+    limit: Option<usize>,
 }
 
-#[get("/")]
-pub async fn get_reviews(db: Connection<BearoData>) -> Option<Json<Vec<Review>>> {
-    let collection = db.database("bearodata").collection::<Review>("reviews");
+impl ReviewQuery {
+    /// Builds the MongoDB filter for this query.
+    fn to_filter(&self) -> Result<Document, status::Custom<String>> {
+        let mut filter = Document::new();
+
+        if let (Some(from), Some(to)) = (self.from, self.to)
+            && from > to
+        {
+            return Err(status::Custom(
+                Status::BadRequest,
+                format!("`from` ({}) must not be greater than `to` ({})", from, to),
+            ));
+        }
+
+        if self.from.is_some() || self.to.is_some() {
+            let mut chapter_filter = Document::new();
+            if let Some(from) = self.from {
+                chapter_filter.insert("$gte", from);
+            }
+            if let Some(to) = self.to {
+                chapter_filter.insert("$lte", to);
+            }
+            filter.insert("chapter", chapter_filter);
+        }
+
+        if self.min_rating.is_some() || self.max_rating.is_some() {
+            let mut rating_filter = Document::new();
+            if let Some(min_rating) = self.min_rating {
+                rating_filter.insert("$gte", min_rating);
+            }
+            if let Some(max_rating) = self.max_rating {
+                rating_filter.insert("$lte", max_rating);
+            }
+            filter.insert("rating", rating_filter);
+        }
+
+        if let Some(work) = &self.work {
+            filter.insert("work_id", parse_work_id(work)?);
+        }
+
+        Ok(trash::live(filter))
+    }
+}
+
+/// Aggregate statistics over a set of chapter reviews.
+#[derive(Serialize, Deserialize, Debug, PartialEq, JsonSchema)]
+pub struct ReviewStats {
+    /// Number of reviews considered
+    pub count: usize,
+    /// Mean rating
+    pub average_rating: Option<f64>,
+    /// Number of reviews per rating
+    pub histogram: BTreeMap<i32, usize>,
+    /// Size of the rolling average window, in reviewed chapters
+    pub window: usize,
+    /// Average of the last `window` reviewed chapters, ending at each chapter
+    pub rolling_averages: Vec<RollingAverage>,
+    /// Chapters sharing the highest rating
+    pub best_chapters: Vec<i32>,
+    /// Chapters sharing the lowest rating
+    pub worst_chapters: Vec<i32>,
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq, JsonSchema)]
+pub struct RollingAverage {
+    pub chapter: i32,
+    pub average: f64,
+}
+
+impl ReviewStats {
+    /// Computes statistics over the given reviews.
+    fn compute(reviews: &[Review], window: usize) -> ReviewStats {
+        let mut sorted: Vec<&Review> = reviews.iter().collect();
+        sorted.sort_by_key(|review| review.chapter);
+
+        let mut histogram: BTreeMap<i32, usize> = (1..=5).map(|rating| (rating, 0)).collect();
+        for review in &sorted {
+            *histogram.entry(review.rating).or_insert(0) += 1;
+        }
+
+        let average_rating = if sorted.is_empty() {
+            None
+        } else {
+            Some(
+                sorted
+                    .iter()
+                    .map(|review| review.rating as f64)
+                    .sum::<f64>()
+                    / sorted.len() as f64,
+            )
+        };
+
+        let rolling_averages = sorted
+            .windows(window.max(1))
+            .map(|chunk| RollingAverage {
+                chapter: chunk[chunk.len() - 1].chapter,
+                average: chunk.iter().map(|review| review.rating as f64).sum::<f64>()
+                    / chunk.len() as f64,
+            })
+            .collect();
+
+        let max_rating = sorted.iter().map(|review| review.rating).max();
+        let min_rating = sorted.iter().map(|review| review.rating).min();
+        let chapters_rated = |rating: Option<i32>| -> Vec<i32> {
+            sorted
+                .iter()
+                .filter(|review| Some(review.rating) == rating)
+                .map(|review| review.chapter)
+                .collect()
+        };
+
+        ReviewStats {
+            count: sorted.len(),
+            average_rating,
+            histogram,
+            window: window.max(1),
+            rolling_averages,
+            best_chapters: chapters_rated(max_rating),
+            worst_chapters: chapters_rated(min_rating),
+        }
+    }
+}
+
+/// Most chapters a single batch delete may cover, summed over its ranges.
+const MAX_BATCH_CHAPTERS: i64 = 1000;
 
-    let mut cursor = match collection.find(doc! {}, None).await {
-        Ok(cursor) => cursor,
-        Err(_) => return None,
+/// Parses a chapter list like `10-20,25` into inclusive ranges.
+///
+/// Lists spanning more than [`MAX_BATCH_CHAPTERS`] chapters are rejected.
+fn parse_chapter_ranges(chapters: &str) -> Result<Vec<RangeInclusive<i32>>, String> {
+    let ranges = chapters
+        .split(',')
+        .map(str::trim)
+        .map(|part| {
+            if part.is_empty() {
+                return Err("empty chapter in list".to_string());
+            }
+
+            let parse = |value: &str| {
+                value
+                    .trim()
+                    .parse::<i32>()
+                    .map_err(|_| format!("invalid chapter `{}`", value.trim()))
+            };
+
+            match part.split_once('-') {
+                Some((start, end)) => {
+                    let (start, end) = (parse(start)?, parse(end)?);
+                    if start > end {
+                        Err(format!("invalid range `{}`: start is after end", part))
+                    } else {
+                        Ok(start..=end)
+                    }
+                }
+                None => parse(part).map(|chapter| chapter..=chapter),
+            }
+        })
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let span: i64 = ranges
+        .iter()
+        .map(|range| *range.end() as i64 - *range.start() as i64 + 1)
+        .sum();
+    if span > MAX_BATCH_CHAPTERS {
+        return Err(format!(
+            "chapter list spans {} chapters; at most {} may be deleted at once",
+            span, MAX_BATCH_CHAPTERS
+        ));
+    }
+
+    Ok(ranges)
+}
+
+#[get("/?<render>&<query..>")]
+pub async fn get_reviews(
+    db: Connection<BearoData>,
+    render: Option<Render>,
+    query: ReviewQuery,
+) -> Result<Json<Vec<Rendered<Review>>>, status::Custom<String>> {
+    let collection = db.database("bearodata").collection::<Review>("reviews");
+    let sort = match query.sort.as_deref() {
+        Some("recent") => history::recent_sort(),
+        _ => doc! { "chapter": 1 },
     };
+    let options = FindOptions::builder()
+        .sort(sort)
+        .skip(query.offset.map(|offset| offset as u64))
+        .limit(query.limit.map(|limit| limit as i64))
+        .build();
+
+    let mut cursor = collection
+        .find(query.to_filter()?, options)
+        .await
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?;
 
     let mut reviews = Vec::new();
     while let Some(result) = cursor.next().await {
         match result {
             Ok(review) => reviews.push(review),
-            Err(_) => return None,
+            Err(e) => return Err(status::Custom(Status::InternalServerError, e.to_string())),
         }
     }
 
-    Some(Json(reviews))
+    Ok(Json(Rendered::all(reviews, render)))
 }
 
-#[patch("/<chapter>", format = "json", data = "<update_data>")]
-pub async fn patch_review_by_chapter(
+/// Returns rating statistics for the reviews matching the query.
+#[get("/stats?<window>&<query..>")]
+pub async fn get_review_stats(
     db: Connection<BearoData>,
-    chapter: i32,
-    update_data: Json<UpdateReview>,
-) -> Result<Json<Review>, status::Custom<String>> {
+    window: Option<usize>,
+    query: ReviewQuery,
+) -> Result<Json<ReviewStats>, status::Custom<String>> {
+    let reviews: Vec<Review> = db
+        .database("bearodata")
+        .collection::<Review>("reviews")
+        .find(query.to_filter()?, None)
+        .await
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?
+        .try_collect()
+        .await
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?;
+
+    Ok(Json(ReviewStats::compute(&reviews, window.unwrap_or(10))))
+}
+
+/// Rejects moving a review to a chapter its work already has a review for.
+fn chapter_conflict(chapter: i32) -> status::Custom<String> {
+    status::Custom(
+        Status::Conflict,
+        format!("Review for chapter {} already found", chapter),
+    )
+}
+
+/// Validates `update` and applies it to the review matching `filter`,
+/// returning the updated review.
+///
+/// Changing the chapter to one the review's work already has a review for is
+/// rejected with `409 Conflict`.
+///
+/// Shared by the review `PATCH` routes and the `reviews patch` CLI command.
+pub async fn update_review(
+    db: &Client,
+    filter: Document,
+    update: UpdateReview,
+) -> Result<Review, status::Custom<String>> {
     let collection = db.database("bearodata").collection::<Review>("reviews");
+    let filter = trash::live(filter);
+    validate_thoughts(update.thoughts.as_deref())?;
 
-    let update_doc = match bson::to_document(&update_data.into_inner()) {
+    if let Some(new_chapter) = update.chapter {
+        let current = collection
+            .find_one(filter.clone(), None)
+            .await
+            .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?
+            .ok_or_else(|| {
+                status::Custom(Status::NotFound, "No review found for this chapter".into())
+            })?;
+
+        if new_chapter != current.chapter {
+            let conflict = collection
+                .find_one(
+                    trash::live(doc! { "work_id": current.work_id, "chapter": new_chapter }),
+                    None,
+                )
+                .await
+                .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?;
+            if conflict.is_some() {
+                return Err(chapter_conflict(new_chapter));
+            }
+        }
+    }
+
+    let mut update_doc = match bson::to_document(&update) {
         Ok(doc) => doc,
         Err(e) => {
             return Err(status::Custom(
@@ -109,20 +543,27 @@ pub async fn patch_review_by_chapter(
             ));
         }
     };
+    history::stamp_updated(&mut update_doc, history::now());
 
     match collection
         .find_one_and_update(
-            doc! { "chapter": chapter },
+            filter,
             doc! { "$set": update_doc },
-            None,
+            FindOneAndUpdateOptions::builder()
+                .return_document(ReturnDocument::After)
+                .build(),
         )
         .await
     {
-        Ok(Some(updated_review)) => Ok(Json(updated_review)),
+        Ok(Some(updated)) => {
+            events::publish(db, Event::updated(Resource::Review), &updated).await;
+            Ok(updated)
+        }
         Ok(None) => Err(status::Custom(
             Status::NotFound,
             "No review found for this chapter".into(),
         )),
+        Err(e) if is_duplicate_key(&e) => Err(chapter_conflict(update.chapter.unwrap_or_default())),
         Err(e) => Err(status::Custom(
             Status::InternalServerError,
             format!("Failed to update review: {}", e),
@@ -130,130 +571,239 @@ pub async fn patch_review_by_chapter(
     }
 }
 
+#[patch("/<chapter>?<work>", format = "json", data = "<update_data>")]
+pub async fn patch_review_by_chapter(
+    db: Connection<BearoData>,
+    user: User,
+    chapter: i32,
+    work: Option<&str>,
+    update_data: Json<UpdateReview>,
+) -> Result<Json<Review>, status::Custom<String>> {
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
+
+    update_review(
+        &db,
+        chapter_filter(chapter, work)?,
+        update_data.into_inner(),
+    )
+    .await
+    .map(Json)
+}
+
 #[patch("/<id>", format = "json", data = "<update_data>", rank = 2)]
 pub async fn patch_review_by_id(
     db: Connection<BearoData>,
+    user: User,
     id: &str,
     update_data: Json<UpdateReview>,
 ) -> Result<Json<Review>, status::Custom<String>> {
-    let collection = db.database("bearodata").collection::<Review>("reviews");
-    let oid = ObjectId::parse_str(id).expect("Failed to parse oid");
-    let update_doc = match bson::to_document(&update_data.into_inner()) {
-        Ok(doc) => doc,
-        Err(e) => {
-            return Err(status::Custom(
-                Status::BadRequest,
-                format!("Invalid update data: {}", e),
-            ));
-        }
-    };
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
 
-    match collection
-        .find_one_and_update(doc! { "_id": oid }, doc! { "$set": update_doc }, None)
+    let oid = parse_review_id(id)?;
+
+    update_review(&db, doc! { "_id": oid }, update_data.into_inner())
         .await
-    {
-        Ok(Some(updated_review)) => Ok(Json(updated_review)),
-        Ok(None) => Err(status::Custom(
-            Status::NotFound,
-            "No review found for this chapter".into(),
-        )),
-        Err(e) => Err(status::Custom(
-            Status::InternalServerError,
-            format!("Failed to update review: {}", e),
-        )),
-    }
+        .map(Json)
 }
 
-#[delete("/batch/<chapters>")]
+/// Moves the reviews of `?work=`, or the default work, for a chapter list
+/// such as `10-20,25` to the trash.
+///
+/// Requires `confirm=true`.
+#[delete("/batch/<chapters>?<work>&<confirm>")]
 pub async fn batch_delete_reviews(
     db: Connection<BearoData>,
+    user: User,
     chapters: &str,
+    work: Option<&str>,
+    confirm: Option<bool>,
 ) -> Result<status::NoContent, status::Custom<String>> {
-    let collection = db.database("bearodata").collection::<Review>("reviews");
-    let chapters: Vec<i32> = chapters
-        .split(',')
-        .map(|chapter| {
-            chapter
-                .parse::<i32>()
-                .expect("Failed to parse i32 from str")
-        })
-        .collect::<Vec<i32>>();
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
 
-    let filter = doc! { "chapter": { "$in": &chapters } };
+    let ranges =
+        parse_chapter_ranges(chapters).map_err(|e| status::Custom(Status::BadRequest, e))?;
 
-    match collection.delete_many(filter, None).await {
-        Ok(delete_result) => {
-            if delete_result.deleted_count > 0 {
-                println!(
-                    "Deleted {} reviews for chapters {:?}",
-                    delete_result.deleted_count, chapters
-                );
-            } else {
-                println!("No reviews found for chapters {:?}", chapters);
-            }
-        }
-        Err(e) => {
-            return Err(status::Custom(
-                Status::InternalServerError,
-                format!("failed to delete reviews: {}", e),
-            ));
-        }
+    let ranges_filter: Vec<Document> = ranges
+        .iter()
+        .map(|range| doc! { "chapter": { "$gte": range.start(), "$lte": range.end() } })
+        .collect();
+    let filter = doc! { "$or": ranges_filter, "work_id": resolve_work(work)? };
+    trash::guard_bulk(&filter, confirm)
+        .map_err(|e| status::Custom(Status::UnprocessableEntity, e.to_string()))?;
+
+    let reviews = trash_reviews(&db, filter).await?;
+    if reviews.is_empty() {
+        tracing::info!("no reviews found to delete");
+    } else {
+        tracing::info!(deleted = reviews.len(), "deleted reviews");
     }
 
     Ok(status::NoContent)
 }
 
-#[delete("/<chapter>")]
+#[delete("/<chapter>?<work>")]
 pub async fn delete_review(
     db: Connection<BearoData>,
+    user: User,
     chapter: i32,
+    work: Option<&str>,
 ) -> Result<status::NoContent, status::Custom<String>> {
-    let collection = db.database("bearodata").collection::<Review>("reviews");
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
 
-    match collection
-        .delete_many(doc! { "chapter": chapter }, None)
-        .await
-    {
-        Ok(delete_result) => {
-            if delete_result.deleted_count > 0 {
-                Ok(status::NoContent)
-            } else {
-                Err(status::Custom(
-                    Status::NotFound,
-                    "No review found for this chapter".into(),
-                ))
-            }
-        }
-        Err(e) => Err(status::Custom(
-            Status::InternalServerError,
-            format!("failed to delete review: {}", e),
+    match trash_review(&db, chapter_filter(chapter, work)?).await? {
+        Some(_) => Ok(status::NoContent),
+        None => Err(status::Custom(
+            Status::NotFound,
+            "No review found for this chapter".into(),
         )),
     }
 }
+/// Moves a review to the trash by id.
+///
+/// Shared by `DELETE /reviews/<id>` and the `reviews delete` CLI command.
+pub async fn remove_review(db: &Client, oid: ObjectId) -> Result<(), status::Custom<String>> {
+    if trash_reviews(db, doc! { "_id": oid }).await?.is_empty() {
+        Err(status::Custom(
+            Status::NotFound,
+            "No review found with this ID".into(),
+        ))
+    } else {
+        Ok(())
+    }
+}
+
 #[delete("/<id>", rank = 2)]
 pub async fn delete_review_by_id(
     db: Connection<BearoData>,
+    user: User,
     id: &str,
 ) -> Result<status::NoContent, status::Custom<String>> {
-    let collection = db.database("bearodata").collection::<Review>("reviews");
-    let oid = ObjectId::parse_str(id).expect("Failed to parse oid");
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
 
-    match collection.delete_many(doc! { "_id": oid }, None).await {
-        Ok(delete_result) => {
-            if delete_result.deleted_count > 0 {
-                Ok(status::NoContent)
-            } else {
-                Err(status::Custom(
-                    Status::NotFound,
-                    "No review found with this ID".into(),
-                ))
-            }
-        }
-        Err(e) => Err(status::Custom(
-            Status::InternalServerError,
-            format!("failed to delete review: {}", e),
-        )),
+    let oid = parse_review_id(id)?;
+
+    remove_review(&db, oid).await.map(|()| status::NoContent)
+}
+
+/// Lists the reviews of a work in chapter order.
+#[get("/<work_id>/reviews?<render>", rank = 2)]
+pub async fn get_work_reviews(
+    db: Connection<BearoData>,
+    work_id: &str,
+    render: Option<Render>,
+) -> Result<Json<Vec<Rendered<Review>>>, status::Custom<String>> {
+    let work_id = parse_work_id(work_id)?;
+    let options = FindOptions::builder().sort(doc! { "chapter": 1 }).build();
+
+    let reviews: Vec<Review> = db
+        .database("bearodata")
+        .collection::<Review>("reviews")
+        .find(trash::live(doc! { "work_id": work_id }), options)
+        .await
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?
+        .try_collect()
+        .await
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?;
+
+    Ok(Json(Rendered::all(reviews, render)))
+}
+
+#[get("/<work_id>/reviews/<chapter>?<render>")]
+pub async fn get_work_review(
+    db: Connection<BearoData>,
+    work_id: &str,
+    chapter: i32,
+    render: Option<Render>,
+) -> Result<Json<Rendered<Review>>, status::Custom<String>> {
+    let filter = chapter_filter(chapter, Some(work_id))?;
+
+    db.database("bearodata")
+        .collection::<Review>("reviews")
+        .find_one(filter, None)
+        .await
+        .map_err(|e| status::Custom(Status::InternalServerError, e.to_string()))?
+        .map(|review| Json(Rendered::new(review, render)))
+        .ok_or_else(|| status::Custom(Status::NotFound, "No review found for this chapter".into()))
+}
+
+#[post("/<work_id>/reviews", format = "json", data = "<review>")]
+pub async fn create_work_review(
+    db: Connection<BearoData>,
+    user: User,
+    work_id: &str,
+    review: Json<NewReview>,
+) -> Result<Json<Review>, status::Custom<String>> {
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
+
+    let work_id = parse_work_id(work_id)?;
+
+    insert_review(&db, work_id, review.into_inner())
+        .await
+        .map(Json)
+}
+
+#[patch(
+    "/<work_id>/reviews/<chapter>",
+    format = "json",
+    data = "<update_data>"
+)]
+pub async fn patch_work_review(
+    db: Connection<BearoData>,
+    user: User,
+    work_id: &str,
+    chapter: i32,
+    update_data: Json<UpdateReview>,
+) -> Result<Json<Review>, status::Custom<String>> {
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
+
+    update_review(
+        &db,
+        chapter_filter(chapter, Some(work_id))?,
+        update_data.into_inner(),
+    )
+    .await
+    .map(Json)
+}
+
+#[delete("/<work_id>/reviews/<chapter>")]
+pub async fn delete_work_review(
+    db: Connection<BearoData>,
+    user: User,
+    work_id: &str,
+    chapter: i32,
+) -> Result<status::NoContent, status::Custom<String>> {
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))?;
+
+    if trash_review(&db, chapter_filter(chapter, Some(work_id))?)
+        .await?
+        .is_none()
+    {
+        return Err(status::Custom(
+            Status::NotFound,
+            "No review found for this chapter".into(),
+        ));
     }
+
+    Ok(status::NoContent)
+}
+
+/// Routes for reviews nested under a work, mounted under `/read-watch`, `/series` and `/games`.
+pub fn work_routes() -> Vec<rocket::Route> {
+    routes![
+        get_work_reviews,
+        get_work_review,
+        create_work_review,
+        patch_work_review,
+        delete_work_review
+    ]
 }
 
 pub fn routes() -> Vec<rocket::Route> {
@@ -261,6 +811,7 @@ pub fn routes() -> Vec<rocket::Route> {
         get_review_by_chapter,
         get_review_by_oid,
         get_reviews,
+        get_review_stats,
         create_review,
         delete_review,
         delete_review_by_id,
@@ -269,3 +820,157 @@ pub fn routes() -> Vec<rocket::Route> {
         patch_review_by_id
     ]
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::{fairing::AdHoc, local::blocking::Client as LocalClient};
+    use rocket_db_pools::Database;
+
+    fn review(chapter: i32, rating: i32) -> Review {
+        Review {
+            oid: ObjectId::new(),
+            work_id: None,
+            work_kind: None,
+            chapter,
+            description: String::new(),
+            rating,
+            thoughts: String::new(),
+            created_at: None,
+            updated_at: None,
+            deleted_at: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_chapter_ranges() {
+        assert_eq!(
+            parse_chapter_ranges("10-20,25").unwrap(),
+            vec![10..=20, 25..=25]
+        );
+        assert_eq!(
+            parse_chapter_ranges(" 3 , 5 - 7 ").unwrap(),
+            vec![3..=3, 5..=7]
+        );
+
+        assert!(parse_chapter_ranges("").is_err());
+        assert!(parse_chapter_ranges("1,,2").is_err());
+        assert!(parse_chapter_ranges("abc").is_err());
+        assert!(parse_chapter_ranges("20-10").is_err());
+        assert!(parse_chapter_ranges("1-x").is_err());
+
+        assert!(parse_chapter_ranges("1-1000").is_ok());
+        assert!(parse_chapter_ranges("1-1000,1001").is_err());
+        assert!(parse_chapter_ranges("0-2147483647").is_err());
+    }
+
+    #[test]
+    fn test_chapter_filter() {
+        let work_id = ObjectId::new();
+        assert_eq!(
+            chapter_filter(3, Some(&work_id.to_hex())).unwrap(),
+            doc! { "chapter": 3, "work_id": work_id, "deleted_at": bson::Bson::Null }
+        );
+        assert_eq!(
+            chapter_filter(3, Some("nope")).unwrap_err().0,
+            Status::BadRequest
+        );
+    }
+
+    #[test]
+    fn test_review_query_rejects_inverted_range() {
+        let query = ReviewQuery {
+            from: Some(10),
+            to: Some(5),
+            ..Default::default()
+        };
+        assert!(query.to_filter().is_err());
+
+        let query = ReviewQuery {
+            from: Some(5),
+            min_rating: Some(3),
+            ..Default::default()
+        };
+        let filter = query.to_filter().unwrap();
+        assert_eq!(filter.get_document("chapter").unwrap(), &doc! { "$gte": 5 });
+        assert_eq!(filter.get_document("rating").unwrap(), &doc! { "$gte": 3 });
+    }
+
+    #[test]
+    fn test_review_stats() {
+        let reviews = vec![review(3, 2), review(1, 5), review(2, 4), review(4, 5)];
+        let stats = ReviewStats::compute(&reviews, 2);
+
+        assert_eq!(stats.count, 4);
+        assert_eq!(stats.average_rating, Some(4.0));
+        assert_eq!(stats.histogram[&5], 2);
+        assert_eq!(stats.histogram[&1], 0);
+        assert_eq!(
+            stats.rolling_averages,
+            vec![
+                RollingAverage {
+                    chapter: 2,
+                    average: 4.5
+                },
+                RollingAverage {
+                    chapter: 3,
+                    average: 3.0
+                },
+                RollingAverage {
+                    chapter: 4,
+                    average: 3.5
+                },
+            ]
+        );
+        assert_eq!(stats.best_chapters, vec![1, 4]);
+        assert_eq!(stats.worst_chapters, vec![3]);
+    }
+
+    #[test]
+    fn test_review_stats_empty() {
+        let stats = ReviewStats::compute(&[], 10);
+
+        assert_eq!(stats.count, 0);
+        assert_eq!(stats.average_rating, None);
+        assert!(stats.rolling_averages.is_empty());
+        assert!(stats.best_chapters.is_empty());
+    }
+
+    #[test]
+    fn test_numeric_get_reaches_chapter_route() {
+        let figment = rocket::Config::figment()
+            .merge(("databases.bearodata.url", "mongodb://127.0.0.1:9/bearodata"))
+            .merge(("databases.bearodata.connect_timeout", 1));
+        let rocket = rocket::custom(figment)
+            .attach(BearoData::init())
+            .attach(AdHoc::on_response("Matched route", |request, response| {
+                Box::pin(async move {
+                    if let Some(name) = request.route().and_then(|route| route.name.as_deref()) {
+                        response.set_raw_header("X-Route", name.to_string());
+                    }
+                })
+            }))
+            .mount(
+                "/reviews",
+                routes![get_review_by_oid, get_review_by_chapter],
+            );
+        let client = LocalClient::tracked(rocket).expect("valid rocket");
+        let matched = |uri: String| {
+            client
+                .get(uri)
+                .dispatch()
+                .headers()
+                .get_one("X-Route")
+                .map(str::to_string)
+        };
+
+        assert_eq!(
+            matched(format!("/reviews/5?work={}", ObjectId::new())).as_deref(),
+            Some("get_review_by_chapter")
+        );
+        assert_eq!(
+            matched(format!("/reviews/{}", ObjectId::new())).as_deref(),
+            Some("get_review_by_oid")
+        );
+    }
+}
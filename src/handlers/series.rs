@@ -0,0 +1,385 @@
+//! # Series handlers
+//!
+//! This module groups volumes of the read-watch catalog into series.
+//! Members are books referencing the series through `series_id`, ordered by
+//! `series_index`.
+
+use {
+    crate::{
+        auth::User,
+        db::BearoData,
+        events::{self, Event, Resource},
+        handlers::books::render_terms,
+        history,
+        models::{Book, Locale, LocalizedSeries, NewSeries, Series, UpdateSeries},
+        taxonomy::Taxonomy,
+        trash,
+    },
+    mongodb::bson::{self, Document, doc, oid::ObjectId},
+    rocket::{
+        Route, delete,
+        futures::TryStreamExt,
+        get,
+        http::Status,
+        patch, post, put, routes,
+        serde::{Deserialize, Serialize, json::Json},
+    },
+    rocket_db_pools::{
+        Connection,
+        mongodb::{
+            Client,
+            options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+        },
+    },
+    schemars::JsonSchema,
+    std::collections::HashMap,
+};
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct MembersPayload {
+    pub members: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[schemars(rename = "SeriesApiResponse")]
+pub struct ApiResponse {
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated: Option<i64>,
+}
+
+/// Loads the members of a series in series order, with taxonomy terms rendered.
+async fn load_members(
+    db: &Client,
+    taxonomy: &Taxonomy,
+    series_id: ObjectId,
+) -> Result<Vec<Book>, Status> {
+    let options = FindOptions::builder()
+        .sort(doc! { "series_index": 1 })
+        .build();
+
+    let mut members: Vec<Book> = db
+        .database("bearodata")
+        .collection::<Book>("books")
+        .find(trash::live(doc! { "series_id": series_id }), options)
+        .await
+        .map_err(|_| Status::InternalServerError)?
+        .try_collect()
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+
+    members
+        .iter_mut()
+        .for_each(|book| render_terms(book, taxonomy));
+
+    Ok(members)
+}
+
+/// Applies `update` to every book matching `filter`, stamping each one.
+///
+/// Publishes an `updated` event for every book that is not in the trash.
+///
+/// # Returns
+///
+/// The number of books that were changed.
+async fn update_books(db: &Client, filter: Document, mut update: Document) -> Result<u64, Status> {
+    let books = db.database("bearodata").collection::<Book>("books");
+    let mut set = update.get_document("$set").cloned().unwrap_or_default();
+    history::stamp_updated(&mut set, history::now());
+    update.insert("$set", set);
+
+    let ids: Vec<ObjectId> = books
+        .find(filter, None)
+        .await
+        .map_err(|_| Status::InternalServerError)?
+        .try_collect::<Vec<Book>>()
+        .await
+        .map_err(|_| Status::InternalServerError)?
+        .into_iter()
+        .map(|book| book.oid)
+        .collect();
+
+    let mut changed = 0;
+    for id in ids {
+        let book = books
+            .find_one_and_update(
+                doc! { "_id": id },
+                update.clone(),
+                FindOneAndUpdateOptions::builder()
+                    .return_document(ReturnDocument::After)
+                    .build(),
+            )
+            .await
+            .map_err(|_| Status::InternalServerError)?;
+        if let Some(book) = book {
+            changed += 1;
+            if book.deleted_at.is_none() {
+                events::publish(db, Event::updated(Resource::Book), &book).await;
+            }
+        }
+    }
+
+    Ok(changed)
+}
+
+#[get("/")]
+pub async fn get_all_series(
+    db: Connection<BearoData>,
+    taxonomy: &Taxonomy,
+    locale: Locale,
+) -> Result<Json<Vec<LocalizedSeries>>, Status> {
+    let all_series: Vec<Series> = db
+        .database("bearodata")
+        .collection::<Series>("series")
+        .find(doc! {}, None)
+        .await
+        .map_err(|_| Status::InternalServerError)?
+        .try_collect()
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+
+    let series_ids: Vec<ObjectId> = all_series.iter().map(|series| series.oid).collect();
+    let options = FindOptions::builder()
+        .sort(doc! { "series_index": 1 })
+        .build();
+    let books: Vec<Book> = db
+        .database("bearodata")
+        .collection::<Book>("books")
+        .find(
+            trash::live(doc! { "series_id": { "$in": series_ids } }),
+            options,
+        )
+        .await
+        .map_err(|_| Status::InternalServerError)?
+        .try_collect()
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+
+    let mut members: HashMap<ObjectId, Vec<Book>> = HashMap::new();
+    for mut book in books {
+        render_terms(&mut book, taxonomy);
+        if let Some(series_id) = book.series_id {
+            members.entry(series_id).or_default().push(book);
+        }
+    }
+
+    let results = all_series
+        .into_iter()
+        .map(|series| {
+            let members = members.remove(&series.oid).unwrap_or_default();
+            series.localize(&members, locale.0.as_deref())
+        })
+        .collect();
+
+    Ok(Json(results))
+}
+
+#[get("/<series_id>")]
+pub async fn get_series(
+    db: Connection<BearoData>,
+    taxonomy: &Taxonomy,
+    series_id: String,
+    locale: Locale,
+) -> Result<Json<LocalizedSeries>, Status> {
+    let oid = ObjectId::parse_str(&series_id).map_err(|_| Status::BadRequest)?;
+
+    let series = db
+        .database("bearodata")
+        .collection::<Series>("series")
+        .find_one(doc! { "_id": oid }, None)
+        .await
+        .map_err(|_| Status::InternalServerError)?
+        .ok_or(Status::NotFound)?;
+
+    let members = load_members(&db, taxonomy, oid).await?;
+
+    Ok(Json(series.localize(&members, locale.0.as_deref())))
+}
+
+#[post("/", format = "json", data = "<new_series>")]
+pub async fn create_series(
+    db: Connection<BearoData>,
+    user: User,
+    new_series: Json<NewSeries>,
+) -> Result<Json<Series>, Status> {
+    user.require_admin().map_err(|_| Status::Forbidden)?;
+
+    let new_series = new_series.into_inner();
+    let series = Series {
+        oid: ObjectId::new(),
+        title: new_series.title,
+        description: new_series.description,
+        cover_image: new_series.cover_image,
+    };
+
+    db.database("bearodata")
+        .collection::<Series>("series")
+        .insert_one(&series, None)
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+
+    events::publish(&db, Event::created(Resource::Series), &series).await;
+    Ok(Json(series))
+}
+
+#[patch("/<series_id>", format = "json", data = "<update_data>")]
+pub async fn patch_series(
+    db: Connection<BearoData>,
+    user: User,
+    series_id: String,
+    update_data: Json<UpdateSeries>,
+) -> Result<Json<Series>, Status> {
+    user.require_admin().map_err(|_| Status::Forbidden)?;
+
+    let collection = db.database("bearodata").collection::<Series>("series");
+    let oid = ObjectId::parse_str(&series_id).map_err(|_| Status::BadRequest)?;
+
+    let mut update_doc = Document::new();
+    let patch = update_data.into_inner();
+
+    if let Some(title) = patch.title {
+        update_doc.insert(
+            "title",
+            bson::to_bson(&title).map_err(|_| Status::InternalServerError)?,
+        );
+    }
+    if let Some(description) = patch.description {
+        update_doc.insert(
+            "description",
+            bson::to_bson(&description).map_err(|_| Status::InternalServerError)?,
+        );
+    }
+    if let Some(cover_image) = patch.cover_image {
+        update_doc.insert("cover_image", cover_image);
+    }
+
+    if !update_doc.is_empty() {
+        collection
+            .update_one(doc! { "_id": oid }, doc! { "$set": update_doc }, None)
+            .await
+            .map_err(|_| Status::InternalServerError)?;
+    }
+
+    let updated_series = collection
+        .find_one(doc! { "_id": oid }, None)
+        .await
+        .map_err(|_| Status::InternalServerError)?
+        .ok_or(Status::NotFound)?;
+
+    events::publish(&db, Event::updated(Resource::Series), &updated_series).await;
+    Ok(Json(updated_series))
+}
+
+/// Replaces the ordered member list of a series.
+///
+/// Listed books get the series' id and their 1-based position as `series_index`;
+/// books no longer listed are removed from the series.
+#[put("/<series_id>/members", format = "json", data = "<payload>")]
+pub async fn set_series_members(
+    db: Connection<BearoData>,
+    user: User,
+    taxonomy: &Taxonomy,
+    series_id: String,
+    payload: Json<MembersPayload>,
+    locale: Locale,
+) -> Result<Json<LocalizedSeries>, Status> {
+    user.require_admin().map_err(|_| Status::Forbidden)?;
+
+    let oid = ObjectId::parse_str(&series_id).map_err(|_| Status::BadRequest)?;
+    let member_ids = payload
+        .members
+        .iter()
+        .map(ObjectId::parse_str)
+        .collect::<Result<Vec<_>, _>>()
+        .map_err(|_| Status::BadRequest)?;
+
+    let series = db
+        .database("bearodata")
+        .collection::<Series>("series")
+        .find_one(doc! { "_id": oid }, None)
+        .await
+        .map_err(|_| Status::InternalServerError)?
+        .ok_or(Status::NotFound)?;
+
+    let books = db.database("bearodata").collection::<Book>("books");
+
+    let found = books
+        .count_documents(trash::live(doc! { "_id": { "$in": &member_ids } }), None)
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+    if found as usize != member_ids.len() {
+        return Err(Status::UnprocessableEntity);
+    }
+
+    update_books(
+        &db,
+        trash::live(doc! { "series_id": oid, "_id": { "$nin": &member_ids } }),
+        doc! { "$unset": { "series_id": "", "series_index": "" } },
+    )
+    .await?;
+
+    for (index, member_id) in member_ids.iter().enumerate() {
+        let series_index = (index + 1) as f64;
+        update_books(
+            &db,
+            trash::live(doc! {
+                "_id": member_id,
+                "$or": [
+                    { "series_id": { "$ne": oid } },
+                    { "series_index": { "$ne": series_index } },
+                ],
+            }),
+            doc! { "$set": { "series_id": oid, "series_index": series_index } },
+        )
+        .await?;
+    }
+
+    let members = load_members(&db, taxonomy, oid).await?;
+    let localized = series.localize(&members, locale.0.as_deref());
+
+    events::publish(&db, Event::updated(Resource::Series), &localized).await;
+    Ok(Json(localized))
+}
+
+#[delete("/<series_id>")]
+pub async fn delete_series(
+    db: Connection<BearoData>,
+    user: User,
+    series_id: String,
+) -> Result<Json<ApiResponse>, Status> {
+    user.require_admin().map_err(|_| Status::Forbidden)?;
+
+    let oid = ObjectId::parse_str(&series_id).map_err(|_| Status::BadRequest)?;
+
+    let series = db
+        .database("bearodata")
+        .collection::<Series>("series")
+        .find_one_and_delete(doc! { "_id": oid }, None)
+        .await
+        .map_err(|_| Status::InternalServerError)?
+        .ok_or(Status::NotFound)?;
+
+    let detached = update_books(
+        &db,
+        doc! { "series_id": oid },
+        doc! { "$unset": { "series_id": "", "series_index": "" } },
+    )
+    .await?;
+
+    events::publish(&db, Event::deleted(Resource::Series), &series).await;
+    Ok(Json(ApiResponse {
+        message: "series deleted".to_string(),
+        updated: Some(detached as i64),
+    }))
+}
+
+pub fn routes() -> Vec<Route> {
+    routes![
+        get_all_series,
+        get_series,
+        create_series,
+        patch_series,
+        set_series_members,
+        delete_series
+    ]
+}
@@ -0,0 +1,624 @@
+//! # Taxonomy handlers
+//!
+//! This module manages the shared genre/tag taxonomy: listing terms with
+//! usage counts, creating and editing terms, and renaming or merging terms
+//! while keeping book and game references consistent.
+
+use {
+    crate::{
+        auth::User,
+        db::{BearoData, is_duplicate_key},
+        events::{self, Event, Resource},
+        history,
+        models::{
+            Book, Game, Locale, LocalizedString, NewTaxonomyTerm, TaxonomyTerm, TermKind,
+            UpdateTaxonomyTerm,
+        },
+        taxonomy::{Taxonomy, retarget_array, retarget_list, slugify},
+        trash,
+    },
+    mongodb::bson::{self, Bson, Document, doc, oid::ObjectId},
+    rocket::{
+        Route,
+        futures::TryStreamExt,
+        get,
+        http::Status,
+        patch, post, routes,
+        serde::{Deserialize, Serialize, json::Json},
+    },
+    rocket_db_pools::{
+        Connection,
+        mongodb::{
+            Client,
+            options::{FindOneAndUpdateOptions, ReturnDocument},
+        },
+    },
+    schemars::JsonSchema,
+    std::collections::HashMap,
+};
+
+/// A taxonomy term together with its rendered label and usage counts.
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct TermSummary {
+    #[serde(flatten)]
+    pub term: TaxonomyTerm,
+    /// Label rendered in the requested locale
+    pub display: String,
+    /// Number of books referencing this term
+    pub books: usize,
+    /// Number of games referencing this term
+    pub games: usize,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct RenamePayload {
+    pub slug: String,
+}
+
+#[derive(Serialize, Deserialize, JsonSchema)]
+pub struct MergePayload {
+    pub sources: Vec<String>,
+    pub target: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[schemars(rename = "TaxonomyApiResponse")]
+pub struct ApiResponse {
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub updated: Option<i64>,
+}
+
+/// Rejects a label or aliases that already resolve to a term other than `slug`.
+///
+/// Text resolving to two terms would be canonicalized to whichever one the
+/// database happened to return last.
+fn check_texts(
+    taxonomy: &Taxonomy,
+    kind: TermKind,
+    slug: &str,
+    label: Option<&LocalizedString>,
+    aliases: &[String],
+) -> Result<(), Status> {
+    let labels = match label {
+        Some(LocalizedString::Simple(text)) => vec![text.as_str()],
+        Some(LocalizedString::Localized(map)) => map.values().map(String::as_str).collect(),
+        None => Vec::new(),
+    };
+
+    let conflict = labels
+        .into_iter()
+        .chain(aliases.iter().map(String::as_str))
+        .filter_map(|text| taxonomy.resolve(kind, text))
+        .any(|resolved| resolved != slug);
+    if conflict {
+        Err(Status::Conflict)
+    } else {
+        Ok(())
+    }
+}
+
+/// Counts the live documents in `collection` referencing each term of `kind`.
+async fn count_terms(
+    db: &Client,
+    collection: &str,
+    kind: TermKind,
+) -> Result<HashMap<String, usize>, Status> {
+    let field = format!("${}", kind.field());
+    let pipeline = [
+        doc! { "$match": trash::live(doc! {}) },
+        doc! { "$project": { "values": { "$setUnion": [{ "$ifNull": [field, []] }, []] } } },
+        doc! { "$unwind": "$values" },
+        doc! { "$match": { "values": { "$type": "string" } } },
+        doc! { "$group": { "_id": "$values", "count": { "$sum": 1 } } },
+    ];
+
+    let counts: Vec<Document> = db
+        .database("bearodata")
+        .collection::<Document>(collection)
+        .aggregate(pipeline, None)
+        .await
+        .map_err(|_| Status::InternalServerError)?
+        .try_collect()
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+
+    Ok(counts
+        .into_iter()
+        .filter_map(|count| {
+            let slug = count.get_str("_id").ok()?.to_string();
+            let count = match count.get("count")? {
+                Bson::Int32(count) => *count as usize,
+                Bson::Int64(count) => *count as usize,
+                _ => return None,
+            };
+            Some((slug, count))
+        })
+        .collect())
+}
+
+async fn count_references(
+    db: &Client,
+    kind: TermKind,
+) -> Result<(HashMap<String, usize>, HashMap<String, usize>), Status> {
+    Ok((
+        count_terms(db, "books", kind).await?,
+        count_terms(db, "games", kind).await?,
+    ))
+}
+
+/// Rewrites every book and game reference to one of `from` into `to`.
+///
+/// Trashed documents are rewritten too, so they stay consistent when restored,
+/// but only live ones publish an `updated` event.
+async fn retarget_references(
+    db: &Client,
+    kind: TermKind,
+    from: &[String],
+    to: &str,
+) -> Result<i64, Status> {
+    let field = kind.field();
+    let now = history::now();
+    let book_collection = db.database("bearodata").collection::<Book>("books");
+    let game_collection = db.database("bearodata").collection::<Game>("games");
+    let mut updated = 0;
+
+    let mut books = book_collection
+        .find(doc! {}, None)
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+    while let Some(book) = books
+        .try_next()
+        .await
+        .map_err(|_| Status::InternalServerError)?
+    {
+        let values = match kind {
+            TermKind::Genre => &book.genres,
+            TermKind::Tag => &book.tags,
+        };
+        if let Some(values) = retarget_array(values, from, to) {
+            let mut update_doc = doc! {
+                field: bson::to_bson(&values).map_err(|_| Status::InternalServerError)?,
+            };
+            history::stamp_updated(&mut update_doc, now);
+            let book = book_collection
+                .find_one_and_update(
+                    doc! { "_id": book.oid },
+                    doc! { "$set": update_doc },
+                    FindOneAndUpdateOptions::builder()
+                        .return_document(ReturnDocument::After)
+                        .build(),
+                )
+                .await
+                .map_err(|_| Status::InternalServerError)?;
+            if let Some(book) = book.filter(|book| book.deleted_at.is_none()) {
+                events::publish(db, Event::updated(Resource::Book), &book).await;
+            }
+            updated += 1;
+        }
+    }
+
+    let mut games = game_collection
+        .find(doc! {}, None)
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+    while let Some(game) = games
+        .try_next()
+        .await
+        .map_err(|_| Status::InternalServerError)?
+    {
+        let values: Vec<String> = match kind {
+            TermKind::Genre => game.genres.iter().flatten().cloned().collect(),
+            TermKind::Tag => game.tags.iter().flatten().cloned().collect(),
+        };
+        if let Some(values) = retarget_list(&values, from, to) {
+            let mut update_doc = doc! { field: values };
+            history::stamp_updated(&mut update_doc, now);
+            let game = game_collection
+                .find_one_and_update(
+                    doc! { "_id": game.oid },
+                    doc! { "$set": update_doc },
+                    FindOneAndUpdateOptions::builder()
+                        .return_document(ReturnDocument::After)
+                        .build(),
+                )
+                .await
+                .map_err(|_| Status::InternalServerError)?;
+            if let Some(game) = game.filter(|game| game.deleted_at.is_none()) {
+                events::publish(db, Event::updated(Resource::Game), &game).await;
+            }
+            updated += 1;
+        }
+    }
+
+    Ok(updated)
+}
+
+#[get("/?<kind>")]
+pub async fn get_terms(
+    db: Connection<BearoData>,
+    taxonomy: &Taxonomy,
+    kind: Option<TermKind>,
+    locale: Locale,
+) -> Result<Json<Vec<TermSummary>>, Status> {
+    let (genre_books, genre_games) = count_references(&db, TermKind::Genre).await?;
+    let (tag_books, tag_games) = count_references(&db, TermKind::Tag).await?;
+
+    let summaries = taxonomy
+        .terms(kind)
+        .into_iter()
+        .map(|term| {
+            let (books, games) = match term.kind {
+                TermKind::Genre => (&genre_books, &genre_games),
+                TermKind::Tag => (&tag_books, &tag_games),
+            };
+            TermSummary {
+                display: term.label.get_text(locale.0.as_deref()),
+                books: books.get(&term.slug).copied().unwrap_or(0),
+                games: games.get(&term.slug).copied().unwrap_or(0),
+                term: term.clone(),
+            }
+        })
+        .collect();
+
+    Ok(Json(summaries))
+}
+
+#[get("/<kind>/<slug>")]
+pub async fn get_term(
+    db: Connection<BearoData>,
+    taxonomy: &Taxonomy,
+    kind: TermKind,
+    slug: &str,
+    locale: Locale,
+) -> Result<Json<TermSummary>, Status> {
+    let term = taxonomy.get(kind, slug).ok_or(Status::NotFound)?;
+    let (books, games) = count_references(&db, kind).await?;
+
+    Ok(Json(TermSummary {
+        display: term.label.get_text(locale.0.as_deref()),
+        books: books.get(&term.slug).copied().unwrap_or(0),
+        games: games.get(&term.slug).copied().unwrap_or(0),
+        term: term.clone(),
+    }))
+}
+
+#[post("/", format = "json", data = "<new_term>")]
+pub async fn create_term(
+    db: Connection<BearoData>,
+    user: User,
+    taxonomy: &Taxonomy,
+    new_term: Json<NewTaxonomyTerm>,
+) -> Result<Json<TaxonomyTerm>, Status> {
+    user.require_admin().map_err(|_| Status::Forbidden)?;
+
+    let new_term = new_term.into_inner();
+    let slug = slugify(
+        new_term
+            .slug
+            .as_deref()
+            .unwrap_or(&new_term.label.get_text(Some("en"))),
+    );
+    if slug.is_empty() {
+        return Err(Status::UnprocessableEntity);
+    }
+
+    if taxonomy.resolve(new_term.kind, &slug).is_some() {
+        return Err(Status::Conflict);
+    }
+    let aliases = new_term.aliases.unwrap_or_default();
+    check_texts(
+        taxonomy,
+        new_term.kind,
+        &slug,
+        Some(&new_term.label),
+        &aliases,
+    )?;
+
+    let term = TaxonomyTerm {
+        oid: ObjectId::new(),
+        kind: new_term.kind,
+        slug,
+        label: new_term.label,
+        aliases,
+    };
+
+    db.database("bearodata")
+        .collection::<TaxonomyTerm>("taxonomy")
+        .insert_one(&term, None)
+        .await
+        .map_err(|e| {
+            if is_duplicate_key(&e) {
+                Status::Conflict
+            } else {
+                Status::InternalServerError
+            }
+        })?;
+
+    Ok(Json(term))
+}
+
+#[patch("/<kind>/<slug>", format = "json", data = "<update_data>")]
+pub async fn patch_term(
+    db: Connection<BearoData>,
+    user: User,
+    taxonomy: &Taxonomy,
+    kind: TermKind,
+    slug: &str,
+    update_data: Json<UpdateTaxonomyTerm>,
+) -> Result<Json<TaxonomyTerm>, Status> {
+    user.require_admin().map_err(|_| Status::Forbidden)?;
+
+    let collection = db
+        .database("bearodata")
+        .collection::<TaxonomyTerm>("taxonomy");
+
+    let mut update_doc = Document::new();
+    let patch = update_data.into_inner();
+
+    taxonomy.get(kind, slug).ok_or(Status::NotFound)?;
+    check_texts(
+        taxonomy,
+        kind,
+        slug,
+        patch.label.as_ref(),
+        patch.aliases.as_deref().unwrap_or_default(),
+    )?;
+
+    if let Some(label) = patch.label {
+        update_doc.insert(
+            "label",
+            bson::to_bson(&label).map_err(|_| Status::InternalServerError)?,
+        );
+    }
+    if let Some(aliases) = patch.aliases {
+        update_doc.insert("aliases", aliases);
+    }
+
+    let filter = doc! { "kind": bson::to_bson(&kind).map_err(|_| Status::InternalServerError)?, "slug": slug };
+
+    if !update_doc.is_empty() {
+        collection
+            .update_one(filter.clone(), doc! { "$set": update_doc }, None)
+            .await
+            .map_err(|_| Status::InternalServerError)?;
+    }
+
+    let term = collection
+        .find_one(filter, None)
+        .await
+        .map_err(|_| Status::InternalServerError)?
+        .ok_or(Status::NotFound)?;
+
+    Ok(Json(term))
+}
+
+/// Renames a term's slug, keeping the old slug as an alias and rewriting references.
+#[post("/<kind>/<slug>/rename", format = "json", data = "<payload>")]
+pub async fn rename_term(
+    db: Connection<BearoData>,
+    user: User,
+    taxonomy: &Taxonomy,
+    kind: TermKind,
+    slug: &str,
+    payload: Json<RenamePayload>,
+) -> Result<Json<TaxonomyTerm>, Status> {
+    user.require_admin().map_err(|_| Status::Forbidden)?;
+
+    let new_slug = slugify(&payload.slug);
+    if new_slug.is_empty() {
+        return Err(Status::UnprocessableEntity);
+    }
+
+    let mut term = taxonomy.get(kind, slug).cloned().ok_or(Status::NotFound)?;
+
+    if new_slug == term.slug {
+        return Ok(Json(term));
+    }
+    if taxonomy
+        .resolve(kind, &new_slug)
+        .is_some_and(|existing| existing != term.slug)
+    {
+        return Err(Status::Conflict);
+    }
+
+    term.aliases.retain(|alias| slugify(alias) != new_slug);
+    term.aliases.push(term.slug.clone());
+    let old_slug = std::mem::replace(&mut term.slug, new_slug);
+
+    db.database("bearodata")
+        .collection::<TaxonomyTerm>("taxonomy")
+        .update_one(
+            doc! { "_id": term.oid },
+            doc! { "$set": { "slug": &term.slug, "aliases": &term.aliases } },
+            None,
+        )
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+
+    retarget_references(&db, kind, &[old_slug], &term.slug).await?;
+
+    Ok(Json(term))
+}
+
+/// Merges the source terms into the target term.
+///
+/// Source slugs and aliases become aliases of the target, the source terms are
+/// removed, and every book and game reference is rewritten to the target slug.
+#[post("/<kind>/merge", format = "json", data = "<payload>")]
+pub async fn merge_terms(
+    db: Connection<BearoData>,
+    user: User,
+    taxonomy: &Taxonomy,
+    kind: TermKind,
+    payload: Json<MergePayload>,
+) -> Result<Json<TaxonomyTerm>, Status> {
+    user.require_admin().map_err(|_| Status::Forbidden)?;
+
+    let payload = payload.into_inner();
+    let mut target = taxonomy
+        .get(kind, &payload.target)
+        .cloned()
+        .ok_or(Status::NotFound)?;
+
+    let mut sources = Vec::new();
+    for slug in &payload.sources {
+        if *slug == target.slug {
+            continue;
+        }
+        let source = taxonomy.get(kind, slug).ok_or(Status::NotFound)?;
+        sources.push(source.clone());
+    }
+
+    for source in &sources {
+        for alias in std::iter::once(&source.slug).chain(source.aliases.iter()) {
+            if !target.aliases.contains(alias) {
+                target.aliases.push(alias.clone());
+            }
+        }
+    }
+
+    let collection = db
+        .database("bearodata")
+        .collection::<TaxonomyTerm>("taxonomy");
+
+    collection
+        .update_one(
+            doc! { "_id": target.oid },
+            doc! { "$set": { "aliases": &target.aliases } },
+            None,
+        )
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+
+    let source_ids: Vec<ObjectId> = sources.iter().map(|source| source.oid).collect();
+    collection
+        .delete_many(doc! { "_id": { "$in": source_ids } }, None)
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+
+    let source_slugs: Vec<String> = sources.into_iter().map(|source| source.slug).collect();
+    retarget_references(&db, kind, &source_slugs, &target.slug).await?;
+
+    Ok(Json(target))
+}
+
+/// Rewrites free-text genres and tags on every book and game to canonical slugs.
+#[post("/normalize")]
+pub async fn normalize_references(
+    db: Connection<BearoData>,
+    user: User,
+    taxonomy: &Taxonomy,
+) -> Result<Json<ApiResponse>, Status> {
+    user.require_admin().map_err(|_| Status::Forbidden)?;
+
+    let now = history::now();
+    let book_collection = db.database("bearodata").collection::<Book>("books");
+    let game_collection = db.database("bearodata").collection::<Game>("games");
+    let mut updated = 0;
+
+    let mut books = book_collection
+        .find(doc! {}, None)
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+    while let Some(book) = books
+        .try_next()
+        .await
+        .map_err(|_| Status::InternalServerError)?
+    {
+        let genres = bson::to_bson(&taxonomy.canonicalize_array(TermKind::Genre, &book.genres))
+            .map_err(|_| Status::InternalServerError)?;
+        let tags = bson::to_bson(&taxonomy.canonicalize_array(TermKind::Tag, &book.tags))
+            .map_err(|_| Status::InternalServerError)?;
+        if bson::to_bson(&book.genres).ok() == Some(genres.clone())
+            && bson::to_bson(&book.tags).ok() == Some(tags.clone())
+        {
+            continue;
+        }
+
+        let mut update_doc = doc! { "genres": genres, "tags": tags };
+        history::stamp_updated(&mut update_doc, now);
+        let Some(book) = book_collection
+            .find_one_and_update(
+                doc! { "_id": book.oid },
+                doc! { "$set": update_doc },
+                FindOneAndUpdateOptions::builder()
+                    .return_document(ReturnDocument::After)
+                    .build(),
+            )
+            .await
+            .map_err(|_| Status::InternalServerError)?
+        else {
+            continue;
+        };
+        if book.deleted_at.is_none() {
+            events::publish(&db, Event::updated(Resource::Book), &book).await;
+        }
+        updated += 1;
+    }
+
+    let mut games = game_collection
+        .find(doc! {}, None)
+        .await
+        .map_err(|_| Status::InternalServerError)?;
+    while let Some(game) = games
+        .try_next()
+        .await
+        .map_err(|_| Status::InternalServerError)?
+    {
+        let genres: Vec<String> = game.genres.iter().flatten().cloned().collect();
+        let tags: Vec<String> = game.tags.iter().flatten().cloned().collect();
+        let genres = taxonomy.canonicalize_list(TermKind::Genre, &genres);
+        let tags = taxonomy.canonicalize_list(TermKind::Tag, &tags);
+        if game
+            .genres
+            .iter()
+            .map(Option::as_ref)
+            .eq(genres.iter().map(Some))
+            && game
+                .tags
+                .iter()
+                .map(Option::as_ref)
+                .eq(tags.iter().map(Some))
+        {
+            continue;
+        }
+
+        let mut update_doc = doc! { "genres": genres, "tags": tags };
+        history::stamp_updated(&mut update_doc, now);
+        let Some(game) = game_collection
+            .find_one_and_update(
+                doc! { "_id": game.oid },
+                doc! { "$set": update_doc },
+                FindOneAndUpdateOptions::builder()
+                    .return_document(ReturnDocument::After)
+                    .build(),
+            )
+            .await
+            .map_err(|_| Status::InternalServerError)?
+        else {
+            continue;
+        };
+        if game.deleted_at.is_none() {
+            events::publish(&db, Event::updated(Resource::Game), &game).await;
+        }
+        updated += 1;
+    }
+
+    Ok(Json(ApiResponse {
+        message: "references normalized".to_string(),
+        updated: Some(updated),
+    }))
+}
+
+pub fn routes() -> Vec<Route> {
+    routes![
+        get_terms,
+        get_term,
+        create_term,
+        patch_term,
+        rename_term,
+        merge_terms,
+        normalize_references
+    ]
+}
@@ -0,0 +1,123 @@
+//! # Trash handlers
+//!
+//! Deleted books, games, projects, reviews and wplace screenshots. Every
+//! route requires an admin key. See [`crate::trash`] for how documents are
+//! trashed and purged.
+//!
+//! - `GET /trash` lists trashed documents, most recently deleted first,
+//!   optionally only those of one `?collection=`
+//! - `POST /trash/<collection>/<id>/restore` takes a document out of the trash
+
+use {
+    crate::{
+        auth::User,
+        db::BearoData,
+        errors::TrashError,
+        events::Resource,
+        models::{Book, Game, Project, Review, TrashedDocument, WplaceScreenshot},
+        trash,
+    },
+    mongodb::bson::oid::ObjectId,
+    rocket::{
+        Request, Route, get,
+        http::Status,
+        post,
+        request::{FromRequest, Outcome},
+        response::status,
+        routes,
+        serde::{Serialize, json::Json},
+    },
+    rocket_db_pools::{Connection, mongodb::Client},
+    serde::de::DeserializeOwned,
+    serde_json::Value,
+};
+
+fn error_status(e: TrashError) -> status::Custom<String> {
+    let status = match e {
+        TrashError::UnknownCollection(_) | TrashError::NotFound(_) => Status::NotFound,
+        TrashError::EmptyFilter | TrashError::Unconfirmed => Status::UnprocessableEntity,
+        TrashError::Conflict(_) | TrashError::Deserialization(_) => Status::Conflict,
+        TrashError::Serialization(_) | TrashError::Database(_) => Status::InternalServerError,
+    };
+    status::Custom(status, e.to_string())
+}
+
+fn require_admin(user: &User) -> Result<(), status::Custom<String>> {
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))
+}
+
+fn parse_id(id: &str) -> Result<ObjectId, status::Custom<String>> {
+    ObjectId::parse_str(id)
+        .map_err(|_| status::Custom(Status::BadRequest, "invalid id".to_string()))
+}
+
+/// The configured trash retention period, in days.
+pub struct Retention(i64);
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Retention {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(Retention(trash::retention_days(request.rocket().figment())))
+    }
+}
+
+async fn restore<T>(db: &Client, resource: Resource, id: ObjectId) -> Result<Value, TrashError>
+where
+    T: Serialize + DeserializeOwned + Send + Sync + Unpin,
+{
+    let document: T = trash::restore(db, resource, id).await?;
+    Ok(serde_json::to_value(document).unwrap_or(Value::Null))
+}
+
+#[get("/?<collection>&<offset>&<limit>")]
+pub async fn list_trash(
+    db: Connection<BearoData>,
+    user: User,
+    retention: Retention,
+    collection: Option<&str>,
+    offset: Option<usize>,
+    limit: Option<usize>,
+) -> Result<Json<Vec<TrashedDocument>>, status::Custom<String>> {
+    require_admin(&user)?;
+
+    let resources = match collection {
+        Some(name) => vec![trash::resource(name).map_err(error_status)?],
+        None => trash::trashable().collect(),
+    };
+
+    trash::list(&db, &resources, retention.0)
+        .await
+        .map(|trashed| Json(super::paginate(trashed, offset, limit)))
+        .map_err(error_status)
+}
+
+#[post("/<collection>/<id>/restore")]
+pub async fn restore_from_trash(
+    db: Connection<BearoData>,
+    user: User,
+    collection: &str,
+    id: &str,
+) -> Result<Json<Value>, status::Custom<String>> {
+    require_admin(&user)?;
+
+    let id = parse_id(id)?;
+    let restored = match trash::resource(collection).map_err(error_status)? {
+        resource @ Resource::Book => restore::<Book>(&db, resource, id).await,
+        resource @ Resource::Game => restore::<Game>(&db, resource, id).await,
+        resource @ Resource::Project => restore::<Project>(&db, resource, id).await,
+        resource @ Resource::Review => restore::<Review>(&db, resource, id).await,
+        resource @ Resource::Screenshot => restore::<WplaceScreenshot>(&db, resource, id).await,
+        resource => Err(TrashError::UnknownCollection(
+            resource.collection().to_string(),
+        )),
+    };
+
+    restored.map(Json).map_err(error_status)
+}
+
+pub fn routes() -> Vec<Route> {
+    routes![list_trash, restore_from_trash]
+}
@@ -0,0 +1,337 @@
+//! # Webhook handlers
+//!
+//! Registration of webhooks and their delivery log. Every route requires an
+//! admin key. See [`crate::webhooks`] for events, signing and retries.
+//!
+//! - `GET /webhooks` lists webhooks
+//! - `POST /webhooks` registers a webhook; the response is the only one
+//!   that includes its signing secret
+//! - `GET/PATCH/DELETE /webhooks/<id>` reads, updates or removes a webhook
+//! - `GET /webhooks/<id>/deliveries` is the delivery log, newest first, at
+//!   most [`MAX_PAGE`] at a time; finished deliveries are kept for
+//!   [`webhooks::RETENTION`]
+//! - `POST /webhooks/<id>/deliveries/<delivery_id>/retry` queues a delivery again
+//! - `POST /webhooks/<id>/ping` queues a `ping` delivery
+
+use {
+    crate::{
+        auth::User,
+        db::BearoData,
+        errors::WebhookError,
+        events::Event,
+        models::{DeliveryStatus, NewWebhook, UpdateWebhook, Webhook, WebhookDelivery},
+        webhooks::{self, DELIVERIES, WEBHOOKS},
+    },
+    mongodb::bson::{self, Document, doc, oid::ObjectId},
+    rocket::{
+        Route, delete,
+        futures::TryStreamExt,
+        get,
+        http::Status,
+        patch, post,
+        response::status,
+        routes,
+        serde::{Deserialize, Serialize, json::Json},
+    },
+    rocket_db_pools::{
+        Connection,
+        mongodb::{
+            Client,
+            options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+        },
+    },
+    schemars::JsonSchema,
+    serde_json::json,
+};
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[schemars(rename = "WebhookApiResponse")]
+pub struct ApiResponse {
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub deleted: Option<i64>,
+}
+
+fn error_status(e: WebhookError) -> status::Custom<String> {
+    let status = match e {
+        WebhookError::NotFound(_) => Status::NotFound,
+        WebhookError::Database(_) | WebhookError::Serialization(_) | WebhookError::Json(_) => {
+            Status::InternalServerError
+        }
+        _ => Status::UnprocessableEntity,
+    };
+    status::Custom(status, e.to_string())
+}
+
+fn require_admin(user: &User) -> Result<(), status::Custom<String>> {
+    user.require_admin()
+        .map_err(|e| status::Custom(Status::Forbidden, e.to_string()))
+}
+
+fn parse_id(id: &str) -> Result<ObjectId, status::Custom<String>> {
+    ObjectId::parse_str(id)
+        .map_err(|_| status::Custom(Status::BadRequest, format!("Invalid id: {}", id)))
+}
+
+async fn find_webhook(db: &Client, oid: ObjectId) -> Result<Webhook, WebhookError> {
+    db.database("bearodata")
+        .collection::<Webhook>(WEBHOOKS)
+        .find_one(doc! { "_id": oid }, None)
+        .await?
+        .ok_or(WebhookError::NotFound(oid))
+}
+
+#[get("/")]
+pub async fn get_webhooks(
+    db: Connection<BearoData>,
+    user: User,
+) -> Result<Json<Vec<Webhook>>, status::Custom<String>> {
+    require_admin(&user)?;
+
+    let webhooks: Vec<Webhook> = db
+        .database("bearodata")
+        .collection::<Webhook>(WEBHOOKS)
+        .find(doc! {}, None)
+        .await
+        .map_err(|e| error_status(e.into()))?
+        .try_collect()
+        .await
+        .map_err(|e| error_status(e.into()))?;
+
+    Ok(Json(
+        webhooks.into_iter().map(Webhook::without_secret).collect(),
+    ))
+}
+
+/// Registers a webhook, generating a secret unless one is given.
+#[post("/", format = "json", data = "<new_webhook>")]
+pub async fn create_webhook(
+    db: Connection<BearoData>,
+    user: User,
+    new_webhook: Json<NewWebhook>,
+) -> Result<Json<Webhook>, status::Custom<String>> {
+    require_admin(&user)?;
+
+    let new_webhook = new_webhook.into_inner();
+    webhooks::validate(&new_webhook.url, &new_webhook.events).map_err(error_status)?;
+    let secret = match new_webhook.secret {
+        Some(secret) if secret.is_empty() => return Err(error_status(WebhookError::EmptySecret)),
+        Some(secret) => secret,
+        None => webhooks::generate_secret(),
+    };
+
+    let webhook = Webhook {
+        oid: ObjectId::new(),
+        url: new_webhook.url,
+        events: new_webhook.events,
+        secret,
+        active: true,
+        description: new_webhook.description,
+        created_at: chrono::Utc::now().naive_utc(),
+    };
+
+    db.database("bearodata")
+        .collection::<Webhook>(WEBHOOKS)
+        .insert_one(&webhook, None)
+        .await
+        .map_err(|e| error_status(e.into()))?;
+
+    Ok(Json(webhook))
+}
+
+#[get("/<webhook_id>")]
+pub async fn get_webhook(
+    db: Connection<BearoData>,
+    user: User,
+    webhook_id: &str,
+) -> Result<Json<Webhook>, status::Custom<String>> {
+    require_admin(&user)?;
+
+    find_webhook(&db, parse_id(webhook_id)?)
+        .await
+        .map(|webhook| Json(webhook.without_secret()))
+        .map_err(error_status)
+}
+
+#[patch("/<webhook_id>", format = "json", data = "<update_data>")]
+pub async fn patch_webhook(
+    db: Connection<BearoData>,
+    user: User,
+    webhook_id: &str,
+    update_data: Json<UpdateWebhook>,
+) -> Result<Json<Webhook>, status::Custom<String>> {
+    require_admin(&user)?;
+
+    let oid = parse_id(webhook_id)?;
+    let update = update_data.into_inner();
+    let existing = find_webhook(&db, oid).await.map_err(error_status)?;
+    webhooks::validate(
+        update.url.as_ref().unwrap_or(&existing.url),
+        update.events.as_ref().unwrap_or(&existing.events),
+    )
+    .map_err(error_status)?;
+
+    let update_doc = bson::to_document(&update).map_err(|e| error_status(e.into()))?;
+    let update_doc: Document = update_doc
+        .into_iter()
+        .filter(|(_, value)| *value != bson::Bson::Null)
+        .collect();
+
+    db.database("bearodata")
+        .collection::<Webhook>(WEBHOOKS)
+        .find_one_and_update(
+            doc! { "_id": oid },
+            doc! { "$set": update_doc },
+            FindOneAndUpdateOptions::builder()
+                .return_document(ReturnDocument::After)
+                .build(),
+        )
+        .await
+        .map_err(|e| error_status(e.into()))?
+        .map(|webhook| Json(webhook.without_secret()))
+        .ok_or_else(|| error_status(WebhookError::NotFound(oid)))
+}
+
+/// Removes a webhook together with its delivery log.
+#[delete("/<webhook_id>")]
+pub async fn delete_webhook(
+    db: Connection<BearoData>,
+    user: User,
+    webhook_id: &str,
+) -> Result<Json<ApiResponse>, status::Custom<String>> {
+    require_admin(&user)?;
+
+    let oid = parse_id(webhook_id)?;
+    let result = db
+        .database("bearodata")
+        .collection::<Webhook>(WEBHOOKS)
+        .delete_one(doc! { "_id": oid }, None)
+        .await
+        .map_err(|e| error_status(e.into()))?;
+    if result.deleted_count == 0 {
+        return Err(error_status(WebhookError::NotFound(oid)));
+    }
+
+    let deliveries = db
+        .database("bearodata")
+        .collection::<WebhookDelivery>(DELIVERIES)
+        .delete_many(doc! { "webhook_id": oid }, None)
+        .await
+        .map_err(|e| error_status(e.into()))?;
+
+    Ok(Json(ApiResponse {
+        message: "webhook deleted".to_string(),
+        deleted: Some(deliveries.deleted_count as i64),
+    }))
+}
+
+/// Deliveries returned when no `limit` is given.
+const DEFAULT_PAGE: u32 = 50;
+/// Most deliveries returned at once.
+pub const MAX_PAGE: u32 = 200;
+
+/// Lists the deliveries of a webhook, newest first.
+///
+/// `limit` is clamped to between 1 and [`MAX_PAGE`] deliveries.
+#[get("/<webhook_id>/deliveries?<status>&<offset>&<limit>")]
+pub async fn get_deliveries(
+    db: Connection<BearoData>,
+    user: User,
+    webhook_id: &str,
+    status: Option<DeliveryStatus>,
+    offset: Option<u64>,
+    limit: Option<u32>,
+) -> Result<Json<Vec<WebhookDelivery>>, status::Custom<String>> {
+    require_admin(&user)?;
+
+    let oid = parse_id(webhook_id)?;
+    find_webhook(&db, oid).await.map_err(error_status)?;
+
+    let mut filter = doc! { "webhook_id": oid };
+    if let Some(status) = status {
+        filter.insert("status", status.as_str());
+    }
+    let options = FindOptions::builder()
+        .sort(doc! { "created_at": -1 })
+        .skip(offset)
+        .limit(i64::from(limit.unwrap_or(DEFAULT_PAGE).clamp(1, MAX_PAGE)))
+        .build();
+
+    db.database("bearodata")
+        .collection::<WebhookDelivery>(DELIVERIES)
+        .find(filter, options)
+        .await
+        .map_err(|e| error_status(e.into()))?
+        .try_collect()
+        .await
+        .map(Json)
+        .map_err(|e| error_status(e.into()))
+}
+
+/// Queues a delivery again, with a fresh set of attempts.
+#[post("/<webhook_id>/deliveries/<delivery_id>/retry")]
+pub async fn retry_delivery(
+    db: Connection<BearoData>,
+    user: User,
+    webhook_id: &str,
+    delivery_id: &str,
+) -> Result<Json<WebhookDelivery>, status::Custom<String>> {
+    require_admin(&user)?;
+
+    let webhook_id = parse_id(webhook_id)?;
+    let oid = parse_id(delivery_id)?;
+    let now = bson::to_bson(&chrono::Utc::now().naive_utc()).map_err(|e| error_status(e.into()))?;
+
+    db.database("bearodata")
+        .collection::<WebhookDelivery>(DELIVERIES)
+        .find_one_and_update(
+            doc! { "_id": oid, "webhook_id": webhook_id },
+            doc! {
+                "$set": {
+                    "status": DeliveryStatus::Pending.as_str(),
+                    "attempts": 0,
+                    "next_attempt_at": now,
+                }
+            },
+            FindOneAndUpdateOptions::builder()
+                .return_document(ReturnDocument::After)
+                .build(),
+        )
+        .await
+        .map_err(|e| error_status(e.into()))?
+        .map(Json)
+        .ok_or_else(|| error_status(WebhookError::NotFound(oid)))
+}
+
+/// Queues a `ping` delivery, regardless of the webhook's event filters.
+#[post("/<webhook_id>/ping")]
+pub async fn ping_webhook(
+    db: Connection<BearoData>,
+    user: User,
+    webhook_id: &str,
+) -> Result<Json<WebhookDelivery>, status::Custom<String>> {
+    require_admin(&user)?;
+
+    let webhook = find_webhook(&db, parse_id(webhook_id)?)
+        .await
+        .map_err(error_status)?;
+    let data = json!({ "webhook_id": webhook.oid.to_hex(), "url": &webhook.url });
+
+    webhooks::enqueue(&db, &webhook, Event::Ping, &data)
+        .await
+        .map(Json)
+        .map_err(error_status)
+}
+
+pub fn routes() -> Vec<Route> {
+    routes![
+        get_webhooks,
+        create_webhook,
+        get_webhook,
+        patch_webhook,
+        delete_webhook,
+        get_deliveries,
+        retry_delivery,
+        ping_webhook
+    ]
+}
@@ -2,13 +2,16 @@ use {
     crate::{
         auth::User,
         db::BearoData,
+        events::{self, Event, Resource},
+        history,
         models::{NewWplaceScreenshot, WplaceScreenshot},
+        trash,
     },
     mongodb::bson::{doc, oid::ObjectId},
     rocket::{
         delete, futures::StreamExt, get, http::Status, post, response::status, serde::json::Json,
     },
-    rocket_db_pools::Connection,
+    rocket_db_pools::{Connection, mongodb::options::FindOptions},
 };
 
 #[post("/", data = "<screenshot>", format = "json")]
@@ -22,18 +25,20 @@ pub async fn create_screenshot(
         .database("bearodata")
         .collection::<WplaceScreenshot>("wplace_screenshots");
 
+    let now = history::now();
     let new_screenshot = WplaceScreenshot {
         oid: ObjectId::new(),
         cover_image: screenshot.cover_image.clone(),
         alt: screenshot.alt.clone(),
+        created_at: Some(now),
+        updated_at: Some(now),
+        deleted_at: None,
     };
 
     match collection.insert_one(new_screenshot.clone(), None).await {
         Ok(_) => {
-            println!(
-                "Created wplace screenshot with data {:?}",
-                new_screenshot.clone()
-            );
+            tracing::info!(screenshot_id = %new_screenshot.oid, "created wplace screenshot");
+            events::publish(&db, Event::created(Resource::Screenshot), &new_screenshot).await;
             Ok(Json(new_screenshot))
         }
         Err(e) => Err(status::Custom(
@@ -54,22 +59,29 @@ pub async fn get_screenshot_by_id(
     let collection = db
         .database("bearodata")
         .collection::<WplaceScreenshot>("wplace_screenshots");
-    let oid = ObjectId::parse_str(id).expect("Failed to parse oid");
+    let oid = ObjectId::parse_str(id).ok()?;
     let found_screenshot = collection
-        .find_one(doc! { "_id": oid }, None)
+        .find_one(trash::live(doc! { "_id": oid }), None)
         .await
-        .unwrap();
+        .ok()?;
 
-    Some(Json(found_screenshot.unwrap()))
+    found_screenshot.map(Json)
 }
 
-#[get("/")]
-pub async fn get_screenshots(db: Connection<BearoData>) -> Option<Json<Vec<WplaceScreenshot>>> {
+/// Lists screenshots; `sort=recent` puts the most recently created first.
+#[get("/?<sort>")]
+pub async fn get_screenshots(
+    db: Connection<BearoData>,
+    sort: Option<&str>,
+) -> Option<Json<Vec<WplaceScreenshot>>> {
     let collection = db
         .database("bearodata")
         .collection::<WplaceScreenshot>("wplace_screenshots");
+    let options = FindOptions::builder()
+        .sort((sort == Some("recent")).then(history::recent_sort))
+        .build();
 
-    let mut cursor = match collection.find(doc! {}, None).await {
+    let mut cursor = match collection.find(trash::live(doc! {}), options).await {
         Ok(cursor) => cursor,
         Err(_) => return None,
     };
@@ -90,22 +102,15 @@ pub async fn delete_screenshot(
     db: Connection<BearoData>,
     id: &str,
 ) -> Result<status::NoContent, status::Custom<String>> {
-    let collection = db
-        .database("bearodata")
-        .collection::<WplaceScreenshot>("wplace_screenshots");
-
     if let Ok(ss_id) = ObjectId::parse_str(id) {
-        match collection.delete_many(doc! { "_id": ss_id }, None).await {
-            Ok(delete_result) => {
-                if delete_result.deleted_count > 0 {
-                    Ok(status::NoContent)
-                } else {
-                    Err(status::Custom(
-                        Status::NotFound,
-                        format!("No screenshot found with the id {}", id),
-                    ))
-                }
-            }
+        match trash::trash::<WplaceScreenshot>(&db, Resource::Screenshot, doc! { "_id": ss_id })
+            .await
+        {
+            Ok(trashed) if !trashed.is_empty() => Ok(status::NoContent),
+            Ok(_) => Err(status::Custom(
+                Status::NotFound,
+                format!("No screenshot found with the id {}", id),
+            )),
             Err(e) => Err(status::Custom(
                 Status::InternalServerError,
                 format!("failed to delete screenshot: {}", e),
@@ -0,0 +1,318 @@
+//! # Document history
+//!
+//! Books, games, projects, reviews and wplace screenshots carry `created_at`
+//! and `updated_at`. Write paths set them with [`stamp_created`] and
+//! [`stamp_updated`]; documents written before the fields existed are
+//! back-filled by the `timestamps` migration.
+//!
+//! Every [published](crate::events::publish) change of those documents is
+//! also recorded in the `revisions` collection as a numbered [`Revision`]:
+//! a snapshot of the document and the fields that changed since the previous
+//! revision. `GET /…/<id>/history` lists the revisions and
+//! `POST /…/<id>/history/<revision>/revert` writes a snapshot back with
+//! [`revert`], itself recorded as a new revision. The `timestamps` migration
+//! also creates the index keeping revision numbers unique per document.
+
+use {
+    crate::{
+        db::is_duplicate_key,
+        errors::RevisionError,
+        events::{self, Action, Event, Resource},
+        models::{Revision, RevisionAction},
+    },
+    chrono::NaiveDateTime,
+    mongodb::bson::{self, Bson, Document, doc, oid::ObjectId},
+    rocket::{futures::TryStreamExt, serde::Serialize},
+    rocket_db_pools::mongodb::{
+        Client,
+        options::{FindOneOptions, FindOptions, ReplaceOptions},
+    },
+    serde::de::DeserializeOwned,
+};
+
+/// Collection holding document revisions.
+pub const REVISIONS: &str = "revisions";
+
+/// Name of the unique `(collection, document_id, number)` revision index.
+pub const REVISION_INDEX: &str = "revision_number_unique";
+
+/// Attempts at numbering a revision before giving up on concurrent writes.
+const RECORD_ATTEMPTS: usize = 5;
+
+/// Revisions listed per page when no `limit` is given.
+const DEFAULT_PAGE: u32 = 50;
+
+/// Most revisions listed per page.
+pub const MAX_PAGE: u32 = 200;
+
+/// Fields ignored when listing what changed between revisions.
+const UNTRACKED_FIELDS: [&str; 1] = ["updated_at"];
+
+pub fn now() -> NaiveDateTime {
+    chrono::Utc::now().naive_utc()
+}
+
+/// A timestamp as stored by the models' `created_at`/`updated_at` fields.
+fn timestamp(time: NaiveDateTime) -> Bson {
+    bson::to_bson(&time).expect("timestamps serialize to BSON")
+}
+
+/// Sets `created_at` and `updated_at` on a document about to be inserted.
+pub fn stamp_created(document: &mut Document, time: NaiveDateTime) {
+    document.insert("created_at", timestamp(time));
+    document.insert("updated_at", timestamp(time));
+}
+
+/// Sets `updated_at` on a document or a `$set` update.
+pub fn stamp_updated(document: &mut Document, time: NaiveDateTime) {
+    document.insert("updated_at", timestamp(time));
+}
+
+/// Sort order of `sort=recent`: most recently created first.
+pub fn recent_sort() -> Document {
+    doc! { "created_at": -1, "_id": -1 }
+}
+
+/// The collection of a resource whose changes are kept as revisions.
+pub fn tracked_collection(resource: Resource) -> Option<&'static str> {
+    match resource {
+        Resource::Book => Some("books"),
+        Resource::Game => Some("games"),
+        Resource::Project => Some("projects"),
+        Resource::Review => Some("reviews"),
+        Resource::Screenshot => Some("wplace_screenshots"),
+        Resource::Series => None,
+    }
+}
+
+/// Top-level fields that differ between two snapshots, in document order.
+///
+/// Every field counts as changed when there is no previous snapshot.
+pub fn changed_fields(previous: Option<&Document>, current: &Document) -> Vec<String> {
+    let removed = previous
+        .into_iter()
+        .flat_map(|previous| previous.keys())
+        .filter(|key| !current.contains_key(key.as_str()));
+
+    current
+        .iter()
+        .filter(|(key, value)| {
+            previous.and_then(|previous| previous.get(key.as_str())) != Some(value)
+        })
+        .map(|(key, _)| key)
+        .chain(removed)
+        .filter(|key| key.as_str() != "_id" && !UNTRACKED_FIELDS.contains(&key.as_str()))
+        .cloned()
+        .collect()
+}
+
+/// Records a revision of the document published with `event`.
+///
+/// Revisions are numbered after the latest one; when a concurrent write takes
+/// the same number first, the unique [`REVISION_INDEX`] rejects the insert and
+/// the revision is numbered again.
+///
+/// Returns `None` for events of untracked resources.
+pub async fn record<T: Serialize>(
+    db: &Client,
+    event: Event,
+    data: &T,
+    reverted_to: Option<u32>,
+) -> Result<Option<Revision>, RevisionError> {
+    let Event::Change(resource, action) = event else {
+        return Ok(None);
+    };
+    let Some(collection) = tracked_collection(resource) else {
+        return Ok(None);
+    };
+
+    let document = bson::to_document(data)?;
+    let Ok(document_id) = document.get_object_id("_id") else {
+        return Ok(None);
+    };
+
+    let revisions = db.database("bearodata").collection::<Revision>(REVISIONS);
+    let mut attempt = 1;
+    loop {
+        let previous = revisions
+            .find_one(
+                doc! { "collection": collection, "document_id": document_id },
+                FindOneOptions::builder()
+                    .sort(doc! { "number": -1 })
+                    .build(),
+            )
+            .await?;
+
+        let revision = Revision {
+            oid: ObjectId::new(),
+            collection: collection.to_string(),
+            document_id,
+            number: previous.as_ref().map_or(1, |previous| previous.number + 1),
+            action: match (action, reverted_to) {
+                (_, Some(_)) => RevisionAction::Reverted,
+                (Action::Created, None) => RevisionAction::Created,
+                (Action::Updated, None) => RevisionAction::Updated,
+                (Action::Deleted, None) => RevisionAction::Deleted,
+            },
+            reverted_to,
+            changed: match action {
+                Action::Deleted => Vec::new(),
+                _ => changed_fields(
+                    previous.as_ref().map(|previous| &previous.document),
+                    &document,
+                ),
+            },
+            document: document.clone(),
+            recorded_at: now(),
+        };
+        match revisions.insert_one(&revision, None).await {
+            Ok(_) => return Ok(Some(revision)),
+            Err(e) if is_duplicate_key(&e) && attempt < RECORD_ATTEMPTS => attempt += 1,
+            Err(e) => return Err(e.into()),
+        }
+    }
+}
+
+/// Lists the revisions of a document, newest first.
+///
+/// `limit` is clamped to between 1 and [`MAX_PAGE`] revisions.
+pub async fn history(
+    db: &Client,
+    collection: &str,
+    document_id: ObjectId,
+    offset: Option<u64>,
+    limit: Option<u32>,
+) -> Result<Vec<Revision>, RevisionError> {
+    let options = FindOptions::builder()
+        .sort(doc! { "number": -1 })
+        .skip(offset)
+        .limit(i64::from(limit.unwrap_or(DEFAULT_PAGE).clamp(1, MAX_PAGE)))
+        .build();
+
+    Ok(db
+        .database("bearodata")
+        .collection::<Revision>(REVISIONS)
+        .find(
+            doc! { "collection": collection, "document_id": document_id },
+            options,
+        )
+        .await?
+        .try_collect()
+        .await?)
+}
+
+/// Writes revision `number` of a document back, restoring it if it was deleted
+/// or is in the trash.
+///
+/// The document keeps its original `created_at` and gets a fresh `updated_at`.
+/// The snapshot must still deserialize into `T`, the current model.
+pub async fn revert<T>(
+    db: &Client,
+    resource: Resource,
+    document_id: ObjectId,
+    number: u32,
+) -> Result<T, RevisionError>
+where
+    T: Serialize + DeserializeOwned + Send + Sync + Unpin,
+{
+    let not_found = RevisionError::NotFound {
+        document_id,
+        number,
+    };
+    let Some(collection) = tracked_collection(resource) else {
+        return Err(not_found);
+    };
+
+    let revision = db
+        .database("bearodata")
+        .collection::<Revision>(REVISIONS)
+        .find_one(
+            doc! { "collection": collection, "document_id": document_id, "number": number },
+            None,
+        )
+        .await?
+        .ok_or(not_found)?;
+
+    let documents = db.database("bearodata").collection::<Document>(collection);
+    let current = documents
+        .find_one(doc! { "_id": document_id }, None)
+        .await?;
+
+    let mut document = revision.document;
+    document.insert("_id", document_id);
+    document.insert("deleted_at", Bson::Null);
+    if let Some(created_at) = current
+        .as_ref()
+        .and_then(|current| current.get("created_at"))
+    {
+        document.insert("created_at", created_at.clone());
+    }
+    stamp_updated(&mut document, now());
+    let restored: T = bson::from_document(document.clone())?;
+
+    documents
+        .replace_one(
+            doc! { "_id": document_id },
+            document,
+            ReplaceOptions::builder().upsert(true).build(),
+        )
+        .await?;
+
+    let trashed = current
+        .as_ref()
+        .is_some_and(|current| !matches!(current.get("deleted_at"), None | Some(Bson::Null)));
+    let event = match current {
+        Some(_) if !trashed => Event::updated(resource),
+        _ => Event::created(resource),
+    };
+    events::publish_revert(db, event, &restored, number).await;
+
+    Ok(restored)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_changed_fields() {
+        let first = doc! { "_id": 1, "title": "Dune", "rating": 4, "updated_at": "a" };
+        assert_eq!(changed_fields(None, &first), ["title", "rating"]);
+
+        let second =
+            doc! { "_id": 1, "title": "Dune", "rating": 5, "color": "red", "updated_at": "b" };
+        assert_eq!(changed_fields(Some(&first), &second), ["rating", "color"]);
+
+        let third = doc! { "_id": 1, "title": "Dune Messiah", "updated_at": "c" };
+        assert_eq!(
+            changed_fields(Some(&second), &third),
+            ["title", "rating", "color"]
+        );
+        assert!(changed_fields(Some(&third), &third).is_empty());
+    }
+
+    #[test]
+    fn test_stamps() {
+        let time = chrono::NaiveDate::from_ymd_opt(2025, 3, 14)
+            .unwrap()
+            .and_hms_opt(9, 26, 53)
+            .unwrap();
+
+        let mut document = doc! { "title": "Dune" };
+        stamp_created(&mut document, time);
+        assert_eq!(document.get_str("created_at"), Ok("2025-03-14T09:26:53"));
+        assert_eq!(document.get("created_at"), document.get("updated_at"));
+
+        // Stamps read back into the models' fields.
+        #[derive(serde::Deserialize)]
+        struct Stamped {
+            created_at: Option<NaiveDateTime>,
+        }
+        let stamped: Stamped = bson::from_document(document).unwrap();
+        assert_eq!(stamped.created_at, Some(time));
+
+        let mut update = doc! { "rating": 5 };
+        stamp_updated(&mut update, time);
+        assert!(update.contains_key("updated_at") && !update.contains_key("created_at"));
+    }
+}
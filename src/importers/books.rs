@@ -0,0 +1,677 @@
+//! # Book importer
+//!
+//! Supported formats:
+//!
+//! - `goodreads`: Goodreads "Export Library" CSV
+//! - `storygraph`: StoryGraph "Export your data" CSV
+//!
+//! Shelves map to `status`, star ratings to `rating`, dates read to
+//! `progress` and review text to `my_thoughts`.
+
+use {
+    super::{
+        CsvRow, CsvTable, FieldChange, ImportReport, PlannedUpdate, SkippedRecord, normalize_key,
+        same_creator,
+    },
+    crate::{
+        errors::ImportError,
+        events::Resource,
+        markdown,
+        models::{
+            Book, LocalizedString, LocalizedStringArray, MediaDetails, MediaType, NewBook,
+            ProgressUnit, ReadingProgress, ReadingStatus, TermKind,
+        },
+        taxonomy::Taxonomy,
+        trash,
+    },
+    chrono::{NaiveDate, NaiveDateTime},
+    mongodb::bson::doc,
+    rocket::{
+        FromFormField,
+        futures::TryStreamExt,
+        serde::{Deserialize, Serialize},
+    },
+    rocket_db_pools::mongodb::Client,
+    schemars::JsonSchema,
+    serde_json::json,
+    std::{collections::HashMap, str::FromStr},
+};
+
+/// Shelves that only encode the reading status.
+const STATUS_SHELVES: [&str; 4] = ["read", "currently-reading", "to-read", "did-not-finish"];
+
+/// A supported book export format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromFormField)]
+pub enum BookExportFormat {
+    #[field(value = "goodreads")]
+    Goodreads,
+    #[field(value = "storygraph")]
+    StoryGraph,
+}
+
+impl BookExportFormat {
+    /// Every format name accepted on the command line.
+    pub const NAMES: [&'static str; 2] = ["goodreads", "storygraph"];
+}
+
+impl FromStr for BookExportFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "goodreads" => Ok(BookExportFormat::Goodreads),
+            "storygraph" => Ok(BookExportFormat::StoryGraph),
+            _ => Err(format!("unknown book export format `{}`", s)),
+        }
+    }
+}
+
+/// Books read from an export file.
+#[derive(Debug, Default)]
+pub struct ParsedBooks {
+    pub books: Vec<NewBook>,
+    pub skipped: Vec<SkippedRecord>,
+    pub warnings: Vec<SkippedRecord>,
+}
+
+/// What importing a book export would do.
+#[derive(Debug, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct BookImportPlan {
+    /// Books not in the catalog yet
+    pub create: Vec<NewBook>,
+    /// Catalog books whose rating, status, progress or thoughts changed
+    pub update: Vec<PlannedUpdate>,
+    /// Titles already in the catalog with nothing to change
+    pub unchanged: Vec<String>,
+    /// Records that could not be imported
+    pub skipped: Vec<SkippedRecord>,
+    /// Records imported with some data left out
+    pub warnings: Vec<SkippedRecord>,
+}
+
+/// Removes a trailing series marker such as `(The Lord of the Rings, #1)`.
+fn strip_series_suffix(title: &str) -> &str {
+    let title = title.trim();
+    match title.rfind('(') {
+        Some(start) if title.ends_with(')') && title[start..].contains('#') => {
+            title[..start].trim_end()
+        }
+        _ => title,
+    }
+}
+
+fn texts(value: &LocalizedString) -> Vec<&str> {
+    match value {
+        LocalizedString::Simple(text) => vec![text.as_str()],
+        LocalizedString::Localized(map) => map.values().map(String::as_str).collect(),
+    }
+}
+
+/// Whether an imported title/author pair names the given book in any locale.
+fn matches_book(book: &Book, title: &str, author: &str) -> bool {
+    let key = normalize_key(strip_series_suffix(title));
+
+    texts(&book.title)
+        .iter()
+        .any(|text| normalize_key(strip_series_suffix(text)) == key)
+        && texts(&book.author)
+            .iter()
+            .any(|text| same_creator(text, author))
+}
+
+fn parse_date(text: &str) -> Option<NaiveDateTime> {
+    ["%Y/%m/%d", "%Y-%m-%d"]
+        .iter()
+        .find_map(|format| NaiveDate::parse_from_str(text.trim(), format).ok())
+        .and_then(|date| date.and_hms_opt(0, 0, 0))
+}
+
+/// Strips Excel's `="..."` wrapping from Goodreads ISBN cells.
+fn clean_isbn(text: &str) -> Option<String> {
+    let isbn = text.trim_start_matches('=').trim_matches('"').trim();
+    (!isbn.is_empty()).then(|| isbn.to_string())
+}
+
+fn status_for_shelf(shelf: &str) -> &'static str {
+    match shelf.trim() {
+        "read" => ReadingStatus::Completed.as_str(),
+        "currently-reading" => ReadingStatus::Reading.as_str(),
+        "did-not-finish" => ReadingStatus::Dropped.as_str(),
+        _ => ReadingStatus::Planned.as_str(),
+    }
+}
+
+fn new_book(title: &str, author: &str) -> NewBook {
+    NewBook {
+        title: LocalizedString::Simple(strip_series_suffix(title).to_string()),
+        author: LocalizedString::Simple(author.trim().to_string()),
+        genres: LocalizedStringArray::Simple(vec![]),
+        tags: LocalizedStringArray::Simple(vec![]),
+        rating: 0,
+        status: LocalizedString::Simple(ReadingStatus::Planned.as_str().to_string()),
+        description: LocalizedString::default(),
+        my_thoughts: LocalizedString::default(),
+        links: None,
+        cover_image: String::new(),
+        explicit: false,
+        color: None,
+        progress: None,
+        media_type: MediaType::Book,
+        details: MediaDetails::default(),
+        series_id: None,
+        series_index: None,
+    }
+}
+
+/// Fields shared by both export formats, read from one row.
+struct ExportRow {
+    title: String,
+    author: String,
+    shelf: String,
+    rating: i32,
+    review: String,
+    tags: Vec<String>,
+    started_at: Option<NaiveDateTime>,
+    finished_at: Option<NaiveDateTime>,
+    pages: Option<i32>,
+    read_count: i32,
+    links: HashMap<String, String>,
+}
+
+fn goodreads_rows(table: &CsvTable) -> Result<Vec<(usize, Option<ExportRow>)>, ImportError> {
+    let title = table.require(&["title"])?;
+    let author = table.column(&["author"]);
+    let book_id = table.column(&["book id"]);
+    let isbn = table.column(&["isbn13", "isbn"]);
+    let rating = table.column(&["my rating"]);
+    let pages = table.column(&["number of pages"]);
+    let date_read = table.column(&["date read"]);
+    let shelves = table.column(&["bookshelves"]);
+    let shelf = table.column(&["exclusive shelf"]);
+    let review = table.column(&["my review"]);
+    let read_count = table.column(&["read count"]);
+
+    Ok(table
+        .rows()
+        .map(|(line, row)| {
+            let parsed = row.get(Some(title)).map(|title| {
+                let mut links = HashMap::new();
+                if let Some(id) = row.get(book_id) {
+                    links.insert(
+                        "goodreads".to_string(),
+                        format!("https://www.goodreads.com/book/show/{}", id),
+                    );
+                }
+                if let Some(isbn) = row.get(isbn).and_then(clean_isbn) {
+                    links.insert("isbn".to_string(), isbn);
+                }
+
+                ExportRow {
+                    title: title.to_string(),
+                    author: row.get(author).unwrap_or_default().to_string(),
+                    shelf: row.get(shelf).unwrap_or("to-read").to_string(),
+                    rating: parse_number(&row, rating).round() as i32,
+                    review: row.get(review).unwrap_or_default().to_string(),
+                    tags: row
+                        .get(shelves)
+                        .map(|shelves| {
+                            shelves
+                                .split(',')
+                                .map(str::trim)
+                                .filter(|shelf| {
+                                    !shelf.is_empty() && !STATUS_SHELVES.contains(shelf)
+                                })
+                                .map(str::to_string)
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                    started_at: None,
+                    finished_at: row.get(date_read).and_then(parse_date),
+                    pages: row.get(pages).and_then(|pages| pages.parse().ok()),
+                    read_count: parse_number(&row, read_count) as i32,
+                    links,
+                }
+            });
+            (line, parsed)
+        })
+        .collect())
+}
+
+fn storygraph_rows(table: &CsvTable) -> Result<Vec<(usize, Option<ExportRow>)>, ImportError> {
+    let title = table.require(&["title"])?;
+    let authors = table.column(&["authors", "author"]);
+    let isbn = table.column(&["isbn/uid", "isbn"]);
+    let status = table.column(&["read status"]);
+    let last_read = table.column(&["last date read"]);
+    let dates_read = table.column(&["dates read"]);
+    let read_count = table.column(&["read count"]);
+    let rating = table.column(&["star rating"]);
+    let review = table.column(&["review"]);
+    let tags = table.column(&["tags"]);
+
+    Ok(table
+        .rows()
+        .map(|(line, row)| {
+            let parsed = row.get(Some(title)).map(|title| {
+                let last_range = row
+                    .get(dates_read)
+                    .and_then(|ranges| ranges.split(',').next_back())
+                    .map(|range| match range.split_once('-') {
+                        Some((start, end)) if start.contains('/') => {
+                            (parse_date(start), parse_date(end))
+                        }
+                        _ => (None, parse_date(range)),
+                    });
+                let (started_at, finished_at) = match last_range {
+                    Some((started_at, finished_at)) => (
+                        started_at,
+                        finished_at.or_else(|| row.get(last_read).and_then(parse_date)),
+                    ),
+                    None => (None, row.get(last_read).and_then(parse_date)),
+                };
+
+                let mut links = HashMap::new();
+                if let Some(isbn) = row.get(isbn).and_then(clean_isbn) {
+                    links.insert("isbn".to_string(), isbn);
+                }
+
+                ExportRow {
+                    title: title.to_string(),
+                    author: row.get(authors).unwrap_or_default().to_string(),
+                    shelf: row.get(status).unwrap_or("to-read").to_string(),
+                    rating: parse_number(&row, rating).round() as i32,
+                    review: row.get(review).unwrap_or_default().to_string(),
+                    tags: row
+                        .get(tags)
+                        .map(|tags| {
+                            tags.split(',')
+                                .map(str::trim)
+                                .filter(|tag| !tag.is_empty())
+                                .map(str::to_string)
+                                .collect()
+                        })
+                        .unwrap_or_default(),
+                    started_at,
+                    finished_at,
+                    pages: None,
+                    read_count: parse_number(&row, read_count) as i32,
+                    links,
+                }
+            });
+            (line, parsed)
+        })
+        .collect())
+}
+
+fn parse_number(row: &CsvRow<'_>, column: Option<usize>) -> f64 {
+    row.get(column)
+        .and_then(|value| value.parse::<f64>().ok())
+        .unwrap_or_default()
+}
+
+fn to_new_book(row: ExportRow, line: usize, warnings: &mut Vec<SkippedRecord>) -> NewBook {
+    let mut book = new_book(&row.title, &row.author);
+    let status = status_for_shelf(&row.shelf);
+
+    book.status = LocalizedString::Simple(status.to_string());
+    book.rating = row.rating.clamp(0, 5);
+    book.tags = LocalizedStringArray::Simple(row.tags);
+    if !row.links.is_empty() {
+        book.links = Some(row.links);
+    }
+
+    let review = row
+        .review
+        .replace("<br/>", "\n")
+        .replace("<br />", "\n")
+        .replace("<br>", "\n");
+    match markdown::validate(&review) {
+        Ok(()) => book.my_thoughts = LocalizedString::Simple(review.trim().to_string()),
+        Err(e) => warnings.push(SkippedRecord {
+            source: format!("line {}", line),
+            reason: format!("review left out: {}", e),
+        }),
+    }
+
+    if row.started_at.is_some() || row.finished_at.is_some() {
+        let finished = status == ReadingStatus::Completed.as_str();
+        book.progress = Some(ReadingProgress {
+            current: match (finished, row.pages) {
+                (true, Some(pages)) => pages,
+                _ => 0,
+            },
+            total: row.pages,
+            unit: ProgressUnit::Page,
+            started_at: row.started_at,
+            finished_at: row.finished_at.filter(|_| finished),
+            reread_count: (row.read_count - 1).max(0),
+        });
+    }
+
+    book
+}
+
+/// Parses an export file into books, dropping repeated titles.
+pub fn parse_export(format: BookExportFormat, input: &str) -> Result<ParsedBooks, ImportError> {
+    let table = CsvTable::parse(input)?;
+    let rows = match format {
+        BookExportFormat::Goodreads => goodreads_rows(&table)?,
+        BookExportFormat::StoryGraph => storygraph_rows(&table)?,
+    };
+
+    let mut parsed = ParsedBooks::default();
+    let mut seen: Vec<(String, String, usize)> = Vec::new();
+
+    for (line, row) in rows {
+        let Some(row) = row else {
+            parsed.skipped.push(SkippedRecord {
+                source: format!("line {}", line),
+                reason: "missing title".to_string(),
+            });
+            continue;
+        };
+
+        let key = normalize_key(strip_series_suffix(&row.title));
+        if let Some((_, _, first)) = seen
+            .iter()
+            .find(|(title, author, _)| *title == key && same_creator(author, &row.author))
+        {
+            parsed.skipped.push(SkippedRecord {
+                source: format!("line {}", line),
+                reason: format!("duplicate of line {}", first),
+            });
+            continue;
+        }
+        seen.push((key, row.author.clone(), line));
+
+        parsed
+            .books
+            .push(to_new_book(row, line, &mut parsed.warnings));
+    }
+
+    Ok(parsed)
+}
+
+/// Lists the fields of `current` an imported book would change.
+fn changes(current: &Book, imported: &NewBook) -> Vec<FieldChange> {
+    let mut changes = Vec::new();
+
+    if imported.rating > 0 && imported.rating != current.rating {
+        changes.push(FieldChange {
+            field: "rating".into(),
+            from: json!(current.rating),
+            to: json!(imported.rating),
+        });
+    }
+
+    if let (LocalizedString::Simple(from), LocalizedString::Simple(to)) =
+        (&current.status, &imported.status)
+        && !from.eq_ignore_ascii_case(to)
+    {
+        changes.push(FieldChange {
+            field: "status".into(),
+            from: json!(from),
+            to: json!(to),
+        });
+    }
+
+    if imported.progress.is_some() && imported.progress != current.progress {
+        changes.push(FieldChange {
+            field: "progress".into(),
+            from: json!(current.progress),
+            to: json!(imported.progress),
+        });
+    }
+
+    if let (LocalizedString::Simple(from), LocalizedString::Simple(to)) =
+        (&current.my_thoughts, &imported.my_thoughts)
+        && !to.is_empty()
+        && from != to
+    {
+        changes.push(FieldChange {
+            field: "my_thoughts".into(),
+            from: json!(from),
+            to: json!(to),
+        });
+    }
+
+    changes
+}
+
+/// Compares parsed books against the catalog.
+///
+/// Books match on title and author in any locale, ignoring series markers.
+/// Matches are left alone unless `update_existing` is set, in which case
+/// rating, status, progress and thoughts are brought in line with the export.
+/// Re-importing the same file therefore never creates duplicates, and a
+/// second updating run finds nothing to change.
+pub fn plan_import(
+    parsed: ParsedBooks,
+    existing: &[Book],
+    update_existing: bool,
+) -> BookImportPlan {
+    let mut plan = BookImportPlan {
+        skipped: parsed.skipped,
+        warnings: parsed.warnings,
+        ..Default::default()
+    };
+
+    for book in parsed.books {
+        let title = book.title.get_text(None);
+        let author = book.author.get_text(None);
+
+        let Some(current) = existing
+            .iter()
+            .find(|current| matches_book(current, &title, &author))
+        else {
+            plan.create.push(book);
+            continue;
+        };
+
+        let changes = if update_existing {
+            changes(current, &book)
+        } else {
+            vec![]
+        };
+
+        if changes.is_empty() {
+            plan.unchanged.push(current.title.get_text(None));
+        } else {
+            plan.update.push(PlannedUpdate {
+                id: current.oid,
+                title: current.title.get_text(None),
+                changes,
+            });
+        }
+    }
+
+    plan
+}
+
+/// Imports a book export, writing to the database only when `apply` is set.
+pub async fn import_book_export(
+    db: &Client,
+    format: BookExportFormat,
+    input: &str,
+    update_existing: bool,
+    apply: bool,
+) -> Result<ImportReport<BookImportPlan>, ImportError> {
+    let mut parsed = parse_export(format, input)?;
+
+    let taxonomy = Taxonomy::load(db).await?;
+    for book in &mut parsed.books {
+        book.genres = taxonomy.canonicalize_array(TermKind::Genre, &book.genres);
+        book.tags = taxonomy.canonicalize_array(TermKind::Tag, &book.tags);
+    }
+
+    let database = db.database("bearodata");
+    let existing: Vec<Book> = database
+        .collection::<Book>("books")
+        .find(trash::live(doc! {}), None)
+        .await?
+        .try_collect()
+        .await?;
+
+    let plan = plan_import(parsed, &existing, update_existing);
+
+    if apply {
+        super::apply_plan::<Book, _>(db, Resource::Book, &plan.create, &plan.update).await?;
+    }
+
+    Ok(ImportReport {
+        applied: apply,
+        plan,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::oid::ObjectId;
+
+    fn titles(parsed: &ParsedBooks) -> Vec<String> {
+        parsed
+            .books
+            .iter()
+            .map(|book| book.title.get_text(None))
+            .collect()
+    }
+
+    #[test]
+    fn test_strip_series_suffix() {
+        assert_eq!(
+            strip_series_suffix("The Fellowship of the Ring (The Lord of the Rings, #1)"),
+            "The Fellowship of the Ring"
+        );
+        assert_eq!(
+            strip_series_suffix("Dune (40th Anniversary)"),
+            "Dune (40th Anniversary)"
+        );
+    }
+
+    #[test]
+    fn test_parse_goodreads() {
+        let parsed = parse_export(
+            BookExportFormat::Goodreads,
+            include_str!("fixtures/goodreads.csv"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            titles(&parsed),
+            vec!["The Fellowship of the Ring", "Dune", "The Hobbit"]
+        );
+
+        let fellowship = &parsed.books[0];
+        assert_eq!(fellowship.rating, 5);
+        assert_eq!(fellowship.status.get_text(None), "completed");
+        assert_eq!(
+            fellowship.my_thoughts.get_text(None),
+            "Loved it.\n\nThe *Shire* chapters especially."
+        );
+        assert_eq!(
+            fellowship.tags.get_texts(None),
+            vec!["fantasy", "favorites"]
+        );
+        assert_eq!(fellowship.links.as_ref().unwrap()["isbn"], "9780618346257");
+        let progress = fellowship.progress.as_ref().unwrap();
+        assert_eq!((progress.current, progress.total), (398, Some(398)));
+        assert_eq!(progress.finished_at, parse_date("2023/05/14"));
+        assert_eq!(progress.reread_count, 1);
+
+        let dune = &parsed.books[1];
+        assert_eq!(dune.status.get_text(None), "reading");
+        assert_eq!(dune.rating, 0);
+        assert!(dune.progress.is_none());
+
+        assert_eq!(parsed.books[2].my_thoughts.get_text(None), "");
+        assert_eq!(parsed.warnings[0].source, "line 4");
+
+        let reasons: Vec<&str> = parsed.skipped.iter().map(|s| s.reason.as_str()).collect();
+        assert_eq!(reasons, vec!["duplicate of line 4", "missing title"]);
+    }
+
+    #[test]
+    fn test_parse_storygraph() {
+        let parsed = parse_export(
+            BookExportFormat::StoryGraph,
+            include_str!("fixtures/storygraph.csv"),
+        )
+        .unwrap();
+
+        assert_eq!(
+            titles(&parsed),
+            vec!["Piranesi", "Project Hail Mary", "Babel"]
+        );
+
+        let piranesi = &parsed.books[0];
+        assert_eq!(piranesi.rating, 5);
+        assert_eq!(piranesi.status.get_text(None), "completed");
+        let progress = piranesi.progress.as_ref().unwrap();
+        assert_eq!(progress.started_at, parse_date("2022/03/05"));
+        assert_eq!(progress.finished_at, parse_date("2022/03/20"));
+        assert_eq!(progress.reread_count, 0);
+
+        assert_eq!(parsed.books[1].status.get_text(None), "dropped");
+        assert_eq!(parsed.books[2].status.get_text(None), "planned");
+    }
+
+    #[test]
+    fn test_plan_import_matches_across_locales() {
+        let parsed = parse_export(
+            BookExportFormat::StoryGraph,
+            include_str!("fixtures/storygraph.csv"),
+        )
+        .unwrap();
+
+        let mut title = HashMap::new();
+        title.insert("en".to_string(), "Piranesi".to_string());
+        title.insert("es".to_string(), "Piranesi (ES)".to_string());
+        let mut existing = new_book("", "Susanna Clarke").to_book_with_id(ObjectId::new());
+        existing.title = LocalizedString::Localized(title);
+        existing.rating = 4;
+
+        let plan = plan_import(parsed, &[existing], false);
+        assert_eq!(plan.create.len(), 2);
+        assert_eq!(plan.unchanged, vec!["Piranesi"]);
+        assert!(plan.update.is_empty());
+    }
+
+    #[test]
+    fn test_reimport_is_idempotent() {
+        let input = include_str!("fixtures/goodreads.csv");
+        let parsed = parse_export(BookExportFormat::Goodreads, input).unwrap();
+
+        let mut existing = new_book("The Fellowship of the Ring", "J.R.R. Tolkien")
+            .to_book_with_id(ObjectId::new());
+        existing.rating = 3;
+
+        let plan = plan_import(parsed, std::slice::from_ref(&existing), true);
+        assert_eq!(plan.create.len(), 2);
+        let fields: Vec<&str> = plan.update[0]
+            .changes
+            .iter()
+            .map(|change| change.field.as_ref())
+            .collect();
+        assert_eq!(fields, vec!["rating", "status", "progress", "my_thoughts"]);
+
+        let mut catalog: Vec<Book> = plan
+            .create
+            .iter()
+            .map(|book| book.to_book_with_id(ObjectId::new()))
+            .collect();
+        let imported = parse_export(BookExportFormat::Goodreads, input).unwrap();
+        let fellowship = imported.books[0].to_book_with_id(existing.oid);
+        catalog.push(fellowship);
+
+        let second = plan_import(
+            parse_export(BookExportFormat::Goodreads, input).unwrap(),
+            &catalog,
+            true,
+        );
+        assert!(second.create.is_empty());
+        assert!(second.update.is_empty());
+        assert_eq!(second.unchanged.len(), 3);
+    }
+}
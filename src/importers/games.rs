@@ -0,0 +1,840 @@
+//! # Game library importer
+//!
+//! Supported formats:
+//!
+//! - `steam-vdf`: Steam `libraryfolders.vdf`, optionally followed by the
+//!   `appmanifest_*.acf` files naming the installed apps
+//! - `steam-csv`: CSV with `appid`, `name` and `playtime_forever` (minutes) columns
+//! - `gog-csv`: GOG Galaxy 2.0 CSV export
+//! - `playnite`: Playnite JSON library export
+
+use {
+    super::{
+        CsvTable, FieldChange, ImportReport, PlannedUpdate, SkippedRecord, normalize_key,
+        same_creator, split_list,
+    },
+    crate::{
+        errors::ImportError,
+        events::Resource,
+        models::{Game, GameStatus, NewGame, TermKind, clamp_percent},
+        taxonomy::Taxonomy,
+        trash,
+    },
+    mongodb::bson::doc,
+    rocket::{
+        FromFormField,
+        futures::TryStreamExt,
+        serde::{Deserialize, Serialize},
+    },
+    rocket_db_pools::mongodb::Client,
+    schemars::JsonSchema,
+    serde_json::{Value, json},
+    std::{collections::HashMap, str::FromStr},
+};
+
+/// A supported game library export format.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromFormField)]
+pub enum GameLibraryFormat {
+    #[field(value = "steam-vdf")]
+    SteamVdf,
+    #[field(value = "steam-csv")]
+    SteamCsv,
+    #[field(value = "gog-csv")]
+    GogCsv,
+    #[field(value = "playnite")]
+    Playnite,
+}
+
+impl GameLibraryFormat {
+    /// Every format name accepted on the command line.
+    pub const NAMES: [&'static str; 4] = ["steam-vdf", "steam-csv", "gog-csv", "playnite"];
+}
+
+impl FromStr for GameLibraryFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "steam-vdf" => Ok(GameLibraryFormat::SteamVdf),
+            "steam-csv" => Ok(GameLibraryFormat::SteamCsv),
+            "gog-csv" => Ok(GameLibraryFormat::GogCsv),
+            "playnite" => Ok(GameLibraryFormat::Playnite),
+            _ => Err(format!("unknown game library format `{}`", s)),
+        }
+    }
+}
+
+/// Games read from an export file.
+#[derive(Debug, Default)]
+pub struct ParsedLibrary {
+    pub games: Vec<NewGame>,
+    pub skipped: Vec<SkippedRecord>,
+}
+
+/// What importing a game library would do.
+#[derive(Debug, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct GameImportPlan {
+    /// Games not in the catalog yet
+    pub create: Vec<NewGame>,
+    /// Catalog games gaining playtime or platforms
+    pub update: Vec<PlannedUpdate>,
+    /// Titles already in the catalog with nothing new
+    pub unchanged: Vec<String>,
+    /// Records that could not be imported
+    pub skipped: Vec<SkippedRecord>,
+}
+
+fn new_game(title: &str, developer: &str) -> NewGame {
+    NewGame {
+        title: title.trim().to_string(),
+        developer: developer.trim().to_string(),
+        genres: vec![],
+        tags: vec![],
+        rating: 0,
+        status: GameStatus::Backlog,
+        description: String::new(),
+        my_thoughts: String::new(),
+        links: None,
+        cover_image: String::new(),
+        explicit: false,
+        percent: 0,
+        bad: false,
+        playtime_hours: 0.0,
+        platforms: vec![],
+        started_at: None,
+        finished_at: None,
+        percent_from_checklist: false,
+    }
+}
+
+fn round_hours(hours: f64) -> f64 {
+    (hours * 100.0).round() / 100.0
+}
+
+/// Converts a playtime counted `per_hour` times an hour (60 for minutes) to
+/// hours, or `None` if it is negative or not finite.
+fn to_hours(value: f64, per_hour: f64) -> Option<f64> {
+    (value.is_finite() && value >= 0.0).then(|| round_hours(value / per_hour))
+}
+
+/// Parses a playtime cell with [`to_hours`]; a missing cell is no playtime.
+fn parse_playtime(cell: Option<&str>, per_hour: f64) -> Result<f64, String> {
+    let Some(cell) = cell else {
+        return Ok(0.0);
+    };
+    cell.parse::<f64>()
+        .ok()
+        .and_then(|value| to_hours(value, per_hour))
+        .ok_or_else(|| format!("invalid playtime `{}`", cell))
+}
+
+fn status_for_playtime(hours: f64) -> GameStatus {
+    if hours > 0.0 {
+        GameStatus::Playing
+    } else {
+        GameStatus::Backlog
+    }
+}
+
+fn steam_link(app_id: &str) -> HashMap<String, String> {
+    HashMap::from([(
+        "steam".to_string(),
+        format!("https://store.steampowered.com/app/{}", app_id),
+    )])
+}
+
+/// A parsed Steam KeyValues node.
+#[derive(Debug, PartialEq)]
+enum Vdf {
+    Value(String),
+    Object(Vec<(String, Vdf)>),
+}
+
+impl Vdf {
+    fn get(&self, key: &str) -> Option<&Vdf> {
+        match self {
+            Vdf::Object(entries) => entries
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(key))
+                .map(|(_, value)| value),
+            Vdf::Value(_) => None,
+        }
+    }
+
+    fn as_str(&self) -> Option<&str> {
+        match self {
+            Vdf::Value(value) => Some(value),
+            Vdf::Object(_) => None,
+        }
+    }
+}
+
+fn vdf_tokens(input: &str) -> Result<Vec<String>, ImportError> {
+    let mut tokens = Vec::new();
+    let mut chars = input.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '{' | '}' => tokens.push(c.to_string()),
+            '"' => {
+                let mut token = String::new();
+                loop {
+                    match chars.next() {
+                        Some('"') => break,
+                        Some('\\') => match chars.next() {
+                            Some('n') => token.push('\n'),
+                            Some('t') => token.push('\t'),
+                            Some(escaped) => token.push(escaped),
+                            None => return Err(ImportError::Vdf("unterminated string".into())),
+                        },
+                        Some(c) => token.push(c),
+                        None => return Err(ImportError::Vdf("unterminated string".into())),
+                    }
+                }
+                tokens.push(format!("\"{}", token));
+            }
+            '/' if chars.peek() == Some(&'/') => {
+                for c in chars.by_ref() {
+                    if c == '\n' {
+                        break;
+                    }
+                }
+            }
+            c if c.is_whitespace() => {}
+            c => return Err(ImportError::Vdf(format!("unexpected character `{}`", c))),
+        }
+    }
+
+    Ok(tokens)
+}
+
+/// Parses one or more top-level KeyValues documents.
+fn parse_vdf(input: &str) -> Result<Vec<(String, Vdf)>, ImportError> {
+    fn entries(
+        tokens: &mut std::vec::IntoIter<String>,
+        nested: bool,
+    ) -> Result<Vec<(String, Vdf)>, ImportError> {
+        let mut result = Vec::new();
+
+        while let Some(token) = tokens.next() {
+            let key = match token.as_str() {
+                "}" if nested => return Ok(result),
+                "}" | "{" => return Err(ImportError::Vdf(format!("unexpected `{}`", token))),
+                _ => token[1..].to_string(),
+            };
+
+            let value = match tokens.next().as_deref() {
+                Some("{") => Vdf::Object(entries(tokens, true)?),
+                Some(value) if value.starts_with('"') => Vdf::Value(value[1..].to_string()),
+                _ => return Err(ImportError::Vdf(format!("missing value for `{}`", key))),
+            };
+            result.push((key, value));
+        }
+
+        if nested {
+            Err(ImportError::Vdf("unclosed `{`".into()))
+        } else {
+            Ok(result)
+        }
+    }
+
+    entries(&mut vdf_tokens(input)?.into_iter(), false)
+}
+
+fn parse_steam_vdf(input: &str) -> Result<ParsedLibrary, ImportError> {
+    let mut app_ids: Vec<String> = Vec::new();
+    let mut names: HashMap<String, String> = HashMap::new();
+
+    for (key, document) in parse_vdf(input)? {
+        if key.eq_ignore_ascii_case("AppState") {
+            if let (Some(app_id), Some(name)) = (
+                document.get("appid").and_then(Vdf::as_str),
+                document.get("name").and_then(Vdf::as_str),
+            ) {
+                names.insert(app_id.to_string(), name.to_string());
+                if !app_ids.iter().any(|id| id == app_id) {
+                    app_ids.push(app_id.to_string());
+                }
+            }
+        } else if let Vdf::Object(folders) = &document {
+            for (_, folder) in folders {
+                if let Some(Vdf::Object(apps)) = folder.get("apps") {
+                    for (app_id, _) in apps {
+                        if !app_ids.contains(app_id) {
+                            app_ids.push(app_id.clone());
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    let mut library = ParsedLibrary::default();
+    for app_id in app_ids {
+        match names.get(&app_id) {
+            Some(name) => {
+                let mut game = new_game(name, "");
+                game.platforms = vec!["Steam".to_string()];
+                game.links = Some(steam_link(&app_id));
+                library.games.push(game);
+            }
+            None => library.skipped.push(SkippedRecord {
+                source: format!("app {}", app_id),
+                reason: "no name; include the app's appmanifest".to_string(),
+            }),
+        }
+    }
+
+    Ok(library)
+}
+
+fn parse_steam_csv(input: &str) -> Result<ParsedLibrary, ImportError> {
+    let table = CsvTable::parse(input)?;
+    let name = table.require(&["name", "title"])?;
+    let app_id = table.column(&["appid", "app_id"]);
+    let developer = table.column(&["developer", "developers"]);
+    let minutes = table.column(&["playtime_forever", "playtime_minutes"]);
+    let hours = table.column(&["playtime_hours", "hours"]);
+
+    let mut library = ParsedLibrary::default();
+    for (line, row) in table.rows() {
+        let Some(title) = row.get(Some(name)) else {
+            library.skipped.push(SkippedRecord {
+                source: format!("line {}", line),
+                reason: "missing name".to_string(),
+            });
+            continue;
+        };
+
+        let playtime = match row.get(minutes) {
+            Some(minutes) => parse_playtime(Some(minutes), 60.0),
+            None => parse_playtime(row.get(hours), 1.0),
+        };
+        let playtime_hours = match playtime {
+            Ok(playtime_hours) => playtime_hours,
+            Err(reason) => {
+                library.skipped.push(SkippedRecord {
+                    source: format!("line {}", line),
+                    reason,
+                });
+                continue;
+            }
+        };
+
+        let mut game = new_game(title, row.get(developer).unwrap_or_default());
+        game.playtime_hours = playtime_hours;
+        game.status = status_for_playtime(game.playtime_hours);
+        game.platforms = vec!["Steam".to_string()];
+        game.links = row.get(app_id).map(steam_link);
+        library.games.push(game);
+    }
+
+    Ok(library)
+}
+
+fn parse_gog_csv(input: &str) -> Result<ParsedLibrary, ImportError> {
+    let table = CsvTable::parse(input)?;
+    let title = table.require(&["title", "name"])?;
+    let developers = table.column(&["developers", "developer"]);
+    let platforms = table.column(&["platformlist", "platforms"]);
+    let genres = table.column(&["genres"]);
+    let tags = table.column(&["tags"]);
+    let minutes = table.column(&["gamemins", "playtime"]);
+    let summary = table.column(&["summary", "description"]);
+
+    let mut library = ParsedLibrary::default();
+    for (line, row) in table.rows() {
+        let Some(name) = row.get(Some(title)) else {
+            library.skipped.push(SkippedRecord {
+                source: format!("line {}", line),
+                reason: "missing title".to_string(),
+            });
+            continue;
+        };
+        let playtime_hours = match parse_playtime(row.get(minutes), 60.0) {
+            Ok(playtime_hours) => playtime_hours,
+            Err(reason) => {
+                library.skipped.push(SkippedRecord {
+                    source: format!("line {}", line),
+                    reason,
+                });
+                continue;
+            }
+        };
+
+        let developer = row
+            .get(developers)
+            .map(split_list)
+            .unwrap_or_default()
+            .join(", ");
+        let mut game = new_game(name, &developer);
+        game.genres = row.get(genres).map(split_list).unwrap_or_default();
+        game.tags = row.get(tags).map(split_list).unwrap_or_default();
+        game.platforms = row.get(platforms).map(split_list).unwrap_or_default();
+        game.playtime_hours = playtime_hours;
+        game.status = status_for_playtime(game.playtime_hours);
+        game.description = row.get(summary).unwrap_or_default().to_string();
+        library.games.push(game);
+    }
+
+    Ok(library)
+}
+
+/// Reads a Playnite list, whose entries are either names or `{ "Name": ... }` objects.
+fn playnite_names(game: &Value, field: &str) -> Vec<String> {
+    game.get(field)
+        .and_then(Value::as_array)
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|value| {
+                    value
+                        .as_str()
+                        .or_else(|| value.get("Name").and_then(Value::as_str))
+                })
+                .map(str::to_string)
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn parse_playnite(input: &str) -> Result<ParsedLibrary, ImportError> {
+    let document: Value = serde_json::from_str(input)?;
+    let games = match &document {
+        Value::Array(games) => games.as_slice(),
+        other => other
+            .get("Games")
+            .and_then(Value::as_array)
+            .map(Vec::as_slice)
+            .unwrap_or_default(),
+    };
+
+    let mut library = ParsedLibrary::default();
+    for (index, entry) in games.iter().enumerate() {
+        let Some(name) = entry.get("Name").and_then(Value::as_str) else {
+            library.skipped.push(SkippedRecord {
+                source: format!("entry {}", index + 1),
+                reason: "missing Name".to_string(),
+            });
+            continue;
+        };
+        let playtime = match entry.get("Playtime") {
+            None | Some(Value::Null) => Some(0.0),
+            Some(playtime) => playtime
+                .as_f64()
+                .and_then(|seconds| to_hours(seconds, 3600.0)),
+        };
+        let Some(playtime_hours) = playtime else {
+            library.skipped.push(SkippedRecord {
+                source: format!("entry {}", index + 1),
+                reason: format!("invalid Playtime `{}`", entry["Playtime"]),
+            });
+            continue;
+        };
+
+        let mut game = new_game(name, &playnite_names(entry, "Developers").join(", "));
+        game.genres = playnite_names(entry, "Genres");
+        game.tags = playnite_names(entry, "Tags");
+        game.platforms = playnite_names(entry, "Platforms");
+        game.description = entry
+            .get("Description")
+            .and_then(Value::as_str)
+            .unwrap_or_default()
+            .to_string();
+        game.playtime_hours = playtime_hours;
+
+        let completion = entry.get("CompletionStatus").and_then(|status| {
+            status
+                .as_str()
+                .or_else(|| status.get("Name").and_then(Value::as_str))
+        });
+        game.status = completion
+            .and_then(GameStatus::parse)
+            .unwrap_or_else(|| status_for_playtime(game.playtime_hours));
+        if game.status == GameStatus::HundredPercent {
+            game.percent = 100;
+        }
+
+        if let Some(score) = entry.get("UserScore").and_then(Value::as_i64) {
+            game.rating = ((score + 10) / 20).clamp(0, 5) as i32;
+        }
+
+        let links: HashMap<String, String> = entry
+            .get("Links")
+            .and_then(Value::as_array)
+            .map(|links| {
+                links
+                    .iter()
+                    .filter_map(|link| {
+                        Some((
+                            link.get("Name")?.as_str()?.to_lowercase(),
+                            link.get("Url")?.as_str()?.to_string(),
+                        ))
+                    })
+                    .collect()
+            })
+            .unwrap_or_default();
+        if !links.is_empty() {
+            game.links = Some(links);
+        }
+
+        library.games.push(game);
+    }
+
+    Ok(library)
+}
+
+fn union(target: &mut Vec<String>, values: Vec<String>) {
+    for value in values {
+        if !target
+            .iter()
+            .any(|existing| normalize_key(existing) == normalize_key(&value))
+        {
+            target.push(value);
+        }
+    }
+}
+
+/// Merges records for the same game, e.g. one owned on two stores.
+fn merge_duplicates(games: Vec<NewGame>) -> Vec<NewGame> {
+    let mut merged: Vec<NewGame> = Vec::with_capacity(games.len());
+
+    for game in games {
+        let existing = merged.iter_mut().find(|existing| {
+            normalize_key(&existing.title) == normalize_key(&game.title)
+                && same_creator(&existing.developer, &game.developer)
+        });
+
+        match existing {
+            Some(existing) => {
+                if existing.developer.is_empty() {
+                    existing.developer = game.developer;
+                }
+                union(&mut existing.platforms, game.platforms);
+                union(&mut existing.genres, game.genres);
+                union(&mut existing.tags, game.tags);
+                existing.playtime_hours = existing.playtime_hours.max(game.playtime_hours);
+                if let Some(links) = game.links {
+                    existing
+                        .links
+                        .get_or_insert_with(HashMap::new)
+                        .extend(links);
+                }
+            }
+            None => merged.push(game),
+        }
+    }
+
+    merged
+}
+
+/// Parses an export file into games, merging duplicate records.
+pub fn parse_library(format: GameLibraryFormat, input: &str) -> Result<ParsedLibrary, ImportError> {
+    let mut library = match format {
+        GameLibraryFormat::SteamVdf => parse_steam_vdf(input)?,
+        GameLibraryFormat::SteamCsv => parse_steam_csv(input)?,
+        GameLibraryFormat::GogCsv => parse_gog_csv(input)?,
+        GameLibraryFormat::Playnite => parse_playnite(input)?,
+    };
+
+    library.games = merge_duplicates(library.games);
+    Ok(library)
+}
+
+/// Compares parsed games against the catalog.
+///
+/// Games match on normalized title and developer. Matching games are only
+/// updated to gain playtime and platforms; nothing is ever removed.
+pub fn plan_import(library: ParsedLibrary, existing: &[Game]) -> GameImportPlan {
+    let mut plan = GameImportPlan {
+        skipped: library.skipped,
+        ..Default::default()
+    };
+
+    for game in library.games {
+        let Some(current) = existing.iter().find(|current| {
+            normalize_key(&current.title) == normalize_key(&game.title)
+                && same_creator(&current.developer, &game.developer)
+        }) else {
+            plan.create.push(game);
+            continue;
+        };
+
+        let mut changes = Vec::new();
+        if game.playtime_hours > current.playtime_hours + 0.01 {
+            changes.push(FieldChange {
+                field: "playtime_hours".into(),
+                from: json!(current.playtime_hours),
+                to: json!(game.playtime_hours),
+            });
+        }
+
+        let mut platforms = current.platforms.clone();
+        union(&mut platforms, game.platforms);
+        if platforms.len() > current.platforms.len() {
+            changes.push(FieldChange {
+                field: "platforms".into(),
+                from: json!(current.platforms),
+                to: json!(platforms),
+            });
+        }
+
+        if current.developer.is_empty() && !game.developer.is_empty() {
+            changes.push(FieldChange {
+                field: "developer".into(),
+                from: json!(current.developer),
+                to: json!(game.developer),
+            });
+        }
+
+        if changes.is_empty() {
+            plan.unchanged.push(current.title.clone());
+        } else {
+            plan.update.push(PlannedUpdate {
+                id: current.oid,
+                title: current.title.clone(),
+                changes,
+            });
+        }
+    }
+
+    plan
+}
+
+/// Imports a game library export, writing to the database only when `apply` is set.
+pub async fn import_library(
+    db: &Client,
+    format: GameLibraryFormat,
+    input: &str,
+    apply: bool,
+) -> Result<ImportReport<GameImportPlan>, ImportError> {
+    let mut library = parse_library(format, input)?;
+
+    let taxonomy = Taxonomy::load(db).await?;
+    for game in &mut library.games {
+        game.genres = taxonomy.canonicalize_list(TermKind::Genre, &game.genres);
+        game.tags = taxonomy.canonicalize_list(TermKind::Tag, &game.tags);
+        game.percent = clamp_percent(game.percent);
+    }
+
+    let database = db.database("bearodata");
+    let existing: Vec<Game> = database
+        .collection::<Game>("games")
+        .find(trash::live(doc! {}), None)
+        .await?
+        .try_collect()
+        .await?;
+
+    let plan = plan_import(library, &existing);
+
+    if apply {
+        super::apply_plan::<Game, _>(db, Resource::Game, &plan.create, &plan.update).await?;
+    }
+
+    Ok(ImportReport {
+        applied: apply,
+        plan,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::oid::ObjectId;
+
+    fn titles(library: &ParsedLibrary) -> Vec<&str> {
+        library
+            .games
+            .iter()
+            .map(|game| game.title.as_str())
+            .collect()
+    }
+
+    fn existing(title: &str, developer: &str, hours: f64, platforms: &[&str]) -> Game {
+        Game {
+            oid: ObjectId::new(),
+            title: title.to_string(),
+            developer: developer.to_string(),
+            genres: vec![],
+            tags: vec![],
+            rating: 0,
+            status: GameStatus::Playing,
+            description: String::new(),
+            my_thoughts: String::new(),
+            links: None,
+            cover_image: String::new(),
+            explicit: false,
+            percent: 0,
+            bad: false,
+            playtime_hours: hours,
+            platforms: platforms.iter().map(|p| p.to_string()).collect(),
+            started_at: None,
+            finished_at: None,
+            checklist: vec![],
+            percent_from_checklist: false,
+            created_at: None,
+            updated_at: None,
+            deleted_at: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_steam_vdf() {
+        let library = parse_library(
+            GameLibraryFormat::SteamVdf,
+            include_str!("fixtures/steam_library.vdf"),
+        )
+        .unwrap();
+
+        assert_eq!(titles(&library), vec!["Portal 2", "Outer Wilds"]);
+        assert_eq!(
+            library.games[0].links.as_ref().unwrap()["steam"],
+            "https://store.steampowered.com/app/620"
+        );
+        assert_eq!(library.skipped.len(), 1);
+        assert_eq!(library.skipped[0].source, "app 228980");
+
+        assert!(parse_vdf("\"a\" { \"b\" \"c\"").is_err());
+    }
+
+    #[test]
+    fn test_parse_steam_csv() {
+        let library = parse_library(
+            GameLibraryFormat::SteamCsv,
+            include_str!("fixtures/steam_library.csv"),
+        )
+        .unwrap();
+
+        assert_eq!(titles(&library), vec!["Portal 2", "Outer Wilds™"]);
+        assert_eq!(library.games[0].playtime_hours, 20.5);
+        assert_eq!(library.games[0].status, GameStatus::Playing);
+        assert_eq!(library.skipped[0].source, "line 4");
+    }
+
+    #[test]
+    fn test_invalid_playtime_is_skipped() {
+        let library = parse_library(
+            GameLibraryFormat::SteamCsv,
+            "appid,name,playtime_forever\n1,Negative,-60\n2,Not a number,NaN\n3,Infinite,inf\n4,Fine,90\n",
+        )
+        .unwrap();
+        assert_eq!(titles(&library), vec!["Fine"]);
+        assert_eq!(library.games[0].playtime_hours, 1.5);
+        let skipped: Vec<_> = library.skipped.iter().map(|s| s.reason.as_str()).collect();
+        assert_eq!(
+            skipped,
+            [
+                "invalid playtime `-60`",
+                "invalid playtime `NaN`",
+                "invalid playtime `inf`"
+            ]
+        );
+
+        let library = parse_library(
+            GameLibraryFormat::GogCsv,
+            "title,gameMins\nBroken,-1\nFine,\n",
+        )
+        .unwrap();
+        assert_eq!(titles(&library), vec!["Fine"]);
+        assert_eq!(library.skipped[0].source, "line 2");
+
+        let library = parse_library(
+            GameLibraryFormat::Playnite,
+            r#"[{"Name": "Broken", "Playtime": -3600}, {"Name": "Fine"}]"#,
+        )
+        .unwrap();
+        assert_eq!(titles(&library), vec!["Fine"]);
+        assert_eq!(library.skipped[0].reason, "invalid Playtime `-3600`");
+    }
+
+    #[test]
+    fn test_parse_gog_csv_merges_duplicates() {
+        let library = parse_library(
+            GameLibraryFormat::GogCsv,
+            include_str!("fixtures/gog_galaxy.csv"),
+        )
+        .unwrap();
+
+        assert_eq!(titles(&library), vec!["Disco Elysium", "Outer Wilds"]);
+        let disco = &library.games[0];
+        assert_eq!(disco.developer, "ZA/UM");
+        assert_eq!(disco.platforms, vec!["GOG", "Steam"]);
+        assert_eq!(disco.playtime_hours, 40.0);
+        assert_eq!(disco.genres, vec!["Role-playing (RPG)"]);
+    }
+
+    #[test]
+    fn test_parse_playnite() {
+        let library = parse_library(
+            GameLibraryFormat::Playnite,
+            include_str!("fixtures/playnite.json"),
+        )
+        .unwrap();
+
+        assert_eq!(titles(&library), vec!["Hades", "Hollow Knight"]);
+        let hades = &library.games[0];
+        assert_eq!(hades.developer, "Supergiant Games");
+        assert_eq!(hades.status, GameStatus::Completed);
+        assert_eq!(hades.playtime_hours, 20.0);
+        assert_eq!(hades.rating, 5);
+        assert_eq!(hades.platforms, vec!["PC (Windows)"]);
+        assert_eq!(library.games[1].status, GameStatus::Backlog);
+        assert_eq!(library.skipped[0].source, "entry 3");
+    }
+
+    #[test]
+    fn test_plan_import_diff() {
+        let library = parse_library(
+            GameLibraryFormat::GogCsv,
+            include_str!("fixtures/gog_galaxy.csv"),
+        )
+        .unwrap();
+        let catalog = vec![
+            existing("Outer Wilds", "Mobius Digital", 10.0, &["Steam", "GOG"]),
+            existing("DISCO ELYSIUM", "", 12.0, &["PC"]),
+        ];
+
+        let plan = plan_import(library, &catalog);
+
+        assert!(plan.create.is_empty());
+        assert_eq!(plan.unchanged, vec!["Outer Wilds"]);
+        assert_eq!(plan.update.len(), 1);
+
+        let update = &plan.update[0];
+        assert_eq!(update.id, catalog[1].oid);
+        let fields: Vec<&str> = update
+            .changes
+            .iter()
+            .map(|change| change.field.as_ref())
+            .collect();
+        assert_eq!(fields, vec!["playtime_hours", "platforms", "developer"]);
+        assert_eq!(
+            update.to_set_document().unwrap(),
+            doc! {
+                "playtime_hours": 40.0,
+                "platforms": ["PC", "GOG", "Steam"],
+                "developer": "ZA/UM",
+            }
+        );
+    }
+
+    #[test]
+    fn test_plan_import_is_idempotent() {
+        let library = parse_library(
+            GameLibraryFormat::SteamCsv,
+            include_str!("fixtures/steam_library.csv"),
+        )
+        .unwrap();
+        let catalog = vec![
+            existing("Portal 2", "Valve", 20.5, &["Steam"]),
+            existing("Outer Wilds", "Mobius Digital", 15.0, &["Steam"]),
+        ];
+
+        let plan = plan_import(library, &catalog);
+
+        assert!(plan.create.is_empty());
+        assert!(plan.update.is_empty());
+        assert_eq!(plan.unchanged, vec!["Portal 2", "Outer Wilds"]);
+    }
+}
@@ -0,0 +1,268 @@
+//! # Library importers
+//!
+//! Turns exported library files from other services into catalog documents.
+//! Every importer works in two steps: it first builds a plan describing what
+//! would be created, updated or skipped (the dry run), and only writes to the
+//! database when the plan is applied.
+//!
+//! ## Submodules
+//!
+//! - `books`: Goodreads and StoryGraph reading libraries
+//! - `games`: Steam, GOG Galaxy and Playnite game libraries
+
+pub mod books;
+pub mod games;
+
+use {
+    crate::{
+        errors::ImportError,
+        events::{self, Event, Resource},
+        history,
+        taxonomy::slugify,
+        trash,
+    },
+    mongodb::bson::{self, Document, doc, oid::ObjectId},
+    rocket::serde::{Deserialize, Serialize, de::DeserializeOwned},
+    rocket_db_pools::mongodb::{
+        Client,
+        options::{FindOneAndUpdateOptions, ReturnDocument},
+    },
+    schemars::JsonSchema,
+    std::{borrow::Cow, collections::HashMap},
+};
+
+/// A change to one field of an existing document.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct FieldChange {
+    /// Document field name
+    pub field: Cow<'static, str>,
+    /// Current value
+    pub from: serde_json::Value,
+    /// Imported value
+    pub to: serde_json::Value,
+}
+
+/// An existing document the import would change.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct PlannedUpdate {
+    /// Id of the existing document
+    #[schemars(with = "crate::openapi::ObjectIdSchema")]
+    pub id: ObjectId,
+    /// Title of the existing document
+    pub title: String,
+    /// Field changes
+    pub changes: Vec<FieldChange>,
+}
+
+impl PlannedUpdate {
+    /// Builds the `$set` document applying these changes.
+    pub fn to_set_document(&self) -> Result<Document, ImportError> {
+        let mut set = Document::new();
+        for change in &self.changes {
+            set.insert(change.field.as_ref(), bson::to_bson(&change.to)?);
+        }
+        Ok(set)
+    }
+}
+
+/// An input record that could not be imported.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct SkippedRecord {
+    /// Where the record came from (line number, app id, title, ...)
+    pub source: String,
+    /// Why it was skipped
+    pub reason: String,
+}
+
+/// The outcome of an import, dry run or not.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[schemars(rename = "ImportReport{T}")]
+pub struct ImportReport<T> {
+    /// Whether the plan was written to the database
+    pub applied: bool,
+    /// What was (or would be) created, updated and skipped
+    #[serde(flatten)]
+    pub plan: T,
+}
+
+/// Writes a plan into `resource`'s collection: inserts `create`, applies
+/// `update` to documents that are not trashed, and publishes an event for
+/// every written document.
+pub async fn apply_plan<T, N>(
+    db: &Client,
+    resource: Resource,
+    create: &[N],
+    update: &[PlannedUpdate],
+) -> Result<(), ImportError>
+where
+    T: Serialize + DeserializeOwned + Send + Sync + Unpin,
+    N: Serialize,
+{
+    let database = db.database("bearodata");
+    let now = history::now();
+
+    if !create.is_empty() {
+        let documents = create
+            .iter()
+            .map(|new| {
+                let mut document = bson::to_document(new)?;
+                document.insert("_id", ObjectId::new());
+                history::stamp_created(&mut document, now);
+                Ok(document)
+            })
+            .collect::<Result<Vec<Document>, ImportError>>()?;
+        database
+            .collection::<Document>(resource.collection())
+            .insert_many(&documents, None)
+            .await?;
+        for document in documents {
+            let created: T = bson::from_document(document)?;
+            events::publish(db, Event::created(resource), &created).await;
+        }
+    }
+
+    for update in update {
+        let mut set = update.to_set_document()?;
+        history::stamp_updated(&mut set, now);
+        let updated = database
+            .collection::<T>(resource.collection())
+            .find_one_and_update(
+                trash::live(doc! { "_id": update.id }),
+                doc! { "$set": set },
+                FindOneAndUpdateOptions::builder()
+                    .return_document(ReturnDocument::After)
+                    .build(),
+            )
+            .await?;
+        if let Some(updated) = updated {
+            events::publish(db, Event::updated(resource), &updated).await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Builds the key used to match imported records against existing documents.
+///
+/// Titles and names are compared by slug, so case, punctuation and trademark
+/// symbols do not matter.
+pub fn normalize_key(text: &str) -> String {
+    slugify(text)
+}
+
+/// Whether two creator names (developer, author) refer to the same creator.
+///
+/// A missing name on either side matches anything, since many exports do not
+/// include one.
+pub fn same_creator(a: &str, b: &str) -> bool {
+    let (a, b) = (normalize_key(a), normalize_key(b));
+    a.is_empty() || b.is_empty() || a == b
+}
+
+/// Splits a list cell such as `PC, Switch` or `['PC', 'Switch']` into values.
+pub fn split_list(cell: &str) -> Vec<String> {
+    cell.trim()
+        .trim_start_matches('[')
+        .trim_end_matches(']')
+        .split([',', ';'])
+        .map(|value| value.trim().trim_matches(['\'', '"']).trim().to_string())
+        .filter(|value| !value.is_empty())
+        .collect()
+}
+
+/// A CSV file with case-insensitive column lookup.
+pub struct CsvTable {
+    columns: HashMap<String, usize>,
+    rows: Vec<csv::StringRecord>,
+}
+
+impl CsvTable {
+    /// Reads a CSV file with a header row.
+    pub fn parse(input: &str) -> Result<Self, ImportError> {
+        let mut reader = csv::ReaderBuilder::new()
+            .flexible(true)
+            .trim(csv::Trim::All)
+            .from_reader(input.trim_start_matches('\u{feff}').as_bytes());
+
+        let columns = reader
+            .headers()?
+            .iter()
+            .enumerate()
+            .map(|(index, name)| (name.to_lowercase(), index))
+            .collect();
+        let rows = reader.records().collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self { columns, rows })
+    }
+
+    /// Returns the index of the first of `names` present in the header.
+    pub fn column(&self, names: &[&str]) -> Option<usize> {
+        names
+            .iter()
+            .find_map(|name| self.columns.get(&name.to_lowercase()).copied())
+    }
+
+    /// Like [`CsvTable::column`], failing when none of the names is present.
+    pub fn require(&self, names: &[&str]) -> Result<usize, ImportError> {
+        self.column(names)
+            .ok_or_else(|| ImportError::MissingColumn(names.join(" or ")))
+    }
+
+    /// Iterates rows with their 1-based line number (the header is line 1).
+    pub fn rows(&self) -> impl Iterator<Item = (usize, CsvRow<'_>)> {
+        self.rows
+            .iter()
+            .enumerate()
+            .map(|(index, record)| (index + 2, CsvRow { record }))
+    }
+}
+
+/// A row of a [`CsvTable`].
+pub struct CsvRow<'a> {
+    record: &'a csv::StringRecord,
+}
+
+impl CsvRow<'_> {
+    /// Returns the non-empty value of a column.
+    pub fn get(&self, column: Option<usize>) -> Option<&str> {
+        column
+            .and_then(|column| self.record.get(column))
+            .filter(|value| !value.is_empty())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_split_list() {
+        assert_eq!(split_list("PC, Switch"), vec!["PC", "Switch"]);
+        assert_eq!(split_list("['PC', 'Steam']"), vec!["PC", "Steam"]);
+        assert!(split_list(" ").is_empty());
+    }
+
+    #[test]
+    fn test_same_creator() {
+        assert!(same_creator("Mobius Digital", "mobius digital"));
+        assert!(same_creator("", "Mobius Digital"));
+        assert!(!same_creator("Valve", "Mobius Digital"));
+    }
+
+    #[test]
+    fn test_csv_table_columns() {
+        let table = CsvTable::parse("\u{feff}Title,Author\nDune,Frank Herbert\nEmpty,\n").unwrap();
+        let title = table.require(&["name", "title"]).unwrap();
+        let author = table.column(&["author"]);
+
+        let rows: Vec<_> = table.rows().collect();
+        assert_eq!(rows[0].0, 2);
+        assert_eq!(rows[0].1.get(Some(title)), Some("Dune"));
+        assert_eq!(rows[1].1.get(author), None);
+        assert!(table.require(&["isbn"]).is_err());
+    }
+}
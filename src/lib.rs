@@ -0,0 +1,77 @@
+//! # Apiodactyl library
+//!
+//! The server's modules, shared by the `apiodactyl` binary and the
+//! `apiodactyl-client` crate.
+#![feature(duration_constructors)]
+#![cfg_attr(test, feature(str_as_str))]
+
+use rocket::{Build, Rocket, Route, catchers, routes};
+
+pub mod auth;
+pub mod backup;
+pub mod cli;
+pub mod config;
+pub mod content;
+pub mod db;
+pub mod errors;
+pub mod events;
+pub mod feeds;
+pub mod handlers;
+pub mod history;
+pub mod importers;
+pub mod logging;
+pub mod markdown;
+pub mod metrics;
+pub mod migrations;
+pub mod models;
+pub mod openapi;
+pub mod taxonomy;
+pub mod trash;
+pub mod webhooks;
+
+/// Registers every catcher and mounts every route of the API, each handler
+/// running in a request span (see [`logging::traced`]).
+pub fn mount_api(rocket: Rocket<Build>) -> Rocket<Build> {
+    let mounts: Vec<(&str, Vec<Route>)> = vec![
+        ("/", routes![handlers::index]),
+        ("/", handlers::docs::routes()),
+        ("/reviews", handlers::reviews::routes()),
+        ("/reviews", handlers::history::review_routes()),
+        (
+            "/wplace",
+            routes![
+                handlers::wplace::get_screenshot_by_id,
+                handlers::wplace::get_screenshots,
+                handlers::wplace::create_screenshot,
+                handlers::wplace::delete_screenshot
+            ],
+        ),
+        ("/wplace", handlers::history::screenshot_routes()),
+        ("/read-watch", handlers::books::routes()),
+        ("/read-watch", handlers::reviews::work_routes()),
+        ("/read-watch", handlers::history::book_routes()),
+        ("/games", handlers::games::routes()),
+        ("/games", handlers::reviews::work_routes()),
+        ("/games", handlers::history::game_routes()),
+        ("/projects", handlers::projects::routes()),
+        ("/projects", handlers::history::project_routes()),
+        ("/misc", handlers::misc::routes()),
+        ("/taxonomy", handlers::taxonomy::routes()),
+        ("/series", handlers::series::routes()),
+        ("/series", handlers::reviews::work_routes()),
+        ("/admin", handlers::admin::routes()),
+        ("/metrics", handlers::metrics::routes()),
+        ("/webhooks", handlers::webhooks::routes()),
+        ("/trash", handlers::trash::routes()),
+        ("/events", routes![handlers::events::stream_events]),
+        ("/feeds", handlers::feeds::routes()),
+    ];
+
+    mounts.into_iter().fold(
+        rocket.register(
+            "/",
+            catchers![handlers::catch401, handlers::catch404, handlers::catch500],
+        ),
+        |rocket, (base, routes)| rocket.mount(base, logging::traced(routes)),
+    )
+}
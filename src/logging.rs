@@ -0,0 +1,269 @@
+//! # Structured logging
+//!
+//! Configures `tracing` output and tags every request with an ID.
+//!
+//! ## Configuration
+//!
+//! - `LOG_FORMAT`: `logfmt` (default) or `json`
+//! - `RUST_LOG`: level filter, e.g. `info` (default) or `apiodactyl=debug`
+//!
+//! ## Request IDs
+//!
+//! [`RequestLogger`] assigns each request an ID (reusing a well-formed incoming
+//! `X-Request-Id`), echoes it in the `X-Request-Id` response header and logs
+//! one line per request with the route, status, latency and the ID of the
+//! authenticated API key. Routes mounted through [`traced`] run their handler
+//! in a `request` span carrying the same ID, so everything a handler logs
+//! (including MongoDB commands) can be tied back to its request.
+
+use {
+    rocket::{
+        Data, Request, Response, Route,
+        fairing::{Fairing, Info, Kind},
+        http::Header,
+        request::{FromRequest, Outcome},
+        route::{self, Handler},
+    },
+    std::{
+        convert::Infallible,
+        fmt,
+        time::{Duration, Instant},
+    },
+    tracing::Instrument,
+    tracing_subscriber::{EnvFilter, layer::SubscriberExt, util::SubscriberInitExt},
+};
+
+/// Name of the request/response header carrying the request ID.
+pub const REQUEST_ID_HEADER: &str = "X-Request-Id";
+
+/// Installs the global `tracing` subscriber.
+///
+/// Does nothing if a subscriber is already installed.
+pub fn init() {
+    let filter = EnvFilter::try_from_default_env().unwrap_or_else(|_| EnvFilter::new("info"));
+    let registry = tracing_subscriber::registry().with(filter);
+
+    let _ = match std::env::var("LOG_FORMAT").as_deref() {
+        Ok("json") => registry
+            .with(
+                tracing_subscriber::fmt::layer()
+                    .json()
+                    .flatten_event(true)
+                    .with_current_span(true),
+            )
+            .try_init(),
+        _ => registry.with(tracing_logfmt::layer()).try_init(),
+    };
+}
+
+/// The ID of the current request.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RequestId(pub String);
+
+impl fmt::Display for RequestId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl RequestId {
+    /// Reuses a client-supplied ID if it is short and printable, otherwise
+    /// generates a new one.
+    fn from_header(header: Option<&str>) -> Self {
+        match header {
+            Some(id)
+                if !id.is_empty()
+                    && id.len() <= 64
+                    && id
+                        .chars()
+                        .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') =>
+            {
+                Self(id.to_string())
+            }
+            _ => Self(uuid::Uuid::new_v4().simple().to_string()),
+        }
+    }
+
+    /// Returns the ID assigned to a request, assigning one if needed.
+    pub fn of(request: &Request<'_>) -> Self {
+        request
+            .local_cache(|| Self::from_header(request.headers().get_one(REQUEST_ID_HEADER)))
+            .clone()
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for RequestId {
+    type Error = Infallible;
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(RequestId::of(request))
+    }
+}
+
+/// A route handler run inside a `request` span.
+#[derive(Clone)]
+struct Traced(Box<dyn Handler>);
+
+#[rocket::async_trait]
+impl Handler for Traced {
+    async fn handle<'r>(&self, request: &'r Request<'_>, data: Data<'r>) -> route::Outcome<'r> {
+        let route = request
+            .route()
+            .and_then(|route| route.name.as_deref())
+            .unwrap_or("unnamed");
+        let span = tracing::info_span!(
+            "request",
+            request_id = %RequestId::of(request),
+            method = %request.method(),
+            route,
+        );
+        self.0.handle(request, data).instrument(span).await
+    }
+}
+
+/// Makes each route run its handler in a `request` span tagged with the
+/// request ID, method and route name.
+pub fn traced(routes: Vec<Route>) -> Vec<Route> {
+    routes
+        .into_iter()
+        .map(|mut route| {
+            route.handler = Box::new(Traced(route.handler));
+            route
+        })
+        .collect()
+}
+
+/// The ID of the API key that authenticated a request, recorded by the `User` guard.
+#[derive(Debug, Clone, Default)]
+pub struct AuthenticatedKey(pub Option<String>);
+
+/// When the current request started.
+struct RequestStart(Instant);
+
+/// Assigns request IDs and logs every request.
+pub struct RequestLogger;
+
+#[rocket::async_trait]
+impl Fairing for RequestLogger {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request logger",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _: &mut Data<'_>) {
+        RequestId::of(req);
+        req.local_cache(|| RequestStart(Instant::now()));
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let request_id = RequestId::of(req);
+        let elapsed: Duration = req.local_cache(|| RequestStart(Instant::now())).0.elapsed();
+        let key_id = req.local_cache(AuthenticatedKey::default);
+        let route = req
+            .route()
+            .and_then(|route| route.name.as_deref())
+            .unwrap_or("unmatched");
+        let status = res.status().code;
+
+        if status >= 500 {
+            tracing::error!(
+                request_id = %request_id,
+                method = %req.method(),
+                path = %req.uri().path(),
+                route,
+                status,
+                duration_ms = elapsed.as_secs_f64() * 1000.0,
+                key_id = key_id.0.as_deref(),
+                "request failed"
+            );
+        } else {
+            tracing::info!(
+                request_id = %request_id,
+                method = %req.method(),
+                path = %req.uri().path(),
+                route,
+                status,
+                duration_ms = elapsed.as_secs_f64() * 1000.0,
+                key_id = key_id.0.as_deref(),
+                "request handled"
+            );
+        }
+
+        res.set_header(Header::new(REQUEST_ID_HEADER, request_id.0));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::{get, local::blocking::Client, routes};
+
+    #[get("/echo")]
+    fn echo(request_id: RequestId) -> String {
+        request_id.0
+    }
+
+    #[test]
+    fn test_request_id_header() {
+        let rocket = rocket::build()
+            .attach(RequestLogger)
+            .mount("/", routes![echo]);
+        let client = Client::tracked(rocket).expect("valid rocket");
+
+        let response = client.get("/echo").dispatch();
+        let header = response
+            .headers()
+            .get_one(REQUEST_ID_HEADER)
+            .unwrap()
+            .to_string();
+        assert_eq!(header.len(), 32);
+        assert_eq!(response.into_string().unwrap(), header);
+
+        let response = client
+            .get("/echo")
+            .header(Header::new(REQUEST_ID_HEADER, "frontend-123"))
+            .dispatch();
+        assert_eq!(
+            response.headers().get_one(REQUEST_ID_HEADER),
+            Some("frontend-123")
+        );
+
+        let response = client
+            .get("/missing")
+            .header(Header::new(REQUEST_ID_HEADER, "bad id"))
+            .dispatch();
+        assert_ne!(
+            response.headers().get_one(REQUEST_ID_HEADER),
+            Some("bad id")
+        );
+    }
+
+    #[get("/span")]
+    fn span() -> String {
+        tracing::Span::current()
+            .metadata()
+            .map(|metadata| metadata.name().to_string())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_traced_routes_run_in_request_span() {
+        let _guard = tracing::subscriber::set_default(tracing_subscriber::registry());
+        let rocket = rocket::build()
+            .mount("/", traced(routes![span]))
+            .mount("/plain", routes![span]);
+        let client = Client::tracked(rocket).expect("valid rocket");
+
+        assert_eq!(
+            client.get("/span").dispatch().into_string().unwrap(),
+            "request"
+        );
+        assert_eq!(
+            client.get("/plain/span").dispatch().into_string().unwrap(),
+            ""
+        );
+    }
+}
@@ -1,34 +1,41 @@
 //! # Apiodactyl - A RESTful API for data management on `https://bearodactyl.dev`
 //!
+//! Run `apiodactyl serve` to start the server, or `apiodactyl --help` for the
+//! administrative commands.
+//!
 //! ## Environment Variables
 //!
 //! - `DATABASE_URL` or `MONGODB_URL`: MongoDB connection string
 //! - `BOOTSTRAP_ADMIN_KEY`: Initial admin API key (optional, for first-time setup)
-#![feature(duration_constructors, str_as_str)]
+//! - `DEFAULT_REVIEW_WORK`: ObjectId of the work legacy chapter reviews belong to (optional)
+//! - `LOG_FORMAT`: `logfmt` (default) or `json` log output
+//! - `RUST_LOG`: Log level filter (default `info`)
+//! - `METRICS_REQUIRE_ADMIN`: Require an admin API key for `/metrics` (optional)
+//! - `ROCKET_TRASH_RETENTION_DAYS`: Days deleted documents stay in the trash (default 30)
+//! - `ROCKET_PUBLIC_URL`: Public base URL used for absolute feed links (optional)
+//! - `ROCKET_DOCS_ASSETS`: Directory of the Swagger UI and Redoc assets (default `assets/docs`)
 
-use rocket::{catchers, http::Method, launch, routes};
+use apiodactyl::{
+    auth::AuthService,
+    cli::{self, Cli, Commands},
+    db::BearoData,
+    handlers, logging, metrics, mount_api, trash, webhooks,
+};
+use clap::Parser;
+use rocket::{Build, Rocket, figment::Figment, http::Method};
 use rocket_cors::{AllowedOrigins, CorsOptions};
 use rocket_db_pools::Database;
+use std::process::ExitCode;
 
-use crate::{auth::AuthService, db::BearoData};
-
-pub mod auth;
-pub mod cli;
-pub mod db;
-pub mod errors;
-pub mod handlers;
-pub mod models;
-
-/// Main entry point for the Rocket application.
+/// Builds the Rocket application from the shared configuration.
 ///
-/// Initializes the authentication service, database connection, and CORS configuration.
-/// If command-line arguments are provided, handles CLI commands before starting the server.
+/// Initializes the authentication service, database connection, webhook delivery
+/// worker, trash purger, and CORS configuration.
 ///
 /// # Returns
 ///
 /// A configured Rocket instance ready for launch.
-#[launch]
-async fn rocket() -> _ {
+fn rocket(figment: Figment) -> Rocket<Build> {
     let auth_service = AuthService::new();
     let db = BearoData::init();
     let cors = CorsOptions::default()
@@ -47,33 +54,43 @@ async fn rocket() -> _ {
         )
         .allow_credentials(true);
 
-    if std::env::args().len() > 1 {
-        cli::handle_cli(auth_service.clone())
-            .await
-            .expect("Failed to handle CLI");
-    }
-
-    rocket::build()
+    mount_api(rocket::custom(figment))
         .manage(auth_service)
+        .manage(handlers::misc::Uptime::default())
         .attach(db)
+        .attach(metrics::MetricsFairing)
+        .attach(logging::RequestLogger)
+        .attach(webhooks::WebhookWorker)
+        .attach(trash::TrashPurger)
         .attach(cors.to_cors().expect("Failed to build cors"))
-        .register(
-            "/",
-            catchers![handlers::catch401, handlers::catch404, handlers::catch500],
-        )
-        .mount("/", routes![handlers::index])
-        .mount("/reviews", handlers::reviews::routes())
-        .mount(
-            "/wplace",
-            routes![
-                handlers::wplace::get_screenshot_by_id,
-                handlers::wplace::get_screenshots,
-                handlers::wplace::create_screenshot,
-                handlers::wplace::delete_screenshot
-            ],
-        )
-        .mount("/read-watch", handlers::books::routes())
-        .mount("/games", handlers::games::routes())
-        .mount("/projects", handlers::projects::routes())
-        .mount("/misc", handlers::misc::routes())
+}
+
+/// Main entry point.
+///
+/// `apiodactyl serve` launches the server; every other command runs against
+/// the database and exits.
+#[rocket::main]
+async fn main() -> ExitCode {
+    dotenvy::dotenv().ok();
+    let cli = Cli::parse();
+
+    if let Commands::Serve = cli.command {
+        logging::init();
+
+        return match rocket(cli.figment()).launch().await {
+            Ok(_) => ExitCode::SUCCESS,
+            Err(e) => {
+                eprintln!("{}", e);
+                ExitCode::FAILURE
+            }
+        };
+    }
+
+    match cli::run(cli, AuthService::new()).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
 }
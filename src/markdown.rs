@@ -0,0 +1,328 @@
+//! # Markdown fields
+//!
+//! Free-text fields such as review thoughts and project descriptions are
+//! stored as Markdown. This module validates that source on write and renders
+//! it to sanitized HTML when clients ask for `?render=html`.
+
+use {
+    crate::{
+        errors::MarkdownError,
+        models::{Game, LocalizedBook, LocalizedString, Project, Review},
+    },
+    html5ever::{
+        tendril::StrTendril,
+        tokenizer::{
+            self, BufferQueue, TagKind, Token, TokenSink, TokenSinkResult, Tokenizer, TokenizerOpts,
+        },
+    },
+    pulldown_cmark::{Event, Options, Parser, Tag, html},
+    rocket::{
+        FromFormField,
+        serde::{Deserialize, Serialize},
+    },
+    schemars::JsonSchema,
+    std::{cell::RefCell, collections::BTreeMap},
+};
+
+/// Link schemes allowed in links and images; relative links are always allowed.
+const SAFE_SCHEMES: &[&str] = &["http", "https", "mailto"];
+
+/// HTML elements rejected in raw HTML blocks.
+const UNSAFE_TAGS: &[&str] = &["script", "iframe", "object", "embed", "style"];
+
+/// HTML attributes holding URLs, checked like Markdown link destinations.
+const URL_ATTRIBUTES: &[&str] = &[
+    "href",
+    "src",
+    "action",
+    "formaction",
+    "poster",
+    "data",
+    "background",
+    "xlink:href",
+];
+
+/// How Markdown fields are returned by read endpoints.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, FromFormField)]
+pub enum Render {
+    /// Only the Markdown source (default)
+    Markdown,
+    /// The Markdown source plus sanitized HTML
+    Html,
+}
+
+/// Types with fields holding Markdown source.
+pub trait MarkdownFields {
+    /// Returns each Markdown field name with its source.
+    fn markdown_fields(&self) -> Vec<(&'static str, &str)>;
+}
+
+impl MarkdownFields for Review {
+    fn markdown_fields(&self) -> Vec<(&'static str, &str)> {
+        vec![("thoughts", &self.thoughts)]
+    }
+}
+
+impl MarkdownFields for LocalizedBook {
+    fn markdown_fields(&self) -> Vec<(&'static str, &str)> {
+        vec![("my_thoughts", &self.my_thoughts)]
+    }
+}
+
+impl MarkdownFields for Game {
+    fn markdown_fields(&self) -> Vec<(&'static str, &str)> {
+        vec![("my_thoughts", &self.my_thoughts)]
+    }
+}
+
+impl MarkdownFields for Project {
+    fn markdown_fields(&self) -> Vec<(&'static str, &str)> {
+        vec![("description", &self.description)]
+    }
+}
+
+/// A document served together with the rendered HTML of its Markdown fields.
+///
+/// The `html` map is keyed by field name and only present with `?render=html`.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+#[schemars(rename = "Rendered{T}")]
+pub struct Rendered<T> {
+    #[serde(flatten)]
+    pub item: T,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub html: Option<BTreeMap<String, String>>,
+}
+
+impl<T: MarkdownFields> Rendered<T> {
+    /// Wraps a document, rendering its Markdown fields when `render` is `Html`.
+    pub fn new(item: T, render: Option<Render>) -> Self {
+        let html = (render == Some(Render::Html)).then(|| {
+            item.markdown_fields()
+                .into_iter()
+                .map(|(field, source)| (field.to_string(), render_html(source)))
+                .collect()
+        });
+
+        Self { item, html }
+    }
+
+    /// Wraps a list of documents.
+    pub fn all(items: Vec<T>, render: Option<Render>) -> Vec<Self> {
+        items
+            .into_iter()
+            .map(|item| Self::new(item, render))
+            .collect()
+    }
+}
+
+fn options() -> Options {
+    Options::ENABLE_TABLES | Options::ENABLE_STRIKETHROUGH
+}
+
+/// Renders Markdown to HTML, stripping anything unsafe.
+pub fn render_html(source: &str) -> String {
+    let mut output = String::new();
+    html::push_html(&mut output, Parser::new_ext(source, options()));
+
+    ammonia::clean(&output)
+}
+
+fn is_safe_url(url: &str) -> bool {
+    let url: String = url
+        .chars()
+        .filter(|c| !c.is_whitespace() && !c.is_control())
+        .collect();
+
+    match url.split_once(':') {
+        Some((scheme, _)) if !scheme.contains(['/', '?', '#']) => {
+            SAFE_SCHEMES.contains(&scheme.to_lowercase().as_str())
+        }
+        _ => true,
+    }
+}
+
+/// Collects the start tags of a raw HTML fragment.
+#[derive(Default)]
+struct StartTags(RefCell<Vec<tokenizer::Tag>>);
+
+impl TokenSink for StartTags {
+    type Handle = ();
+
+    fn process_token(&self, token: Token, _line_number: u64) -> TokenSinkResult<()> {
+        if let Token::TagToken(tag) = token
+            && tag.kind == TagKind::StartTag
+        {
+            self.0.borrow_mut().push(tag);
+        }
+
+        TokenSinkResult::Continue
+    }
+}
+
+/// Tokenizes raw HTML the way a browser would, decoding character references
+/// in attribute values.
+fn start_tags(raw: &str) -> Vec<tokenizer::Tag> {
+    let tokenizer = Tokenizer::new(StartTags::default(), TokenizerOpts::default());
+    let input = BufferQueue::default();
+    input.push_back(StrTendril::from_slice(raw));
+    let _ = tokenizer.feed(&input);
+    tokenizer.end();
+
+    tokenizer.sink.0.take()
+}
+
+fn check_html(raw: &str) -> Result<(), MarkdownError> {
+    for tag in start_tags(raw) {
+        if UNSAFE_TAGS.contains(&&*tag.name) {
+            return Err(MarkdownError::UnsafeHtml(format!("<{}>", tag.name)));
+        }
+
+        for attribute in &tag.attrs {
+            let name = &*attribute.name.local;
+            if name.starts_with("on") {
+                return Err(MarkdownError::UnsafeHtml(name.to_string()));
+            }
+            if URL_ATTRIBUTES.contains(&name) && !is_safe_url(&attribute.value) {
+                return Err(MarkdownError::UnsafeLink(attribute.value.to_string()));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Checks Markdown source for scripts and unsafe links.
+///
+/// Consecutive raw HTML events are checked together, since an HTML block is
+/// split into one event per line.
+pub fn validate(source: &str) -> Result<(), MarkdownError> {
+    let mut raw_html = String::new();
+
+    for event in Parser::new_ext(source, options()) {
+        if let Event::Html(raw) | Event::InlineHtml(raw) = &event {
+            raw_html.push_str(raw);
+            continue;
+        }
+
+        check_html(&raw_html)?;
+        raw_html.clear();
+
+        match event {
+            Event::Start(Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. })
+                if !is_safe_url(&dest_url) =>
+            {
+                return Err(MarkdownError::UnsafeLink(dest_url.to_string()));
+            }
+            _ => {}
+        }
+    }
+
+    check_html(&raw_html)
+}
+
+/// Validates every translation of a localized Markdown field.
+pub fn validate_localized(source: &LocalizedString) -> Result<(), MarkdownError> {
+    match source {
+        LocalizedString::Simple(text) => validate(text),
+        LocalizedString::Localized(map) => map.values().try_for_each(|text| validate(text)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_html() {
+        assert_eq!(
+            render_html("**bold** and [link](https://example.com)"),
+            "<p><strong>bold</strong> and <a href=\"https://example.com\" rel=\"noopener noreferrer\">link</a></p>\n"
+        );
+        assert!(!render_html("<img src=x onerror=alert(1)>").contains("onerror"));
+        assert!(!render_html("[x](javascript:alert(1))").contains("javascript"));
+    }
+
+    #[test]
+    fn test_validate_accepts_plain_markdown() {
+        assert!(
+            validate("# Title\n\nSome *text* with a [link](https://a.b) and [rel](/books).")
+                .is_ok()
+        );
+        assert!(validate("mail me at <mailto:me@example.com>").is_ok());
+        assert!(validate("the online mode=fun").is_ok());
+        assert!(validate("<em>fine</em>").is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_scripts() {
+        assert_eq!(
+            validate("hi <script>alert(1)</script>"),
+            Err(MarkdownError::UnsafeHtml("<script>".to_string()))
+        );
+        assert!(validate("<div>\n<IFRAME src=\"https://a.b\"></iframe>\n</div>").is_err());
+        assert_eq!(
+            validate("<img src=x onerror=alert(1)>"),
+            Err(MarkdownError::UnsafeHtml("onerror".to_string()))
+        );
+        assert!(validate("<a href=\"java\tscript:alert(1)\">x</a>").is_err());
+    }
+
+    #[test]
+    fn test_validate_rejects_obfuscated_html() {
+        assert_eq!(
+            validate("<img src=x onerror =alert(1)>"),
+            Err(MarkdownError::UnsafeHtml("onerror".to_string()))
+        );
+        assert_eq!(
+            validate("<div>\n<img src=x onerror\n=alert(1)>\n</div>"),
+            Err(MarkdownError::UnsafeHtml("onerror".to_string()))
+        );
+        assert_eq!(
+            validate("<a href=\"jav&#x61;script:alert(1)\">x</a>"),
+            Err(MarkdownError::UnsafeLink("javascript:alert(1)".to_string()))
+        );
+        assert!(validate("<a href=\"https://example.com\" title=\"online\">x</a>").is_ok());
+    }
+
+    #[test]
+    fn test_validate_rejects_unsafe_links() {
+        assert!(validate("[x](javascript:alert(1))").is_err());
+        assert!(validate("![x](data:image/png;base64,AAAA)").is_err());
+        assert!(validate("[x](vbscript:msgbox)").is_err());
+    }
+
+    #[test]
+    fn test_rendered_only_includes_html_when_requested() {
+        let project = Project {
+            oid: mongodb::bson::oid::ObjectId::new(),
+            name: "apiodactyl".to_string(),
+            description: "*api*".to_string(),
+            tags: None,
+            source: String::new(),
+            cover_image: None,
+            install_command: None,
+            created_at: None,
+            updated_at: None,
+            deleted_at: None,
+        };
+
+        let rendered = Rendered::new(project, Some(Render::Html));
+        assert_eq!(
+            rendered.html.as_ref().unwrap()["description"],
+            "<p><em>api</em></p>\n"
+        );
+
+        let json = serde_json::to_value(&rendered).unwrap();
+        assert_eq!(json["description"], "*api*");
+        assert_eq!(json["html"]["description"], "<p><em>api</em></p>\n");
+
+        let rendered = Rendered::new(rendered.item, None);
+        assert!(
+            serde_json::to_value(&rendered)
+                .unwrap()
+                .get("html")
+                .is_none()
+        );
+    }
+}
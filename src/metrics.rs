@@ -0,0 +1,252 @@
+//! # Prometheus metrics
+//!
+//! Collects request, authentication and database metrics and renders them in
+//! the Prometheus text exposition format.
+//!
+//! - [`MetricsFairing`] records request counts and latencies per route name
+//! - [`MongoMetrics`] records and logs MongoDB command timings from driver events
+//! - API key cache hits and misses are recorded by [`crate::auth::AuthService`]
+//! - Document counts are refreshed whenever `/metrics` is scraped
+//!
+//! ## Configuration
+//!
+//! - `METRICS_REQUIRE_ADMIN`: when `true`, `/metrics` requires an admin API key
+
+use {
+    prometheus::{
+        Encoder, HistogramOpts, HistogramVec, IntCounterVec, IntGaugeVec, Opts, Registry,
+        TextEncoder,
+    },
+    rocket::{
+        Data, Request, Response,
+        fairing::{Fairing, Info, Kind},
+    },
+    rocket_db_pools::mongodb::event::command::{
+        CommandEventHandler, CommandFailedEvent, CommandSucceededEvent,
+    },
+    std::{
+        sync::LazyLock,
+        time::{Duration, Instant},
+    },
+};
+
+/// Every metric exported by the API.
+pub struct Metrics {
+    registry: Registry,
+    /// Requests by route, method and status code
+    pub requests: IntCounterVec,
+    /// Request latency by route and method
+    pub request_duration: HistogramVec,
+    /// API key cache lookups by result (`hit` or `miss`)
+    pub auth_cache: IntCounterVec,
+    /// MongoDB command latency by command name and outcome
+    pub mongo_duration: HistogramVec,
+    /// Documents per collection, as of the last scrape
+    pub documents: IntGaugeVec,
+}
+
+/// The process-wide metrics registry.
+pub static METRICS: LazyLock<Metrics> = LazyLock::new(Metrics::new);
+
+impl Metrics {
+    fn new() -> Self {
+        let registry = Registry::new_custom(Some("apiodactyl".to_string()), None)
+            .expect("metric prefix is valid");
+
+        let requests = IntCounterVec::new(
+            Opts::new("http_requests_total", "HTTP requests handled"),
+            &["route", "method", "status"],
+        )
+        .expect("metric options are valid");
+        let request_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "http_request_duration_seconds",
+                "HTTP request latency in seconds",
+            ),
+            &["route", "method"],
+        )
+        .expect("metric options are valid");
+        let auth_cache = IntCounterVec::new(
+            Opts::new("auth_cache_lookups_total", "API key cache lookups"),
+            &["result"],
+        )
+        .expect("metric options are valid");
+        let mongo_duration = HistogramVec::new(
+            HistogramOpts::new(
+                "mongo_command_duration_seconds",
+                "MongoDB command latency in seconds",
+            )
+            .buckets(vec![
+                0.0005, 0.001, 0.0025, 0.005, 0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5,
+            ]),
+            &["command", "outcome"],
+        )
+        .expect("metric options are valid");
+        let documents = IntGaugeVec::new(
+            Opts::new("collection_documents", "Documents per collection"),
+            &["collection"],
+        )
+        .expect("metric options are valid");
+
+        for collector in [
+            Box::new(requests.clone()) as Box<dyn prometheus::core::Collector>,
+            Box::new(request_duration.clone()),
+            Box::new(auth_cache.clone()),
+            Box::new(mongo_duration.clone()),
+            Box::new(documents.clone()),
+        ] {
+            registry
+                .register(collector)
+                .expect("metrics are registered once");
+        }
+
+        Self {
+            registry,
+            requests,
+            request_duration,
+            auth_cache,
+            mongo_duration,
+            documents,
+        }
+    }
+
+    /// Records an API key cache lookup.
+    pub fn record_auth_cache(&self, hit: bool) {
+        self.auth_cache
+            .with_label_values(&[if hit { "hit" } else { "miss" }])
+            .inc();
+    }
+
+    /// Records a handled request.
+    pub fn record_request(&self, route: &str, method: &str, status: u16, elapsed: Duration) {
+        self.requests
+            .with_label_values(&[route, method, &status.to_string()])
+            .inc();
+        self.request_duration
+            .with_label_values(&[route, method])
+            .observe(elapsed.as_secs_f64());
+    }
+
+    /// Renders every metric in the Prometheus text format.
+    pub fn render(&self) -> String {
+        let mut buffer = Vec::new();
+        TextEncoder::new()
+            .encode(&self.registry.gather(), &mut buffer)
+            .expect("text encoding does not fail");
+        String::from_utf8(buffer).unwrap_or_default()
+    }
+}
+
+/// Whether `/metrics` requires an admin API key.
+pub fn require_admin() -> bool {
+    std::env::var("METRICS_REQUIRE_ADMIN").is_ok_and(|value| value == "true" || value == "1")
+}
+
+/// When the current request started.
+struct RequestStart(Instant);
+
+/// Records request counts and latencies.
+pub struct MetricsFairing;
+
+#[rocket::async_trait]
+impl Fairing for MetricsFairing {
+    fn info(&self) -> Info {
+        Info {
+            name: "Prometheus metrics",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _: &mut Data<'_>) {
+        req.local_cache(|| RequestStart(Instant::now()));
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let started = req.local_cache(|| RequestStart(Instant::now()));
+        let route = req
+            .route()
+            .and_then(|route| route.name.as_deref())
+            .unwrap_or("unmatched");
+
+        METRICS.record_request(
+            route,
+            req.method().as_str(),
+            res.status().code,
+            started.0.elapsed(),
+        );
+    }
+}
+
+/// Records MongoDB command timings and logs each command at `debug` level.
+///
+/// Driver events fire inside the task running the command, so they are logged
+/// within the calling handler's span.
+pub struct MongoMetrics;
+
+impl CommandEventHandler for MongoMetrics {
+    fn handle_command_succeeded_event(&self, event: CommandSucceededEvent) {
+        tracing::debug!(
+            target: "mongo",
+            command = %event.command_name,
+            mongo_request_id = event.request_id,
+            duration_ms = event.duration.as_secs_f64() * 1000.0,
+            "command succeeded"
+        );
+        METRICS
+            .mongo_duration
+            .with_label_values(&[&event.command_name, "success"])
+            .observe(event.duration.as_secs_f64());
+    }
+
+    fn handle_command_failed_event(&self, event: CommandFailedEvent) {
+        tracing::warn!(
+            target: "mongo",
+            command = %event.command_name,
+            mongo_request_id = event.request_id,
+            duration_ms = event.duration.as_secs_f64() * 1000.0,
+            error = %event.failure,
+            "command failed"
+        );
+        METRICS
+            .mongo_duration
+            .with_label_values(&[&event.command_name, "failure"])
+            .observe(event.duration.as_secs_f64());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rocket::{get, http::Status, local::blocking::Client, routes};
+
+    #[get("/ping")]
+    fn ping() -> &'static str {
+        "pong"
+    }
+
+    #[test]
+    fn test_fairing_records_requests() {
+        let rocket = rocket::build()
+            .attach(MetricsFairing)
+            .mount("/", routes![ping]);
+        let client = Client::tracked(rocket).expect("valid rocket");
+
+        assert_eq!(client.get("/ping").dispatch().status(), Status::Ok);
+        assert_eq!(client.get("/nope").dispatch().status(), Status::NotFound);
+
+        let ok = METRICS.requests.with_label_values(&["ping", "GET", "200"]);
+        let missing = METRICS
+            .requests
+            .with_label_values(&["unmatched", "GET", "404"]);
+        assert!(ok.get() >= 1);
+        assert!(missing.get() >= 1);
+
+        METRICS.record_auth_cache(true);
+        let output = METRICS.render();
+        assert!(output.contains(
+            "apiodactyl_http_requests_total{method=\"GET\",route=\"ping\",status=\"200\"}"
+        ));
+        assert!(output.contains("apiodactyl_http_request_duration_seconds_bucket"));
+        assert!(output.contains("apiodactyl_auth_cache_lookups_total{result=\"hit\"}"));
+    }
+}
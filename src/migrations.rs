@@ -0,0 +1,573 @@
+//! # Database migrations
+//!
+//! One-off data migrations. Each applied migration is recorded in the
+//! `migrations` collection so it can be reported and not silently re-run.
+//!
+//! ## Available Migrations
+//!
+//! - `review-works`: Assigns chapter reviews without a parent work to a default work
+//!   and makes chapters unique per work
+//! - `game-status`: Normalizes free-text game statuses and clamps completion percentages
+//! - `timestamps`: Back-fills `created_at`/`updated_at` from document ids and makes
+//!   revision numbers unique per document
+//! - `review-index`: Limits the unique `(work_id, chapter)` review index to reviews
+//!   that are not in the trash
+//! - `taxonomy-index`: Merges taxonomy terms sharing a slug and makes `(kind, slug)` unique
+
+use {
+    crate::{
+        errors::MigrationError,
+        handlers::reviews::find_work_kind,
+        history::{REVISION_INDEX, REVISIONS},
+        models::{GameStatus, Review, TaxonomyTerm, clamp_percent},
+    },
+    chrono::NaiveDateTime,
+    mongodb::bson::{Bson, Document, doc, oid::ObjectId},
+    rocket::futures::TryStreamExt,
+    rocket_db_pools::mongodb::{
+        Client, IndexModel,
+        options::{FindOptions, IndexOptions, ReplaceOptions},
+    },
+    schemars::JsonSchema,
+    serde::{Deserialize, Serialize},
+};
+
+/// Name of the migration attaching reviews to works.
+pub const REVIEW_WORKS: &str = "review-works";
+
+/// Name of the migration normalizing game statuses.
+pub const GAME_STATUS: &str = "game-status";
+
+/// Name of the migration back-filling document timestamps.
+pub const TIMESTAMPS: &str = "timestamps";
+
+/// Name of the migration limiting the review index to live reviews.
+pub const REVIEW_INDEX: &str = "review-index";
+
+/// Name of the migration making taxonomy slugs unique.
+pub const TAXONOMY_INDEX: &str = "taxonomy-index";
+
+/// Every known migration, in the order they were introduced.
+pub const ALL_MIGRATIONS: [&str; 5] = [
+    REVIEW_WORKS,
+    GAME_STATUS,
+    TIMESTAMPS,
+    REVIEW_INDEX,
+    TAXONOMY_INDEX,
+];
+
+/// Name of the unique `(work_id, chapter)` review index.
+pub const REVIEW_INDEX_NAME: &str = "work_chapter_unique";
+
+/// Name of the unique `(kind, slug)` taxonomy index.
+pub const TAXONOMY_INDEX_NAME: &str = "kind_slug_unique";
+
+/// Collections whose documents carry `created_at`/`updated_at`.
+const TIMESTAMPED_COLLECTIONS: [&str; 5] = [
+    "books",
+    "games",
+    "projects",
+    "reviews",
+    "wplace_screenshots",
+];
+
+/// A record of an applied migration.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct MigrationRecord {
+    /// Migration name
+    #[serde(rename = "_id")]
+    pub name: String,
+    /// When the migration was last applied
+    pub applied_at: NaiveDateTime,
+    /// Number of documents changed
+    pub affected: u64,
+}
+
+/// Returns the default review work from the `DEFAULT_REVIEW_WORK` environment variable.
+pub fn default_review_work() -> Option<ObjectId> {
+    std::env::var("DEFAULT_REVIEW_WORK")
+        .ok()
+        .and_then(|id| ObjectId::parse_str(id.trim()).ok())
+}
+
+/// Lists every applied migration.
+pub async fn applied_migrations(client: &Client) -> Result<Vec<MigrationRecord>, MigrationError> {
+    let records = client
+        .database("bearodata")
+        .collection::<MigrationRecord>("migrations")
+        .find(doc! {}, None)
+        .await?
+        .try_collect()
+        .await?;
+
+    Ok(records)
+}
+
+async fn record_migration(
+    client: &Client,
+    name: &str,
+    affected: u64,
+) -> Result<(), MigrationError> {
+    let record = MigrationRecord {
+        name: name.to_string(),
+        applied_at: chrono::Utc::now().naive_utc(),
+        affected,
+    };
+
+    client
+        .database("bearodata")
+        .collection::<MigrationRecord>("migrations")
+        .replace_one(
+            doc! { "_id": name },
+            &record,
+            ReplaceOptions::builder().upsert(true).build(),
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Assigns every review without a parent work to `work_id`.
+///
+/// Also creates the unique `(work_id, chapter)` index used to reject duplicate
+/// chapter reviews within a work; see [`ensure_review_index`].
+///
+/// # Returns
+///
+/// The number of reviews that were reassigned.
+pub async fn migrate_review_works(
+    client: &Client,
+    work_id: ObjectId,
+) -> Result<u64, MigrationError> {
+    let work_kind = find_work_kind(client, work_id)
+        .await?
+        .ok_or(MigrationError::WorkNotFound(work_id))?;
+
+    let collection = client.database("bearodata").collection::<Review>("reviews");
+
+    let result = collection
+        .update_many(
+            doc! { "work_id": { "$exists": false } },
+            doc! { "$set": {
+                "work_id": work_id,
+                "work_kind": mongodb::bson::to_bson(&work_kind)?,
+            } },
+            None,
+        )
+        .await?;
+
+    ensure_review_index(client).await?;
+
+    record_migration(client, REVIEW_WORKS, result.modified_count).await?;
+
+    Ok(result.modified_count)
+}
+
+/// Creates the unique `(work_id, chapter)` review index over live reviews.
+///
+/// The index only covers reviews whose `deleted_at` is an explicit `null`, so
+/// a trashed review keeps its chapter without blocking a new review of it.
+/// Reviews without `deleted_at` are given one, and an older index covering
+/// every review is dropped first.
+///
+/// # Returns
+///
+/// The number of reviews that were given a `deleted_at`.
+async fn ensure_review_index(client: &Client) -> Result<u64, MigrationError> {
+    let collection = client
+        .database("bearodata")
+        .collection::<Document>("reviews");
+
+    let result = collection
+        .update_many(
+            doc! { "deleted_at": { "$exists": false } },
+            doc! { "$set": { "deleted_at": Bson::Null } },
+            None,
+        )
+        .await?;
+
+    let indexes: Vec<IndexModel> = collection.list_indexes(None).await?.try_collect().await?;
+    let outdated = indexes.iter().any(|index| {
+        index.options.as_ref().is_some_and(|options| {
+            options.name.as_deref() == Some(REVIEW_INDEX_NAME)
+                && options.partial_filter_expression.is_none()
+        })
+    });
+    if outdated {
+        collection.drop_index(REVIEW_INDEX_NAME, None).await?;
+    }
+
+    collection
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "work_id": 1, "chapter": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .name(REVIEW_INDEX_NAME.to_string())
+                        .unique(true)
+                        .partial_filter_expression(doc! { "deleted_at": { "$type": "null" } })
+                        .build(),
+                )
+                .build(),
+            None,
+        )
+        .await?;
+
+    Ok(result.modified_count)
+}
+
+/// Rebuilds the unique review index so it ignores trashed reviews.
+///
+/// # Returns
+///
+/// The number of reviews that were given a `deleted_at`.
+pub async fn migrate_review_index(client: &Client) -> Result<u64, MigrationError> {
+    let affected = ensure_review_index(client).await?;
+
+    record_migration(client, REVIEW_INDEX, affected).await?;
+
+    Ok(affected)
+}
+
+/// Returns the normalized status and percent for a stored game, if either changes.
+///
+/// Unknown statuses fall back to `backlog`.
+fn normalize_game(game: &Document) -> Option<(GameStatus, i32)> {
+    let raw_status = game.get_str("status").unwrap_or_default();
+    let status = GameStatus::parse(raw_status).unwrap_or_default();
+
+    let raw_percent = game
+        .get_i32("percent")
+        .or_else(|_| game.get_i64("percent").map(|percent| percent as i32))
+        .unwrap_or_default();
+    let percent = clamp_percent(raw_percent);
+
+    let has_percent = game.get_i32("percent").is_ok();
+    if raw_status == status.as_str() && raw_percent == percent && has_percent {
+        None
+    } else {
+        Some((status, percent))
+    }
+}
+
+/// Rewrites every game's free-text status to a [`GameStatus`] value and clamps
+/// `percent` to 0–100.
+///
+/// # Returns
+///
+/// The number of games that were changed.
+pub async fn migrate_game_status(client: &Client) -> Result<u64, MigrationError> {
+    let collection = client.database("bearodata").collection::<Document>("games");
+    let games: Vec<Document> = collection.find(doc! {}, None).await?.try_collect().await?;
+
+    let mut affected = 0;
+    for game in games {
+        let (Ok(oid), Some((status, percent))) = (game.get_object_id("_id"), normalize_game(&game))
+        else {
+            continue;
+        };
+
+        collection
+            .update_one(
+                doc! { "_id": oid },
+                doc! { "$set": { "status": status.as_str(), "percent": percent } },
+                None,
+            )
+            .await?;
+        affected += 1;
+    }
+
+    record_migration(client, GAME_STATUS, affected).await?;
+
+    Ok(affected)
+}
+
+/// Returns the `$set` update adding missing timestamps to a stored document.
+///
+/// `created_at` falls back to the creation time encoded in the document's id,
+/// `updated_at` to `created_at`.
+fn backfill_timestamps(document: &Document) -> Option<Document> {
+    if document.contains_key("created_at") && document.contains_key("updated_at") {
+        return None;
+    }
+
+    let mut set = Document::new();
+    let created_at = match document.get("created_at") {
+        Some(created_at) => created_at.clone(),
+        None => {
+            let millis = document
+                .get_object_id("_id")
+                .ok()?
+                .timestamp()
+                .timestamp_millis();
+            let created_at = chrono::DateTime::from_timestamp_millis(millis)?.naive_utc();
+            let created_at = mongodb::bson::to_bson(&created_at).ok()?;
+            set.insert("created_at", created_at.clone());
+            created_at
+        }
+    };
+    if !document.contains_key("updated_at") {
+        set.insert("updated_at", created_at);
+    }
+
+    Some(set)
+}
+
+/// Renumbers the revisions of documents where concurrent writes recorded two
+/// revisions under the same number, in the order they were recorded.
+///
+/// # Returns
+///
+/// The number of revisions that were renumbered.
+async fn renumber_revisions(client: &Client) -> Result<u64, MigrationError> {
+    let revisions = client
+        .database("bearodata")
+        .collection::<Document>(REVISIONS);
+
+    let duplicates: Vec<Document> = revisions
+        .aggregate(
+            [
+                doc! { "$group": {
+                    "_id": { "collection": "$collection", "document_id": "$document_id", "number": "$number" },
+                    "count": { "$sum": 1 },
+                } },
+                doc! { "$match": { "count": { "$gt": 1 } } },
+                doc! { "$group": {
+                    "_id": { "collection": "$_id.collection", "document_id": "$_id.document_id" },
+                } },
+            ],
+            None,
+        )
+        .await?
+        .try_collect()
+        .await?;
+
+    let mut affected = 0;
+    for duplicate in duplicates {
+        let Ok(filter) = duplicate.get_document("_id") else {
+            continue;
+        };
+        let ordered: Vec<Document> = revisions
+            .find(
+                filter.clone(),
+                FindOptions::builder()
+                    .sort(doc! { "number": 1, "recorded_at": 1, "_id": 1 })
+                    .projection(doc! { "number": 1 })
+                    .build(),
+            )
+            .await?
+            .try_collect()
+            .await?;
+
+        for (number, revision) in (1..).zip(ordered) {
+            let Ok(oid) = revision.get_object_id("_id") else {
+                continue;
+            };
+            if revision.get_i64("number").ok() == Some(number)
+                || revision.get_i32("number").ok().map(i64::from) == Some(number)
+            {
+                continue;
+            }
+            revisions
+                .update_one(
+                    doc! { "_id": oid },
+                    doc! { "$set": { "number": number } },
+                    None,
+                )
+                .await?;
+            affected += 1;
+        }
+    }
+
+    Ok(affected)
+}
+
+/// Adds `created_at`/`updated_at` to documents written before they existed.
+///
+/// Also creates the unique `(collection, document_id, number)` revision index,
+/// renumbering revisions that share a number first.
+///
+/// # Returns
+///
+/// The number of documents that were changed.
+pub async fn migrate_timestamps(client: &Client) -> Result<u64, MigrationError> {
+    let database = client.database("bearodata");
+
+    let mut affected = 0;
+    for name in TIMESTAMPED_COLLECTIONS {
+        let collection = database.collection::<Document>(name);
+        let documents: Vec<Document> = collection
+            .find(
+                doc! { "$or": [
+                    { "created_at": { "$exists": false } },
+                    { "updated_at": { "$exists": false } },
+                ] },
+                None,
+            )
+            .await?
+            .try_collect()
+            .await?;
+
+        for document in documents {
+            let (Ok(oid), Some(set)) = (
+                document.get_object_id("_id"),
+                backfill_timestamps(&document),
+            ) else {
+                continue;
+            };
+
+            collection
+                .update_one(doc! { "_id": oid }, doc! { "$set": set }, None)
+                .await?;
+            affected += 1;
+        }
+    }
+
+    affected += renumber_revisions(client).await?;
+    database
+        .collection::<Document>(REVISIONS)
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "collection": 1, "document_id": 1, "number": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .name(REVISION_INDEX.to_string())
+                        .unique(true)
+                        .build(),
+                )
+                .build(),
+            None,
+        )
+        .await?;
+
+    record_migration(client, TIMESTAMPS, affected).await?;
+
+    Ok(affected)
+}
+
+/// Merges taxonomy terms sharing a kind and slug into the oldest of them,
+/// then creates the unique `(kind, slug)` taxonomy index.
+///
+/// The merged terms' aliases are kept on the surviving term.
+///
+/// # Returns
+///
+/// The number of terms that were merged away.
+pub async fn migrate_taxonomy_index(client: &Client) -> Result<u64, MigrationError> {
+    let collection = client
+        .database("bearodata")
+        .collection::<TaxonomyTerm>("taxonomy");
+
+    let duplicates: Vec<Document> = collection
+        .aggregate(
+            [
+                doc! { "$group": {
+                    "_id": { "kind": "$kind", "slug": "$slug" },
+                    "count": { "$sum": 1 },
+                } },
+                doc! { "$match": { "count": { "$gt": 1 } } },
+            ],
+            None,
+        )
+        .await?
+        .try_collect()
+        .await?;
+
+    let mut affected = 0;
+    for duplicate in duplicates {
+        let Ok(filter) = duplicate.get_document("_id") else {
+            continue;
+        };
+        let mut terms: Vec<TaxonomyTerm> = collection
+            .find(
+                filter.clone(),
+                FindOptions::builder().sort(doc! { "_id": 1 }).build(),
+            )
+            .await?
+            .try_collect()
+            .await?;
+        if terms.len() < 2 {
+            continue;
+        }
+
+        let merged = terms.split_off(1);
+        let mut kept = terms.remove(0);
+        for term in &merged {
+            for alias in &term.aliases {
+                if !kept.aliases.contains(alias) {
+                    kept.aliases.push(alias.clone());
+                }
+            }
+        }
+
+        collection
+            .update_one(
+                doc! { "_id": kept.oid },
+                doc! { "$set": { "aliases": &kept.aliases } },
+                None,
+            )
+            .await?;
+        let merged_ids: Vec<ObjectId> = merged.iter().map(|term| term.oid).collect();
+        affected += collection
+            .delete_many(doc! { "_id": { "$in": merged_ids } }, None)
+            .await?
+            .deleted_count;
+    }
+
+    collection
+        .create_index(
+            IndexModel::builder()
+                .keys(doc! { "kind": 1, "slug": 1 })
+                .options(
+                    IndexOptions::builder()
+                        .name(TAXONOMY_INDEX_NAME.to_string())
+                        .unique(true)
+                        .build(),
+                )
+                .build(),
+            None,
+        )
+        .await?;
+
+    record_migration(client, TAXONOMY_INDEX, affected).await?;
+
+    Ok(affected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backfill_timestamps() {
+        let oid = ObjectId::parse_str("67d3f6ad0000000000000000").unwrap();
+        let set = backfill_timestamps(&doc! { "_id": oid }).unwrap();
+        assert_eq!(set.get_str("created_at"), Ok("2025-03-14T09:28:13"));
+        assert_eq!(set.get("updated_at"), set.get("created_at"));
+
+        let set =
+            backfill_timestamps(&doc! { "_id": oid, "created_at": "2024-01-01T00:00:00" }).unwrap();
+        assert!(!set.contains_key("created_at"));
+        assert_eq!(set.get_str("updated_at"), Ok("2024-01-01T00:00:00"));
+
+        let stamped = doc! { "_id": oid, "created_at": "a", "updated_at": "b" };
+        assert_eq!(backfill_timestamps(&stamped), None);
+    }
+
+    #[test]
+    fn test_normalize_game() {
+        assert_eq!(
+            normalize_game(&doc! { "status": "On Hold", "percent": 140 }),
+            Some((GameStatus::Paused, 100))
+        );
+        assert_eq!(
+            normalize_game(&doc! { "status": "whatever", "percent": 20 }),
+            Some((GameStatus::Backlog, 20))
+        );
+        assert_eq!(
+            normalize_game(&doc! { "status": "100%", "percent": 100 }),
+            None
+        );
+    }
+}
@@ -4,12 +4,14 @@
 //! including database models, request/response DTOs, and localization support.
 
 use {
+    crate::openapi::ObjectIdSchema,
     chrono::NaiveDateTime,
-    mongodb::bson::{doc, oid::ObjectId},
+    mongodb::bson::{Document, doc, oid::ObjectId},
     rocket::{
-        Request,
-        request::{FromRequest, Outcome},
+        FromFormField, Request,
+        request::{FromParam, FromRequest, Outcome},
     },
+    schemars::JsonSchema,
     serde::{Deserialize, Serialize},
     std::collections::HashMap,
 };
@@ -37,7 +39,7 @@ pub struct Locale(pub Option<String>);
 ///   "fr": "Bonjour le monde"
 /// }
 /// ```
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
 #[serde(untagged)]
 pub enum LocalizedString {
     /// A simple non-localized string
@@ -81,10 +83,16 @@ impl LocalizedString {
     }
 }
 
+impl Default for LocalizedString {
+    fn default() -> Self {
+        LocalizedString::Simple(String::new())
+    }
+}
+
 /// An array of strings that can be either simple or localized.
 ///
 /// Similar to LocalizedString but for arrays of strings.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 #[serde(untagged)]
 pub enum LocalizedStringArray {
     /// A simple array of non-localized strings
@@ -114,13 +122,44 @@ impl LocalizedStringArray {
     }
 }
 
+/// The kind of work a review can be attached to.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum WorkKind {
+    /// An entry in the read-watch catalog (`books` collection)
+    Book,
+    /// A series of read-watch entries (`series` collection)
+    Series,
+    /// A game (`games` collection)
+    Game,
+}
+
+impl WorkKind {
+    /// Returns the collection holding works of this kind.
+    pub fn collection(&self) -> &'static str {
+        match self {
+            WorkKind::Book => "books",
+            WorkKind::Series => "series",
+            WorkKind::Game => "games",
+        }
+    }
+}
+
 /// Represents a review in the database.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct Review {
     /// MongoDB ObjectId
     #[serde(rename = "_id")]
+    #[schemars(with = "ObjectIdSchema")]
     pub oid: ObjectId,
+    /// The book, series or game being reviewed
+    #[serde(default)]
+    #[schemars(with = "Option<ObjectIdSchema>")]
+    pub work_id: Option<ObjectId>,
+    /// The kind of work being reviewed
+    #[serde(default)]
+    pub work_kind: Option<WorkKind>,
     /// Chapter number being reviewed
     pub chapter: i32,
     /// Description of the review
@@ -129,12 +168,26 @@ pub struct Review {
     pub rating: i32,
     /// Personal thoughts about the chapter
     pub thoughts: String,
+    /// When the document was created
+    #[serde(default)]
+    pub created_at: Option<NaiveDateTime>,
+    /// When the document was last written
+    #[serde(default)]
+    pub updated_at: Option<NaiveDateTime>,
+    /// When the document was moved to the trash; stored as `null` otherwise,
+    /// as the unique chapter index only covers those reviews
+    #[serde(default)]
+    pub deleted_at: Option<NaiveDateTime>,
 }
 
 /// Data transfer object for creating a new review.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct NewReview {
+    /// The work being reviewed; taken from the route when nested under a work
+    #[serde(default)]
+    #[schemars(with = "Option<ObjectIdSchema>")]
+    pub work_id: Option<ObjectId>,
     /// Chapter number being reviewed
     pub chapter: i32,
     /// Description of the review
@@ -147,46 +200,61 @@ pub struct NewReview {
 
 /// Data transfer object for updating an existing review.
 /// All fields are optional to support partial updates.
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct UpdateReview {
     /// Updated chapter number
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub chapter: Option<i32>,
     /// Updated description
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub description: Option<String>,
     /// Updated rating
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub rating: Option<i32>,
     /// Updated thoughts
+    #[serde(skip_serializing_if = "Option::is_none")]
     pub thoughts: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct WplaceScreenshot {
     #[serde(rename = "_id")]
+    #[schemars(with = "ObjectIdSchema")]
     pub oid: ObjectId,
     pub alt: String,
     pub cover_image: String,
+    /// When the document was created
+    #[serde(default)]
+    pub created_at: Option<NaiveDateTime>,
+    /// When the document was last written
+    #[serde(default)]
+    pub updated_at: Option<NaiveDateTime>,
+    /// When the document was moved to the trash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<NaiveDateTime>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct NewWplaceScreenshot {
     pub alt: String,
     pub cover_image: String,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct UpdateWplaceScreenshot {
     alt: Option<String>,
     cover_image: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct Project {
     #[serde(rename = "_id")]
+    #[schemars(with = "ObjectIdSchema")]
     pub oid: ObjectId,
     pub name: String,
     pub description: String,
@@ -194,9 +262,18 @@ pub struct Project {
     pub source: String,
     pub cover_image: Option<String>,
     pub install_command: Option<String>,
+    /// When the document was created
+    #[serde(default)]
+    pub created_at: Option<NaiveDateTime>,
+    /// When the document was last written
+    #[serde(default)]
+    pub updated_at: Option<NaiveDateTime>,
+    /// When the document was moved to the trash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<NaiveDateTime>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct NewProject {
     pub name: String,
@@ -207,7 +284,7 @@ pub struct NewProject {
     pub install_command: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct UpdateProject {
     pub name: Option<String>,
@@ -219,15 +296,17 @@ pub struct UpdateProject {
 }
 
 /// Represents a book in the database with full localization support.
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct Book {
     /// MongoDB ObjectId
     #[serde(rename = "_id")]
+    #[schemars(with = "ObjectIdSchema")]
     pub oid: ObjectId,
     /// Book title (localized)
     pub title: LocalizedString,
-    /// Book author (localized)
+    /// Book author (localized); empty for media credited to a studio
+    #[serde(default)]
     pub author: LocalizedString,
     /// List of genres (localized)
     pub genres: LocalizedStringArray,
@@ -249,12 +328,38 @@ pub struct Book {
     pub explicit: bool,
     /// Optional color theme for UI display
     pub color: Option<String>,
+    /// Structured reading/watching progress
+    #[serde(default)]
+    pub progress: Option<ReadingProgress>,
+    /// Kind of media; documents without one are books
+    #[serde(default)]
+    pub media_type: MediaType,
+    /// Fields specific to the media type
+    #[serde(flatten, default)]
+    pub details: MediaDetails,
+    /// The series this book belongs to
+    #[serde(default)]
+    #[schemars(with = "Option<ObjectIdSchema>")]
+    pub series_id: Option<ObjectId>,
+    /// Position within the series (e.g., 1, 2, 2.5)
+    #[serde(default)]
+    pub series_index: Option<f64>,
+    /// When the document was created
+    #[serde(default)]
+    pub created_at: Option<NaiveDateTime>,
+    /// When the document was last written
+    #[serde(default)]
+    pub updated_at: Option<NaiveDateTime>,
+    /// When the document was moved to the trash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<NaiveDateTime>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct NewBook {
     pub title: LocalizedString,
+    #[serde(default)]
     pub author: LocalizedString,
     pub genres: LocalizedStringArray,
     pub tags: LocalizedStringArray,
@@ -266,9 +371,20 @@ pub struct NewBook {
     pub cover_image: String,
     pub explicit: bool,
     pub color: Option<String>,
+    #[serde(default)]
+    pub progress: Option<ReadingProgress>,
+    #[serde(default)]
+    pub media_type: MediaType,
+    #[serde(flatten, default)]
+    pub details: MediaDetails,
+    #[serde(default)]
+    #[schemars(with = "Option<ObjectIdSchema>")]
+    pub series_id: Option<ObjectId>,
+    #[serde(default)]
+    pub series_index: Option<f64>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct UpdateBook {
     pub title: Option<LocalizedString>,
@@ -283,19 +399,502 @@ pub struct UpdateBook {
     pub cover_image: Option<String>,
     pub explicit: Option<bool>,
     pub color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media_type: Option<MediaType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volumes: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub episodes: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runtime_minutes: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub studio: Option<LocalizedString>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<ObjectIdSchema>")]
+    pub series_id: Option<ObjectId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub series_index: Option<f64>,
+}
+
+/// A series grouping several volumes of the read-watch catalog.
+///
+/// Members are the books whose `series_id` points at the series, ordered by
+/// their `series_index`.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct Series {
+    /// MongoDB ObjectId
+    #[serde(rename = "_id")]
+    #[schemars(with = "ObjectIdSchema")]
+    pub oid: ObjectId,
+    /// Series title (localized)
+    pub title: LocalizedString,
+    /// Series description (localized)
+    pub description: Option<LocalizedString>,
+    /// URL to a cover image for the whole series
+    pub cover_image: Option<String>,
+}
+
+/// Reading progress aggregated over the members of a series.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct SeriesProgress {
+    pub total: usize,
+    pub planned: usize,
+    pub reading: usize,
+    pub completed: usize,
+    pub dropped: usize,
+}
+
+/// A series with resolved strings, localized members and aggregates.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct LocalizedSeries {
+    #[serde(rename = "_id")]
+    #[schemars(with = "ObjectIdSchema")]
+    pub oid: ObjectId,
+    pub title: String,
+    pub description: Option<String>,
+    pub cover_image: Option<String>,
+    pub members: Vec<LocalizedBook>,
+    /// Average rating over rated members
+    pub average_rating: Option<f64>,
+    pub progress: SeriesProgress,
+}
+
+/// Data transfer object for creating a new series.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct NewSeries {
+    pub title: LocalizedString,
+    pub description: Option<LocalizedString>,
+    pub cover_image: Option<String>,
+}
+
+/// Data transfer object for updating a series.
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct UpdateSeries {
+    pub title: Option<LocalizedString>,
+    pub description: Option<LocalizedString>,
+    pub cover_image: Option<String>,
+}
+
+/// The kind of media an entry in the read-watch catalog represents.
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, FromFormField, JsonSchema,
+)]
+#[serde(crate = "rocket::serde", rename_all = "snake_case")]
+pub enum MediaType {
+    #[default]
+    Book,
+    Manga,
+    #[field(value = "light_novel")]
+    LightNovel,
+    Anime,
+    Show,
+    Film,
+}
+
+impl MediaType {
+    /// Returns the value stored in the `media_type` field.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MediaType::Book => "book",
+            MediaType::Manga => "manga",
+            MediaType::LightNovel => "light_novel",
+            MediaType::Anime => "anime",
+            MediaType::Show => "show",
+            MediaType::Film => "film",
+        }
+    }
+
+    /// Whether this media is written and credited to an author.
+    pub fn is_written(&self) -> bool {
+        matches!(
+            self,
+            MediaType::Book | MediaType::Manga | MediaType::LightNovel
+        )
+    }
+
+    /// Detail fields that do not apply to this media type.
+    pub fn inapplicable_fields(&self) -> &'static [&'static str] {
+        match self {
+            MediaType::Book | MediaType::Manga | MediaType::LightNovel => {
+                &["episodes", "runtime_minutes", "studio"]
+            }
+            MediaType::Anime | MediaType::Show => &["volumes"],
+            MediaType::Film => &["volumes", "episodes"],
+        }
+    }
+
+    /// Validates the type-specific fields of an entry.
+    ///
+    /// Written media need an author and may have volumes; anime and shows may
+    /// have episodes; screen media may have a runtime and a studio.
+    pub fn validate(&self, author: &LocalizedString, details: &MediaDetails) -> Result<(), String> {
+        if self.is_written() && author.get_text(None).trim().is_empty() {
+            return Err(format!("{} entries require an author", self.as_str()));
+        }
+        if let Some(field) = details
+            .set_fields()
+            .into_iter()
+            .find(|field| self.inapplicable_fields().contains(field))
+        {
+            return Err(format!(
+                "`{}` is not applicable to {} entries",
+                field,
+                self.as_str()
+            ));
+        }
+
+        for (field, value) in [
+            ("volumes", details.volumes),
+            ("episodes", details.episodes),
+            ("runtime_minutes", details.runtime_minutes),
+        ] {
+            if value.is_some_and(|value| value <= 0) {
+                return Err(format!("`{}` must be positive", field));
+            }
+        }
+
+        Ok(())
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// Type-specific fields of a read-watch entry.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct MediaDetails {
+    /// Number of volumes (books, manga, light novels)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub volumes: Option<i32>,
+    /// Number of episodes (anime, shows)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub episodes: Option<i32>,
+    /// Runtime in minutes, per episode for series (anime, shows, films)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub runtime_minutes: Option<i32>,
+    /// Producing studio (anime, shows, films) (localized)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub studio: Option<LocalizedString>,
+}
+
+impl MediaDetails {
+    /// Names of the fields that have a value.
+    pub fn set_fields(&self) -> Vec<&'static str> {
+        [
+            ("volumes", self.volumes.is_some()),
+            ("episodes", self.episodes.is_some()),
+            ("runtime_minutes", self.runtime_minutes.is_some()),
+            ("studio", self.studio.is_some()),
+        ]
+        .into_iter()
+        .filter_map(|(field, set)| set.then_some(field))
+        .collect()
+    }
+}
+
+/// Reading status derived from a book's progress.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum ReadingStatus {
+    /// Not started yet
+    Planned,
+    /// Currently in progress
+    Reading,
+    /// Finished
+    Completed,
+    /// Abandoned before finishing; only set through the `status` field
+    Dropped,
+}
+
+impl ReadingStatus {
+    /// Returns the value stored in a book's `status` field.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReadingStatus::Planned => "planned",
+            ReadingStatus::Reading => "reading",
+            ReadingStatus::Completed => "completed",
+            ReadingStatus::Dropped => "dropped",
+        }
+    }
+}
+
+/// The unit progress is counted in.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, JsonSchema)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum ProgressUnit {
+    #[default]
+    Page,
+    Chapter,
+    Episode,
+}
+
+/// Structured reading/watching progress for a book.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct ReadingProgress {
+    /// Current page, chapter or episode
+    pub current: i32,
+    /// Total pages, chapters or episodes, if known
+    pub total: Option<i32>,
+    /// What `current` and `total` count
+    #[serde(default)]
+    pub unit: ProgressUnit,
+    /// When the current read started
+    pub started_at: Option<NaiveDateTime>,
+    /// When the current read finished
+    pub finished_at: Option<NaiveDateTime>,
+    /// Number of times the book has been re-read
+    #[serde(default)]
+    pub reread_count: i32,
+}
+
+/// Data transfer object for updating a book's progress.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct UpdateProgress {
+    /// New current position
+    pub current: Option<i32>,
+    /// New total
+    pub total: Option<i32>,
+    /// New unit
+    pub unit: Option<ProgressUnit>,
+    /// Explicit start date
+    pub started_at: Option<NaiveDateTime>,
+    /// Explicit finish date
+    pub finished_at: Option<NaiveDateTime>,
+    /// Start a re-read of a finished book
+    #[serde(default)]
+    pub reread: bool,
+}
+
+/// A recorded progress update, stored in the `progress_history` collection.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct ProgressEntry {
+    /// MongoDB ObjectId
+    #[serde(rename = "_id")]
+    #[schemars(with = "ObjectIdSchema")]
+    pub oid: ObjectId,
+    /// The book this entry belongs to
+    #[schemars(with = "ObjectIdSchema")]
+    pub book_id: ObjectId,
+    /// Progress after the update
+    pub progress: ReadingProgress,
+    /// Status after the update
+    pub status: ReadingStatus,
+    /// When the update was recorded
+    pub recorded_at: NaiveDateTime,
+}
+
+impl ReadingProgress {
+    /// Returns the status implied by this progress.
+    pub fn status(&self) -> ReadingStatus {
+        if self.finished_at.is_some() {
+            ReadingStatus::Completed
+        } else if self.current > 0 || self.started_at.is_some() {
+            ReadingStatus::Reading
+        } else {
+            ReadingStatus::Planned
+        }
+    }
+
+    /// Checks that `total` is positive and `current` lies between zero and `total`.
+    pub fn validate(&self) -> Result<(), String> {
+        if self.total.is_some_and(|total| total <= 0) {
+            return Err("total must be positive".to_string());
+        }
+        if self.current < 0 {
+            return Err("current must not be negative".to_string());
+        }
+        if let Some(total) = self.total
+            && self.current > total
+        {
+            return Err(format!("current exceeds total of {}", total));
+        }
+        Ok(())
+    }
+
+    /// Applies an update to the given progress, starting from scratch when there is none.
+    ///
+    /// Starting progress sets `started_at`, reaching `total` sets `finished_at`,
+    /// and `reread` restarts a finished book while bumping `reread_count`.
+    ///
+    /// # Returns
+    ///
+    /// The updated progress, or an error message if the update is invalid.
+    pub fn apply(
+        existing: Option<&ReadingProgress>,
+        update: &UpdateProgress,
+        now: NaiveDateTime,
+    ) -> Result<ReadingProgress, String> {
+        let mut progress = existing.cloned().unwrap_or(ReadingProgress {
+            current: 0,
+            total: None,
+            unit: ProgressUnit::default(),
+            started_at: None,
+            finished_at: None,
+            reread_count: 0,
+        });
+
+        if update.reread {
+            if progress.finished_at.is_none() {
+                return Err("only finished books can be re-read".to_string());
+            }
+            progress.reread_count += 1;
+            progress.current = 0;
+            progress.started_at = Some(now);
+            progress.finished_at = None;
+        }
+
+        if let Some(unit) = update.unit {
+            progress.unit = unit;
+        }
+        if let Some(total) = update.total {
+            progress.total = Some(total);
+        }
+        if let Some(current) = update.current {
+            progress.current = current;
+        }
+        progress.validate()?;
+
+        if let Some(started_at) = update.started_at {
+            progress.started_at = Some(started_at);
+        } else if progress.current > 0 && progress.started_at.is_none() {
+            progress.started_at = Some(now);
+        }
+
+        if let Some(finished_at) = update.finished_at {
+            progress.finished_at = Some(finished_at);
+            if let Some(total) = progress.total {
+                progress.current = total;
+            }
+        } else if progress
+            .total
+            .is_some_and(|total| progress.current >= total)
+        {
+            progress.finished_at.get_or_insert(now);
+        } else if update.current.is_some() {
+            progress.finished_at = None;
+        }
+
+        Ok(progress)
+    }
+}
+
+/// Where a game stands in the backlog.
+///
+/// Deserialization accepts common spellings ("on hold", "Finished", ...) and
+/// rejects anything else, which is what request bodies want. Stored games
+/// read their status with [`GameStatus::deserialize_stored`] instead, so an
+/// unrecognized legacy status cannot break listing them.
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default, FromFormField, JsonSchema,
+)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase", try_from = "String")]
+pub enum GameStatus {
+    /// Not started yet
+    #[default]
+    Backlog,
+    /// Currently being played
+    Playing,
+    /// Started but on hold
+    Paused,
+    /// Finished the main story
+    Completed,
+    /// Everything done
+    #[serde(rename = "100%")]
+    #[field(value = "100%")]
+    #[field(value = "hundred_percent")]
+    HundredPercent,
+    /// Given up on
+    Dropped,
+}
+
+impl GameStatus {
+    /// Parses a status, accepting case-insensitive aliases.
+    pub fn parse(text: &str) -> Option<Self> {
+        let normalized: String = text
+            .trim()
+            .to_lowercase()
+            .chars()
+            .map(|c| if c == ' ' || c == '_' { '-' } else { c })
+            .collect();
+
+        match normalized.as_str() {
+            "backlog" | "planned" | "plan-to-play" | "wishlist" | "not-started" | "not-played" => {
+                Some(GameStatus::Backlog)
+            }
+            "playing" | "in-progress" | "started" | "played" => Some(GameStatus::Playing),
+            "paused" | "on-hold" => Some(GameStatus::Paused),
+            "completed" | "finished" | "beaten" => Some(GameStatus::Completed),
+            "100%" | "100" | "hundred-percent" | "completionist" => {
+                Some(GameStatus::HundredPercent)
+            }
+            "dropped" | "abandoned" => Some(GameStatus::Dropped),
+            _ => None,
+        }
+    }
+
+    /// Returns the value stored in a game's `status` field.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            GameStatus::Backlog => "backlog",
+            GameStatus::Playing => "playing",
+            GameStatus::Paused => "paused",
+            GameStatus::Completed => "completed",
+            GameStatus::HundredPercent => "100%",
+            GameStatus::Dropped => "dropped",
+        }
+    }
+
+    /// Whether the game has been finished.
+    pub fn is_finished(&self) -> bool {
+        matches!(self, GameStatus::Completed | GameStatus::HundredPercent)
+    }
+
+    /// Reads a stored status, falling back to `backlog` for a missing or
+    /// unrecognized one until the `game-status` migration rewrites it.
+    pub fn deserialize_stored<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let status = Option::<String>::deserialize(deserializer)?;
+        Ok(status
+            .as_deref()
+            .and_then(GameStatus::parse)
+            .unwrap_or_default())
+    }
+}
+
+impl TryFrom<String> for GameStatus {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        GameStatus::parse(&value).ok_or_else(|| format!("unknown game status `{}`", value))
+    }
+}
+
+/// Clamps a completion percentage to 0–100.
+pub fn clamp_percent(percent: i32) -> i32 {
+    percent.clamp(0, 100)
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct Game {
     #[serde(rename = "_id")]
+    #[schemars(with = "ObjectIdSchema")]
     pub oid: ObjectId,
     pub title: String,
     pub developer: String,
     pub genres: Vec<Option<String>>,
     pub tags: Vec<Option<String>>,
     pub rating: i32,
-    pub status: String,
+    #[serde(default, deserialize_with = "GameStatus::deserialize_stored")]
+    pub status: GameStatus,
     pub description: String,
     pub my_thoughts: String,
     pub links: Option<HashMap<String, String>>,
@@ -303,9 +902,236 @@ pub struct Game {
     pub explicit: bool,
     pub percent: i32,
     pub bad: bool,
+    /// Total hours played
+    #[serde(default)]
+    pub playtime_hours: f64,
+    /// Platforms the game is played on (e.g., "PC", "Switch")
+    #[serde(default)]
+    pub platforms: Vec<String>,
+    /// When the game was first played
+    #[serde(default)]
+    pub started_at: Option<NaiveDateTime>,
+    /// When the game was finished
+    #[serde(default)]
+    pub finished_at: Option<NaiveDateTime>,
+    /// Achievements and other completion checklist items
+    #[serde(default)]
+    pub checklist: Vec<ChecklistItem>,
+    /// Whether `percent` is derived from checklist completion
+    #[serde(default)]
+    pub percent_from_checklist: bool,
+    /// When the document was created
+    #[serde(default)]
+    pub created_at: Option<NaiveDateTime>,
+    /// When the document was last written
+    #[serde(default)]
+    pub updated_at: Option<NaiveDateTime>,
+    /// When the document was moved to the trash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<NaiveDateTime>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+impl Game {
+    /// Applies a play session, updating totals, dates and status.
+    ///
+    /// An explicit status on the session wins. Otherwise reaching 100% marks
+    /// the game completed, and playing a backlogged, paused or dropped game
+    /// marks it as playing.
+    pub fn record_session(
+        &mut self,
+        session: &NewPlaySession,
+        now: NaiveDateTime,
+    ) -> Result<(), String> {
+        if !session.hours.is_finite() || session.hours <= 0.0 {
+            return Err("hours must be a positive number".to_string());
+        }
+
+        let played_at = session.played_at.unwrap_or(now);
+
+        self.playtime_hours += session.hours;
+        if let Some(percent) = session.percent
+            && !self.percent_from_checklist
+        {
+            self.percent = clamp_percent(percent);
+        }
+        if let Some(platform) = &session.platform
+            && !self.platforms.contains(platform)
+        {
+            self.platforms.push(platform.clone());
+        }
+        self.started_at.get_or_insert(played_at);
+
+        self.status = match session.status {
+            Some(status) => status,
+            None if self.percent >= 100 && !self.status.is_finished() => GameStatus::Completed,
+            None => match self.status {
+                GameStatus::Backlog | GameStatus::Paused | GameStatus::Dropped => {
+                    GameStatus::Playing
+                }
+                status => status,
+            },
+        };
+
+        if self.status == GameStatus::HundredPercent {
+            self.percent = 100;
+        }
+        if self.status.is_finished() {
+            self.finished_at.get_or_insert(played_at);
+        } else {
+            self.finished_at = None;
+        }
+
+        Ok(())
+    }
+
+    /// Returns the share of unlocked checklist items, or `None` for an empty checklist.
+    pub fn checklist_percent(&self) -> Option<i32> {
+        let total = self.checklist.len();
+        if total == 0 {
+            return None;
+        }
+
+        let unlocked = self
+            .checklist
+            .iter()
+            .filter(|item| item.is_unlocked())
+            .count();
+        Some((unlocked * 100 / total) as i32)
+    }
+
+    /// Updates `percent` from the checklist when it is derived from it.
+    pub fn sync_checklist_percent(&mut self) {
+        if self.percent_from_checklist
+            && let Some(percent) = self.checklist_percent()
+        {
+            self.percent = percent;
+        }
+    }
+
+    /// Finds a checklist item by name, ignoring case and surrounding whitespace.
+    pub fn find_checklist_item(&self, name: &str) -> Option<usize> {
+        let name = name.trim().to_lowercase();
+        self.checklist
+            .iter()
+            .position(|item| item.name.trim().to_lowercase() == name)
+    }
+
+    /// Merges imported items into the checklist by name.
+    ///
+    /// Existing items take the imported description and hidden flag, and are
+    /// unlocked if the import says so; unlocks are never reverted by an import.
+    pub fn import_checklist(&mut self, items: Vec<NewChecklistItem>) -> ChecklistImport {
+        let mut result = ChecklistImport::default();
+
+        for item in items {
+            match self.find_checklist_item(&item.name) {
+                Some(index) => {
+                    let existing = &mut self.checklist[index];
+                    let before = existing.clone();
+                    if item.description.is_some() {
+                        existing.description = item.description;
+                    }
+                    existing.hidden = item.hidden;
+                    if existing.unlocked_at.is_none() {
+                        existing.unlocked_at = item.unlocked_at;
+                    }
+                    if *existing != before {
+                        result.updated += 1;
+                    }
+                }
+                None => {
+                    self.checklist.push(ChecklistItem::from(item));
+                    result.added += 1;
+                }
+            }
+        }
+
+        self.sync_checklist_percent();
+        result
+    }
+}
+
+/// An achievement or checklist entry of a game.
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct ChecklistItem {
+    /// Identifier of the item within its game
+    #[schemars(with = "ObjectIdSchema")]
+    pub id: ObjectId,
+    /// Item name
+    pub name: String,
+    /// What has to be done
+    pub description: Option<String>,
+    /// Whether the item is a secret until unlocked
+    #[serde(default)]
+    pub hidden: bool,
+    /// When the item was unlocked
+    pub unlocked_at: Option<NaiveDateTime>,
+}
+
+impl ChecklistItem {
+    /// Whether the item has been unlocked.
+    pub fn is_unlocked(&self) -> bool {
+        self.unlocked_at.is_some()
+    }
+}
+
+impl From<NewChecklistItem> for ChecklistItem {
+    fn from(item: NewChecklistItem) -> Self {
+        ChecklistItem {
+            id: ObjectId::new(),
+            name: item.name.trim().to_string(),
+            description: item.description,
+            hidden: item.hidden,
+            unlocked_at: item.unlocked_at,
+        }
+    }
+}
+
+/// Data transfer object for adding or importing a checklist item.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct NewChecklistItem {
+    /// Item name
+    pub name: String,
+    /// What has to be done
+    #[serde(default)]
+    pub description: Option<String>,
+    /// Whether the item is a secret until unlocked
+    #[serde(default)]
+    pub hidden: bool,
+    /// When the item was unlocked, if it already is
+    #[serde(default)]
+    pub unlocked_at: Option<NaiveDateTime>,
+}
+
+/// Data transfer object for editing or ticking a checklist item.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct UpdateChecklistItem {
+    /// Updated name
+    pub name: Option<String>,
+    /// Updated description
+    pub description: Option<String>,
+    /// Updated hidden flag
+    pub hidden: Option<bool>,
+    /// Tick (`true`) or untick (`false`) the item
+    pub unlocked: Option<bool>,
+    /// Explicit unlock time when ticking (defaults to now)
+    pub unlocked_at: Option<NaiveDateTime>,
+}
+
+/// Counts of checklist items changed by an import.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct ChecklistImport {
+    /// Items that were not on the checklist yet
+    pub added: usize,
+    /// Existing items that changed
+    pub updated: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct NewGame {
     pub title: String,
@@ -313,7 +1139,7 @@ pub struct NewGame {
     pub genres: Vec<String>,
     pub tags: Vec<String>,
     pub rating: i32,
-    pub status: String,
+    pub status: GameStatus,
     pub description: String,
     pub my_thoughts: String,
     pub links: Option<HashMap<String, String>>,
@@ -321,9 +1147,19 @@ pub struct NewGame {
     pub explicit: bool,
     pub percent: i32,
     pub bad: bool,
+    #[serde(default)]
+    pub playtime_hours: f64,
+    #[serde(default)]
+    pub platforms: Vec<String>,
+    #[serde(default)]
+    pub started_at: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub finished_at: Option<NaiveDateTime>,
+    #[serde(default)]
+    pub percent_from_checklist: bool,
 }
 
-#[derive(Deserialize, Serialize, Debug, Clone)]
+#[derive(Deserialize, Serialize, Debug, Clone, JsonSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct UpdateGame {
     pub title: Option<String>,
@@ -331,7 +1167,7 @@ pub struct UpdateGame {
     pub genres: Option<Vec<String>>,
     pub tags: Option<Vec<String>>,
     pub rating: Option<i32>,
-    pub status: Option<String>,
+    pub status: Option<GameStatus>,
     pub description: Option<String>,
     pub my_thoughts: Option<String>,
     pub links: Option<HashMap<String, String>>,
@@ -339,30 +1175,479 @@ pub struct UpdateGame {
     pub explicit: Option<bool>,
     pub percent: Option<i32>,
     pub bad: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub playtime_hours: Option<f64>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub platforms: Option<Vec<String>>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<NaiveDateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<NaiveDateTime>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub percent_from_checklist: Option<bool>,
+}
+
+/// Data transfer object for logging a play session.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct NewPlaySession {
+    /// Hours played in this session
+    pub hours: f64,
+    /// When the session took place (defaults to now)
+    #[serde(default)]
+    pub played_at: Option<NaiveDateTime>,
+    /// Completion percentage after the session
+    #[serde(default)]
+    pub percent: Option<i32>,
+    /// Explicit status after the session
+    #[serde(default)]
+    pub status: Option<GameStatus>,
+    /// Platform the session was played on
+    #[serde(default)]
+    pub platform: Option<String>,
+    /// Free-form notes
+    #[serde(default)]
+    pub notes: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// A logged play session, stored in the `play_sessions` collection.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct PlaySession {
+    /// MongoDB ObjectId
+    #[serde(rename = "_id")]
+    #[schemars(with = "ObjectIdSchema")]
+    pub oid: ObjectId,
+    /// The game this session belongs to
+    #[schemars(with = "ObjectIdSchema")]
+    pub game_id: ObjectId,
+    /// Hours played in this session
+    pub hours: f64,
+    /// When the session took place
+    pub played_at: NaiveDateTime,
+    /// Platform the session was played on
+    pub platform: Option<String>,
+    /// Free-form notes
+    pub notes: Option<String>,
+    /// Completion percentage after the session
+    pub percent: i32,
+    /// Status after the session
+    #[serde(default, deserialize_with = "GameStatus::deserialize_stored")]
+    pub status: GameStatus,
+    /// Total playtime after the session
+    pub playtime_hours: f64,
+}
+
+/// The kind of a taxonomy term, which also names the document field it is used in.
+#[derive(
+    Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, FromFormField, JsonSchema,
+)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum TermKind {
+    /// A genre, stored in the `genres` field of books and games
+    Genre,
+    /// A tag, stored in the `tags` field of books and games
+    Tag,
+}
+
+impl TermKind {
+    /// Returns the book/game document field holding terms of this kind.
+    pub fn field(&self) -> &'static str {
+        match self {
+            TermKind::Genre => "genres",
+            TermKind::Tag => "tags",
+        }
+    }
+}
+
+impl<'a> FromParam<'a> for TermKind {
+    type Error = &'a str;
+
+    fn from_param(param: &'a str) -> Result<Self, Self::Error> {
+        match param {
+            "genre" | "genres" => Ok(TermKind::Genre),
+            "tag" | "tags" => Ok(TermKind::Tag),
+            _ => Err(param),
+        }
+    }
+}
+
+/// A canonical genre or tag shared by books and games.
+///
+/// Documents reference terms by `slug`; the `label` is rendered in the
+/// requested locale when documents are served.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct TaxonomyTerm {
+    /// MongoDB ObjectId
+    #[serde(rename = "_id")]
+    #[schemars(with = "ObjectIdSchema")]
+    pub oid: ObjectId,
+    /// Whether this is a genre or a tag
+    pub kind: TermKind,
+    /// Canonical identifier (e.g., "science-fiction")
+    pub slug: String,
+    /// Display label (localized)
+    pub label: LocalizedString,
+    /// Alternative spellings that resolve to this term (e.g., "sci-fi")
+    #[serde(default)]
+    pub aliases: Vec<String>,
+}
+
+/// Data transfer object for creating a new taxonomy term.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct NewTaxonomyTerm {
+    /// Whether this is a genre or a tag
+    pub kind: TermKind,
+    /// Canonical identifier, derived from the label when omitted
+    pub slug: Option<String>,
+    /// Display label (localized)
+    pub label: LocalizedString,
+    /// Alternative spellings that resolve to this term
+    pub aliases: Option<Vec<String>>,
+}
+
+/// Data transfer object for updating a taxonomy term's label and aliases.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct UpdateTaxonomyTerm {
+    /// Updated display label
+    pub label: Option<LocalizedString>,
+    /// Replacement list of aliases
+    pub aliases: Option<Vec<String>>,
+}
+
+/// An area of the API an API key can be limited to, by path prefix.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, JsonSchema)]
+#[serde(crate = "rocket::serde", rename_all = "snake_case")]
+pub enum KeyScope {
+    /// `/read-watch`
+    Books,
+    /// `/games`
+    Games,
+    /// `/projects`
+    Projects,
+    /// `/reviews`
+    Reviews,
+    /// `/series`
+    Series,
+    /// `/taxonomy`
+    Taxonomy,
+    /// `/wplace`
+    Wplace,
+    /// `/admin`
+    Admin,
+    /// `/metrics`
+    Metrics,
+    /// `/webhooks`
+    Webhooks,
+    /// `/trash`
+    Trash,
+}
+
+impl KeyScope {
+    /// Every scope name accepted on the command line.
+    pub const NAMES: [&'static str; 11] = [
+        "books", "games", "projects", "reviews", "series", "taxonomy", "wplace", "admin",
+        "metrics", "webhooks", "trash",
+    ];
+
+    /// Path prefixes outside every scope, open to scoped keys too.
+    const UNSCOPED: [&'static str; 7] = [
+        "",
+        "misc",
+        "docs",
+        "redoc",
+        "openapi.json",
+        "feeds",
+        "events",
+    ];
+
+    /// Returns the stored name of the scope.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyScope::Books => "books",
+            KeyScope::Games => "games",
+            KeyScope::Projects => "projects",
+            KeyScope::Reviews => "reviews",
+            KeyScope::Series => "series",
+            KeyScope::Taxonomy => "taxonomy",
+            KeyScope::Wplace => "wplace",
+            KeyScope::Admin => "admin",
+            KeyScope::Metrics => "metrics",
+            KeyScope::Webhooks => "webhooks",
+            KeyScope::Trash => "trash",
+        }
+    }
+
+    /// Returns the scope a request path belongs to, if any.
+    ///
+    /// Paths outside every scope (`/`, `/misc/...`, the docs) and unknown
+    /// paths return `None`.
+    pub fn for_path(path: &str) -> Option<Self> {
+        let prefix = path.trim_start_matches('/').split('/').next()?;
+        match prefix {
+            "read-watch" => Some(KeyScope::Books),
+            "games" => Some(KeyScope::Games),
+            "projects" => Some(KeyScope::Projects),
+            "reviews" => Some(KeyScope::Reviews),
+            "series" => Some(KeyScope::Series),
+            "taxonomy" => Some(KeyScope::Taxonomy),
+            "wplace" => Some(KeyScope::Wplace),
+            "admin" => Some(KeyScope::Admin),
+            "metrics" => Some(KeyScope::Metrics),
+            "webhooks" => Some(KeyScope::Webhooks),
+            "trash" => Some(KeyScope::Trash),
+            _ => None,
+        }
+    }
+
+    /// Whether a request path is outside every scope, e.g. `/misc/...` or the docs.
+    pub fn is_unscoped(path: &str) -> bool {
+        let prefix = path
+            .trim_start_matches('/')
+            .split('/')
+            .next()
+            .unwrap_or_default();
+        Self::UNSCOPED.contains(&prefix)
+    }
+}
+
+impl std::str::FromStr for KeyScope {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "books" => Ok(KeyScope::Books),
+            "games" => Ok(KeyScope::Games),
+            "projects" => Ok(KeyScope::Projects),
+            "reviews" => Ok(KeyScope::Reviews),
+            "series" => Ok(KeyScope::Series),
+            "taxonomy" => Ok(KeyScope::Taxonomy),
+            "wplace" => Ok(KeyScope::Wplace),
+            "admin" => Ok(KeyScope::Admin),
+            "metrics" => Ok(KeyScope::Metrics),
+            "webhooks" => Ok(KeyScope::Webhooks),
+            "trash" => Ok(KeyScope::Trash),
+            _ => Err(format!("unknown key scope `{}`", s)),
+        }
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct ApiKey {
     #[serde(rename = "_id")]
+    #[schemars(with = "ObjectIdSchema")]
     pub oid: ObjectId,
     pub key_hash: String,
     pub is_admin: bool,
     pub created_at: NaiveDateTime,
     pub last_used_at: Option<NaiveDateTime>,
+    /// What or who the key is for
+    #[serde(default)]
+    pub label: Option<String>,
+    /// When the key stops being accepted
+    #[serde(default)]
+    pub expires_at: Option<NaiveDateTime>,
+    /// Areas of the API the key is limited to; empty means unrestricted
+    #[serde(default)]
+    pub scopes: Vec<KeyScope>,
+    /// Number of authenticated requests made with the key
+    #[serde(default)]
+    pub use_count: i64,
+}
+
+impl ApiKey {
+    /// Whether the key has expired at `now`.
+    pub fn is_expired(&self, now: NaiveDateTime) -> bool {
+        self.expires_at.is_some_and(|expires_at| expires_at <= now)
+    }
+
+    /// Whether the key may be used for a request to `path`.
+    ///
+    /// Keys with scopes are only accepted within them and on paths outside
+    /// every scope; paths no scope knows about are rejected.
+    pub fn allows(&self, path: &str) -> bool {
+        if self.scopes.is_empty() {
+            return true;
+        }
+        match KeyScope::for_path(path) {
+            Some(scope) => self.scopes.contains(&scope),
+            None => KeyScope::is_unscoped(path),
+        }
+    }
 }
 
-#[derive(Debug, Serialize, Deserialize, Clone)]
+/// Options for a new API key.
+#[derive(Debug, Serialize, Deserialize, Clone, Default, JsonSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct NewApiKey {
-    pub key_hash: String,
     pub is_admin: bool,
+    pub label: Option<String>,
+    pub expires_at: Option<NaiveDateTime>,
+    pub scopes: Vec<KeyScope>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// A registered webhook target.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct Webhook {
+    #[serde(rename = "_id")]
+    #[schemars(with = "ObjectIdSchema")]
+    pub oid: ObjectId,
+    /// URL deliveries are posted to
+    pub url: String,
+    /// Event filters such as `book.created`, `game.*` or `*`
+    pub events: Vec<String>,
+    /// Key for the HMAC-SHA256 signature of every delivery; only returned
+    /// when the webhook is registered
+    #[serde(default, skip_serializing_if = "String::is_empty")]
+    pub secret: String,
+    /// Inactive webhooks receive no new deliveries
+    pub active: bool,
+    pub description: Option<String>,
+    pub created_at: NaiveDateTime,
+}
+
+impl Webhook {
+    /// Returns the webhook with its secret removed, for API responses.
+    pub fn without_secret(self) -> Self {
+        Self {
+            secret: String::new(),
+            ..self
+        }
+    }
+}
+
+/// Data transfer object for registering a webhook.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct NewWebhook {
+    pub url: String,
+    pub events: Vec<String>,
+    /// Signing secret; generated if omitted
+    pub secret: Option<String>,
+    pub description: Option<String>,
+}
+
+/// Data transfer object for updating a webhook.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct UpdateWebhook {
+    pub url: Option<String>,
+    pub events: Option<Vec<String>>,
+    pub active: Option<bool>,
+    pub description: Option<String>,
+}
+
+/// State of a queued webhook delivery.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, FromFormField, JsonSchema)]
+#[serde(crate = "rocket::serde", rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    /// Waiting for its first or next attempt
+    Pending,
+    /// The target answered with a 2xx status
+    Delivered,
+    /// Every attempt failed
+    Failed,
+}
+
+impl DeliveryStatus {
+    /// Returns the value stored in the `status` field.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Pending => "pending",
+            DeliveryStatus::Delivered => "delivered",
+            DeliveryStatus::Failed => "failed",
+        }
+    }
+}
+
+/// One event queued for one webhook, with the outcome of its last attempt.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct WebhookDelivery {
+    #[serde(rename = "_id")]
+    #[schemars(with = "ObjectIdSchema")]
+    pub oid: ObjectId,
+    #[schemars(with = "ObjectIdSchema")]
+    pub webhook_id: ObjectId,
+    /// Event name, e.g. `book.updated`
+    pub event: String,
+    /// JSON request body, signed as sent
+    pub body: String,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    pub created_at: NaiveDateTime,
+    /// When the worker may next try this delivery
+    pub next_attempt_at: NaiveDateTime,
+    pub last_attempt_at: Option<NaiveDateTime>,
+    /// HTTP status of the last response
+    pub response_status: Option<u16>,
+    /// Why the last attempt failed
+    pub error: Option<String>,
+    pub delivered_at: Option<NaiveDateTime>,
+}
+
+/// What a [`Revision`] records.
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, JsonSchema)]
+#[serde(crate = "rocket::serde", rename_all = "lowercase")]
+pub enum RevisionAction {
+    Created,
+    Updated,
+    Deleted,
+    /// An earlier revision was written back
+    Reverted,
+}
+
+/// A snapshot of a document after one change.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct Revision {
+    #[serde(rename = "_id")]
+    #[schemars(with = "ObjectIdSchema")]
+    pub oid: ObjectId,
+    /// Collection of the document, e.g. `books`
+    pub collection: String,
+    #[schemars(with = "ObjectIdSchema")]
+    pub document_id: ObjectId,
+    /// 1 for the first recorded change, increasing with every change
+    pub number: u32,
+    pub action: RevisionAction,
+    /// The revision written back by a revert
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reverted_to: Option<u32>,
+    /// Top-level fields that differ from the previous revision
+    pub changed: Vec<String>,
+    /// The document after the change, or as it was before deletion
+    #[schemars(with = "serde_json::Value")]
+    pub document: Document,
+    pub recorded_at: NaiveDateTime,
+}
+
+/// A deleted document waiting in the trash.
+#[derive(Debug, Serialize, Deserialize, Clone, JsonSchema)]
+#[serde(crate = "rocket::serde")]
+pub struct TrashedDocument {
+    /// Collection of the document: `books`, `games`, `projects`, `reviews` or `wplace`
+    pub collection: String,
+    #[schemars(with = "ObjectIdSchema")]
+    pub document_id: ObjectId,
+    pub deleted_at: NaiveDateTime,
+    /// When the scheduled purge removes the document for good
+    pub purge_at: NaiveDateTime,
+    /// The document as it was deleted
+    #[schemars(with = "serde_json::Value")]
+    pub document: Document,
+}
+
+#[derive(Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(crate = "rocket::serde")]
 pub struct LocalizedBook {
     #[serde(rename = "_id")]
+    #[schemars(with = "ObjectIdSchema")]
     pub oid: ObjectId,
     pub title: String,
     pub author: String,
@@ -376,9 +1661,108 @@ pub struct LocalizedBook {
     pub cover_image: String,
     pub explicit: bool,
     pub color: Option<String>,
+    pub progress: Option<ReadingProgress>,
+    pub media_type: MediaType,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub volumes: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub episodes: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runtime_minutes: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub studio: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[schemars(with = "Option<ObjectIdSchema>")]
+    pub series_id: Option<ObjectId>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub series_index: Option<f64>,
+    /// When the document was created
+    #[serde(default)]
+    pub created_at: Option<NaiveDateTime>,
+    /// When the document was last written
+    #[serde(default)]
+    pub updated_at: Option<NaiveDateTime>,
+    /// When the document was moved to the trash
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub deleted_at: Option<NaiveDateTime>,
+}
+
+impl Series {
+    /// Converts a Series into a LocalizedSeries with its members and aggregates.
+    ///
+    /// # Arguments
+    ///
+    /// * `members` - The books in the series, already in series order
+    /// * `locale` - The preferred locale for text resolution
+    ///
+    /// # Returns
+    ///
+    /// A LocalizedSeries with localized members, average rating and progress counts.
+    pub fn localize(&self, members: &[Book], locale: Option<&str>) -> LocalizedSeries {
+        let mut progress = SeriesProgress {
+            total: members.len(),
+            ..Default::default()
+        };
+        for book in members {
+            match book.reading_status() {
+                ReadingStatus::Planned => progress.planned += 1,
+                ReadingStatus::Reading => progress.reading += 1,
+                ReadingStatus::Completed => progress.completed += 1,
+                ReadingStatus::Dropped => progress.dropped += 1,
+            }
+        }
+
+        let rated: Vec<i32> = members
+            .iter()
+            .map(|book| book.rating)
+            .filter(|rating| *rating > 0)
+            .collect();
+        let average_rating = if rated.is_empty() {
+            None
+        } else {
+            Some(rated.iter().sum::<i32>() as f64 / rated.len() as f64)
+        };
+
+        LocalizedSeries {
+            oid: self.oid,
+            title: self.title.get_text(locale),
+            description: self
+                .description
+                .as_ref()
+                .map(|description| description.get_text(locale)),
+            cover_image: self.cover_image.clone(),
+            members: members.iter().map(|book| book.localize(locale)).collect(),
+            average_rating,
+            progress,
+        }
+    }
 }
 
 impl Book {
+    /// Returns the reading status of the book.
+    ///
+    /// A book whose `status` field says it was dropped is dropped. Otherwise
+    /// uses structured progress when present, falling back to the free-text
+    /// `status` field for books that have never had progress recorded.
+    /// Recording progress rewrites `status`, so picking a dropped book back up
+    /// clears it.
+    pub fn reading_status(&self) -> ReadingStatus {
+        let status = self.status.get_text(Some("en")).trim().to_lowercase();
+        if matches!(status.as_str(), "dropped" | "dnf" | "did not finish") {
+            return ReadingStatus::Dropped;
+        }
+
+        if let Some(progress) = &self.progress {
+            return progress.status();
+        }
+
+        match status.as_str() {
+            "completed" | "complete" | "finished" | "read" | "watched" => ReadingStatus::Completed,
+            "reading" | "watching" | "in progress" => ReadingStatus::Reading,
+            _ => ReadingStatus::Planned,
+        }
+    }
+
     /// Converts a Book with localized fields into a LocalizedBook with resolved strings.
     ///
     /// # Arguments
@@ -403,6 +1787,21 @@ impl Book {
             cover_image: self.cover_image.clone(),
             explicit: self.explicit,
             color: self.color.clone(),
+            progress: self.progress.clone(),
+            media_type: self.media_type,
+            volumes: self.details.volumes,
+            episodes: self.details.episodes,
+            runtime_minutes: self.details.runtime_minutes,
+            studio: self
+                .details
+                .studio
+                .as_ref()
+                .map(|studio| studio.get_text(locale)),
+            series_id: self.series_id,
+            series_index: self.series_index,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+            deleted_at: self.deleted_at,
         }
     }
 }
@@ -432,6 +1831,14 @@ impl NewBook {
             cover_image: self.cover_image.clone(),
             explicit: self.explicit,
             color: self.color.clone(),
+            progress: self.progress.clone(),
+            media_type: self.media_type,
+            details: self.details.clone(),
+            series_id: self.series_id,
+            series_index: self.series_index,
+            created_at: None,
+            updated_at: None,
+            deleted_at: None,
         }
     }
 }
@@ -556,6 +1963,14 @@ mod tests {
             cover_image: "https://example.com/cover.jpg".to_string(),
             explicit: false,
             color: Some("#FF0000".to_string()),
+            progress: None,
+            media_type: MediaType::Book,
+            details: MediaDetails::default(),
+            series_id: None,
+            series_index: None,
+            created_at: None,
+            updated_at: None,
+            deleted_at: None,
         };
 
         let localized_en = book.localize(Some("en"));
@@ -582,6 +1997,11 @@ mod tests {
             cover_image: "test.jpg".to_string(),
             explicit: false,
             color: None,
+            progress: None,
+            media_type: MediaType::Book,
+            details: MediaDetails::default(),
+            series_id: None,
+            series_index: None,
         };
 
         let oid = ObjectId::new();
@@ -590,6 +2010,501 @@ mod tests {
         assert_eq!(book.oid, oid);
         assert_eq!(book.rating, 4);
         assert_eq!(book.cover_image, "test.jpg");
-        assert_eq!(book.explicit, false);
+        assert!(!book.explicit);
+    }
+
+    #[test]
+    fn test_progress_status_transitions() {
+        let now = chrono::Utc::now().naive_utc();
+
+        let planned = ReadingProgress::apply(
+            None,
+            &UpdateProgress {
+                total: Some(300),
+                ..Default::default()
+            },
+            now,
+        )
+        .unwrap();
+        assert_eq!(planned.status(), ReadingStatus::Planned);
+        assert!(planned.started_at.is_none());
+
+        let reading = ReadingProgress::apply(
+            Some(&planned),
+            &UpdateProgress {
+                current: Some(120),
+                ..Default::default()
+            },
+            now,
+        )
+        .unwrap();
+        assert_eq!(reading.status(), ReadingStatus::Reading);
+        assert_eq!(reading.started_at, Some(now));
+
+        let completed = ReadingProgress::apply(
+            Some(&reading),
+            &UpdateProgress {
+                current: Some(300),
+                ..Default::default()
+            },
+            now,
+        )
+        .unwrap();
+        assert_eq!(completed.status(), ReadingStatus::Completed);
+        assert_eq!(completed.finished_at, Some(now));
+    }
+
+    #[test]
+    fn test_progress_reread() {
+        let now = chrono::Utc::now().naive_utc();
+        let finished = ReadingProgress {
+            current: 10,
+            total: Some(10),
+            unit: ProgressUnit::Chapter,
+            started_at: Some(now),
+            finished_at: Some(now),
+            reread_count: 0,
+        };
+
+        let reread = ReadingProgress::apply(
+            Some(&finished),
+            &UpdateProgress {
+                reread: true,
+                ..Default::default()
+            },
+            now,
+        )
+        .unwrap();
+        assert_eq!(reread.reread_count, 1);
+        assert_eq!(reread.current, 0);
+        assert_eq!(reread.status(), ReadingStatus::Reading);
+
+        assert!(
+            ReadingProgress::apply(
+                Some(&reread),
+                &UpdateProgress {
+                    reread: true,
+                    ..Default::default()
+                },
+                now,
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_progress_validation() {
+        let now = chrono::Utc::now().naive_utc();
+
+        let negative = UpdateProgress {
+            current: Some(-1),
+            ..Default::default()
+        };
+        assert!(ReadingProgress::apply(None, &negative, now).is_err());
+
+        let overflow = UpdateProgress {
+            current: Some(11),
+            total: Some(10),
+            ..Default::default()
+        };
+        assert!(ReadingProgress::apply(None, &overflow, now).is_err());
+
+        let progress = |current, total| ReadingProgress {
+            current,
+            total,
+            unit: ProgressUnit::Page,
+            started_at: None,
+            finished_at: None,
+            reread_count: 0,
+        };
+        assert!(progress(5, Some(10)).validate().is_ok());
+        assert!(progress(5, None).validate().is_ok());
+        assert!(progress(-5, Some(10)).validate().is_err());
+        assert!(progress(0, Some(0)).validate().is_err());
+        assert!(progress(11, Some(10)).validate().is_err());
+    }
+
+    #[test]
+    fn test_media_type_defaults_to_book() {
+        let json = r#"{
+            "title": "Legacy Book",
+            "author": "Someone",
+            "genres": [],
+            "tags": [],
+            "rating": 3,
+            "status": "planned",
+            "description": "",
+            "my_thoughts": "",
+            "links": null,
+            "cover_image": "cover.jpg",
+            "explicit": false,
+            "color": null
+        }"#;
+
+        let new_book: NewBook = serde_json::from_str(json).unwrap();
+        assert_eq!(new_book.media_type, MediaType::Book);
+        assert_eq!(new_book.details, MediaDetails::default());
+    }
+
+    #[test]
+    fn test_media_details_flattened() {
+        let json = r#"{
+            "title": "Some Anime",
+            "genres": [],
+            "tags": [],
+            "rating": 4,
+            "status": "reading",
+            "description": "",
+            "my_thoughts": "",
+            "links": null,
+            "cover_image": "cover.jpg",
+            "explicit": false,
+            "color": null,
+            "media_type": "anime",
+            "episodes": 24,
+            "studio": { "en": "Studio", "jp": "スタジオ" }
+        }"#;
+
+        let new_book: NewBook = serde_json::from_str(json).unwrap();
+        assert_eq!(new_book.media_type, MediaType::Anime);
+        assert_eq!(new_book.details.episodes, Some(24));
+        assert!(
+            new_book
+                .media_type
+                .validate(&new_book.author, &new_book.details)
+                .is_ok()
+        );
+
+        let book = new_book.to_book_with_id(ObjectId::new());
+        let localized = book.localize(Some("jp"));
+        assert_eq!(localized.studio.as_deref(), Some("スタジオ"));
+    }
+
+    #[test]
+    fn test_media_type_validation() {
+        let author = LocalizedString::Simple("Author".to_string());
+        let no_author = LocalizedString::default();
+
+        assert!(
+            MediaType::Book
+                .validate(&no_author, &MediaDetails::default())
+                .is_err()
+        );
+        assert!(
+            MediaType::Manga
+                .validate(
+                    &author,
+                    &MediaDetails {
+                        volumes: Some(12),
+                        ..Default::default()
+                    }
+                )
+                .is_ok()
+        );
+        assert!(
+            MediaType::Manga
+                .validate(
+                    &author,
+                    &MediaDetails {
+                        episodes: Some(12),
+                        ..Default::default()
+                    }
+                )
+                .is_err()
+        );
+        assert!(
+            MediaType::Film
+                .validate(
+                    &no_author,
+                    &MediaDetails {
+                        episodes: Some(1),
+                        ..Default::default()
+                    }
+                )
+                .is_err()
+        );
+        assert!(
+            MediaType::Show
+                .validate(
+                    &no_author,
+                    &MediaDetails {
+                        runtime_minutes: Some(0),
+                        ..Default::default()
+                    }
+                )
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_series_aggregates() {
+        let volume = |rating: i32, status: &str| Book {
+            oid: ObjectId::new(),
+            title: LocalizedString::Simple("Volume".to_string()),
+            author: LocalizedString::Simple("Author".to_string()),
+            genres: LocalizedStringArray::Simple(vec![]),
+            tags: LocalizedStringArray::Simple(vec![]),
+            rating,
+            status: LocalizedString::Simple(status.to_string()),
+            description: LocalizedString::Simple(String::new()),
+            my_thoughts: LocalizedString::Simple(String::new()),
+            links: None,
+            cover_image: String::new(),
+            explicit: false,
+            color: None,
+            progress: None,
+            media_type: MediaType::Manga,
+            details: MediaDetails::default(),
+            series_id: None,
+            series_index: None,
+            created_at: None,
+            updated_at: None,
+            deleted_at: None,
+        };
+
+        let mut title = HashMap::new();
+        title.insert("en".to_string(), "Saga".to_string());
+        title.insert("es".to_string(), "Saga ES".to_string());
+        let series = Series {
+            oid: ObjectId::new(),
+            title: LocalizedString::Localized(title),
+            description: None,
+            cover_image: None,
+        };
+
+        let members = vec![
+            volume(5, "Completed"),
+            volume(4, "reading"),
+            volume(0, "planned"),
+            volume(0, "dropped"),
+        ];
+        let localized = series.localize(&members, Some("es"));
+
+        assert_eq!(localized.title, "Saga ES");
+        assert_eq!(localized.members.len(), 4);
+        assert_eq!(localized.average_rating, Some(4.5));
+        assert_eq!(
+            localized.progress,
+            SeriesProgress {
+                total: 4,
+                planned: 1,
+                reading: 1,
+                completed: 1,
+                dropped: 1,
+            }
+        );
+    }
+
+    fn game(status: GameStatus, percent: i32) -> Game {
+        Game {
+            oid: ObjectId::new(),
+            title: "Outer Wilds".to_string(),
+            developer: "Mobius Digital".to_string(),
+            genres: vec![],
+            tags: vec![],
+            rating: 5,
+            status,
+            description: String::new(),
+            my_thoughts: String::new(),
+            links: None,
+            cover_image: String::new(),
+            explicit: false,
+            percent,
+            bad: false,
+            playtime_hours: 0.0,
+            platforms: vec![],
+            started_at: None,
+            finished_at: None,
+            checklist: vec![],
+            percent_from_checklist: false,
+            created_at: None,
+            updated_at: None,
+            deleted_at: None,
+        }
+    }
+
+    fn item(name: &str, unlocked: bool) -> NewChecklistItem {
+        NewChecklistItem {
+            name: name.to_string(),
+            description: None,
+            hidden: false,
+            unlocked_at: unlocked.then(|| chrono::Utc::now().naive_utc()),
+        }
+    }
+
+    #[test]
+    fn test_game_status_parsing() {
+        assert_eq!(GameStatus::parse("On Hold"), Some(GameStatus::Paused));
+        assert_eq!(GameStatus::parse("Finished"), Some(GameStatus::Completed));
+        assert_eq!(GameStatus::parse("100%"), Some(GameStatus::HundredPercent));
+        assert_eq!(GameStatus::parse("vibing"), None);
+
+        let status: GameStatus = serde_json::from_str(r#""Plan to play""#).unwrap();
+        assert_eq!(status, GameStatus::Backlog);
+        assert!(serde_json::from_str::<GameStatus>(r#""vibing""#).is_err());
+
+        let mut stored = mongodb::bson::to_document(&game(GameStatus::Paused, 0)).unwrap();
+        stored.insert("status", "vibing");
+        let game: Game = mongodb::bson::from_document(stored.clone()).unwrap();
+        assert_eq!(game.status, GameStatus::Backlog);
+        stored.remove("status");
+        assert!(mongodb::bson::from_document::<Game>(stored).is_ok());
+        assert_eq!(
+            serde_json::to_string(&GameStatus::HundredPercent).unwrap(),
+            r#""100%""#
+        );
+    }
+
+    #[test]
+    fn test_record_session_starts_and_completes() {
+        let now = chrono::Utc::now().naive_utc();
+        let mut game = game(GameStatus::Backlog, 0);
+
+        let session = NewPlaySession {
+            hours: 2.5,
+            percent: Some(40),
+            platform: Some("PC".to_string()),
+            ..Default::default()
+        };
+        game.record_session(&session, now).unwrap();
+        assert_eq!(game.status, GameStatus::Playing);
+        assert_eq!(game.playtime_hours, 2.5);
+        assert_eq!(game.platforms, vec!["PC"]);
+        assert_eq!(game.started_at, Some(now));
+        assert_eq!(game.finished_at, None);
+
+        let session = NewPlaySession {
+            hours: 1.5,
+            percent: Some(150),
+            platform: Some("PC".to_string()),
+            ..Default::default()
+        };
+        game.record_session(&session, now).unwrap();
+        assert_eq!(game.percent, 100);
+        assert_eq!(game.status, GameStatus::Completed);
+        assert_eq!(game.playtime_hours, 4.0);
+        assert_eq!(game.platforms.len(), 1);
+        assert_eq!(game.finished_at, Some(now));
+    }
+
+    #[test]
+    fn test_record_session_explicit_status() {
+        let now = chrono::Utc::now().naive_utc();
+        let mut game = game(GameStatus::Completed, 90);
+
+        let session = NewPlaySession {
+            hours: 10.0,
+            status: Some(GameStatus::HundredPercent),
+            ..Default::default()
+        };
+        game.record_session(&session, now).unwrap();
+        assert_eq!(game.status, GameStatus::HundredPercent);
+        assert_eq!(game.percent, 100);
+
+        let invalid = NewPlaySession {
+            hours: 0.0,
+            ..Default::default()
+        };
+        assert!(game.record_session(&invalid, now).is_err());
+    }
+
+    #[test]
+    fn test_checklist_percent_derived() {
+        let mut game = game(GameStatus::Playing, 10);
+        assert_eq!(game.checklist_percent(), None);
+
+        game.import_checklist(vec![item("A", true), item("B", false), item("C", false)]);
+        assert_eq!(game.checklist_percent(), Some(33));
+        assert_eq!(game.percent, 10);
+
+        game.percent_from_checklist = true;
+        game.sync_checklist_percent();
+        assert_eq!(game.percent, 33);
+
+        let session = NewPlaySession {
+            hours: 1.0,
+            percent: Some(90),
+            ..Default::default()
+        };
+        game.record_session(&session, chrono::Utc::now().naive_utc())
+            .unwrap();
+        assert_eq!(game.percent, 33);
+    }
+
+    #[test]
+    fn test_import_checklist_merges_by_name() {
+        let mut game = game(GameStatus::Playing, 0);
+        game.percent_from_checklist = true;
+
+        let first = game.import_checklist(vec![item("Ship Log", false), item("Eye", false)]);
+        assert_eq!(
+            first,
+            ChecklistImport {
+                added: 2,
+                updated: 0
+            }
+        );
+
+        let second = game.import_checklist(vec![
+            item(" ship log ", true),
+            item("Eye", false),
+            item("Quantum Moon", false),
+        ]);
+        assert_eq!(
+            second,
+            ChecklistImport {
+                added: 1,
+                updated: 1
+            }
+        );
+        assert_eq!(game.checklist.len(), 3);
+        assert!(game.checklist[0].is_unlocked());
+        assert_eq!(game.percent, 33);
+
+        let relock = game.import_checklist(vec![item("Ship Log", false)]);
+        assert_eq!(relock, ChecklistImport::default());
+        assert!(game.checklist[0].is_unlocked());
+    }
+
+    #[test]
+    fn test_api_key_scopes_and_expiry() {
+        let now = chrono::Utc::now().naive_utc();
+        let mut key = ApiKey {
+            oid: ObjectId::new(),
+            key_hash: "hash".to_string(),
+            is_admin: false,
+            created_at: now,
+            last_used_at: None,
+            label: None,
+            expires_at: None,
+            scopes: Vec::new(),
+            use_count: 0,
+        };
+
+        assert!(key.allows("/games/search"));
+        assert!(!key.is_expired(now));
+
+        key.scopes = vec![KeyScope::Books, KeyScope::Reviews];
+        assert!(key.allows("/read-watch/search"));
+        assert!(key.allows("/reviews"));
+        assert!(!key.allows("/games/search"));
+        assert!(!key.allows("/admin/export"));
+        assert!(key.allows("/misc/check-login"));
+        assert!(key.allows("/"));
+        assert!(!key.allows("/trash"));
+        assert!(!key.allows("/trash/games/67d3f6ad0000000000000000/restore"));
+        assert!(!key.allows("/unknown"));
+
+        key.scopes = vec![KeyScope::Trash];
+        assert!(key.allows("/trash"));
+        assert!(!key.allows("/read-watch/search"));
+
+        key.expires_at = Some(now - chrono::Duration::minutes(1));
+        assert!(key.is_expired(now));
+
+        for name in KeyScope::NAMES {
+            assert_eq!(name.parse::<KeyScope>().unwrap().as_str(), name);
+        }
+        assert!("everything".parse::<KeyScope>().is_err());
     }
 }
@@ -0,0 +1,1023 @@
+//! # OpenAPI specification
+//!
+//! Builds an OpenAPI 3 document from the mounted Rocket routes.
+//!
+//! Paths, methods and parameter names come from the route definitions
+//! themselves, so they cannot drift from the handlers. Everything Rocket does
+//! not know about (summaries, the fields of `<query..>` structs, request and
+//! response bodies) is described in [`operation_docs`], keyed by handler name.
+//! Schemas are generated from the model structs with `schemars`.
+//!
+//! Every mounted route must have an entry in [`operation_docs`]; a test fails
+//! otherwise.
+
+use {
+    crate::{
+        backup::RestoreReport,
+        handlers::{books, games, misc, reviews, series, taxonomy, webhooks},
+        importers::{ImportReport, books::BookImportPlan, games::GameImportPlan},
+        markdown::Rendered,
+        models::{
+            Book, ChecklistImport, Game, LocalizedBook, LocalizedSeries, NewBook, NewChecklistItem,
+            NewGame, NewPlaySession, NewProject, NewReview, NewSeries, NewTaxonomyTerm, NewWebhook,
+            NewWplaceScreenshot, PlaySession, ProgressEntry, Project, Review, Revision, Series,
+            TaxonomyTerm, TrashedDocument, UpdateBook, UpdateChecklistItem, UpdateGame,
+            UpdateProgress, UpdateProject, UpdateReview, UpdateSeries, UpdateTaxonomyTerm,
+            UpdateWebhook, Webhook, WebhookDelivery, WplaceScreenshot,
+        },
+    },
+    rocket::{Route, http::Method},
+    schemars::{JsonSchema, Schema, SchemaGenerator, generate::SchemaSettings, json_schema},
+    serde_json::{Map, Value, json},
+    std::{borrow::Cow, collections::HashMap},
+};
+
+/// Schema for a MongoDB ObjectId as it appears in JSON (`{"$oid": "..."}`).
+pub struct ObjectIdSchema;
+
+impl JsonSchema for ObjectIdSchema {
+    fn schema_name() -> Cow<'static, str> {
+        "ObjectId".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "object",
+            "description": "MongoDB ObjectId in extended JSON form",
+            "properties": {
+                "$oid": { "type": "string", "pattern": "^[0-9a-f]{24}$" }
+            },
+            "required": ["$oid"]
+        })
+    }
+}
+
+/// Produces a schema, registering any referenced definitions with the generator.
+type SchemaFn = fn(&mut SchemaGenerator) -> Schema;
+
+fn schema<T: JsonSchema>(generator: &mut SchemaGenerator) -> Schema {
+    generator.subschema_for::<T>()
+}
+
+/// Produces an inline schema with the OpenAPI 3.0 transforms applied.
+///
+/// `take_definitions` transforms the shared component schemas, but schemas
+/// placed directly in an operation have to be transformed here.
+fn resolve(schema: SchemaFn, generator: &mut SchemaGenerator) -> Value {
+    let mut schema = schema(generator);
+    for transform in generator.transforms_mut() {
+        transform.transform(&mut schema);
+    }
+    schema.to_value()
+}
+
+/// A request or response body.
+pub enum Content {
+    /// JSON described by a schema
+    Json(SchemaFn),
+    /// Non-JSON content of the given media type
+    Raw(&'static str),
+    /// No body
+    Empty,
+}
+
+/// What the spec says about one handler beyond its path and method.
+pub struct OperationDoc {
+    pub summary: &'static str,
+    /// Schema of the struct behind a trailing `<query..>` segment
+    pub query: Option<SchemaFn>,
+    pub body: Content,
+    pub response: Content,
+    /// Whether the handler takes an API key guard
+    pub requires_key: bool,
+}
+
+fn op(summary: &'static str) -> OperationDoc {
+    OperationDoc {
+        summary,
+        query: None,
+        body: Content::Empty,
+        response: Content::Empty,
+        requires_key: false,
+    }
+}
+
+impl OperationDoc {
+    fn keyed(mut self) -> Self {
+        self.requires_key = true;
+        self
+    }
+
+    fn query(mut self, query: SchemaFn) -> Self {
+        self.query = Some(query);
+        self
+    }
+
+    fn body(mut self, body: SchemaFn) -> Self {
+        self.body = Content::Json(body);
+        self
+    }
+
+    fn raw_body(mut self, media_type: &'static str) -> Self {
+        self.body = Content::Raw(media_type);
+        self
+    }
+
+    fn returns(mut self, response: SchemaFn) -> Self {
+        self.response = Content::Json(response);
+        self
+    }
+
+    fn returns_raw(mut self, media_type: &'static str) -> Self {
+        self.response = Content::Raw(media_type);
+        self
+    }
+}
+
+/// Documentation for every handler, keyed by handler name.
+pub fn operation_docs() -> HashMap<&'static str, OperationDoc> {
+    HashMap::from([
+        ("index", op("API banner").returns_raw("text/plain")),
+        (
+            "openapi_json",
+            op("This OpenAPI document").returns_raw("application/json"),
+        ),
+        (
+            "swagger_ui",
+            op("Swagger UI for this API").returns_raw("text/html"),
+        ),
+        (
+            "redoc_ui",
+            op("Redoc UI for this API").returns_raw("text/html"),
+        ),
+        (
+            "docs_asset",
+            op("Swagger UI and Redoc assets").returns_raw("application/octet-stream"),
+        ),
+        // Books
+        (
+            "get_books",
+            op("Search books")
+                .query(books::BookQuery::json_schema)
+                .returns(schema::<Vec<Rendered<LocalizedBook>>>),
+        ),
+        (
+            "get_book_by_id",
+            op("Get a book, localized").returns(schema::<Rendered<LocalizedBook>>),
+        ),
+        (
+            "get_raw_book_by_id",
+            op("Get a book with every locale").returns(schema::<Book>),
+        ),
+        (
+            "post_books",
+            op("Create a book")
+                .keyed()
+                .body(schema::<NewBook>)
+                .returns(schema::<Book>),
+        ),
+        (
+            "update_book",
+            op("Replace a book's fields")
+                .keyed()
+                .body(schema::<UpdateBook>)
+                .returns(schema::<Book>),
+        ),
+        (
+            "patch_book",
+            op("Update some of a book's fields")
+                .keyed()
+                .body(schema::<UpdateBook>)
+                .returns(schema::<Book>),
+        ),
+        (
+            "delete_book",
+            op("Move a book to the trash")
+                .keyed()
+                .returns(schema::<books::ApiResponse>),
+        ),
+        (
+            "bulk_delete_books",
+            op("Move books matching a non-empty filter to the trash; requires `confirm`")
+                .keyed()
+                .body(schema::<books::BulkDeleteFilter>)
+                .returns(schema::<books::ApiResponse>),
+        ),
+        (
+            "bulk_update_books",
+            op("Update books matching a filter")
+                .keyed()
+                .body(schema::<books::BulkUpdatePayload>)
+                .returns(schema::<books::ApiResponse>),
+        ),
+        (
+            "patch_progress",
+            op("Record reading progress")
+                .keyed()
+                .body(schema::<UpdateProgress>)
+                .returns(schema::<Book>),
+        ),
+        (
+            "get_progress_history",
+            op("List a book's progress history").returns(schema::<Vec<ProgressEntry>>),
+        ),
+        (
+            "import_books",
+            op("Import a Goodreads or StoryGraph CSV export")
+                .keyed()
+                .raw_body("text/csv")
+                .returns(schema::<ImportReport<BookImportPlan>>),
+        ),
+        // Games
+        (
+            "get_games",
+            op("Search games")
+                .query(games::GameQuery::json_schema)
+                .returns(schema::<Vec<Rendered<Game>>>),
+        ),
+        (
+            "get_game_by_id",
+            op("Get a game").returns(schema::<Rendered<Game>>),
+        ),
+        (
+            "post_games",
+            op("Create a game")
+                .keyed()
+                .body(schema::<NewGame>)
+                .returns(schema::<Game>),
+        ),
+        (
+            "update_game",
+            op("Replace a game's fields")
+                .keyed()
+                .body(schema::<UpdateGame>)
+                .returns(schema::<Game>),
+        ),
+        (
+            "patch_game",
+            op("Update some of a game's fields")
+                .keyed()
+                .body(schema::<UpdateGame>)
+                .returns(schema::<Game>),
+        ),
+        (
+            "delete_game",
+            op("Move a game to the trash")
+                .keyed()
+                .returns(schema::<games::ApiResponse>),
+        ),
+        (
+            "bulk_delete_games",
+            op("Move games matching a non-empty filter to the trash; requires `confirm`")
+                .keyed()
+                .body(schema::<games::BulkDeleteFilter>)
+                .returns(schema::<games::ApiResponse>),
+        ),
+        (
+            "bulk_update_games",
+            op("Update games matching a filter")
+                .keyed()
+                .body(schema::<games::BulkUpdatePayload>)
+                .returns(schema::<games::ApiResponse>),
+        ),
+        (
+            "post_session",
+            op("Log a play session")
+                .keyed()
+                .body(schema::<NewPlaySession>)
+                .returns(schema::<Game>),
+        ),
+        (
+            "get_sessions",
+            op("List a game's play sessions").returns(schema::<Vec<PlaySession>>),
+        ),
+        (
+            "get_checklist",
+            op("Get a game's achievement checklist").returns(schema::<games::ChecklistSummary>),
+        ),
+        (
+            "add_checklist_item",
+            op("Add a checklist item")
+                .keyed()
+                .body(schema::<NewChecklistItem>)
+                .returns(schema::<games::ChecklistSummary>),
+        ),
+        (
+            "patch_checklist_item",
+            op("Update a checklist item")
+                .keyed()
+                .body(schema::<UpdateChecklistItem>)
+                .returns(schema::<games::ChecklistSummary>),
+        ),
+        (
+            "delete_checklist_item",
+            op("Delete a checklist item")
+                .keyed()
+                .returns(schema::<games::ChecklistSummary>),
+        ),
+        (
+            "import_checklist",
+            op("Import checklist items")
+                .keyed()
+                .body(schema::<games::ChecklistImportPayload>)
+                .returns(schema::<ChecklistImport>),
+        ),
+        (
+            "import_games",
+            op("Import a Steam, GOG Galaxy or Playnite library export")
+                .keyed()
+                .raw_body("text/plain")
+                .returns(schema::<ImportReport<GameImportPlan>>),
+        ),
+        // Projects
+        (
+            "create_project",
+            op("Create a project")
+                .keyed()
+                .body(schema::<NewProject>)
+                .returns(schema::<Project>),
+        ),
+        (
+            "get_project",
+            op("Get a project").returns(schema::<Rendered<Project>>),
+        ),
+        (
+            "get_projects",
+            op("List projects").returns(schema::<Vec<Rendered<Project>>>),
+        ),
+        (
+            "update_project",
+            op("Replace a project's fields")
+                .keyed()
+                .body(schema::<UpdateProject>)
+                .returns(schema::<Project>),
+        ),
+        (
+            "patch_project",
+            op("Update some of a project's fields")
+                .keyed()
+                .body(schema::<UpdateProject>)
+                .returns(schema::<Project>),
+        ),
+        (
+            "delete_project",
+            op("Move a project to the trash")
+                .keyed()
+                .returns(schema::<Project>),
+        ),
+        // Reviews
+        (
+            "create_review",
+            op("Create a chapter review")
+                .keyed()
+                .body(schema::<NewReview>)
+                .returns(schema::<Review>),
+        ),
+        (
+            "get_review_by_oid",
+            op("Get a review by id").returns(schema::<Rendered<Review>>),
+        ),
+        (
+            "get_review_by_chapter",
+            op("Get a review by chapter").returns(schema::<Rendered<Review>>),
+        ),
+        (
+            "get_reviews",
+            op("List reviews")
+                .query(reviews::ReviewQuery::json_schema)
+                .returns(schema::<Vec<Rendered<Review>>>),
+        ),
+        (
+            "get_review_stats",
+            op("Review rating statistics")
+                .query(reviews::ReviewQuery::json_schema)
+                .returns(schema::<reviews::ReviewStats>),
+        ),
+        (
+            "patch_review_by_chapter",
+            op("Update a review by chapter")
+                .keyed()
+                .body(schema::<UpdateReview>)
+                .returns(schema::<Review>),
+        ),
+        (
+            "patch_review_by_id",
+            op("Update a review by id")
+                .keyed()
+                .body(schema::<UpdateReview>)
+                .returns(schema::<Review>),
+        ),
+        (
+            "batch_delete_reviews",
+            op(
+                "Move reviews for chapter ranges such as `10-20,25` to the trash; requires `confirm=true` and covers at most 1000 chapters",
+            )
+            .keyed(),
+        ),
+        (
+            "delete_review",
+            op("Move a review to the trash by chapter").keyed(),
+        ),
+        (
+            "delete_review_by_id",
+            op("Move a review to the trash by id").keyed(),
+        ),
+        (
+            "get_work_reviews",
+            op("List a work's reviews").returns(schema::<Vec<Rendered<Review>>>),
+        ),
+        (
+            "get_work_review",
+            op("Get a work's review for a chapter").returns(schema::<Rendered<Review>>),
+        ),
+        (
+            "create_work_review",
+            op("Review a chapter of a work")
+                .keyed()
+                .body(schema::<NewReview>)
+                .returns(schema::<Review>),
+        ),
+        (
+            "patch_work_review",
+            op("Update a work's review for a chapter")
+                .keyed()
+                .body(schema::<UpdateReview>)
+                .returns(schema::<Review>),
+        ),
+        (
+            "delete_work_review",
+            op("Move a work's review for a chapter to the trash").keyed(),
+        ),
+        // Series
+        (
+            "get_all_series",
+            op("List series").returns(schema::<Vec<LocalizedSeries>>),
+        ),
+        (
+            "get_series",
+            op("Get a series").returns(schema::<LocalizedSeries>),
+        ),
+        (
+            "create_series",
+            op("Create a series")
+                .keyed()
+                .body(schema::<NewSeries>)
+                .returns(schema::<Series>),
+        ),
+        (
+            "patch_series",
+            op("Update a series")
+                .keyed()
+                .body(schema::<UpdateSeries>)
+                .returns(schema::<Series>),
+        ),
+        (
+            "set_series_members",
+            op("Set the books in a series")
+                .keyed()
+                .body(schema::<series::MembersPayload>)
+                .returns(schema::<LocalizedSeries>),
+        ),
+        (
+            "delete_series",
+            op("Delete a series")
+                .keyed()
+                .returns(schema::<series::ApiResponse>),
+        ),
+        // Taxonomy
+        (
+            "get_terms",
+            op("List taxonomy terms").returns(schema::<Vec<taxonomy::TermSummary>>),
+        ),
+        (
+            "get_term",
+            op("Get a taxonomy term").returns(schema::<taxonomy::TermSummary>),
+        ),
+        (
+            "create_term",
+            op("Create a taxonomy term")
+                .keyed()
+                .body(schema::<NewTaxonomyTerm>)
+                .returns(schema::<TaxonomyTerm>),
+        ),
+        (
+            "patch_term",
+            op("Update a taxonomy term")
+                .keyed()
+                .body(schema::<UpdateTaxonomyTerm>)
+                .returns(schema::<TaxonomyTerm>),
+        ),
+        (
+            "rename_term",
+            op("Rename a term and update references")
+                .keyed()
+                .body(schema::<taxonomy::RenamePayload>)
+                .returns(schema::<TaxonomyTerm>),
+        ),
+        (
+            "merge_terms",
+            op("Merge terms into one")
+                .keyed()
+                .body(schema::<taxonomy::MergePayload>)
+                .returns(schema::<TaxonomyTerm>),
+        ),
+        (
+            "normalize_references",
+            op("Rewrite genre and tag references to canonical slugs")
+                .keyed()
+                .returns(schema::<taxonomy::ApiResponse>),
+        ),
+        // wplace
+        (
+            "create_screenshot",
+            op("Add a wplace screenshot")
+                .keyed()
+                .body(schema::<NewWplaceScreenshot>)
+                .returns(schema::<WplaceScreenshot>),
+        ),
+        (
+            "get_screenshot_by_id",
+            op("Get a wplace screenshot").returns(schema::<WplaceScreenshot>),
+        ),
+        (
+            "get_screenshots",
+            op("List wplace screenshots").returns(schema::<Vec<WplaceScreenshot>>),
+        ),
+        (
+            "delete_screenshot",
+            op("Move a wplace screenshot to the trash"),
+        ),
+        // History
+        (
+            "get_book_history",
+            op("Revisions of a book, newest first")
+                .keyed()
+                .returns(schema::<Vec<Revision>>),
+        ),
+        (
+            "revert_book",
+            op("Restore a book to a revision")
+                .keyed()
+                .returns(schema::<Book>),
+        ),
+        (
+            "get_game_history",
+            op("Revisions of a game, newest first")
+                .keyed()
+                .returns(schema::<Vec<Revision>>),
+        ),
+        (
+            "revert_game",
+            op("Restore a game to a revision")
+                .keyed()
+                .returns(schema::<Game>),
+        ),
+        (
+            "get_project_history",
+            op("Revisions of a project, newest first")
+                .keyed()
+                .returns(schema::<Vec<Revision>>),
+        ),
+        (
+            "revert_project",
+            op("Restore a project to a revision")
+                .keyed()
+                .returns(schema::<Project>),
+        ),
+        (
+            "get_review_history",
+            op("Revisions of a review, newest first")
+                .keyed()
+                .returns(schema::<Vec<Revision>>),
+        ),
+        (
+            "revert_review",
+            op("Restore a review to a revision")
+                .keyed()
+                .returns(schema::<Review>),
+        ),
+        (
+            "get_screenshot_history",
+            op("Revisions of a wplace screenshot, newest first")
+                .keyed()
+                .returns(schema::<Vec<Revision>>),
+        ),
+        (
+            "revert_screenshot",
+            op("Restore a wplace screenshot to a revision")
+                .keyed()
+                .returns(schema::<WplaceScreenshot>),
+        ),
+        // Misc
+        (
+            "health",
+            op("Legacy database health check").returns(schema::<misc::HealthStatus>),
+        ),
+        (
+            "live",
+            op("Liveness probe").returns(schema::<misc::Liveness>),
+        ),
+        (
+            "ready",
+            op("Readiness probe; 503 when MongoDB is unreachable. Admin keys also get collections, indexes and migrations")
+                .returns(schema::<misc::Readiness>),
+        ),
+        (
+            "check_admin_status",
+            op("Describe the calling API key")
+                .keyed()
+                .returns(schema::<misc::CheckStatusResponse>),
+        ),
+        (
+            "metrics",
+            op("Prometheus metrics").returns_raw("text/plain"),
+        ),
+        // Admin
+        (
+            "export_ndjson",
+            op("Export the catalog as NDJSON")
+                .keyed()
+                .returns_raw("application/x-ndjson"),
+        ),
+        (
+            "export_csv_file",
+            op("Export `manifest.json` or one `<collection>.csv` file")
+                .keyed()
+                .returns_raw("text/csv"),
+        ),
+        (
+            "import_ndjson",
+            op("Restore an NDJSON backup")
+                .keyed()
+                .raw_body("application/x-ndjson")
+                .returns(schema::<RestoreReport>),
+        ),
+        (
+            "import_csv_collection",
+            op("Restore one collection from CSV")
+                .keyed()
+                .raw_body("text/csv")
+                .returns(schema::<RestoreReport>),
+        ),
+        // Events
+        (
+            "stream_events",
+            op("Server-sent events stream of changes; resumes after `Last-Event-ID`")
+                .returns_raw("text/event-stream"),
+        ),
+        // Feeds
+        (
+            "activity_atom",
+            op("Activity feed as Atom").returns_raw("application/atom+xml"),
+        ),
+        (
+            "activity_rss",
+            op("Activity feed as RSS").returns_raw("application/rss+xml"),
+        ),
+        (
+            "activity_json",
+            op("Activity feed as JSON Feed").returns_raw("application/feed+json"),
+        ),
+        // Webhooks
+        (
+            "get_webhooks",
+            op("List webhooks").keyed().returns(schema::<Vec<Webhook>>),
+        ),
+        (
+            "create_webhook",
+            op("Register a webhook; the response includes its signing secret")
+                .keyed()
+                .body(schema::<NewWebhook>)
+                .returns(schema::<Webhook>),
+        ),
+        (
+            "get_webhook",
+            op("Get a webhook").keyed().returns(schema::<Webhook>),
+        ),
+        (
+            "patch_webhook",
+            op("Update a webhook")
+                .keyed()
+                .body(schema::<UpdateWebhook>)
+                .returns(schema::<Webhook>),
+        ),
+        (
+            "delete_webhook",
+            op("Delete a webhook and its delivery log")
+                .keyed()
+                .returns(schema::<webhooks::ApiResponse>),
+        ),
+        (
+            "get_deliveries",
+            op("Delivery log of a webhook, newest first, at most 200 per page")
+                .keyed()
+                .returns(schema::<Vec<WebhookDelivery>>),
+        ),
+        (
+            "retry_delivery",
+            op("Queue a delivery again")
+                .keyed()
+                .returns(schema::<WebhookDelivery>),
+        ),
+        (
+            "ping_webhook",
+            op("Queue a ping delivery")
+                .keyed()
+                .returns(schema::<WebhookDelivery>),
+        ),
+        // Trash
+        (
+            "list_trash",
+            op("Trashed documents, most recently deleted first")
+                .keyed()
+                .returns(schema::<Vec<TrashedDocument>>),
+        ),
+        (
+            "restore_from_trash",
+            op("Take a document out of the trash").keyed(),
+        ),
+    ])
+}
+
+/// Schema of a simple `<name>` query or path parameter.
+fn parameter_schema(name: &str, generator: &mut SchemaGenerator) -> Value {
+    match name {
+        "render" => json!({ "type": "string", "enum": ["markdown", "html"] }),
+        "apply" | "update" | "keys" => json!({ "type": "boolean" }),
+        "window" | "chapter" | "offset" | "limit" | "revision" => json!({ "type": "integer" }),
+        "policy" => json!({ "type": "string", "enum": ["skip", "overwrite", "fail"] }),
+        "kind" => resolve(schema::<crate::models::TermKind>, generator),
+        "status" => resolve(schema::<crate::models::DeliveryStatus>, generator),
+        _ => json!({ "type": "string" }),
+    }
+}
+
+fn simple_parameter(name: &str, location: &str, generator: &mut SchemaGenerator) -> Value {
+    json!({
+        "name": name,
+        "in": location,
+        "required": location == "path",
+        "schema": parameter_schema(name, generator),
+    })
+}
+
+/// Expands the fields of a `<query..>` struct into query parameters.
+fn struct_parameters(query: SchemaFn, generator: &mut SchemaGenerator) -> Vec<Value> {
+    let schema = resolve(query, generator);
+    let Some(properties) = schema.get("properties").and_then(Value::as_object) else {
+        return vec![];
+    };
+
+    properties
+        .iter()
+        .map(|(name, property)| {
+            let mut property = property.clone();
+            let description = property
+                .as_object_mut()
+                .and_then(|property| {
+                    property.remove("nullable");
+                    property.remove("description")
+                })
+                .unwrap_or(Value::Null);
+
+            let mut parameter = json!({
+                "name": name,
+                "in": "query",
+                "required": false,
+                "schema": property,
+            });
+            if !description.is_null() {
+                parameter["description"] = description;
+            }
+            parameter
+        })
+        .collect()
+}
+
+/// Splits `/a/<b>/<c..>` into an OpenAPI path and its parameter names.
+fn path_template(path: &str) -> (String, Vec<String>) {
+    let mut names = Vec::new();
+    let template = path
+        .split('/')
+        .map(
+            |segment| match segment.strip_prefix('<').and_then(|s| s.strip_suffix('>')) {
+                Some(name) => {
+                    let name = name.trim_end_matches("..").to_string();
+                    let segment = format!("{{{}}}", name);
+                    names.push(name);
+                    segment
+                }
+                None => segment.to_string(),
+            },
+        )
+        .collect::<Vec<_>>()
+        .join("/");
+
+    (template, names)
+}
+
+fn content(content: &Content, generator: &mut SchemaGenerator) -> Option<Value> {
+    match content {
+        Content::Json(schema) => {
+            Some(json!({ "application/json": { "schema": resolve(*schema, generator) } }))
+        }
+        Content::Raw(media_type) => Some(json!({ media_type.to_string(): {} })),
+        Content::Empty => None,
+    }
+}
+
+fn tag_for(path: &str) -> String {
+    path.trim_start_matches('/')
+        .split('/')
+        .next()
+        .filter(|segment| !segment.is_empty() && !segment.contains('.'))
+        .unwrap_or("meta")
+        .to_string()
+}
+
+/// Builds the OpenAPI document for the given routes.
+///
+/// Routes without an entry in [`operation_docs`] are still listed, without a
+/// summary or body schemas.
+pub fn spec<'a>(routes: impl IntoIterator<Item = &'a Route>) -> Value {
+    let docs = operation_docs();
+    let mut generator = SchemaSettings::openapi3().into_generator();
+    let routes: Vec<&Route> = routes.into_iter().collect();
+
+    let mut name_counts: HashMap<&str, usize> = HashMap::new();
+    for route in &routes {
+        if let Some(name) = route.name.as_deref() {
+            *name_counts.entry(name).or_default() += 1;
+        }
+    }
+
+    let mut paths = Map::new();
+    for route in routes {
+        let name = route.name.as_deref().unwrap_or("unnamed");
+        let doc = docs.get(name);
+        let (path, path_names) = path_template(route.uri.path());
+        let tag = tag_for(&path);
+
+        let mut parameters: Vec<Value> = path_names
+            .iter()
+            .map(|name| simple_parameter(name, "path", &mut generator))
+            .collect();
+        for segment in route.uri.query().unwrap_or_default().split('&') {
+            let Some(segment) = segment.strip_prefix('<').and_then(|s| s.strip_suffix('>')) else {
+                continue;
+            };
+            match (segment.strip_suffix(".."), doc.and_then(|doc| doc.query)) {
+                (Some(_), Some(query)) => {
+                    parameters.extend(struct_parameters(query, &mut generator))
+                }
+                (Some(_), None) => {}
+                (None, _) => parameters.push(simple_parameter(segment, "query", &mut generator)),
+            }
+        }
+
+        let operation_id = if name_counts.get(name).copied().unwrap_or_default() > 1 {
+            format!("{}_{}", name, tag.replace('-', "_"))
+        } else {
+            name.to_string()
+        };
+
+        let mut operation = json!({
+            "operationId": operation_id,
+            "tags": [tag],
+            "parameters": parameters,
+        });
+        let mut success = json!({ "description": "Success" });
+
+        if let Some(doc) = doc {
+            operation["summary"] = json!(doc.summary);
+            if let Some(body) = content(&doc.body, &mut generator) {
+                operation["requestBody"] = json!({ "required": true, "content": body });
+            }
+            match content(&doc.response, &mut generator) {
+                Some(response) => success["content"] = response,
+                None if route.method != Method::Get => success["description"] = json!("No content"),
+                None => {}
+            }
+        }
+
+        let status = match doc.map(|doc| &doc.response) {
+            Some(Content::Empty) if route.method != Method::Get => "204",
+            _ => "200",
+        };
+        operation["responses"] = json!({
+            status: success,
+            "default": {
+                "description": "Error",
+                "content": { "text/plain": { "schema": { "type": "string" } } }
+            }
+        });
+        if doc.is_some_and(|doc| doc.requires_key) {
+            operation["security"] = json!([{ "bearerAuth": [] }]);
+        }
+
+        let entry = paths
+            .entry(path)
+            .or_insert_with(|| Value::Object(Map::new()));
+        entry[route.method.as_str().to_lowercase()] = operation;
+    }
+
+    json!({
+        "openapi": "3.0.3",
+        "info": {
+            "title": "Apiodactyl",
+            "description": "Data management API for https://bearodactyl.dev",
+            "version": env!("CARGO_PKG_VERSION"),
+        },
+        "paths": paths,
+        "components": {
+            "schemas": generator.take_definitions(true),
+            "securitySchemes": {
+                "bearerAuth": { "type": "http", "scheme": "bearer" }
+            }
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_every_route_is_documented() {
+        let rocket = crate::mount_api(rocket::build());
+        let docs = operation_docs();
+        let names: HashSet<&str> = rocket
+            .routes()
+            .map(|route| route.name.as_deref().expect("routes are named"))
+            .collect();
+
+        let undocumented: Vec<_> = names.iter().filter(|n| !docs.contains_key(*n)).collect();
+        assert!(
+            undocumented.is_empty(),
+            "undocumented routes: {undocumented:?}"
+        );
+        let stale: Vec<_> = docs.keys().filter(|n| !names.contains(*n)).collect();
+        assert!(stale.is_empty(), "docs for missing routes: {stale:?}");
+
+        let spec = spec(rocket.routes());
+        for route in rocket.routes() {
+            let (path, _) = path_template(route.uri.path());
+            let method = route.method.as_str().to_lowercase();
+            assert!(
+                spec["paths"][&path][&method].is_object(),
+                "{method} {path} missing from spec"
+            );
+        }
+
+        assert_eq!(
+            spec["paths"]["/read-watch/{book_id}"]["get"]["responses"]["200"]["content"]["application/json"]
+                ["schema"]["$ref"],
+            "#/components/schemas/RenderedLocalizedBook"
+        );
+        assert!(spec["components"]["schemas"]["NewBook"].is_object());
+        assert!(spec["paths"]["/read-watch"]["post"]["security"].is_array());
+        assert!(spec["paths"]["/read-watch/search"]["get"]["security"].is_null());
+        assert!(spec["paths"]["/reviews"]["post"]["security"].is_array());
+        assert!(spec["paths"]["/reviews"]["get"]["security"].is_null());
+        // Follows the handler's guards, not the method
+        assert!(spec["paths"]["/webhooks"]["get"]["security"].is_array());
+    }
+
+    #[test]
+    fn test_path_template() {
+        assert_eq!(
+            path_template("/read-watch/<work_id>/reviews/<chapter>"),
+            (
+                "/read-watch/{work_id}/reviews/{chapter}".to_string(),
+                vec!["work_id".to_string(), "chapter".to_string()]
+            )
+        );
+    }
+
+    #[test]
+    fn test_localized_string_schema() {
+        let mut generator = SchemaSettings::openapi3().into_generator();
+        let schema = crate::models::LocalizedString::json_schema(&mut generator).to_value();
+
+        let variants = schema["anyOf"]
+            .as_array()
+            .expect("untagged enum uses anyOf");
+        assert_eq!(variants[0]["type"], "string");
+        assert_eq!(variants[1]["type"], "object");
+        assert_eq!(variants[1]["additionalProperties"]["type"], "string");
+    }
+
+    #[test]
+    fn test_query_struct_parameters() {
+        let mut generator = SchemaSettings::openapi3().into_generator();
+        let names: Vec<String> = struct_parameters(games::GameQuery::json_schema, &mut generator)
+            .iter()
+            .map(|parameter| parameter["name"].as_str().unwrap().to_string())
+            .collect();
+
+        assert!(names.contains(&"minRating".to_string()));
+        assert!(names.contains(&"exactProgress".to_string()));
+        assert!(!names.contains(&"min_rating".to_string()));
+    }
+}
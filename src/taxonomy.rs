@@ -0,0 +1,425 @@
+//! # Shared genre/tag taxonomy
+//!
+//! Books and games reference genres and tags by canonical slug. This module
+//! provides the in-memory index used to resolve free text (slugs, aliases or
+//! any translated label) to a slug on write, and to render slugs back to
+//! localized labels on read.
+//!
+//! Handlers take a `&Taxonomy` request guard, which loads the `taxonomy`
+//! collection at most once per request, and pass it down to the write paths.
+
+use {
+    crate::{
+        db::BearoData,
+        models::{LocalizedString, LocalizedStringArray, TaxonomyTerm, TermKind},
+    },
+    mongodb::bson::doc,
+    rocket::{
+        Request,
+        futures::TryStreamExt,
+        http::Status,
+        request::{FromRequest, Outcome},
+    },
+    rocket_db_pools::{
+        Database,
+        mongodb::{Client, error::Error},
+    },
+    std::collections::HashMap,
+};
+
+/// Normalizes free text into a slug.
+///
+/// Lowercases the input and collapses every run of non-alphanumeric characters
+/// into a single `-`, so "Sci-Fi", "sci fi" and " SCI_FI " all become "sci-fi".
+pub fn slugify(text: &str) -> String {
+    let mut slug = String::with_capacity(text.len());
+
+    for c in text.trim().chars() {
+        if c.is_alphanumeric() {
+            slug.extend(c.to_lowercase());
+        } else if !slug.is_empty() && !slug.ends_with('-') {
+            slug.push('-');
+        }
+    }
+
+    while slug.ends_with('-') {
+        slug.pop();
+    }
+
+    slug
+}
+
+/// Lookup index over all taxonomy terms.
+#[derive(Debug, Default)]
+pub struct Taxonomy {
+    terms: HashMap<(TermKind, String), TaxonomyTerm>,
+    lookup: HashMap<(TermKind, String), String>,
+}
+
+impl Taxonomy {
+    /// Builds the index from a list of terms.
+    ///
+    /// Slugs take precedence over aliases, which take precedence over labels,
+    /// when the same normalized text would resolve to more than one term.
+    pub fn new(terms: Vec<TaxonomyTerm>) -> Self {
+        let mut lookup = HashMap::new();
+
+        for term in &terms {
+            let texts: Vec<String> = match &term.label {
+                LocalizedString::Simple(text) => vec![text.clone()],
+                LocalizedString::Localized(map) => map.values().cloned().collect(),
+            };
+            for text in texts {
+                lookup.insert((term.kind, slugify(&text)), term.slug.clone());
+            }
+        }
+
+        for term in &terms {
+            for alias in &term.aliases {
+                lookup.insert((term.kind, slugify(alias)), term.slug.clone());
+            }
+        }
+
+        for term in &terms {
+            lookup.insert((term.kind, term.slug.clone()), term.slug.clone());
+        }
+
+        let terms = terms
+            .into_iter()
+            .map(|term| ((term.kind, term.slug.clone()), term))
+            .collect();
+
+        Self { terms, lookup }
+    }
+
+    /// Loads every term from the `taxonomy` collection.
+    pub async fn load(client: &Client) -> Result<Self, Error> {
+        let collection = client
+            .database("bearodata")
+            .collection::<TaxonomyTerm>("taxonomy");
+
+        let terms: Vec<TaxonomyTerm> = collection.find(doc! {}, None).await?.try_collect().await?;
+
+        Ok(Self::new(terms))
+    }
+
+    /// Returns the term with the given slug.
+    pub fn get(&self, kind: TermKind, slug: &str) -> Option<&TaxonomyTerm> {
+        self.terms.get(&(kind, slug.to_string()))
+    }
+
+    /// Resolves free text to the slug of a known term.
+    pub fn resolve(&self, kind: TermKind, text: &str) -> Option<&str> {
+        self.lookup
+            .get(&(kind, slugify(text)))
+            .map(|slug| slug.as_str())
+    }
+
+    /// Replaces a value with its canonical slug, leaving unknown text untouched.
+    pub fn canonicalize(&self, kind: TermKind, text: &str) -> String {
+        self.resolve(kind, text)
+            .map(str::to_string)
+            .unwrap_or_else(|| text.to_string())
+    }
+
+    /// Canonicalizes a plain list of values, dropping duplicates.
+    pub fn canonicalize_list(&self, kind: TermKind, values: &[String]) -> Vec<String> {
+        let mut result: Vec<String> = Vec::with_capacity(values.len());
+
+        for value in values {
+            let canonical = self.canonicalize(kind, value);
+            if !result.contains(&canonical) {
+                result.push(canonical);
+            }
+        }
+
+        result
+    }
+
+    /// Canonicalizes a localized array.
+    ///
+    /// Any entry whose text (in any language) resolves to a known term is
+    /// replaced by that term's slug; other entries are kept as-is.
+    pub fn canonicalize_array(
+        &self,
+        kind: TermKind,
+        values: &LocalizedStringArray,
+    ) -> LocalizedStringArray {
+        match values {
+            LocalizedStringArray::Simple(texts) => {
+                LocalizedStringArray::Simple(self.canonicalize_list(kind, texts))
+            }
+            LocalizedStringArray::Localized(entries) => {
+                let mut result: Vec<LocalizedString> = Vec::with_capacity(entries.len());
+
+                for entry in entries {
+                    let slug = match entry {
+                        LocalizedString::Simple(text) => self.resolve(kind, text),
+                        LocalizedString::Localized(map) => {
+                            map.values().find_map(|text| self.resolve(kind, text))
+                        }
+                    };
+                    let canonical = match slug {
+                        Some(slug) => LocalizedString::Simple(slug.to_string()),
+                        None => entry.clone(),
+                    };
+                    if !result
+                        .iter()
+                        .any(|existing| same_entry(existing, &canonical))
+                    {
+                        result.push(canonical);
+                    }
+                }
+
+                if result
+                    .iter()
+                    .all(|entry| matches!(entry, LocalizedString::Simple(_)))
+                {
+                    LocalizedStringArray::Simple(
+                        result
+                            .into_iter()
+                            .map(|entry| entry.get_text(None))
+                            .collect(),
+                    )
+                } else {
+                    LocalizedStringArray::Localized(result)
+                }
+            }
+        }
+    }
+
+    /// Renders a single value, returning the term label in the given locale for slugs.
+    pub fn render(&self, kind: TermKind, value: &str, locale: Option<&str>) -> String {
+        match self.get(kind, value) {
+            Some(term) => term.label.get_text(locale),
+            None => value.to_string(),
+        }
+    }
+
+    /// Renders a localized array, expanding slugs into the term's localized label.
+    pub fn render_array(
+        &self,
+        kind: TermKind,
+        values: &LocalizedStringArray,
+    ) -> LocalizedStringArray {
+        let entries: Vec<LocalizedString> = match values {
+            LocalizedStringArray::Simple(texts) => texts
+                .iter()
+                .map(|text| LocalizedString::Simple(text.clone()))
+                .collect(),
+            LocalizedStringArray::Localized(entries) => entries.clone(),
+        };
+
+        let rendered: Vec<LocalizedString> = entries
+            .into_iter()
+            .map(|entry| match &entry {
+                LocalizedString::Simple(slug) => match self.get(kind, slug) {
+                    Some(term) => term.label.clone(),
+                    None => entry,
+                },
+                LocalizedString::Localized(_) => entry,
+            })
+            .collect();
+
+        LocalizedStringArray::Localized(rendered)
+    }
+
+    /// Returns every term, optionally restricted to one kind, sorted by slug.
+    pub fn terms(&self, kind: Option<TermKind>) -> Vec<&TaxonomyTerm> {
+        let mut terms: Vec<&TaxonomyTerm> = self
+            .terms
+            .values()
+            .filter(|term| kind.is_none_or(|kind| term.kind == kind))
+            .collect();
+        terms.sort_by(|a, b| a.slug.cmp(&b.slug));
+        terms
+    }
+}
+
+/// The taxonomy, loaded once per request and shared by every guard asking for it.
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for &'r Taxonomy {
+    type Error = ();
+
+    async fn from_request(request: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let taxonomy: &Option<Taxonomy> = request
+            .local_cache_async(async {
+                let db = BearoData::fetch(request.rocket())?;
+                Taxonomy::load(db).await.ok()
+            })
+            .await;
+
+        match taxonomy {
+            Some(taxonomy) => Outcome::Success(taxonomy),
+            None => Outcome::Error((Status::InternalServerError, ())),
+        }
+    }
+}
+
+fn same_entry(a: &LocalizedString, b: &LocalizedString) -> bool {
+    match (a, b) {
+        (LocalizedString::Simple(a), LocalizedString::Simple(b)) => a == b,
+        _ => false,
+    }
+}
+
+/// Rewrites references to any of `from` into `to` within a localized array.
+///
+/// Returns `None` when the array does not reference any of the given slugs.
+pub fn retarget_array(
+    values: &LocalizedStringArray,
+    from: &[String],
+    to: &str,
+) -> Option<LocalizedStringArray> {
+    match values {
+        LocalizedStringArray::Simple(texts) => {
+            retarget_list(texts, from, to).map(LocalizedStringArray::Simple)
+        }
+        LocalizedStringArray::Localized(entries) => {
+            let references = |entry: &LocalizedString| matches!(entry, LocalizedString::Simple(text) if from.contains(text));
+            if !entries.iter().any(references) {
+                return None;
+            }
+
+            let mut result: Vec<LocalizedString> = Vec::with_capacity(entries.len());
+            for entry in entries {
+                let entry = if references(entry) {
+                    LocalizedString::Simple(to.to_string())
+                } else {
+                    entry.clone()
+                };
+                if !result.iter().any(|existing| same_entry(existing, &entry)) {
+                    result.push(entry);
+                }
+            }
+
+            Some(LocalizedStringArray::Localized(result))
+        }
+    }
+}
+
+/// Rewrites references to any of `from` into `to` within a plain list.
+///
+/// Returns `None` when the list does not reference any of the given slugs.
+pub fn retarget_list(values: &[String], from: &[String], to: &str) -> Option<Vec<String>> {
+    if !values.iter().any(|value| from.contains(value)) {
+        return None;
+    }
+
+    let mut result: Vec<String> = Vec::with_capacity(values.len());
+    for value in values {
+        let value = if from.contains(value) {
+            to.to_string()
+        } else {
+            value.clone()
+        };
+        if !result.contains(&value) {
+            result.push(value);
+        }
+    }
+
+    Some(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mongodb::bson::oid::ObjectId;
+
+    fn sci_fi() -> TaxonomyTerm {
+        let mut label = HashMap::new();
+        label.insert("en".to_string(), "Science Fiction".to_string());
+        label.insert("es".to_string(), "Ciencia ficción".to_string());
+
+        TaxonomyTerm {
+            oid: ObjectId::new(),
+            kind: TermKind::Genre,
+            slug: "science-fiction".to_string(),
+            label: LocalizedString::Localized(label),
+            aliases: vec!["Sci-Fi".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_slugify() {
+        assert_eq!(slugify("Sci-Fi"), "sci-fi");
+        assert_eq!(slugify("sci fi"), "sci-fi");
+        assert_eq!(slugify("  Science   Fiction!! "), "science-fiction");
+        assert_eq!(slugify("Ciencia ficción"), "ciencia-ficción");
+        assert_eq!(slugify("---"), "");
+    }
+
+    #[test]
+    fn test_resolve_slug_alias_and_label() {
+        let taxonomy = Taxonomy::new(vec![sci_fi()]);
+
+        assert_eq!(
+            taxonomy.resolve(TermKind::Genre, "science-fiction"),
+            Some("science-fiction")
+        );
+        assert_eq!(
+            taxonomy.resolve(TermKind::Genre, "sci fi"),
+            Some("science-fiction")
+        );
+        assert_eq!(
+            taxonomy.resolve(TermKind::Genre, "Ciencia Ficción"),
+            Some("science-fiction")
+        );
+        assert_eq!(taxonomy.resolve(TermKind::Tag, "sci-fi"), None);
+        assert_eq!(taxonomy.resolve(TermKind::Genre, "Fantasy"), None);
+    }
+
+    #[test]
+    fn test_canonicalize_array_dedupes() {
+        let taxonomy = Taxonomy::new(vec![sci_fi()]);
+        let values = LocalizedStringArray::Simple(vec![
+            "Sci-Fi".to_string(),
+            "Science Fiction".to_string(),
+            "Fantasy".to_string(),
+        ]);
+
+        match taxonomy.canonicalize_array(TermKind::Genre, &values) {
+            LocalizedStringArray::Simple(texts) => {
+                assert_eq!(texts, vec!["science-fiction", "Fantasy"]);
+            }
+            _ => panic!("Expected a simple array"),
+        }
+    }
+
+    #[test]
+    fn test_render_array_uses_locale() {
+        let taxonomy = Taxonomy::new(vec![sci_fi()]);
+        let values = LocalizedStringArray::Simple(vec![
+            "science-fiction".to_string(),
+            "Fantasy".to_string(),
+        ]);
+
+        let rendered = taxonomy.render_array(TermKind::Genre, &values);
+        assert_eq!(
+            rendered.get_texts(Some("es")),
+            vec!["Ciencia ficción", "Fantasy"]
+        );
+        assert_eq!(
+            taxonomy.render(TermKind::Genre, "science-fiction", Some("en")),
+            "Science Fiction"
+        );
+    }
+
+    #[test]
+    fn test_retarget_list() {
+        let values = vec![
+            "sci-fi".to_string(),
+            "science-fiction".to_string(),
+            "fantasy".to_string(),
+        ];
+
+        assert_eq!(
+            retarget_list(&values, &["sci-fi".to_string()], "science-fiction"),
+            Some(vec!["science-fiction".to_string(), "fantasy".to_string()])
+        );
+        assert_eq!(
+            retarget_list(&values, &["horror".to_string()], "science-fiction"),
+            None
+        );
+    }
+}
@@ -0,0 +1,398 @@
+//! # Trash
+//!
+//! Deleting a book, game, project, review or wplace screenshot moves it to
+//! the trash: [`trash`] sets its `deleted_at` instead of removing it, and
+//! reads only see [`live`] documents. `GET /trash` lists trashed documents
+//! and `POST /trash/<collection>/<id>/restore` brings one back with
+//! [`restore`].
+//!
+//! The [`TrashPurger`] fairing removes documents for good, with their
+//! revisions, once they have been in the trash longer than the retention
+//! period: `trash_retention_days` in `Rocket.toml` or
+//! `ROCKET_TRASH_RETENTION_DAYS`, [`DEFAULT_RETENTION_DAYS`] by default.
+
+use {
+    crate::{
+        db::{BearoData, is_duplicate_key},
+        errors::TrashError,
+        events::{self, Event, Resource},
+        history::{self, REVISIONS},
+        models::TrashedDocument,
+    },
+    chrono::NaiveDateTime,
+    mongodb::bson::{self, Bson, Document, doc, oid::ObjectId},
+    rocket::{
+        Orbit, Rocket,
+        fairing::{Fairing, Info, Kind},
+        futures::TryStreamExt,
+        serde::Serialize,
+    },
+    rocket_db_pools::{
+        Database,
+        mongodb::{
+            Client, Collection,
+            options::{FindOneAndUpdateOptions, FindOptions, ReturnDocument},
+        },
+    },
+    serde::de::DeserializeOwned,
+    std::time::Duration,
+};
+
+/// Days a document stays in the trash before it is purged.
+pub const DEFAULT_RETENTION_DAYS: i64 = 30;
+
+/// Figment key overriding [`DEFAULT_RETENTION_DAYS`].
+pub const RETENTION_KEY: &str = "trash_retention_days";
+
+/// How often the purger looks for expired documents.
+const PURGE_INTERVAL: Duration = Duration::from_hours(1);
+
+/// Restricts `filter` to documents that are not in the trash.
+pub fn live(mut filter: Document) -> Document {
+    filter.insert("deleted_at", Bson::Null);
+    filter
+}
+
+/// Restricts `filter` to documents in the trash.
+pub fn trashed(mut filter: Document) -> Document {
+    filter.insert("deleted_at", doc! { "$ne": Bson::Null });
+    filter
+}
+
+/// Resources whose documents are moved to the trash when deleted.
+pub fn trashable() -> impl Iterator<Item = Resource> {
+    Resource::ALL
+        .into_iter()
+        .filter(|resource| history::tracked_collection(*resource).is_some())
+}
+
+/// Finds a trashable resource by its `/trash` name, e.g. `books` or `wplace`.
+pub fn resource(name: &str) -> Result<Resource, TrashError> {
+    trashable()
+        .find(|resource| resource.collection() == name)
+        .ok_or_else(|| TrashError::UnknownCollection(name.to_string()))
+}
+
+/// Database collection of a trashable resource.
+fn collection_name(resource: Resource) -> &'static str {
+    history::tracked_collection(resource).expect("trashable resources have a collection")
+}
+
+/// Checks that a bulk delete has a non-empty `filter` and was confirmed.
+pub fn guard_bulk(filter: &Document, confirm: Option<bool>) -> Result<(), TrashError> {
+    if filter.is_empty() {
+        return Err(TrashError::EmptyFilter);
+    }
+    if confirm != Some(true) {
+        return Err(TrashError::Unconfirmed);
+    }
+    Ok(())
+}
+
+/// Moves the live documents matching `filter` to the trash.
+///
+/// Publishes a `deleted` event for each and returns them as they were deleted.
+pub async fn trash<T>(
+    db: &Client,
+    resource: Resource,
+    filter: Document,
+) -> Result<Vec<T>, TrashError>
+where
+    T: Serialize + DeserializeOwned + Send + Sync + Unpin,
+{
+    let collection = db
+        .database("bearodata")
+        .collection::<Document>(collection_name(resource));
+
+    let ids: Vec<ObjectId> = collection
+        .find(
+            live(filter),
+            FindOptions::builder().projection(doc! { "_id": 1 }).build(),
+        )
+        .await?
+        .try_collect::<Vec<Document>>()
+        .await?
+        .iter()
+        .filter_map(|document| document.get_object_id("_id").ok())
+        .collect();
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let deleted_at = bson::to_bson(&history::now())?;
+    collection
+        .update_many(
+            doc! { "_id": { "$in": &ids } },
+            doc! { "$set": { "deleted_at": deleted_at } },
+            None,
+        )
+        .await?;
+
+    let documents: Vec<T> = db
+        .database("bearodata")
+        .collection::<T>(collection_name(resource))
+        .find(doc! { "_id": { "$in": ids } }, None)
+        .await?
+        .try_collect()
+        .await?;
+    for document in &documents {
+        events::publish(db, Event::deleted(resource), document).await;
+    }
+
+    Ok(documents)
+}
+
+/// Takes a document out of the trash, publishing a `created` event.
+///
+/// `deleted_at` is reset to `null` rather than removed, as unique indexes over
+/// live documents only cover those with a `null` marker.
+pub async fn restore<T>(db: &Client, resource: Resource, id: ObjectId) -> Result<T, TrashError>
+where
+    T: Serialize + DeserializeOwned + Send + Sync + Unpin,
+{
+    let mut update = doc! { "deleted_at": Bson::Null };
+    history::stamp_updated(&mut update, history::now());
+
+    let document = db
+        .database("bearodata")
+        .collection::<T>(collection_name(resource))
+        .find_one_and_update(
+            trashed(doc! { "_id": id }),
+            doc! { "$set": update },
+            FindOneAndUpdateOptions::builder()
+                .return_document(ReturnDocument::After)
+                .build(),
+        )
+        .await
+        .map_err(|e| {
+            if is_duplicate_key(&e) {
+                TrashError::Conflict(id)
+            } else {
+                e.into()
+            }
+        })?
+        .ok_or(TrashError::NotFound(id))?;
+
+    events::publish(db, Event::created(resource), &document).await;
+    Ok(document)
+}
+
+/// When a document deleted at `deleted_at` is purged.
+pub fn purge_at(deleted_at: NaiveDateTime, retention_days: i64) -> NaiveDateTime {
+    deleted_at + chrono::Duration::days(retention_days)
+}
+
+/// Lists trashed documents of the given resources, most recently deleted first.
+pub async fn list(
+    db: &Client,
+    resources: &[Resource],
+    retention_days: i64,
+) -> Result<Vec<TrashedDocument>, TrashError> {
+    let mut trashed_documents = Vec::new();
+    for resource in resources {
+        let documents: Vec<Document> = db
+            .database("bearodata")
+            .collection::<Document>(collection_name(*resource))
+            .find(trashed(doc! {}), None)
+            .await?
+            .try_collect()
+            .await?;
+
+        for document in documents {
+            let (Ok(document_id), Some(deleted_at)) = (
+                document.get_object_id("_id"),
+                document
+                    .get("deleted_at")
+                    .cloned()
+                    .and_then(|deleted_at| bson::from_bson::<NaiveDateTime>(deleted_at).ok()),
+            ) else {
+                continue;
+            };
+
+            trashed_documents.push(TrashedDocument {
+                collection: resource.collection().to_string(),
+                document_id,
+                deleted_at,
+                purge_at: purge_at(deleted_at, retention_days),
+                document,
+            });
+        }
+    }
+
+    trashed_documents.sort_by_key(|trashed| std::cmp::Reverse(trashed.deleted_at));
+    Ok(trashed_documents)
+}
+
+/// Ids of the documents matching `filter`.
+async fn find_ids(
+    collection: &Collection<Document>,
+    filter: Document,
+) -> Result<Vec<Bson>, TrashError> {
+    Ok(collection
+        .find(
+            filter,
+            FindOptions::builder().projection(doc! { "_id": 1 }).build(),
+        )
+        .await?
+        .try_collect::<Vec<Document>>()
+        .await?
+        .into_iter()
+        .filter_map(|mut document| document.remove("_id"))
+        .collect())
+}
+
+/// Removes documents trashed before `cutoff`, and their revisions, for good.
+///
+/// # Returns
+///
+/// The number of documents that were removed.
+pub async fn purge(db: &Client, cutoff: NaiveDateTime) -> Result<u64, TrashError> {
+    let database = db.database("bearodata");
+    let cutoff = bson::to_bson(&cutoff)?;
+
+    let mut purged = 0;
+    for resource in trashable() {
+        let name = collection_name(resource);
+        let collection = database.collection::<Document>(name);
+        let expired = find_ids(
+            &collection,
+            doc! { "deleted_at": { "$lt": cutoff.clone() } },
+        )
+        .await?;
+        if expired.is_empty() {
+            continue;
+        }
+
+        // Documents restored since the `find` no longer match the cutoff.
+        let result = collection
+            .delete_many(
+                doc! { "_id": { "$in": &expired }, "deleted_at": { "$lt": cutoff.clone() } },
+                None,
+            )
+            .await?;
+        let restored = find_ids(&collection, doc! { "_id": { "$in": &expired } }).await?;
+        let removed: Vec<Bson> = expired
+            .into_iter()
+            .filter(|id| !restored.contains(id))
+            .collect();
+
+        database
+            .collection::<Document>(REVISIONS)
+            .delete_many(
+                doc! { "collection": name, "document_id": { "$in": removed } },
+                None,
+            )
+            .await?;
+        purged += result.deleted_count;
+    }
+
+    Ok(purged)
+}
+
+/// Reads the retention period from the configuration.
+pub fn retention_days(figment: &rocket::figment::Figment) -> i64 {
+    figment
+        .extract_inner::<i64>(RETENTION_KEY)
+        .unwrap_or(DEFAULT_RETENTION_DAYS)
+}
+
+/// Background task purging expired documents from the trash every hour.
+pub struct TrashPurger;
+
+#[rocket::async_trait]
+impl Fairing for TrashPurger {
+    fn info(&self) -> Info {
+        Info {
+            name: "Trash purger",
+            kind: Kind::Liftoff,
+        }
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<Orbit>) {
+        let Some(db) = BearoData::fetch(rocket) else {
+            tracing::warn!("database is not attached; the trash will not be purged");
+            return;
+        };
+        let db = Client::clone(db);
+        let shutdown = rocket.shutdown();
+        let retention_days = retention_days(rocket.figment());
+
+        rocket::tokio::spawn(async move {
+            let mut interval = rocket::tokio::time::interval(PURGE_INTERVAL);
+            rocket::tokio::pin!(shutdown);
+
+            loop {
+                rocket::tokio::select! {
+                    _ = &mut shutdown => break,
+                    _ = interval.tick() => {
+                        let cutoff = history::now() - chrono::Duration::days(retention_days);
+                        match purge(&db, cutoff).await {
+                            Ok(0) => {}
+                            Ok(purged) => tracing::info!(purged, "purged trashed documents"),
+                            Err(e) => tracing::warn!(error = %e, "failed to purge the trash"),
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filters() {
+        let id = ObjectId::new();
+        assert_eq!(
+            live(doc! { "_id": id }),
+            doc! { "_id": id, "deleted_at": Bson::Null }
+        );
+        assert_eq!(
+            trashed(doc! {}),
+            doc! { "deleted_at": { "$ne": Bson::Null } }
+        );
+    }
+
+    #[test]
+    fn test_guard_bulk() {
+        assert!(matches!(
+            guard_bulk(&doc! {}, Some(true)),
+            Err(TrashError::EmptyFilter)
+        ));
+        assert!(matches!(
+            guard_bulk(&doc! { "status": "dropped" }, None),
+            Err(TrashError::Unconfirmed)
+        ));
+        assert!(matches!(
+            guard_bulk(&doc! { "status": "dropped" }, Some(false)),
+            Err(TrashError::Unconfirmed)
+        ));
+        assert!(guard_bulk(&doc! { "status": "dropped" }, Some(true)).is_ok());
+    }
+
+    #[test]
+    fn test_resource() {
+        assert_eq!(resource("books").unwrap(), Resource::Book);
+        assert_eq!(resource("wplace").unwrap(), Resource::Screenshot);
+        assert!(matches!(
+            resource("series"),
+            Err(TrashError::UnknownCollection(_))
+        ));
+        assert_eq!(trashable().count(), 5);
+    }
+
+    #[test]
+    fn test_retention() {
+        let deleted_at = chrono::NaiveDate::from_ymd_opt(2025, 3, 14)
+            .unwrap()
+            .and_hms_opt(9, 0, 0)
+            .unwrap();
+        assert_eq!(purge_at(deleted_at, 30).to_string(), "2025-04-13 09:00:00");
+
+        let figment = rocket::figment::Figment::new();
+        assert_eq!(retention_days(&figment), DEFAULT_RETENTION_DAYS);
+        assert_eq!(retention_days(&figment.merge((RETENTION_KEY, 7))), 7);
+    }
+}
@@ -0,0 +1,662 @@
+//! # Webhooks
+//!
+//! Admins register target URLs with event filters through `/webhooks`. Every
+//! [published](crate::events::publish) event calls [`emit`], which queues
+//! one [`WebhookDelivery`] per matching active webhook in the
+//! `webhook_deliveries` collection. [`WebhookWorker`] posts due deliveries and
+//! retries failures with exponential [`backoff`], up to [`MAX_ATTEMPTS`] times.
+//! Delivered and failed deliveries are [purged](purge_finished) once they are
+//! older than [`RETENTION`].
+//!
+//! ## Events
+//!
+//! Webhooks subscribe to the events of [`crate::events`], named
+//! `<resource>.<action>`, e.g. `book.created`, `game.updated` or
+//! `review.deleted`. A filter is an event name, `<resource>.*` or `*`.
+//!
+//! ## Requests
+//!
+//! Every delivery is a `POST` with a JSON body
+//! `{"id": ..., "event": ..., "created_at": ..., "data": {...}}`, where `data`
+//! is the document as written (or as it was before deletion), and the headers
+//!
+//! - `X-Apiodactyl-Event`: the event name
+//! - `X-Apiodactyl-Delivery`: the delivery id, the same across retries
+//! - `X-Apiodactyl-Signature`: `sha256=` and the hex HMAC-SHA256 of the body,
+//!   keyed with the webhook secret (see [`verify_signature`])
+//!
+//! `apiodactyl receive-webhooks` runs a local stand-in receiver that prints
+//! and verifies deliveries.
+
+use {
+    crate::{
+        db::BearoData,
+        errors::WebhookError,
+        events::{Event, Resource},
+        models::{DeliveryStatus, Webhook, WebhookDelivery},
+    },
+    chrono::NaiveDateTime,
+    hmac::{Hmac, Mac},
+    mongodb::bson::{self, doc, oid::ObjectId},
+    rocket::{
+        Orbit, Rocket,
+        fairing::{Fairing, Info, Kind},
+        futures::TryStreamExt,
+        serde::Serialize,
+    },
+    rocket_db_pools::{
+        Database,
+        mongodb::{Client, options::FindOneAndUpdateOptions},
+    },
+    serde_json::json,
+    sha2::Sha256,
+    std::time::Duration,
+    tokio::{
+        io::{AsyncReadExt, AsyncWriteExt},
+        net::TcpListener,
+    },
+};
+
+/// Collection holding registered webhooks.
+pub const WEBHOOKS: &str = "webhooks";
+/// Collection holding queued and finished deliveries.
+pub const DELIVERIES: &str = "webhook_deliveries";
+
+/// Header carrying the event name.
+pub const EVENT_HEADER: &str = "X-Apiodactyl-Event";
+/// Header carrying the delivery id.
+pub const DELIVERY_HEADER: &str = "X-Apiodactyl-Delivery";
+/// Header carrying the body signature.
+pub const SIGNATURE_HEADER: &str = "X-Apiodactyl-Signature";
+
+/// Attempts after which a delivery is marked failed.
+pub const MAX_ATTEMPTS: u32 = 8;
+
+/// How often the worker looks for due deliveries.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// Deliveries sent per poll.
+const BATCH_SIZE: usize = 20;
+/// How long a claimed delivery is hidden from other workers.
+const LEASE: chrono::Duration = chrono::Duration::seconds(60);
+/// Timeout of one delivery request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+/// How long delivered and failed deliveries are kept in the delivery log.
+pub const RETENTION: chrono::Duration = chrono::Duration::days(30);
+/// How often the worker purges finished deliveries.
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+/// Checks that `filter` is `*`, `<resource>.*` or an event name.
+pub fn validate_filter(filter: &str) -> Result<(), WebhookError> {
+    let known = filter == "*"
+        || Resource::ALL
+            .iter()
+            .any(|resource| filter == format!("{}.*", resource.as_str()))
+        || matches!(filter.parse::<Event>(), Ok(Event::Change(..)));
+
+    if known {
+        Ok(())
+    } else {
+        Err(WebhookError::UnknownEvent(filter.to_string()))
+    }
+}
+
+/// Checks a webhook target URL and event filters.
+pub fn validate(url: &str, events: &[String]) -> Result<(), WebhookError> {
+    let parsed = reqwest::Url::parse(url).map_err(|e| WebhookError::InvalidUrl(e.to_string()))?;
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+        return Err(WebhookError::InvalidUrl(url.to_string()));
+    }
+
+    if events.is_empty() {
+        return Err(WebhookError::NoEvents);
+    }
+    events.iter().try_for_each(|filter| validate_filter(filter))
+}
+
+/// Generates a signing secret for a new webhook.
+pub fn generate_secret() -> String {
+    format!("whsec_{}", uuid::Uuid::new_v4().simple())
+}
+
+fn mac(secret: &str) -> Hmac<Sha256> {
+    Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length")
+}
+
+/// Signs a request body, returning the `X-Apiodactyl-Signature` value.
+pub fn sign(secret: &str, body: &[u8]) -> String {
+    let mut mac = mac(secret);
+    mac.update(body);
+    format!("sha256={}", hex::encode(mac.finalize().into_bytes()))
+}
+
+/// Checks an `X-Apiodactyl-Signature` value against a request body in constant time.
+pub fn verify_signature(secret: &str, body: &[u8], signature: &str) -> bool {
+    let Some(expected) = signature
+        .strip_prefix("sha256=")
+        .and_then(|digest| hex::decode(digest).ok())
+    else {
+        return false;
+    };
+
+    let mut mac = mac(secret);
+    mac.update(body);
+    mac.verify_slice(&expected).is_ok()
+}
+
+/// Delay before the next attempt after `attempts` failed ones: 30 seconds,
+/// doubling with every failure, capped at six hours.
+pub fn backoff(attempts: u32) -> chrono::Duration {
+    let seconds = 30_i64.saturating_mul(1 << attempts.saturating_sub(1).min(20));
+    chrono::Duration::seconds(seconds.min(6 * 60 * 60))
+}
+
+fn now() -> NaiveDateTime {
+    chrono::Utc::now().naive_utc()
+}
+
+/// Queues one delivery of `event` to `webhook`.
+pub async fn enqueue<T: Serialize>(
+    db: &Client,
+    webhook: &Webhook,
+    event: Event,
+    data: &T,
+) -> Result<WebhookDelivery, WebhookError> {
+    let oid = ObjectId::new();
+    let created_at = now();
+    let body = json!({
+        "id": oid.to_hex(),
+        "event": event.to_string(),
+        "created_at": created_at,
+        "data": data,
+    })
+    .to_string();
+
+    let delivery = WebhookDelivery {
+        oid,
+        webhook_id: webhook.oid,
+        event: event.to_string(),
+        body,
+        status: DeliveryStatus::Pending,
+        attempts: 0,
+        created_at,
+        next_attempt_at: created_at,
+        last_attempt_at: None,
+        response_status: None,
+        error: None,
+        delivered_at: None,
+    };
+
+    db.database("bearodata")
+        .collection::<WebhookDelivery>(DELIVERIES)
+        .insert_one(&delivery, None)
+        .await?;
+
+    Ok(delivery)
+}
+
+async fn try_emit<T: Serialize>(
+    db: &Client,
+    event: Event,
+    data: &T,
+) -> Result<usize, WebhookError> {
+    let webhooks: Vec<Webhook> = db
+        .database("bearodata")
+        .collection::<Webhook>(WEBHOOKS)
+        .find(doc! { "active": true }, None)
+        .await?
+        .try_collect()
+        .await?;
+
+    let mut queued = 0;
+    for webhook in webhooks
+        .iter()
+        .filter(|webhook| webhook.events.iter().any(|filter| event.matches(filter)))
+    {
+        enqueue(db, webhook, event, data).await?;
+        queued += 1;
+    }
+
+    Ok(queued)
+}
+
+/// Queues `event` for every active webhook subscribed to it.
+///
+/// Called after a write has succeeded; a failure to queue is logged and does
+/// not fail the write.
+pub async fn emit<T: Serialize>(db: &Client, event: Event, data: &T) {
+    match try_emit(db, event, data).await {
+        Ok(0) => {}
+        Ok(queued) => tracing::debug!(event = %event, queued, "queued webhook deliveries"),
+        Err(e) => tracing::warn!(event = %event, error = %e, "failed to queue webhook deliveries"),
+    }
+}
+
+/// The result of one delivery attempt.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Attempt {
+    /// HTTP status of the response, if one was received
+    pub response_status: Option<u16>,
+    /// Why the attempt failed
+    pub error: Option<String>,
+}
+
+impl Attempt {
+    fn failed(error: impl Into<String>) -> Self {
+        Self {
+            response_status: None,
+            error: Some(error.into()),
+        }
+    }
+
+    pub fn is_success(&self) -> bool {
+        self.error.is_none()
+            && self
+                .response_status
+                .is_some_and(|s| (200..300).contains(&s))
+    }
+}
+
+/// Posts a delivery to its webhook once.
+pub async fn send(
+    http: &reqwest::Client,
+    webhook: &Webhook,
+    delivery: &WebhookDelivery,
+) -> Attempt {
+    let result = http
+        .post(&webhook.url)
+        .header(reqwest::header::CONTENT_TYPE, "application/json")
+        .header(EVENT_HEADER, &delivery.event)
+        .header(DELIVERY_HEADER, delivery.oid.to_hex())
+        .header(
+            SIGNATURE_HEADER,
+            sign(&webhook.secret, delivery.body.as_bytes()),
+        )
+        .body(delivery.body.clone())
+        .send()
+        .await;
+
+    match result {
+        Ok(response) if response.status().is_success() => Attempt {
+            response_status: Some(response.status().as_u16()),
+            error: None,
+        },
+        Ok(response) => Attempt {
+            response_status: Some(response.status().as_u16()),
+            error: Some(format!("receiver answered {}", response.status())),
+        },
+        Err(e) => Attempt::failed(e.to_string()),
+    }
+}
+
+/// Applies the outcome of an attempt made at `now` to a delivery.
+///
+/// Successful deliveries are marked delivered; failed ones are rescheduled
+/// after [`backoff`], or marked failed after [`MAX_ATTEMPTS`] attempts.
+pub fn record(delivery: &mut WebhookDelivery, attempt: Attempt, now: NaiveDateTime) {
+    delivery.attempts += 1;
+    delivery.last_attempt_at = Some(now);
+
+    if attempt.is_success() {
+        delivery.status = DeliveryStatus::Delivered;
+        delivery.delivered_at = Some(now);
+    } else if delivery.attempts >= MAX_ATTEMPTS {
+        delivery.status = DeliveryStatus::Failed;
+    } else {
+        delivery.next_attempt_at = now + backoff(delivery.attempts);
+    }
+
+    delivery.response_status = attempt.response_status;
+    delivery.error = attempt.error;
+}
+
+/// Claims the next due delivery, hiding it from other workers for [`LEASE`].
+///
+/// Timestamps are stored as ISO 8601 strings, which sort chronologically.
+async fn claim(db: &Client) -> Result<Option<WebhookDelivery>, WebhookError> {
+    let now = now();
+    let options = FindOneAndUpdateOptions::builder()
+        .sort(doc! { "next_attempt_at": 1 })
+        .build();
+
+    Ok(db
+        .database("bearodata")
+        .collection::<WebhookDelivery>(DELIVERIES)
+        .find_one_and_update(
+            doc! {
+                "status": DeliveryStatus::Pending.as_str(),
+                "next_attempt_at": { "$lte": bson::to_bson(&now)? },
+            },
+            doc! { "$set": { "next_attempt_at": bson::to_bson(&(now + LEASE))? } },
+            options,
+        )
+        .await?)
+}
+
+/// Sends up to one batch of due deliveries, returning how many were attempted.
+pub async fn process_due(db: &Client, http: &reqwest::Client) -> Result<usize, WebhookError> {
+    let webhooks = db.database("bearodata").collection::<Webhook>(WEBHOOKS);
+    let deliveries = db
+        .database("bearodata")
+        .collection::<WebhookDelivery>(DELIVERIES);
+
+    let mut attempted = 0;
+    while attempted < BATCH_SIZE {
+        let Some(mut delivery) = claim(db).await? else {
+            break;
+        };
+
+        let webhook = webhooks
+            .find_one(doc! { "_id": delivery.webhook_id }, None)
+            .await?;
+        match webhook {
+            Some(webhook) if webhook.active => {
+                let attempt = send(http, &webhook, &delivery).await;
+                record(&mut delivery, attempt, now());
+            }
+            Some(_) => {
+                delivery.status = DeliveryStatus::Failed;
+                delivery.error = Some("webhook is inactive".to_string());
+            }
+            None => {
+                delivery.status = DeliveryStatus::Failed;
+                delivery.error = Some("webhook was deleted".to_string());
+            }
+        }
+
+        deliveries
+            .replace_one(doc! { "_id": delivery.oid }, &delivery, None)
+            .await?;
+        attempted += 1;
+    }
+
+    Ok(attempted)
+}
+
+/// Deletes delivered and failed deliveries created more than [`RETENTION`]
+/// ago, returning how many were removed. Pending deliveries are kept.
+pub async fn purge_finished(db: &Client) -> Result<u64, WebhookError> {
+    let cutoff = now() - RETENTION;
+    let result = db
+        .database("bearodata")
+        .collection::<WebhookDelivery>(DELIVERIES)
+        .delete_many(
+            doc! {
+                "status": {
+                    "$in": [DeliveryStatus::Delivered.as_str(), DeliveryStatus::Failed.as_str()],
+                },
+                "created_at": { "$lt": bson::to_bson(&cutoff)? },
+            },
+            None,
+        )
+        .await?;
+
+    Ok(result.deleted_count)
+}
+
+/// Sends queued webhook deliveries in the background while the server runs,
+/// and purges finished ones every [`PURGE_INTERVAL`].
+pub struct WebhookWorker;
+
+#[rocket::async_trait]
+impl Fairing for WebhookWorker {
+    fn info(&self) -> Info {
+        Info {
+            name: "Webhook worker",
+            kind: Kind::Liftoff,
+        }
+    }
+
+    async fn on_liftoff(&self, rocket: &Rocket<Orbit>) {
+        let Some(db) = BearoData::fetch(rocket) else {
+            tracing::warn!("database is not attached; webhook deliveries will not be sent");
+            return;
+        };
+        let db = Client::clone(db);
+        let shutdown = rocket.shutdown();
+
+        let http = match reqwest::Client::builder().timeout(REQUEST_TIMEOUT).build() {
+            Ok(http) => http,
+            Err(e) => {
+                tracing::error!(error = %e, "failed to build the webhook HTTP client");
+                return;
+            }
+        };
+
+        rocket::tokio::spawn(async move {
+            let mut interval = rocket::tokio::time::interval(POLL_INTERVAL);
+            let mut purge = rocket::tokio::time::interval(PURGE_INTERVAL);
+            rocket::tokio::pin!(shutdown);
+
+            loop {
+                rocket::tokio::select! {
+                    _ = &mut shutdown => break,
+                    _ = interval.tick() => {
+                        if let Err(e) = process_due(&db, &http).await {
+                            tracing::warn!(error = %e, "failed to process webhook deliveries");
+                        }
+                    }
+                    _ = purge.tick() => {
+                        match purge_finished(&db).await {
+                            Ok(0) => {}
+                            Ok(purged) => tracing::info!(purged, "purged finished webhook deliveries"),
+                            Err(e) => tracing::warn!(error = %e, "failed to purge webhook deliveries"),
+                        }
+                    }
+                }
+            }
+        });
+    }
+}
+
+/// A request captured by [`receive`].
+#[derive(Debug, Clone)]
+pub struct ReceivedRequest {
+    /// Request line, e.g. `POST /hook HTTP/1.1`
+    pub request_line: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl ReceivedRequest {
+    /// Returns the first header with the given name, ignoring case.
+    pub fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(header, _)| header.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+/// Accepts one HTTP request on `listener` and answers it with `status`.
+///
+/// A minimal stand-in for a webhook receiver, used by `receive-webhooks`
+/// and the tests.
+pub async fn receive(listener: &TcpListener, status: u16) -> std::io::Result<ReceivedRequest> {
+    let (mut stream, _) = listener.accept().await?;
+
+    let mut buffer = Vec::new();
+    let head_end = loop {
+        if let Some(end) = buffer.windows(4).position(|window| window == b"\r\n\r\n") {
+            break end;
+        }
+        let mut chunk = [0; 4096];
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+
+    let head = String::from_utf8_lossy(&buffer[..head_end]).into_owned();
+    let mut lines = head.split("\r\n");
+    let request_line = lines.next().unwrap_or_default().to_string();
+    let headers: Vec<(String, String)> = lines
+        .filter_map(|line| line.split_once(':'))
+        .map(|(name, value)| (name.trim().to_string(), value.trim().to_string()))
+        .collect();
+
+    let content_length = headers
+        .iter()
+        .find(|(name, _)| name.eq_ignore_ascii_case("content-length"))
+        .and_then(|(_, value)| value.parse::<usize>().ok())
+        .unwrap_or(0);
+    let mut body = buffer[head_end + 4..].to_vec();
+    while body.len() < content_length {
+        let mut chunk = vec![0; content_length - body.len()];
+        let read = stream.read(&mut chunk).await?;
+        if read == 0 {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        body.extend_from_slice(&chunk[..read]);
+    }
+
+    let response = format!(
+        "HTTP/1.1 {} \r\ncontent-length: 0\r\nconnection: close\r\n\r\n",
+        status
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await?;
+
+    Ok(ReceivedRequest {
+        request_line,
+        headers,
+        body,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn webhook(url: String) -> Webhook {
+        Webhook {
+            oid: ObjectId::new(),
+            url,
+            events: vec!["book.*".to_string()],
+            secret: "whsec_test".to_string(),
+            active: true,
+            description: None,
+            created_at: now(),
+        }
+    }
+
+    fn delivery(webhook: &Webhook) -> WebhookDelivery {
+        WebhookDelivery {
+            oid: ObjectId::new(),
+            webhook_id: webhook.oid,
+            event: "book.created".to_string(),
+            body: r#"{"event":"book.created","data":{"title":"Dune"}}"#.to_string(),
+            status: DeliveryStatus::Pending,
+            attempts: 0,
+            created_at: now(),
+            next_attempt_at: now(),
+            last_attempt_at: None,
+            response_status: None,
+            error: None,
+            delivered_at: None,
+        }
+    }
+
+    #[test]
+    fn test_event_filters() {
+        let event = Event::updated(Resource::Game);
+        assert_eq!(event.to_string(), "game.updated");
+        assert_eq!("game.updated".parse::<Event>().unwrap(), event);
+
+        assert!(event.matches("*"));
+        assert!(event.matches("game.*"));
+        assert!(event.matches("game.updated"));
+        assert!(!event.matches("game.created"));
+        assert!(!event.matches("book.*"));
+        assert!(!Event::Ping.matches("*"));
+
+        assert!(validate_filter("review.deleted").is_ok());
+        assert!(validate_filter("series.*").is_ok());
+        assert!(validate_filter("ping").is_err());
+        assert!(validate_filter("book.read").is_err());
+
+        assert!(validate("https://example.com/hook", &["*".to_string()]).is_ok());
+        assert!(matches!(
+            validate("ftp://example.com/hook", &["*".to_string()]),
+            Err(WebhookError::InvalidUrl(_))
+        ));
+        assert!(matches!(
+            validate("https://example.com/hook", &[]),
+            Err(WebhookError::NoEvents)
+        ));
+    }
+
+    #[test]
+    fn test_signatures() {
+        let signature = sign("secret", b"{}");
+        assert!(signature.starts_with("sha256="));
+        assert_eq!(signature.len(), 7 + 64);
+
+        assert!(verify_signature("secret", b"{}", &signature));
+        assert!(!verify_signature("other", b"{}", &signature));
+        assert!(!verify_signature("secret", b"{ }", &signature));
+        assert!(!verify_signature("secret", b"{}", "sha256=zz"));
+    }
+
+    #[test]
+    fn test_backoff_and_record() {
+        assert_eq!(backoff(1), chrono::Duration::seconds(30));
+        assert_eq!(backoff(2), chrono::Duration::seconds(60));
+        assert_eq!(backoff(4), chrono::Duration::seconds(240));
+        assert_eq!(backoff(30), chrono::Duration::hours(6));
+
+        let webhook = webhook("http://127.0.0.1:1/".to_string());
+        let mut delivery = delivery(&webhook);
+        let now = now();
+
+        record(&mut delivery, Attempt::failed("connection refused"), now);
+        assert_eq!(delivery.status, DeliveryStatus::Pending);
+        assert_eq!(delivery.attempts, 1);
+        assert_eq!(delivery.next_attempt_at, now + backoff(1));
+
+        delivery.attempts = MAX_ATTEMPTS - 1;
+        record(&mut delivery, Attempt::failed("connection refused"), now);
+        assert_eq!(delivery.status, DeliveryStatus::Failed);
+
+        let mut delivery = self::delivery(&webhook);
+        let attempt = Attempt {
+            response_status: Some(204),
+            error: None,
+        };
+        record(&mut delivery, attempt, now);
+        assert_eq!(delivery.status, DeliveryStatus::Delivered);
+        assert_eq!(delivery.delivered_at, Some(now));
+    }
+
+    #[rocket::async_test]
+    async fn test_send_to_local_receiver() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap());
+        let webhook = webhook(url);
+        let delivery = delivery(&webhook);
+        let http = reqwest::Client::new();
+
+        let (received, attempt) =
+            rocket::tokio::join!(receive(&listener, 204), send(&http, &webhook, &delivery));
+        let received = received.unwrap();
+
+        assert!(attempt.is_success(), "{attempt:?}");
+        assert_eq!(received.request_line, "POST /hook HTTP/1.1");
+        assert_eq!(received.header("x-apiodactyl-event"), Some("book.created"));
+        assert_eq!(
+            received.header(DELIVERY_HEADER),
+            Some(delivery.oid.to_hex().as_str())
+        );
+        assert_eq!(received.body, delivery.body.as_bytes());
+        assert!(verify_signature(
+            &webhook.secret,
+            &received.body,
+            received.header(SIGNATURE_HEADER).unwrap()
+        ));
+
+        let (_, attempt) =
+            rocket::tokio::join!(receive(&listener, 500), send(&http, &webhook, &delivery));
+        assert_eq!(attempt.response_status, Some(500));
+        assert!(!attempt.is_success());
+    }
+}